@@ -1,4 +1,7 @@
+pub mod cancellation;
 pub mod dir;
 pub mod errors;
 pub mod logger;
+pub mod secret;
+pub mod similarity;
 pub mod thelper;
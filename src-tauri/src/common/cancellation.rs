@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+/// 登録前に届いたキャンセルを覚えておく期間
+const EARLY_CANCEL_TTL: Duration = Duration::from_secs(60);
+
+/// 実行IDごとのキャンセルトークン
+/// 同じ実行の処理（バージョンごとのチャットなど）は同じトークンを共有し、全ての処理が終わると破棄する
+#[derive(Debug, Default)]
+pub struct RunCancellations {
+    state: Mutex<RunCancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct RunCancellationState {
+    tokens: HashMap<i32, (CancellationToken, usize)>, // (トークン, 実行中の処理数)
+    early_cancels: HashMap<i32, Instant>, // 登録前にキャンセルされた実行と、キャンセルされた時刻
+}
+
+impl RunCancellationState {
+    fn prune_early_cancels(&mut self, now: Instant) {
+        self.early_cancels
+            .retain(|_, cancelled_at| now.duration_since(*cancelled_at) < EARLY_CANCEL_TTL);
+    }
+}
+
+impl RunCancellations {
+    /// 実行中の処理として登録し、処理が終わるまで保持するガードを返す
+    /// 登録前にキャンセルされていた実行は、キャンセル済みのトークンで登録する
+    pub fn register(self: &Arc<Self>, run_id: i32) -> RunCancellationGuard {
+        let mut state = self.state.lock().unwrap();
+        state.prune_early_cancels(Instant::now());
+        let cancelled = state.early_cancels.remove(&run_id).is_some();
+        let (token, count) = state
+            .tokens
+            .entry(run_id)
+            .or_insert_with(|| (CancellationToken::new(), 0));
+        if cancelled {
+            token.cancel();
+        }
+        *count += 1;
+        RunCancellationGuard {
+            cancellations: Arc::clone(self),
+            run_id,
+            token: token.clone(),
+        }
+    }
+
+    /// 実行中の処理をキャンセルし、キャンセルした処理数を返す
+    /// 実行中の処理がない場合は、直後に登録される処理をキャンセルできるようにEARLY_CANCEL_TTLの間だけ覚えておく
+    pub fn cancel(&self, run_id: i32) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.prune_early_cancels(now);
+        match state.tokens.get(&run_id) {
+            Some((token, count)) => {
+                token.cancel();
+                *count
+            }
+            None => {
+                state.early_cancels.insert(run_id, now);
+                0
+            }
+        }
+    }
+}
+
+pub struct RunCancellationGuard {
+    cancellations: Arc<RunCancellations>,
+    run_id: i32,
+    token: CancellationToken,
+}
+
+impl RunCancellationGuard {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for RunCancellationGuard {
+    fn drop(&mut self) {
+        let mut state = self.cancellations.state.lock().unwrap();
+        if let Some((_, count)) = state.tokens.get_mut(&self.run_id) {
+            *count -= 1;
+            if *count == 0 {
+                state.tokens.remove(&self.run_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let cancellations = Arc::new(RunCancellations::default());
+        let first = cancellations.register(1);
+        let second = cancellations.register(1);
+        let other = cancellations.register(2);
+
+        // 同じ実行の処理はまとめてキャンセルされる
+        assert_eq!(cancellations.cancel(1), 2);
+        assert!(first.token().is_cancelled());
+        assert!(second.token().is_cancelled());
+        assert!(!other.token().is_cancelled());
+
+        // 全ての処理が終わるとトークンは破棄され、次の実行には影響しない
+        drop(first);
+        drop(second);
+        let next = cancellations.register(1);
+        assert!(!next.token().is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_before_register() {
+        let cancellations = Arc::new(RunCancellations::default());
+
+        // 登録前のキャンセルは覚えておき、登録した処理をキャンセル済みにする
+        assert_eq!(cancellations.cancel(3), 0);
+        let first = cancellations.register(3);
+        assert!(first.token().is_cancelled());
+        drop(first);
+
+        // 覚えておくのは一度だけで、次の実行には影響しない
+        let next = cancellations.register(3);
+        assert!(!next.token().is_cancelled());
+
+        // 期限切れのキャンセルは破棄する
+        drop(next);
+        cancellations
+            .state
+            .lock()
+            .unwrap()
+            .early_cancels
+            .insert(4, Instant::now().checked_sub(EARLY_CANCEL_TTL).unwrap());
+        let expired = cancellations.register(4);
+        assert!(!expired.token().is_cancelled());
+    }
+}
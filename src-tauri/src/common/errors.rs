@@ -14,4 +14,6 @@ pub enum ApplicationError {
     DBEntityError(String),
     #[error("parse error: {0}")]
     ParseError(String),
+    #[error("validation error: {0}")]
+    ValidationError(String),
 }
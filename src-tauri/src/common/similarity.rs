@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+/// 比較用にテキストを正規化する
+/// 前後の空白を除去し、連続する空白を1つにまとめ、小文字に揃える
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// 正規化した単語集合のJaccard係数で文字列の類似度を計算する（0.0〜1.0）
+/// APIを呼ばずに計算できるので、サンプル間のばらつきの計測などに使用する
+pub fn token_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize_text(a);
+    let b = normalize_text(b);
+    let a: HashSet<&str> = a.split(' ').filter(|t| !t.is_empty()).collect();
+    let b: HashSet<&str> = b.split(' ').filter(|t| !t.is_empty()).collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let intersection = a.intersection(&b).count() as f64;
+    let union = a.union(&b).count() as f64;
+    intersection / union
+}
+
+/// ベクトル同士のコサイン類似度を計算する
+/// 次元が異なる場合やゼロベクトルの場合は0.0を返す
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let mut dot = 0.0_f64;
+    let mut norm_a = 0.0_f64;
+    let mut norm_b = 0.0_f64;
+    for (x, y) in a.iter().zip(b.iter()) {
+        let (x, y) = (*x as f64, *y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        assert_eq!(normalize_text("  Hello \n  World\t"), "hello world");
+        assert_eq!(normalize_text(""), "");
+    }
+
+    #[test]
+    fn test_token_similarity() {
+        assert_eq!(token_similarity("Hello world", "hello  WORLD"), 1.0);
+        assert_eq!(token_similarity("a b", "c d"), 0.0);
+        assert!((token_similarity("a b c", "a b d") - 0.5).abs() < 1e-9);
+        assert_eq!(token_similarity("", " "), 1.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-9);
+        // 次元違い・ゼロベクトルは0.0
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+}
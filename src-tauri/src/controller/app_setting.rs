@@ -0,0 +1,137 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::app_setting::AppSettings;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: AppSettings + ?Sized + 'static,
+{
+    app_settings: T,
+}
+
+impl<T> Controller<T>
+where
+    T: AppSettings + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller {
+            app_settings: usecase,
+        }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn AppSettings>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn AppSettings>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// アプリの設定を取得する（APIキーは末尾のみ）
+#[tauri::command]
+pub async fn get_app_settings(
+    request: usecase::app_setting::GetAppSettingsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().app_settings, get_app_settings, request);
+    convert_to_tauri_result!(res)
+}
+
+/// アプリの設定を保存する（APIキーはアプリを再起動せずに反映される）
+#[tauri::command]
+pub async fn save_app_settings(
+    request: usecase::app_setting::SaveAppSettingsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().app_settings, save_app_settings, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 秘密の設定を暗号化する鍵を新しい鍵に入れ替える
+#[tauri::command]
+pub async fn rotate_secret_key(
+    request: usecase::app_setting::RotateSecretKeyRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().app_settings, rotate_secret_key, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 認証情報のプロファイルの一覧を取得する（APIキーは末尾のみ）
+#[tauri::command]
+pub async fn get_credential_profiles(
+    request: usecase::app_setting::GetCredentialProfilesRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().app_settings,
+        get_credential_profiles,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// 認証情報のプロファイルを登録または更新する
+#[tauri::command]
+pub async fn save_credential_profile(
+    request: usecase::app_setting::SaveCredentialProfileRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().app_settings,
+        save_credential_profile,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// 認証情報のプロファイルを削除する
+#[tauri::command]
+pub async fn delete_credential_profile(
+    request: usecase::app_setting::DeleteCredentialProfileRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().app_settings,
+        delete_credential_profile,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// プロンプトマネージャーで選択しているプロファイルを取得する
+#[tauri::command]
+pub async fn get_manager_credential_profiles(
+    request: usecase::app_setting::GetManagerCredentialProfilesRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().app_settings,
+        get_manager_credential_profiles,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// プロンプトマネージャーで使うプロファイルを選択する
+#[tauri::command]
+pub async fn save_manager_credential_profile(
+    request: usecase::app_setting::SaveManagerCredentialProfileRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().app_settings,
+        save_manager_credential_profile,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// プロファイルとモデルごとの実行数を取得する
+#[tauri::command]
+pub async fn get_credential_profile_usage(
+    request: usecase::app_setting::GetCredentialProfileUsageRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().app_settings,
+        get_credential_profile_usage,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
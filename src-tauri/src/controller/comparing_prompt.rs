@@ -70,6 +70,32 @@ pub async fn get_all_comparing_prompt_settings(
     convert_to_tauri_result!(res)
 }
 
+/// プロンプト比較設定を論理削除する
+#[tauri::command]
+pub async fn delete_comparing_prompt_setting(
+    request: usecase::comparing_prompt::DeleteComparingPromptSettingRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().comparing_prompt,
+        delete_comparing_prompt_setting,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// 論理削除したプロンプト比較設定を元に戻す
+#[tauri::command]
+pub async fn restore_comparing_prompt_setting(
+    request: usecase::comparing_prompt::RestoreComparingPromptSettingRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().comparing_prompt,
+        restore_comparing_prompt_setting,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
 /// プロンプト比較実行を保存する
 #[tauri::command]
 pub async fn save_comparing_prompt_run(
@@ -87,3 +113,87 @@ pub async fn run_comparing_prompt(
     let res = log_ipc!(get_controller().comparing_prompt, run_chat, request);
     convert_to_tauri_result!(res)
 }
+
+/// プロンプト比較実行のサンプル間の一貫性を取得する
+#[tauri::command]
+pub async fn get_comparing_prompt_consistency_metrics(
+    request: usecase::comparing_prompt::GetConsistencyMetricsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().comparing_prompt,
+        get_consistency_metrics,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// パラメータスイープを保存する
+#[tauri::command]
+pub async fn save_comparing_prompt_sweep(
+    request: usecase::comparing_prompt::SaveComparingPromptSweepRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().comparing_prompt, save_sweep, request);
+    convert_to_tauri_result!(res)
+}
+
+/// パラメータスイープの結果をマトリクスで取得する
+#[tauri::command]
+pub async fn get_comparing_prompt_sweep_matrix(
+    request: usecase::comparing_prompt::GetSweepMatrixRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().comparing_prompt, get_sweep_matrix, request);
+    convert_to_tauri_result!(res)
+}
+
+/// プロンプト比較設定のバージョンにツール定義を保存する
+#[tauri::command]
+pub async fn save_comparing_prompt_version_tools(
+    request: usecase::comparing_prompt::SaveVersionToolsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().comparing_prompt,
+        save_version_tools,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// スクリプト化したツールの結果を返しながらエージェントとして実行する
+#[tauri::command]
+pub async fn run_comparing_prompt_agent(
+    request: usecase::comparing_prompt::RunAgentRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().comparing_prompt, run_agent, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 実行前にバージョンごとのプロンプトのトークン数を数え、コンテキストウィンドウに収まるか確認する
+#[tauri::command]
+pub async fn preflight_comparing_prompt_run(
+    request: usecase::comparing_prompt::PreflightRunRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().comparing_prompt, preflight_run, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 回答のキャッシュを削除する
+#[tauri::command]
+pub async fn clear_comparing_prompt_response_cache(
+    request: usecase::comparing_prompt::ClearResponseCacheRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(
+        get_controller().comparing_prompt,
+        clear_response_cache,
+        request
+    );
+    convert_to_tauri_result!(res)
+}
+
+/// 実行中のチャットをキャンセルする
+#[tauri::command]
+pub async fn cancel_comparing_prompt_run(
+    request: usecase::comparing_prompt::CancelRunRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().comparing_prompt, cancel_run, request);
+    convert_to_tauri_result!(res)
+}
@@ -0,0 +1,62 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::job::JobQueue;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: JobQueue + ?Sized + 'static,
+{
+    job_queue: T,
+}
+
+impl<T> Controller<T>
+where
+    T: JobQueue + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller { job_queue: usecase }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn JobQueue>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn JobQueue>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// 実行をバックグラウンドのジョブとして登録する
+#[tauri::command]
+pub async fn enqueue_comparing_prompt_run_job(
+    request: usecase::job::EnqueueRunJobRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().job_queue, enqueue_run_job, request);
+    convert_to_tauri_result!(res)
+}
+
+/// スイープの全ての実行をバックグラウンドのジョブとして登録する
+#[tauri::command]
+pub async fn enqueue_comparing_prompt_sweep_job(
+    request: usecase::job::EnqueueSweepJobRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().job_queue, enqueue_sweep_job, request);
+    convert_to_tauri_result!(res)
+}
+
+/// ジョブの進捗と項目ごとの結果を取得する
+#[tauri::command]
+pub async fn get_job(request: usecase::job::GetJobRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().job_queue, get_job, request);
+    convert_to_tauri_result!(res)
+}
+
+/// ジョブを新しい順に取得する
+#[tauri::command]
+pub async fn get_jobs(request: usecase::job::GetJobsRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().job_queue, get_jobs, request);
+    convert_to_tauri_result!(res)
+}
@@ -0,0 +1,50 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::model_catalog::ModelCatalog;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: ModelCatalog + ?Sized + 'static,
+{
+    model_catalog: T,
+}
+
+impl<T> Controller<T>
+where
+    T: ModelCatalog + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller {
+            model_catalog: usecase,
+        }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn ModelCatalog>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn ModelCatalog>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// プロバイダーのモデル一覧を取得してモデルカタログを更新する
+#[tauri::command]
+pub async fn sync_models(
+    request: usecase::model_catalog::SyncModelsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().model_catalog, sync_models, request);
+    convert_to_tauri_result!(res)
+}
+
+/// モデルカタログからモデルを取得する
+#[tauri::command]
+pub async fn get_models(
+    request: usecase::model_catalog::GetModelsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().model_catalog, get_models, request);
+    convert_to_tauri_result!(res)
+}
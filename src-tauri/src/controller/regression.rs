@@ -0,0 +1,68 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::regression::Regression;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: Regression + ?Sized + 'static,
+{
+    regression: T,
+}
+
+impl<T> Controller<T>
+where
+    T: Regression + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller {
+            regression: usecase,
+        }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn Regression>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn Regression>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// 実行履歴をベースラインとして承認する
+#[tauri::command]
+pub async fn approve_baseline(
+    request: usecase::regression::ApproveBaselineRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().regression, approve_baseline, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 設定のベースラインを全て取得する
+#[tauri::command]
+pub async fn get_baselines(
+    request: usecase::regression::GetBaselinesRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().regression, get_baselines, request);
+    convert_to_tauri_result!(res)
+}
+
+/// ベースラインを削除する
+#[tauri::command]
+pub async fn delete_baseline(
+    request: usecase::regression::DeleteBaselineRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().regression, delete_baseline, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 実行結果とベースラインの差分レポートを取得する
+#[tauri::command]
+pub async fn get_regression_report(
+    request: usecase::regression::GetRegressionReportRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().regression, get_regression_report, request);
+    convert_to_tauri_result!(res)
+}
@@ -0,0 +1,37 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::search::Search;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: Search + ?Sized + 'static,
+{
+    search: T,
+}
+
+impl<T> Controller<T>
+where
+    T: Search + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller { search: usecase }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn Search>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn Search>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// マネージャーのタイトル、システムプロンプト、ユーザープロンプト、回答を全文検索する
+#[tauri::command]
+pub async fn search(request: usecase::search::SearchRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().search, search, request);
+    convert_to_tauri_result!(res)
+}
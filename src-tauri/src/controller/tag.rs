@@ -0,0 +1,69 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::tag::TagManager;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: TagManager + ?Sized + 'static,
+{
+    tag_manager: T,
+}
+
+impl<T> Controller<T>
+where
+    T: TagManager + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller {
+            tag_manager: usecase,
+        }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn TagManager>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn TagManager>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// タグと、タグが付いているマネージャーの数を取得する
+#[tauri::command]
+pub async fn get_tags(request: usecase::tag::GetTagsRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().tag_manager, get_tags, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 全てのマネージャーのタグの値を変更する
+#[tauri::command]
+pub async fn rename_tag(request: usecase::tag::RenameTagRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().tag_manager, rename_tag, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 複数のタグを1つのタグに統合する
+#[tauri::command]
+pub async fn merge_tags(request: usecase::tag::MergeTagsRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().tag_manager, merge_tags, request);
+    convert_to_tauri_result!(res)
+}
+
+/// タグを全てのマネージャーから外して削除する
+#[tauri::command]
+pub async fn delete_tag(request: usecase::tag::DeleteTagRequest) -> Result<String, String> {
+    let res = log_ipc!(get_controller().tag_manager, delete_tag, request);
+    convert_to_tauri_result!(res)
+}
+
+/// どのマネージャーにも付いていないタグを削除する
+#[tauri::command]
+pub async fn delete_unused_tags(
+    request: usecase::tag::DeleteUnusedTagsRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().tag_manager, delete_unused_tags, request);
+    convert_to_tauri_result!(res)
+}
@@ -0,0 +1,41 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::token_count::TokenCount;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: TokenCount + ?Sized + 'static,
+{
+    token_count: T,
+}
+
+impl<T> Controller<T>
+where
+    T: TokenCount + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller {
+            token_count: usecase,
+        }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn TokenCount>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn TokenCount>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// プロンプトのトークン数を数え、コンテキストウィンドウに収まるか確認する
+#[tauri::command]
+pub async fn count_tokens(
+    request: usecase::token_count::CountTokensRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().token_count, count_tokens, request);
+    convert_to_tauri_result!(res)
+}
@@ -0,0 +1,57 @@
+use once_cell::sync::OnceCell;
+
+use crate::usecase::trash::Trash;
+use crate::{convert_to_tauri_result, log_ipc, usecase};
+
+pub struct Controller<T>
+where
+    T: Trash + ?Sized + 'static,
+{
+    trash: T,
+}
+
+impl<T> Controller<T>
+where
+    T: Trash + 'static,
+{
+    /// controllerの初期化
+    pub fn init(usecase: T) {
+        let _ = CONTROLLER.set(Box::new(Controller { trash: usecase }));
+    }
+}
+
+/// usecaseを保持するためのstruct
+/// tauriコマンドはtraitやstruct内に定義できないようなのでこのようにしています
+/// staticはコンパイル時に型を決定するので、traitを使うときはBoxで囲む必要があります
+static CONTROLLER: OnceCell<Box<Controller<dyn Trash>>> = OnceCell::new();
+
+fn get_controller() -> &'static Box<Controller<dyn Trash>> {
+    CONTROLLER.get().expect("Controller is not initialized")
+}
+
+/// 論理削除されたプロンプトマネージャーを取得する
+#[tauri::command]
+pub async fn get_deleted_prompt_managers(
+    request: usecase::trash::GetDeletedPromptManagersRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().trash, get_deleted_prompt_managers, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 論理削除されたプロンプトマネージャーを元に戻す
+#[tauri::command]
+pub async fn restore_prompt_manager(
+    request: usecase::trash::RestorePromptManagerRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().trash, restore_prompt_manager, request);
+    convert_to_tauri_result!(res)
+}
+
+/// 論理削除されたプロンプトマネージャーと、紐づく全てのデータを物理削除する
+#[tauri::command]
+pub async fn purge_prompt_manager(
+    request: usecase::trash::PurgePromptManagerRequest,
+) -> Result<String, String> {
+    let res = log_ipc!(get_controller().trash, purge_prompt_manager, request);
+    convert_to_tauri_result!(res)
+}
@@ -1,3 +1,13 @@
+pub mod app_setting;
 pub mod chat;
 pub mod comparing_prompt;
+pub mod embedding;
+pub mod event;
+pub mod job;
+pub mod model_catalog;
 pub mod prompt_manager;
+pub mod response_cache;
+pub mod search;
+pub mod tag;
+pub mod tokenizer;
+pub mod trash;
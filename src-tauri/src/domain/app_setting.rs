@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::common::errors::ApplicationError;
+use crate::common::secret::SecretString;
+use crate::domain::comparing_prompt::ProviderType;
+
+/// プロバイダーごとのAPIキーの設定名の接頭辞
+const API_KEY_PREFIX: &str = "api_key.";
+/// プロバイダーごとのレート制限の設定名の接頭辞
+const RATE_LIMIT_PREFIX: &str = "rate_limit.";
+
+/// 画面のテーマ（フロントエンドのThemeProviderと同じ値）
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+/// アプリ全体の設定の名前
+#[derive(Clone, Debug, PartialEq)]
+pub enum AppSettingName {
+    ApiKey(ProviderType),
+    DefaultProvider,
+    DefaultModel,
+    Theme,
+    AzureOpenAI,
+    TrashRetentionDays,
+    RateLimit(ProviderType),
+    HttpClient,
+}
+
+impl AppSettingName {
+    /// 暗号化して保存する設定か
+    pub fn is_secret(&self) -> bool {
+        matches!(self, AppSettingName::ApiKey(_))
+    }
+}
+
+impl fmt::Display for AppSettingName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppSettingName::ApiKey(provider_type) => {
+                write!(f, "{}{}", API_KEY_PREFIX, provider_type)
+            }
+            AppSettingName::DefaultProvider => write!(f, "default_provider"),
+            AppSettingName::DefaultModel => write!(f, "default_model"),
+            AppSettingName::Theme => write!(f, "theme"),
+            AppSettingName::AzureOpenAI => write!(f, "azure_openai"),
+            AppSettingName::TrashRetentionDays => write!(f, "trash_retention_days"),
+            AppSettingName::RateLimit(provider_type) => {
+                write!(f, "{}{}", RATE_LIMIT_PREFIX, provider_type)
+            }
+            AppSettingName::HttpClient => write!(f, "http_client"),
+        }
+    }
+}
+
+impl FromStr for AppSettingName {
+    type Err = ApplicationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(provider_type) = s.strip_prefix(API_KEY_PREFIX) {
+            return ProviderType::from_str(provider_type)
+                .map(AppSettingName::ApiKey)
+                .map_err(|e| ApplicationError::ParseError(format!("{}: {}", s, e)));
+        }
+        if let Some(provider_type) = s.strip_prefix(RATE_LIMIT_PREFIX) {
+            return ProviderType::from_str(provider_type)
+                .map(AppSettingName::RateLimit)
+                .map_err(|e| ApplicationError::ParseError(format!("{}: {}", s, e)));
+        }
+        match s {
+            "default_provider" => Ok(AppSettingName::DefaultProvider),
+            "default_model" => Ok(AppSettingName::DefaultModel),
+            "theme" => Ok(AppSettingName::Theme),
+            "azure_openai" => Ok(AppSettingName::AzureOpenAI),
+            "trash_retention_days" => Ok(AppSettingName::TrashRetentionDays),
+            "http_client" => Ok(AppSettingName::HttpClient),
+            _ => Err(ApplicationError::ParseError(format!(
+                "unknown app setting: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Azure OpenAIのリソースの設定（APIキーはApiKey(AzureOpenAI)に保存する）
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AzureOpenAISettings {
+    pub endpoint: String, // https://<リソース名>.openai.azure.com
+    pub api_version: String,
+    pub deployments: Vec<AzureOpenAIDeployment>,
+}
+
+/// デプロイメント名と、デプロイされたモデル名
+/// 実行にはデプロイメント名をモデルとして記録する
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AzureOpenAIDeployment {
+    pub deployment: String,
+    pub model: String,
+}
+
+impl AzureOpenAISettings {
+    /// デプロイされたモデル名を返す（設定されていないデプロイメントの場合はNone）
+    pub fn model_for(&self, deployment: &str) -> Option<&str> {
+        self.deployments
+            .iter()
+            .find(|item| item.deployment == deployment)
+            .map(|item| item.model.as_str())
+    }
+}
+
+/// プロバイダーのAPIのレート制限（未指定(None)の項目は環境変数の既定値を使う）
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitSettings {
+    pub max_concurrent: Option<u32>,
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimitSettings {
+    /// 全ての項目が未指定か（保存せずに既定値に戻す）
+    pub fn is_empty(&self) -> bool {
+        self == &RateLimitSettings::default()
+    }
+}
+
+/// プロバイダーへの通信の設定（未指定(None)または空の項目は環境変数の既定値を使う）
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpClientSettings {
+    pub proxy: Option<String>,
+    pub no_proxy: Option<String>, // プロキシを経由しないホスト（カンマ区切り）
+    #[serde(default)]
+    pub ca_certs: Vec<String>, // 追加で信頼するルート証明書（PEM形式）のパス
+    pub connect_timeout_secs: Option<u64>,
+    pub request_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>, // 全てのリクエストに付けるヘッダー
+}
+
+impl HttpClientSettings {
+    /// 全ての項目が未指定か（保存せずに既定値に戻す）
+    pub fn is_empty(&self) -> bool {
+        self == &HttpClientSettings::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppSettingModel {
+    pub name: AppSettingName,
+    pub value: String,
+}
+
+/// プロバイダーの認証情報のプロファイル（APIキーは暗号化された値）
+#[derive(Clone, Debug, PartialEq)]
+pub struct CredentialProfileModel {
+    pub id: i32,
+    pub provider_type: ProviderType,
+    pub label: String,
+    pub api_key: String,
+    pub organization_id: Option<String>,
+    pub project_id: Option<String>,
+    pub deleted: bool, // 削除されたプロファイルは実行の集計のラベルにのみ使う
+}
+
+/// プロファイルから復号した、リクエストに使う認証情報
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProviderCredential {
+    pub api_key: SecretString,
+    pub organization_id: Option<String>,
+    pub project_id: Option<String>,
+}
+
+/// 暗号化して保存している値の場所
+#[derive(Clone, Debug, PartialEq)]
+pub enum SecretRef {
+    AppSetting(AppSettingName),
+    CredentialProfile(i32),
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretRef::AppSetting(name) => write!(f, "{}", name),
+            SecretRef::CredentialProfile(id) => write!(f, "credential_profile.{}", id),
+        }
+    }
+}
+
+/// 実行に使ったプロファイルとモデルごとの集計
+#[derive(Clone, Debug, PartialEq)]
+pub struct CredentialProfileUsageModel {
+    pub credential_profile_id: Option<i32>, // Noneは設定画面または環境変数のAPIキー
+    pub provider_type: ProviderType,
+    pub model: String,
+    pub runs: i32,
+    pub responses: i32, // APIから返った回答の数（キャッシュとキャンセルを除く）
+    pub cached_responses: i32,
+}
+
+#[async_trait]
+pub trait AppSettingRepository: Send + Sync {
+    async fn find_app_settings(&self) -> Result<Vec<AppSettingModel>, ApplicationError>;
+
+    /// 設定をまとめて保存する（値がNoneの設定は削除する）
+    async fn save_app_settings(
+        &self,
+        settings: &[(AppSettingName, Option<String>)],
+    ) -> Result<(), ApplicationError>;
+
+    /// 暗号化して保存している値を全て取得する（削除されたプロファイルは含まない）
+    async fn find_secrets(&self) -> Result<Vec<(SecretRef, String)>, ApplicationError>;
+
+    /// 暗号化した値をまとめて上書きする（全て保存されるか、全て保存されないかのどちらか）
+    async fn save_secrets(&self, secrets: &[(SecretRef, String)]) -> Result<(), ApplicationError>;
+
+    /// 削除されたプロファイルも含めて取得する
+    async fn find_credential_profiles(
+        &self,
+    ) -> Result<Vec<CredentialProfileModel>, ApplicationError>;
+
+    async fn find_credential_profile_by_id(
+        &self,
+        id: i32,
+    ) -> Result<CredentialProfileModel, ApplicationError>;
+
+    async fn create_credential_profile(
+        &self,
+        param: CredentialProfileModel,
+    ) -> Result<i32, ApplicationError>;
+
+    async fn update_credential_profile(
+        &self,
+        param: CredentialProfileModel,
+    ) -> Result<(), ApplicationError>;
+
+    /// 論理削除してAPIキーを消去し、プロンプトマネージャーでの選択を解除する
+    async fn logical_delete_credential_profile(&self, id: i32) -> Result<(), ApplicationError>;
+
+    /// プロンプトマネージャーがプロバイダーごとに選択しているプロファイル
+    async fn find_manager_credential_profiles(
+        &self,
+        manager_id: i32,
+    ) -> Result<Vec<(ProviderType, i32)>, ApplicationError>;
+
+    /// プロンプトマネージャーのプロファイルを選択する（Noneの場合は選択を解除する）
+    async fn save_manager_credential_profile(
+        &self,
+        manager_id: i32,
+        provider_type: &ProviderType,
+        credential_profile_id: Option<i32>,
+    ) -> Result<(), ApplicationError>;
+
+    async fn find_credential_profile_usages(
+        &self,
+    ) -> Result<Vec<CredentialProfileUsageModel>, ApplicationError>;
+}
+
+/// プロバイダーのクライアントにAPIキーを反映するtrait
+/// 設定が変更された場合にアプリを再起動せずにクライアントを作り直すために使う
+pub trait ApiKeyUpdater: Send + Sync {
+    /// APIキーを反映する（Noneの場合は環境変数のAPIキーに戻す）
+    fn update_api_key(&self, provider_type: &ProviderType, api_key: Option<&str>);
+
+    /// Azure OpenAIのリソースの設定を反映する（Noneの場合はAzure OpenAIを使えなくする）
+    fn update_azure_openai_settings(&self, _settings: Option<&AzureOpenAISettings>) {}
+
+    /// レート制限を反映する（Noneの場合は環境変数の既定値に戻す）
+    fn update_rate_limit(
+        &self,
+        _provider_type: &ProviderType,
+        _settings: Option<&RateLimitSettings>,
+    ) {
+    }
+
+    /// 通信の設定を反映する（Noneの場合は環境変数の設定に戻す）
+    /// 設定からHTTPクライアントを作れない場合（不正なプロキシや証明書など）は反映せずにエラーを返す
+    fn update_http_client(
+        &self,
+        _settings: Option<&HttpClientSettings>,
+    ) -> Result<(), ApplicationError> {
+        Ok(())
+    }
+}
+
+/// 秘密の設定（APIキーなど）を保存する前に暗号化するtrait
+/// 暗号化の鍵はDBとは別に管理し、DBのファイルだけでは復号できないようにする
+pub trait SecretCipher: Send + Sync {
+    fn encrypt(&self, plaintext: &str) -> Result<String, ApplicationError>;
+
+    /// 復号する（暗号化される前に保存された平文はそのまま返す）
+    fn decrypt(&self, value: &str) -> Result<String, ApplicationError>;
+
+    /// 平文または現在の鍵以外で暗号化された値か
+    fn needs_reencryption(&self, value: &str) -> bool;
+
+    /// 新しい鍵を作成し、以降の暗号化に使う（確定するまで古い鍵も復号に使える）
+    fn begin_key_rotation(&self) -> Result<(), ApplicationError>;
+
+    /// 新しい鍵を現在の鍵として確定する
+    fn commit_key_rotation(&self) -> Result<(), ApplicationError>;
+
+    /// 確定していない新しい鍵を破棄する
+    fn abort_key_rotation(&self) -> Result<(), ApplicationError>;
+}
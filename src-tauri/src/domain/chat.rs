@@ -1,20 +1,191 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
 
 use crate::common::errors::ApplicationError;
+use crate::domain::app_setting::ProviderCredential;
+use crate::domain::comparing_prompt::ProviderType;
 
 #[derive(Clone, Debug)]
 pub struct ChatSettings {
     pub id: i32,
+    pub provider_type: ProviderType,
     pub user_prompt: String,
     pub system_prompt: String,
     pub model: String,
     pub temperature: f32,
+    pub top_p: Option<f32>,
     pub max_tokens: Option<u16>,
     pub response_format: Option<String>,
+    pub sampling: SamplingParameters,
+    pub tools: Vec<ToolDefinition>,
+    pub tool_choice: Option<ToolChoice>,
+    pub credential: Option<ProviderCredential>, // Noneの場合は設定画面または環境変数のAPIキーを使う
+}
+
+/// モデルに提示するツール（関数）の定義
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: serde_json::Value, // JSON Schema
+}
+
+/// ツールの選択方針
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ToolChoice {
+    /// モデルがツールを使うかどうかを選択する
+    Auto,
+    /// ツールを使わない
+    None,
+    /// 指定したツールを必ず使う
+    Function { name: String },
+}
+
+/// モデルが行ったツール呼び出し
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String, // モデルが生成したJSON文字列（不正なJSONの場合もある）
+}
+
+/// 複数ターンの会話のメッセージ（システムプロンプトはChatSettingsで指定する）
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "role", rename_all = "camelCase")]
+pub enum ChatMessage {
+    User {
+        content: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Assistant {
+        content: Option<String>,
+        tool_calls: Vec<ToolCall>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Tool {
+        tool_call_id: String,
+        name: String,
+        content: String,
+    },
+}
+
+/// 1回のチャットでのモデルの回答
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatAnswer {
+    pub content: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+impl ChatAnswer {
+    /// 履歴に保存して比較するためのレスポンス文字列に変換する
+    /// ツール呼び出しがある場合は、呼び出したツール名と引数をJSONで表現する
+    /// （呼び出しIDは実行ごとに変わるため含めない）
+    pub fn into_response(self) -> Result<String, ApplicationError> {
+        if self.tool_calls.is_empty() {
+            return self.content.ok_or(ApplicationError::EmptyResult);
+        }
+        let tool_calls: Vec<serde_json::Value> = self
+            .tool_calls
+            .into_iter()
+            .map(|tool_call| {
+                // 引数はJSONとしてパースできればオブジェクトとして保持する
+                let arguments = serde_json::from_str(&tool_call.arguments)
+                    .unwrap_or(serde_json::Value::String(tool_call.arguments));
+                serde_json::json!({ "name": tool_call.name, "arguments": arguments })
+            })
+            .collect();
+        let response = serde_json::json!({ "content": self.content, "toolCalls": tool_calls });
+        serde_json::to_string(&response).map_err(|e| ApplicationError::ParseError(e.to_string()))
+    }
+}
+
+/// model, temperature, top_p, max_tokens以外のサンプリングパラメータ
+/// 未指定(None)の場合はプロバイダーのデフォルト値を使う
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingParameters {
+    pub frequency_penalty: Option<f32>,
+    pub presence_penalty: Option<f32>,
+    pub stop: Option<Vec<String>>,
+    pub seed: Option<i64>,
+    pub logit_bias: Option<BTreeMap<String, i32>>, // トークンID -> バイアス
+    pub user: Option<String>,
+}
+
+/// プロバイダーによって対応状況が異なるパラメータ
+#[derive(Clone, Debug, Display, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum SamplingParameter {
+    TopP,
+    FrequencyPenalty,
+    PresencePenalty,
+    Stop,
+    Seed,
+    LogitBias,
+    User,
+}
+
+impl SamplingParameters {
+    /// 指定されているパラメータの一覧
+    pub fn specified(&self) -> Vec<SamplingParameter> {
+        let mut specified = Vec::new();
+        if self.frequency_penalty.is_some() {
+            specified.push(SamplingParameter::FrequencyPenalty);
+        }
+        if self.presence_penalty.is_some() {
+            specified.push(SamplingParameter::PresencePenalty);
+        }
+        if self.stop.is_some() {
+            specified.push(SamplingParameter::Stop);
+        }
+        if self.seed.is_some() {
+            specified.push(SamplingParameter::Seed);
+        }
+        if self.logit_bias.is_some() {
+            specified.push(SamplingParameter::LogitBias);
+        }
+        if self.user.is_some() {
+            specified.push(SamplingParameter::User);
+        }
+        specified
+    }
 }
 
 // traitでasyncが使えない問題の対処
 #[async_trait]
 pub trait AIChat: Send + Sync {
     async fn do_chat(&self, settings: &ChatSettings) -> Result<String, ApplicationError>;
+
+    /// 同じ設定でn回サンプリングし、完了した回答から順にanswersに追加する
+    /// 途中でキャンセルされた（futureが破棄された）場合も、追加済みの回答は呼び出し元に残る
+    /// 複数回答の生成に対応していないプロバイダー向けに、デフォルトではdo_chatを繰り返し呼び出す
+    async fn do_chat_samples(
+        &self,
+        settings: &ChatSettings,
+        n: u8,
+        answers: &mut Vec<String>,
+    ) -> Result<(), ApplicationError> {
+        for _ in 0..n {
+            answers.push(self.do_chat(settings).await?);
+        }
+        Ok(())
+    }
+
+    /// 会話の続きを1ターン分生成する
+    /// ツール呼び出しに対応していないプロバイダー向けに、デフォルトではエラーを返す
+    async fn do_chat_messages(
+        &self,
+        _settings: &ChatSettings,
+        _messages: &[ChatMessage],
+    ) -> Result<ChatAnswer, ApplicationError> {
+        Err(ApplicationError::UnknownError(
+            "multi-turn chat is not supported".to_string(),
+        ))
+    }
 }
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 
 use crate::common::errors::ApplicationError;
+use crate::domain::chat::{SamplingParameter, SamplingParameters, ToolChoice, ToolDefinition};
 
 #[derive(Clone, Debug)]
 pub struct ComparingPromptSettingModel {
@@ -18,6 +19,28 @@ pub struct ComparingPromptSettingVersionModel {
     pub setting_id: i32,
     pub version: i32,
     pub system_prompt: String,
+    pub tools: Vec<ToolDefinition>,
+    pub tool_choice: Option<ToolChoice>,
+    pub tool_scripts: Vec<ToolScript>,
+}
+
+/// ツールの模擬実行結果の定義
+/// casesを順に評価して最初に引数が一致したものの結果を返し、どれにも一致しなければdefaultを返す
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolScript {
+    pub name: String,
+    #[serde(default)]
+    pub cases: Vec<ToolScriptCase>,
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolScriptCase {
+    /// 引数との部分一致条件（指定したキーの値が全て一致すれば一致とみなす）
+    pub arguments: serde_json::Value,
+    pub result: serde_json::Value,
 }
 
 #[async_trait]
@@ -36,12 +59,47 @@ pub trait ComparingPromptSettingRepository: Send + Sync {
         &self,
         manager_id: i32,
     ) -> Result<i32, ApplicationError>;
+
+    async fn find_comparing_prompt_setting_version_by_id(
+        &self,
+        id: i32,
+    ) -> Result<ComparingPromptSettingVersionModel, ApplicationError>;
+
+    async fn update_comparing_prompt_setting_version_tools(
+        &self,
+        id: i32,
+        tools: &[ToolDefinition],
+        tool_choice: Option<&ToolChoice>,
+        tool_scripts: &[ToolScript],
+    ) -> Result<(), ApplicationError>;
+
+    /// 設定を論理削除する（設定のバージョン・履歴・ベースラインも取得されなくなる）
+    async fn logical_delete_comparing_prompt_setting(
+        &self,
+        id: i32,
+    ) -> Result<(), ApplicationError>;
+
+    /// 設定の論理削除を取り消す
+    /// 削除されていない場合と、マネージャーが削除されている場合はValidationErrorを返す
+    async fn restore_comparing_prompt_setting(&self, id: i32) -> Result<(), ApplicationError>;
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug, EnumString, Display, PartialEq)]
 pub enum ProviderType {
     OpenAI,
     Gemini,
+    AzureOpenAI,
+}
+
+impl ProviderType {
+    /// プロバイダーのAPIがパラメータに対応しているかどうか
+    pub fn supports(&self, _parameter: &SamplingParameter) -> bool {
+        match self {
+            ProviderType::OpenAI | ProviderType::AzureOpenAI => true,
+            // Geminiのクライアントは未実装なので、どのパラメータも送れない
+            ProviderType::Gemini => false,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -54,6 +112,38 @@ pub struct ComparingPromptSettingRunModel {
     pub temperature: f64,
     pub max_tokens: Option<i32>,
     pub response_format: Option<String>,
+    pub repetitions: i32, // バージョンごとのサンプリング回数
+    pub top_p: Option<f64>,
+    pub sweep_id: Option<i32>, // スイープから展開された実行の場合のみ設定される
+    pub sampling: SamplingParameters,
+    pub credential_profile_id: Option<i32>, // 実行に使った認証情報のプロファイル
+}
+
+/// スイープ対象のパラメータの値の指定方法
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SweepValues {
+    /// 値を列挙する
+    List { values: Vec<f64> },
+    /// startからendまでstep刻みで展開する（endを含む）
+    Range { start: f64, end: f64, step: f64 },
+}
+
+/// パラメータスイープの定義
+/// 指定されたパラメータの直積が実行設定として展開される
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepDefinition {
+    pub temperature: SweepValues,
+    pub top_p: Option<SweepValues>,
+    pub max_tokens: Option<SweepValues>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComparingPromptSweepModel {
+    pub id: i32,
+    pub manager_id: i32,
+    pub definition: SweepDefinition,
 }
 
 #[async_trait]
@@ -67,4 +157,122 @@ pub trait ComparingPromptRunRepository: Send + Sync {
         &self,
         param: ComparingPromptSettingRunModel,
     ) -> Result<i32, ApplicationError>;
+
+    async fn find_comparing_prompt_sweep_by_id(
+        &self,
+        id: i32,
+    ) -> Result<ComparingPromptSweepModel, ApplicationError>;
+
+    async fn find_comparing_prompt_runs_by_sweep_id(
+        &self,
+        sweep_id: i32,
+    ) -> Result<Vec<ComparingPromptSettingRunModel>, ApplicationError>;
+
+    /// スイープ定義と展開された実行をまとめて登録する
+    /// 戻り値はスイープIDと、runsと同じ順序の実行ID
+    async fn create_comparing_prompt_sweep(
+        &self,
+        manager_id: i32,
+        definition: &SweepDefinition,
+        runs: Vec<ComparingPromptSettingRunModel>,
+    ) -> Result<(i32, Vec<i32>), ApplicationError>;
+
+    async fn find_comparing_prompt_run_history_by_id(
+        &self,
+        id: i32,
+    ) -> Result<ComparingPromptRunHistoryModel, ApplicationError>;
+
+    async fn find_comparing_prompt_run_histories_by_run_id(
+        &self,
+        run_id: i32,
+    ) -> Result<Vec<ComparingPromptRunHistoryModel>, ApplicationError>;
+
+    async fn create_comparing_prompt_run_history(
+        &self,
+        target: &RunHistoryTarget,
+        sample_index: i32,
+        response: &str,
+        transcript: Option<&str>,
+        cache_hit: bool,
+    ) -> Result<i32, ApplicationError>;
+
+    /// キャンセルされた実行の履歴を登録する（回答は空）
+    async fn create_cancelled_comparing_prompt_run_history(
+        &self,
+        target: &RunHistoryTarget,
+        sample_index: i32,
+        transcript: Option<&str>,
+    ) -> Result<i32, ApplicationError>;
+
+    /// ジョブの項目から登録された履歴を削除し、削除した件数を返す
+    /// 中断された項目を再実行する前に、途中まで登録された履歴を消すために使う（ベースラインに使われている履歴は残す）
+    async fn delete_comparing_prompt_run_histories_by_job_item_id(
+        &self,
+        job_item_id: i32,
+    ) -> Result<u64, ApplicationError>;
+}
+
+/// 履歴を登録する実行と設定のバージョン
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunHistoryTarget {
+    pub run_id: i32,
+    pub version_id: i32,
+    pub job_item_id: Option<i32>, // ジョブの項目から実行した場合のみ設定される
+}
+
+#[derive(Clone, Debug)]
+pub struct ComparingPromptRunHistoryModel {
+    pub id: i32,
+    pub run_id: i32,
+    pub version_id: i32,
+    pub setting_id: i32, // version経由で取得した設定ID
+    pub sample_index: i32,
+    pub response: String,
+    pub transcript: Option<String>, // ツールの模擬実行を含む会話の全体（JSON）
+    pub cache_hit: bool,            // キャッシュから返した回答か（APIの利用料は発生していない）
+    pub cancelled: bool,            // 回答が返る前にキャンセルされたか（回答は空）
+}
+
+/// ベースラインとの差分（ドリフト）の判定方法
+#[derive(Clone, Deserialize, Serialize, Debug, EnumString, Display, PartialEq)]
+pub enum DriftMeasure {
+    /// 完全一致しなければドリフトとみなす
+    Exact,
+    /// 空白と大文字小文字を正規化して一致しなければドリフトとみなす
+    Normalized,
+    /// embeddingのコサイン類似度が閾値未満ならドリフトとみなす
+    Semantic,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComparingPromptBaselineModel {
+    pub id: i32,
+    pub setting_id: i32,
+    pub history_id: i32,
+    pub user_prompt: String, // 入力（データセット行に相当）
+    pub response: String,    // 承認時点のレスポンスのスナップショット
+    pub drift_measure: DriftMeasure,
+    pub threshold: Option<f64>, // Semanticの場合のみ使用する
+}
+
+#[async_trait]
+pub trait ComparingPromptBaselineRepository: Send + Sync {
+    async fn find_comparing_prompt_baselines_by_setting_id(
+        &self,
+        setting_id: i32,
+    ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError>;
+
+    async fn find_comparing_prompt_baselines_by_manager_id_and_user_prompt(
+        &self,
+        manager_id: i32,
+        user_prompt: &str,
+    ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError>;
+
+    /// 設定と入力の組み合わせにつき1件のみ保持するので、既存があれば上書きする
+    async fn upsert_comparing_prompt_baseline(
+        &self,
+        param: ComparingPromptBaselineModel,
+    ) -> Result<i32, ApplicationError>;
+
+    async fn delete_comparing_prompt_baseline(&self, id: i32) -> Result<(), ApplicationError>;
 }
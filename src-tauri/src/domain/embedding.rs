@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::common::errors::ApplicationError;
+
+// traitでasyncが使えない問題の対処
+#[async_trait]
+pub trait AIEmbedding: Send + Sync {
+    /// 入力テキストをそれぞれベクトルに変換する（入力と同じ順序で返す）
+    async fn embed(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>, ApplicationError>;
+}
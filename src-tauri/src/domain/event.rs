@@ -0,0 +1,7 @@
+use serde::Serialize;
+
+/// フロントエンドにイベントを通知するtrait
+/// 通知は進捗の表示のためのもので、失敗しても処理は継続する
+pub trait EventEmitter: Send + Sync {
+    fn emit<T: Serialize + Clone>(&self, event: &str, payload: T);
+}
@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::common::errors::ApplicationError;
+
+/// バックグラウンドで実行するジョブの種類
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+pub enum JobType {
+    /// 実行をマネージャーの全ての設定の現在のバージョンで実行する
+    ComparingPromptRun,
+    /// スイープから展開された全ての実行を実行する
+    ComparingPromptSweep,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed, // 失敗した項目が1つでもある場合
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+pub enum JobItemStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobModel {
+    pub id: i32,
+    pub job_type: JobType,
+    pub payload: String, // JSON
+    pub status: JobStatus,
+    pub total_items: i32,
+    pub completed_items: i32,
+    pub failed_items: i32,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobItemModel {
+    pub id: i32,
+    pub job_id: i32,
+    pub item_index: i32,
+    pub payload: String, // JSON
+    pub status: JobItemStatus,
+    pub result: Option<String>, // JSON
+    pub error: Option<String>,
+}
+
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    /// ジョブと項目をまとめて登録する（ジョブはQueuedで登録される）
+    async fn create_job(
+        &self,
+        job_type: &JobType,
+        payload: &str,
+        items: &[String],
+    ) -> Result<i32, ApplicationError>;
+
+    async fn find_job_by_id(&self, id: i32) -> Result<JobModel, ApplicationError>;
+
+    /// ジョブを新しい順に取得する（statusが指定された場合はそのステータスのみ）
+    async fn find_jobs(
+        &self,
+        status: Option<&JobStatus>,
+    ) -> Result<Vec<JobModel>, ApplicationError>;
+
+    async fn find_job_items_by_job_id(
+        &self,
+        job_id: i32,
+    ) -> Result<Vec<JobItemModel>, ApplicationError>;
+
+    /// 最も古いQueuedのジョブをRunningにして返す
+    /// 複数のワーカーが同じジョブを取得しないよう、ステータスを条件に更新する
+    async fn claim_next_job(&self) -> Result<Option<JobModel>, ApplicationError>;
+
+    async fn start_job_item(&self, item_id: i32) -> Result<(), ApplicationError>;
+
+    /// 項目の結果（成功時はresult、失敗時はerror）を保存し、ジョブの進捗を更新する
+    async fn finish_job_item(
+        &self,
+        item_id: i32,
+        result: Result<String, String>,
+    ) -> Result<(), ApplicationError>;
+
+    async fn finish_job(
+        &self,
+        id: i32,
+        status: &JobStatus,
+        error: Option<&str>,
+    ) -> Result<(), ApplicationError>;
+
+    /// アプリの終了で中断されたジョブをQueuedに、実行中だった項目をPendingに戻し、戻したジョブ数を返す
+    async fn resume_interrupted_jobs(&self) -> Result<u64, ApplicationError>;
+}
@@ -0,0 +1,324 @@
+use async_trait::async_trait;
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::ProviderType;
+
+/// モデルが対応している機能と料金（料金は100万トークンあたりのUSD）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelCapabilities {
+    pub vision: bool,
+    pub json_mode: bool,
+    pub tools: bool,
+    pub context_window: Option<i32>,
+    pub input_price: Option<f64>,
+    pub output_price: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelCatalogModel {
+    pub id: i32,
+    pub provider_type: ProviderType,
+    pub model: String,
+    /// 組み込みのマニフェストに含まれるモデルか（falseの場合capabilitiesは不明）
+    pub known: bool,
+    pub capabilities: ModelCapabilities,
+}
+
+/// プロバイダーのAPIから取得したモデル
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListedModel {
+    pub model: String,
+    /// Azure OpenAIのデプロイメントのように、別名で公開されている場合の元のモデル名
+    pub base_model: Option<String>,
+}
+
+impl ListedModel {
+    pub fn new(model: impl Into<String>) -> Self {
+        ListedModel {
+            model: model.into(),
+            base_model: None,
+        }
+    }
+}
+
+impl ModelCatalogModel {
+    /// プロバイダーの一覧に含まれるモデル名から、マニフェストの対応機能を付与して作成する
+    pub fn from_listed(provider_type: ProviderType, model: String) -> Self {
+        let capabilities = builtin_capabilities(&provider_type, &model);
+        ModelCatalogModel::with_capabilities(provider_type, model, capabilities)
+    }
+
+    /// デプロイメント名で公開されているモデルは、デプロイされた元のモデルの対応機能を付与する
+    /// デプロイされているのはOpenAIのモデルなので、OpenAIのマニフェストを使う
+    pub fn from_deployment(
+        provider_type: ProviderType,
+        deployment: String,
+        base_model: &str,
+    ) -> Self {
+        let capabilities = builtin_capabilities(&ProviderType::OpenAI, base_model);
+        ModelCatalogModel::with_capabilities(provider_type, deployment, capabilities)
+    }
+
+    fn with_capabilities(
+        provider_type: ProviderType,
+        model: String,
+        capabilities: Option<ModelCapabilities>,
+    ) -> Self {
+        ModelCatalogModel {
+            id: 0,
+            known: capabilities.is_some(),
+            capabilities: capabilities.unwrap_or_default(),
+            provider_type,
+            model,
+        }
+    }
+}
+
+/// (プロバイダー, モデル名の接頭辞, vision, json_mode, tools, context_window, 入力料金, 出力料金)
+type ManifestEntry = (ProviderType, &'static str, bool, bool, bool, i32, f64, f64);
+
+/// 組み込みのモデルの対応機能のマニフェスト
+/// 最も長く一致した接頭辞を使うので、日付付きのモデル名にも対応する
+const BUILTIN_MANIFEST: &[ManifestEntry] = &[
+    (
+        ProviderType::OpenAI,
+        "gpt-4o",
+        true,
+        true,
+        true,
+        128_000,
+        2.5,
+        10.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4o-mini",
+        true,
+        true,
+        true,
+        128_000,
+        0.15,
+        0.6,
+    ),
+    (
+        ProviderType::OpenAI,
+        "o1",
+        true,
+        true,
+        true,
+        200_000,
+        15.0,
+        60.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "o1-mini",
+        false,
+        false,
+        false,
+        128_000,
+        3.0,
+        12.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "o1-preview",
+        false,
+        false,
+        false,
+        128_000,
+        15.0,
+        60.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "o3-mini",
+        false,
+        true,
+        true,
+        200_000,
+        1.1,
+        4.4,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4-turbo",
+        true,
+        true,
+        true,
+        128_000,
+        10.0,
+        30.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4-1106",
+        false,
+        true,
+        true,
+        128_000,
+        10.0,
+        30.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4-0125",
+        false,
+        true,
+        true,
+        128_000,
+        10.0,
+        30.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4-vision",
+        true,
+        false,
+        false,
+        128_000,
+        10.0,
+        30.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4-32k",
+        false,
+        false,
+        true,
+        32_768,
+        60.0,
+        120.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-4",
+        false,
+        false,
+        true,
+        8_192,
+        30.0,
+        60.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-3.5-turbo",
+        false,
+        true,
+        true,
+        16_385,
+        0.5,
+        1.5,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-3.5-turbo-0613",
+        false,
+        false,
+        true,
+        4_096,
+        1.5,
+        2.0,
+    ),
+    (
+        ProviderType::OpenAI,
+        "gpt-3.5-turbo-0301",
+        false,
+        false,
+        false,
+        4_096,
+        1.5,
+        2.0,
+    ),
+    (
+        ProviderType::Gemini,
+        "gemini-pro",
+        false,
+        false,
+        true,
+        30_720,
+        0.5,
+        1.5,
+    ),
+    (
+        ProviderType::Gemini,
+        "gemini-pro-vision",
+        true,
+        false,
+        false,
+        12_288,
+        0.5,
+        1.5,
+    ),
+    (
+        ProviderType::Gemini,
+        "gemini-1.5-pro",
+        true,
+        true,
+        true,
+        2_097_152,
+        1.25,
+        5.0,
+    ),
+    (
+        ProviderType::Gemini,
+        "gemini-1.5-flash",
+        true,
+        true,
+        true,
+        1_048_576,
+        0.075,
+        0.3,
+    ),
+];
+
+/// 組み込みのマニフェストからモデルの対応機能を返す（マニフェストに無いモデルはNone）
+pub fn builtin_capabilities(
+    provider_type: &ProviderType,
+    model: &str,
+) -> Option<ModelCapabilities> {
+    // Geminiのモデル一覧は"models/gemini-pro"の形式で返る
+    let model = model.strip_prefix("models/").unwrap_or(model);
+    BUILTIN_MANIFEST
+        .iter()
+        .filter(|entry| &entry.0 == provider_type && model.starts_with(entry.1))
+        .max_by_key(|entry| entry.1.len())
+        .map(
+            |&(_, _, vision, json_mode, tools, context_window, input_price, output_price)| {
+                ModelCapabilities {
+                    vision,
+                    json_mode,
+                    tools,
+                    context_window: Some(context_window),
+                    input_price: Some(input_price),
+                    output_price: Some(output_price),
+                }
+            },
+        )
+}
+
+// traitでasyncが使えない問題の対処
+#[async_trait]
+pub trait AIModelList: Send + Sync {
+    /// プロバイダーのAPIから利用可能なモデルの一覧を取得する
+    async fn list_models(
+        &self,
+        provider_type: &ProviderType,
+    ) -> Result<Vec<ListedModel>, ApplicationError>;
+}
+
+#[async_trait]
+pub trait ModelCatalogRepository: Send + Sync {
+    /// プロバイダーを指定しない場合は全てのモデルを返す
+    async fn find_model_catalog(
+        &self,
+        provider_type: Option<&ProviderType>,
+    ) -> Result<Vec<ModelCatalogModel>, ApplicationError>;
+
+    /// プロバイダーのモデルを全て入れ替える
+    async fn replace_model_catalog(
+        &self,
+        provider_type: &ProviderType,
+        models: Vec<ModelCatalogModel>,
+    ) -> Result<(), ApplicationError>;
+}
@@ -25,13 +25,80 @@ pub struct PromptManagerModel {
     pub tags: Vec<String>,
 }
 
+/// プロンプトマネージャーの並び順
+#[derive(Clone, Copy, Default, Deserialize, Serialize, Debug, EnumString, Display, PartialEq)]
+pub enum PromptManagerSort {
+    /// タイトルの昇順
+    Title,
+    /// 作成日時の新しい順（IDの降順）
+    #[default]
+    Created,
+    /// 最後に実行した日時の新しい順（実行していないマネージャーは最後）
+    LastRun,
+}
+
+/// タグの絞り込み方法
+#[derive(Clone, Copy, Default, Deserialize, Serialize, Debug, EnumString, Display, PartialEq)]
+pub enum TagMatch {
+    /// いずれかのタグを含む
+    #[default]
+    Any,
+    /// 全てのタグを含む
+    All,
+}
+
+/// ページの最後のマネージャーの並び順の値（次のページはこの値の後から取得する）
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "sort", rename_all = "camelCase")]
+pub enum PromptManagerCursor {
+    #[serde(rename_all = "camelCase")]
+    Title { title: String, id: i32 },
+    #[serde(rename_all = "camelCase")]
+    Created { id: i32 },
+    /// 実行はIDの順に作成されるので、最後の実行のIDで並べる（実行していない場合は0）
+    #[serde(rename_all = "camelCase")]
+    LastRun { last_run_id: i32, id: i32 },
+}
+
+impl PromptManagerCursor {
+    pub fn sort(&self) -> PromptManagerSort {
+        match self {
+            PromptManagerCursor::Title { .. } => PromptManagerSort::Title,
+            PromptManagerCursor::Created { .. } => PromptManagerSort::Created,
+            PromptManagerCursor::LastRun { .. } => PromptManagerSort::LastRun,
+        }
+    }
+}
+
+/// 削除されていないプロンプトマネージャーの検索条件（未指定の条件では絞り込まない）
+#[derive(Clone, Debug, PartialEq)]
+pub struct PromptManagerQuery {
+    pub title: Option<String>, // タイトルの部分一致
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    pub action_type: Option<ActionType>,
+    pub api_type: Option<APIType>,
+    pub sort: PromptManagerSort,
+    pub cursor: Option<PromptManagerCursor>,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PromptManagerPage {
+    pub managers: Vec<PromptManagerModel>,
+    pub next_cursor: Option<PromptManagerCursor>, // 次のページが無い場合はNone
+}
+
 #[async_trait]
 pub trait PromptManagerRepository: Send + Sync {
     async fn find_prompt_manager_by_id(
         &self,
         id: i32,
     ) -> Result<PromptManagerModel, ApplicationError>;
-    async fn find_all_prompt_managers(&self) -> Result<Vec<PromptManagerModel>, ApplicationError>;
+    async fn find_prompt_managers(
+        &self,
+        query: &PromptManagerQuery,
+    ) -> Result<PromptManagerPage, ApplicationError>;
     async fn create_prompt_manager(&self, title: &str) -> Result<i32, ApplicationError>;
     async fn logical_delete_prompt_manager(&self, id: i32) -> Result<(), ApplicationError>;
     async fn update_prompt_manager(
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::chat::ChatSettings;
+use crate::domain::comparing_prompt::ProviderType;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseCacheModel {
+    pub id: i32,
+    pub cache_key: String,
+    pub provider_type: ProviderType,
+    pub model: String,
+    pub responses: Vec<String>, // サンプルごとの回答
+}
+
+#[async_trait]
+pub trait ResponseCacheRepository: Send + Sync {
+    /// 期限切れでないキャッシュを取得する
+    async fn find_response_cache(
+        &self,
+        cache_key: &str,
+    ) -> Result<Option<ResponseCacheModel>, ApplicationError>;
+
+    /// キャッシュを保存する（同じキーがある場合は上書きする）
+    async fn save_response_cache(
+        &self,
+        cache_key: &str,
+        provider_type: &ProviderType,
+        model: &str,
+        responses: &[String],
+        ttl_seconds: u32,
+    ) -> Result<(), ApplicationError>;
+
+    /// 条件に一致するキャッシュを削除し、削除した件数を返す
+    async fn delete_response_caches(
+        &self,
+        provider_type: Option<&ProviderType>,
+        model: Option<&str>,
+        expired_only: bool,
+    ) -> Result<u64, ApplicationError>;
+}
+
+/// 同じ入力から同じ回答が期待できる設定か（temperatureが0、またはseedが指定されている）
+pub fn is_cacheable(settings: &ChatSettings) -> bool {
+    settings.temperature == 0.0 || settings.sampling.seed.is_some()
+}
+
+/// プロバイダー、モデル、メッセージ、全てのサンプリングパラメータからキャッシュキーを作成する
+/// 未指定(null)の項目は取り除き、オブジェクトのキーはソートされた状態でハッシュ化する
+pub fn response_cache_key(
+    provider_type: &ProviderType,
+    settings: &ChatSettings,
+    repetitions: u8,
+) -> Result<String, ApplicationError> {
+    let to_error = |e: serde_json::Error| ApplicationError::ParseError(e.to_string());
+    let request = serde_json::json!({
+        "providerType": provider_type.to_string(),
+        "model": settings.model,
+        "messages": [
+            { "role": "system", "content": settings.system_prompt },
+            { "role": "user", "content": settings.user_prompt },
+        ],
+        "temperature": settings.temperature,
+        "topP": settings.top_p,
+        "maxTokens": settings.max_tokens,
+        "responseFormat": settings.response_format,
+        "sampling": serde_json::to_value(&settings.sampling).map_err(to_error)?,
+        "tools": serde_json::to_value(&settings.tools).map_err(to_error)?,
+        "toolChoice": serde_json::to_value(&settings.tool_choice).map_err(to_error)?,
+        "n": repetitions,
+    });
+    // serde_jsonのMapはBTreeMapなので、シリアライズするとキーはソートされる
+    let normalized = serde_json::to_string(&remove_nulls(request)).map_err(to_error)?;
+    Ok(format!("{:x}", Sha256::digest(normalized.as_bytes())))
+}
+
+fn remove_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| (k, remove_nulls(v)))
+            .collect(),
+        serde_json::Value::Array(values) => values.into_iter().map(remove_nulls).collect(),
+        value => value,
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::common::errors::ApplicationError;
+
+/// 検索で一致した項目の種類
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+pub enum SearchHitKind {
+    /// マネージャーのタイトル
+    Manager,
+    /// 設定のバージョンのシステムプロンプト
+    Version,
+    /// 実行のユーザープロンプト
+    Run,
+    /// 実行の履歴の回答
+    Response,
+}
+
+/// 抜粋の一部（highlightedは検索語に一致した部分）
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+/// 検索で一致した項目と、項目を持つマネージャー、バージョン、実行
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHitModel {
+    pub kind: SearchHitKind,
+    pub manager_id: i32,
+    pub manager_title: String,
+    pub setting_id: Option<i32>,
+    pub version_id: Option<i32>,
+    pub version: Option<i32>,
+    pub run_id: Option<i32>,
+    pub snippet: Vec<SnippetPart>,
+    pub score: f64, // 小さいほど関連度が高い（bm25）
+}
+
+#[async_trait]
+pub trait SearchRepository: Send + Sync {
+    /// 全文検索の索引から関連度の高い順に取得する（削除されたマネージャーと設定は含まない）
+    /// queryはFTS5のMATCHの構文で指定する
+    async fn search(
+        &self,
+        query: &str,
+        kinds: &[SearchHitKind],
+        limit: u64,
+    ) -> Result<Vec<SearchHitModel>, ApplicationError>;
+}
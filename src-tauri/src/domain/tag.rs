@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use crate::common::errors::ApplicationError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagModel {
+    pub id: i32,
+    pub value: String,
+    pub usage_count: i32, // タグが付いている削除されていないマネージャーの数
+}
+
+/// タグはマネージャーの更新時に作成されるので、ここでは作成しない
+/// 全ての操作はトランザクション内で実行する
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    /// 値の昇順で取得する
+    async fn find_tags(&self) -> Result<Vec<TagModel>, ApplicationError>;
+
+    /// 同じ値のタグがある場合はValidationErrorを返す（統合はmerge_tagsを使う）
+    async fn rename_tag(&self, id: i32, value: &str) -> Result<(), ApplicationError>;
+
+    /// 統合元のタグの付いたマネージャーに統合先のタグを付け、統合元のタグを削除する
+    async fn merge_tags(&self, source_ids: &[i32], target_id: i32) -> Result<(), ApplicationError>;
+
+    /// タグを全てのマネージャーから外して削除する
+    async fn delete_tag(&self, id: i32) -> Result<(), ApplicationError>;
+
+    /// どのマネージャーにも付いていないタグを削除し、削除した件数を返す
+    /// 論理削除されたマネージャーに付いているタグは、復元できるように削除しない
+    async fn delete_unused_tags(&self) -> Result<u64, ApplicationError>;
+}
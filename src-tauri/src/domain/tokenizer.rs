@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+/// チャットの各メッセージに付与される制御トークン数（<|start|>{role}\n{content}<|end|>\n）
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// 回答の先頭に付与される制御トークン数（<|start|>assistant<|message|>）
+const TOKENS_PER_REPLY: usize = 3;
+
+/// トークン数を数えるためのエンコーディング
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TokenEncoding {
+    Cl100kBase,
+    O200kBase,
+}
+
+/// モデルのエンコーディングとコンテキストウィンドウ
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelTokenLimit {
+    pub encoding: TokenEncoding,
+    pub context_window: usize,
+}
+
+/// プロンプトのトークン数とコンテキストウィンドウの使用状況
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContextWindowUsage {
+    pub encoding: TokenEncoding,
+    pub prompt_tokens: usize,
+    pub max_tokens: Option<usize>,
+    pub context_window: usize,
+}
+
+impl ContextWindowUsage {
+    /// プロンプトと最大出力トークン数の合計がコンテキストウィンドウに収まるか
+    pub fn fits(&self) -> bool {
+        self.prompt_tokens + self.max_tokens.unwrap_or(0) <= self.context_window
+    }
+}
+
+/// ローカルでトークン数を数えるトークナイザー
+pub trait Tokenizer: Send + Sync {
+    /// モデルのエンコーディングとコンテキストウィンドウを返す（未知のモデルの場合はNone）
+    fn model_limit(&self, model: &str) -> Option<ModelTokenLimit>;
+
+    /// テキストのトークン数を数える
+    fn count_tokens(&self, encoding: TokenEncoding, text: &str) -> usize;
+
+    /// システムプロンプトとユーザープロンプトからなるチャットのトークン数を数える
+    /// メッセージごとの制御トークンを含む（ツール定義は含まないため概算になる）
+    fn count_chat_tokens(
+        &self,
+        encoding: TokenEncoding,
+        system_prompt: &str,
+        user_prompt: &str,
+    ) -> usize {
+        [("system", system_prompt), ("user", user_prompt)]
+            .iter()
+            .map(|(role, content)| {
+                TOKENS_PER_MESSAGE
+                    + self.count_tokens(encoding, role)
+                    + self.count_tokens(encoding, content)
+            })
+            .sum::<usize>()
+            + TOKENS_PER_REPLY
+    }
+
+    /// チャットのプロンプトがモデルのコンテキストウィンドウに収まるか確認する
+    /// 未知のモデルの場合は確認できないのでNoneを返す
+    fn context_window_usage(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        user_prompt: &str,
+        max_tokens: Option<usize>,
+    ) -> Option<ContextWindowUsage> {
+        let limit = self.model_limit(model)?;
+        Some(ContextWindowUsage {
+            encoding: limit.encoding,
+            prompt_tokens: self.count_chat_tokens(limit.encoding, system_prompt, user_prompt),
+            max_tokens,
+            context_window: limit.context_window,
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+use crate::common::errors::ApplicationError;
+
+/// 論理削除されたプロンプトマネージャー
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeletedPromptManagerModel {
+    pub id: i32,
+    pub title: String,
+    pub deleted_at: String,
+}
+
+#[async_trait]
+pub trait TrashRepository: Send + Sync {
+    /// 削除日時の新しい順に取得する
+    async fn find_deleted_prompt_managers(
+        &self,
+    ) -> Result<Vec<DeletedPromptManagerModel>, ApplicationError>;
+
+    /// 論理削除を取り消す（削除されていない場合はValidationErrorを返す）
+    async fn restore_prompt_manager(&self, id: i32) -> Result<(), ApplicationError>;
+
+    /// 論理削除されたマネージャーと、マネージャーに紐づく全てのデータを物理削除する
+    /// （タグの紐付け、設定とバージョン、実行と履歴、ベースライン、スイープなど）
+    /// 削除されていない場合はValidationErrorを返す
+    async fn purge_prompt_manager(&self, id: i32) -> Result<(), ApplicationError>;
+}
@@ -1,3 +1,8 @@
 pub mod chat;
 pub mod core;
+pub mod embedding;
+pub mod event;
+pub mod mock_chat;
+pub mod model_list;
 pub mod repository;
+pub mod tokenizer;
@@ -1,53 +1,195 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use async_openai::error::OpenAIError;
 use async_openai::types::{
-    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-    ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs,
+    ChatCompletionFunctions, ChatCompletionMessageToolCall, ChatCompletionNamedToolChoice,
+    ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestToolMessageArgs,
+    ChatCompletionRequestUserMessageArgs, ChatCompletionResponseFormat,
+    ChatCompletionResponseFormatType, ChatCompletionTool, ChatCompletionToolChoiceOption,
+    ChatCompletionToolType, CreateChatCompletionRequestArgs, FunctionCall, FunctionName, Stop,
 };
 use async_trait::async_trait;
 
 use crate::common::errors::ApplicationError;
-use crate::domain::chat::{AIChat, ChatSettings};
+use crate::domain::chat::{
+    AIChat, ChatAnswer, ChatMessage, ChatSettings, ToolCall, ToolChoice, ToolDefinition,
+};
+use crate::domain::comparing_prompt::ProviderType;
 use crate::infra::core::openai::AIClient;
 
+/// OpenAIとAzure OpenAIは同じAPIなので、プロバイダーに応じてクライアントを切り替える
 #[derive(Clone, Debug)]
-pub struct OpenAIChat<T>
+pub struct OpenAIChat<T, Z>
 where
     T: AIClient,
+    Z: AIClient,
 {
     client: Arc<T>,
+    azure_client: Arc<Z>,
 }
 
 #[async_trait]
-impl<T> AIChat for OpenAIChat<T>
+impl<T, Z> AIChat for OpenAIChat<T, Z>
 where
     T: AIClient,
+    Z: AIClient,
 {
     async fn do_chat(&self, settings: &ChatSettings) -> Result<String, ApplicationError> {
+        let messages = self.build_messages(settings.clone());
+        let answers = self.create_chat(settings, messages, None).await?;
+        answers
+            .into_iter()
+            .next()
+            .ok_or(ApplicationError::EmptyResult)?
+            .into_response()
+    }
+
+    /// OpenAIはnパラメータで1回のリクエストで複数の回答を生成できる
+    async fn do_chat_samples(
+        &self,
+        settings: &ChatSettings,
+        n: u8,
+        answers: &mut Vec<String>,
+    ) -> Result<(), ApplicationError> {
+        let messages = self.build_messages(settings.clone());
+        for answer in self.create_chat(settings, messages, Some(n)).await? {
+            answers.push(answer.into_response()?);
+        }
+        Ok(())
+    }
+
+    async fn do_chat_messages(
+        &self,
+        settings: &ChatSettings,
+        messages: &[ChatMessage],
+    ) -> Result<ChatAnswer, ApplicationError> {
+        let messages = self.build_conversation_messages(settings, messages)?;
+        let answers = self.create_chat(settings, messages, None).await?;
+        answers
+            .into_iter()
+            .next()
+            .ok_or(ApplicationError::EmptyResult)
+    }
+}
+
+impl<T, Z> OpenAIChat<T, Z>
+where
+    T: AIClient,
+    Z: AIClient,
+{
+    pub fn new(client: Arc<T>, azure_client: Arc<Z>) -> Self {
+        OpenAIChat {
+            client,
+            azure_client,
+        }
+    }
+
+    async fn create_chat(
+        &self,
+        settings: &ChatSettings,
+        messages: Vec<ChatCompletionRequestMessage>,
+        n: Option<u8>,
+    ) -> Result<Vec<ChatAnswer>, ApplicationError> {
         let mut req = CreateChatCompletionRequestArgs::default();
 
         req.model(&settings.model)
             .temperature(settings.temperature)
-            .messages(self.build_messages(settings.clone()));
+            .messages(messages);
 
+        if let Some(top_p) = settings.top_p {
+            req.top_p(top_p);
+        }
         if let Some(max_tokens) = &settings.max_tokens {
             req.max_tokens(*max_tokens);
         }
-        // if let Some(response_format) = &settings.response_format {
-        //     req.response_format(response_format);
-        // }
+        if let Some(n) = n {
+            req.n(n);
+        }
+
+        let sampling = &settings.sampling;
+        if let Some(frequency_penalty) = sampling.frequency_penalty {
+            req.frequency_penalty(frequency_penalty);
+        }
+        if let Some(presence_penalty) = sampling.presence_penalty {
+            req.presence_penalty(presence_penalty);
+        }
+        if let Some(stop) = &sampling.stop {
+            req.stop(Stop::StringArray(stop.clone()));
+        }
+        if let Some(seed) = sampling.seed {
+            req.seed(seed);
+        }
+        if let Some(logit_bias) = &sampling.logit_bias {
+            let logit_bias: HashMap<String, serde_json::Value> = logit_bias
+                .iter()
+                .map(|(token, bias)| (token.clone(), serde_json::Value::from(*bias)))
+                .collect();
+            req.logit_bias(logit_bias);
+        }
+        if let Some(user) = &sampling.user {
+            req.user(user);
+        }
+        if !settings.tools.is_empty() {
+            req.tools(self.build_tools(&settings.tools));
+        }
+        if let Some(tool_choice) = &settings.tool_choice {
+            req.tool_choice(build_tool_choice(tool_choice));
+        }
+        if let Some(response_format) = &settings.response_format {
+            req.response_format(build_response_format(response_format)?);
+        }
 
         let req = req
             .build()
             .map_err(|e| ApplicationError::UnknownError(e.to_string()))?;
 
-        match self.client.create_chat(req).await {
+        let credential = settings.credential.as_ref();
+        let res = match settings.provider_type {
+            ProviderType::OpenAI => {
+                self.client
+                    .create_chat_with_credential(req, credential)
+                    .await
+            }
+            ProviderType::AzureOpenAI => {
+                self.azure_client
+                    .create_chat_with_credential(req, credential)
+                    .await
+            }
+            // Geminiのクライアントは未実装なので、OpenAIの形式のリクエストは送らない
+            ProviderType::Gemini => {
+                return Err(ApplicationError::ValidationError(format!(
+                    "unsupported provider: {}",
+                    settings.provider_type
+                )))
+            }
+        };
+        match res {
             Ok(response) => {
-                if response.choices.is_empty() || response.choices[0].message.content.is_none() {
-                    Err(ApplicationError::EmptyResult)
-                } else {
-                    Ok(response.choices[0].message.content.clone().unwrap())
+                if response.choices.is_empty() {
+                    return Err(ApplicationError::EmptyResult);
                 }
+                // indexの順序で回答を返す
+                let mut choices = response.choices;
+                choices.sort_by_key(|choice| choice.index);
+                Ok(choices
+                    .into_iter()
+                    .map(|choice| ChatAnswer {
+                        content: choice.message.content,
+                        tool_calls: choice
+                            .message
+                            .tool_calls
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|tool_call| ToolCall {
+                                id: tool_call.id,
+                                name: tool_call.function.name,
+                                arguments: tool_call.function.arguments,
+                            })
+                            .collect(),
+                    })
+                    .collect())
             }
             Err(err) => {
                 println!("OpenAI chat error: {}", err);
@@ -55,14 +197,19 @@ where
             }
         }
     }
-}
 
-impl<T> OpenAIChat<T>
-where
-    T: AIClient,
-{
-    pub fn new(client: Arc<T>) -> Self {
-        OpenAIChat { client }
+    fn build_tools(&self, tools: &[ToolDefinition]) -> Vec<ChatCompletionTool> {
+        tools
+            .iter()
+            .map(|tool| ChatCompletionTool {
+                r#type: ChatCompletionToolType::Function,
+                function: ChatCompletionFunctions {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: tool.parameters.clone(),
+                },
+            })
+            .collect()
     }
 
     fn build_messages(&self, settings: ChatSettings) -> Vec<ChatCompletionRequestMessage> {
@@ -82,22 +229,218 @@ where
         ];
         messages
     }
+
+    /// システムプロンプトと会話の履歴からリクエストのメッセージを組み立てる
+    fn build_conversation_messages(
+        &self,
+        settings: &ChatSettings,
+        messages: &[ChatMessage],
+    ) -> Result<Vec<ChatCompletionRequestMessage>, ApplicationError> {
+        let to_error = |e: OpenAIError| ApplicationError::UnknownError(e.to_string());
+        let mut request_messages = vec![ChatCompletionRequestMessage::System(
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(settings.system_prompt.clone())
+                .build()
+                .map_err(to_error)?,
+        )];
+        for message in messages {
+            let request_message = match message {
+                ChatMessage::User { content } => ChatCompletionRequestMessage::User(
+                    ChatCompletionRequestUserMessageArgs::default()
+                        .content(content.clone())
+                        .build()
+                        .map_err(to_error)?,
+                ),
+                ChatMessage::Assistant {
+                    content,
+                    tool_calls,
+                } => {
+                    let mut args = ChatCompletionRequestAssistantMessageArgs::default();
+                    if let Some(content) = content {
+                        args.content(content.clone());
+                    }
+                    if !tool_calls.is_empty() {
+                        args.tool_calls(
+                            tool_calls
+                                .iter()
+                                .map(|tool_call| ChatCompletionMessageToolCall {
+                                    id: tool_call.id.clone(),
+                                    r#type: ChatCompletionToolType::Function,
+                                    function: FunctionCall {
+                                        name: tool_call.name.clone(),
+                                        arguments: tool_call.arguments.clone(),
+                                    },
+                                })
+                                .collect::<Vec<_>>(),
+                        );
+                    }
+                    ChatCompletionRequestMessage::Assistant(args.build().map_err(to_error)?)
+                }
+                ChatMessage::Tool {
+                    tool_call_id,
+                    content,
+                    ..
+                } => ChatCompletionRequestMessage::Tool(
+                    ChatCompletionRequestToolMessageArgs::default()
+                        .tool_call_id(tool_call_id.clone())
+                        .content(content.clone())
+                        .build()
+                        .map_err(to_error)?,
+                ),
+            };
+            request_messages.push(request_message);
+        }
+        Ok(request_messages)
+    }
+}
+
+fn build_tool_choice(tool_choice: &ToolChoice) -> ChatCompletionToolChoiceOption {
+    match tool_choice {
+        ToolChoice::Auto => ChatCompletionToolChoiceOption::Auto,
+        ToolChoice::None => ChatCompletionToolChoiceOption::None,
+        ToolChoice::Function { name } => {
+            ChatCompletionToolChoiceOption::Named(ChatCompletionNamedToolChoice {
+                r#type: ChatCompletionToolType::Function,
+                function: FunctionName { name: name.clone() },
+            })
+        }
+    }
+}
+
+/// response_formatの値（text / json_object）をリクエストの形式に変換する
+fn build_response_format(
+    response_format: &str,
+) -> Result<ChatCompletionResponseFormat, ApplicationError> {
+    let r#type: ChatCompletionResponseFormatType =
+        serde_json::from_value(serde_json::Value::from(response_format)).map_err(|_| {
+            ApplicationError::ValidationError(format!(
+                "unsupported response_format: {}",
+                response_format
+            ))
+        })?;
+    Ok(ChatCompletionResponseFormat { r#type })
 }
 
 #[cfg(test)]
 mod tests {
-    use async_openai::error::{ApiError, OpenAIError};
+    use async_openai::error::ApiError;
     use async_openai::types::{
         ChatChoice, ChatCompletionResponseMessage, CompletionUsage, CreateChatCompletionRequest,
-        CreateChatCompletionResponse, FinishReason, Role,
+        CreateChatCompletionResponse, CreateEmbeddingRequest, CreateEmbeddingResponse,
+        FinishReason, ListModelResponse, Role,
     };
     use async_trait::async_trait;
 
-    use crate::domain::chat::ChatSettings;
+    use crate::domain::chat::{ChatSettings, SamplingParameters};
     use crate::infra::core::openai::AIClient;
 
     use super::*;
 
+    #[tokio::test]
+    async fn test_do_chat_azure_openai() {
+        /// 自分の名前を回答し、受け取ったモデル名を記録する
+        struct MockNamedClient {
+            name: &'static str,
+            models: std::sync::Mutex<Vec<String>>,
+        }
+
+        #[async_trait]
+        impl AIClient for MockNamedClient {
+            async fn create_chat(
+                &self,
+                req: CreateChatCompletionRequest,
+            ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+                self.models.lock().unwrap().push(req.model.clone());
+                Ok(CreateChatCompletionResponse {
+                    id: "test".to_string(),
+                    object: "chat.completion".to_string(),
+                    created: 0,
+                    model: req.model,
+                    usage: None,
+                    choices: vec![ChatChoice {
+                        message: ChatCompletionResponseMessage {
+                            role: Role::Assistant,
+                            content: Some(self.name.to_string()),
+                            tool_calls: None,
+                            function_call: None, // NOTE: function_callが完全に廃止されたら削除する
+                        },
+                        finish_reason: Option::from(FinishReason::Stop),
+                        index: 0,
+                    }],
+                    system_fingerprint: None,
+                })
+            }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+        }
+
+        let client = |name: &'static str| {
+            Arc::new(MockNamedClient {
+                name,
+                models: Default::default(),
+            })
+        };
+        let mock_chat = OpenAIChat {
+            client: client("openai"),
+            azure_client: client("azure"),
+        };
+        let settings = ChatSettings {
+            id: 0,
+            provider_type: ProviderType::AzureOpenAI,
+            system_prompt: "System prompt".to_string(),
+            user_prompt: "User prompt".to_string(),
+            model: "prod-gpt4o".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            sampling: SamplingParameters::default(),
+            tools: vec![],
+            tool_choice: None,
+            credential: None,
+            max_tokens: None,
+            response_format: None,
+        };
+
+        // Azure OpenAIのクライアントにデプロイメント名をモデルとして渡す
+        let result = mock_chat.do_chat(&settings).await;
+        assert_eq!(result.unwrap(), "azure");
+        assert_eq!(
+            *mock_chat.azure_client.models.lock().unwrap(),
+            vec!["prod-gpt4o".to_string()]
+        );
+        assert!(mock_chat.client.models.lock().unwrap().is_empty());
+
+        let settings = ChatSettings {
+            provider_type: ProviderType::OpenAI,
+            model: "gpt-4o".to_string(),
+            ..settings
+        };
+        let result = mock_chat.do_chat(&settings).await;
+        assert_eq!(result.unwrap(), "openai");
+
+        // Geminiはどちらのクライアントにも送らない
+        let settings = ChatSettings {
+            provider_type: ProviderType::Gemini,
+            model: "gemini-pro".to_string(),
+            ..settings
+        };
+        let result = mock_chat.do_chat(&settings).await;
+        assert_eq!(
+            result.unwrap_err(),
+            ApplicationError::ValidationError("unsupported provider: Gemini".to_string())
+        );
+        assert_eq!(mock_chat.client.models.lock().unwrap().len(), 1);
+        assert_eq!(mock_chat.azure_client.models.lock().unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_do_chat() {
         struct MockOpenAIClient {}
@@ -131,17 +474,35 @@ mod tests {
                     system_fingerprint: None,
                 })
             }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
         }
 
         let mock_chat = OpenAIChat {
             client: Arc::new(MockOpenAIClient {}),
+            azure_client: Arc::new(MockOpenAIClient {}),
         };
         let settings = ChatSettings {
             id: 0,
+            provider_type: ProviderType::OpenAI,
             system_prompt: "System prompt".to_string(),
             user_prompt: "User prompt".to_string(),
             model: "gpt-4-1106-preview".to_string(),
             temperature: 0.0,
+            top_p: None,
+            sampling: SamplingParameters::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            credential: None,
             max_tokens: None,
             response_format: None,
         };
@@ -167,17 +528,35 @@ mod tests {
                     code: None,
                 }))
             }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
         }
 
         let mock_chat = OpenAIChat {
             client: Arc::new(MockOpenAIClient),
+            azure_client: Arc::new(MockOpenAIClient),
         };
         let settings = ChatSettings {
             id: 0,
+            provider_type: ProviderType::OpenAI,
             system_prompt: "System prompt".to_string(),
             user_prompt: "User prompt".to_string(),
             model: "gpt-4-1106-preview".to_string(),
             temperature: 0.0,
+            top_p: None,
+            sampling: SamplingParameters::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            credential: None,
             max_tokens: None,
             response_format: None,
         };
@@ -196,4 +575,394 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    async fn test_do_chat_samples() {
+        struct MockOpenAIClient;
+
+        #[async_trait]
+        impl AIClient for MockOpenAIClient {
+            async fn create_chat(
+                &self,
+                req: CreateChatCompletionRequest,
+            ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+                // nの数だけ逆順で回答を返す
+                let n = req.n.unwrap_or(1) as u32;
+                let choices = (0..n)
+                    .rev()
+                    .map(|index| ChatChoice {
+                        message: ChatCompletionResponseMessage {
+                            role: Role::Assistant,
+                            content: Some(format!("Answer {}", index)),
+                            tool_calls: None,
+                            function_call: None, // NOTE: function_callが完全に廃止されたら削除する
+                        },
+                        finish_reason: Option::from(FinishReason::Stop),
+                        index,
+                    })
+                    .collect();
+                Ok(CreateChatCompletionResponse {
+                    id: "test".to_string(),
+                    object: "chat.completion".to_string(),
+                    created: 0,
+                    model: "gpt-4-1106-preview".to_string(),
+                    usage: None,
+                    choices,
+                    system_fingerprint: None,
+                })
+            }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+        }
+
+        let mock_chat = OpenAIChat {
+            client: Arc::new(MockOpenAIClient),
+            azure_client: Arc::new(MockOpenAIClient),
+        };
+        let settings = ChatSettings {
+            id: 0,
+            provider_type: ProviderType::OpenAI,
+            system_prompt: "System prompt".to_string(),
+            user_prompt: "User prompt".to_string(),
+            model: "gpt-4-1106-preview".to_string(),
+            temperature: 0.7,
+            top_p: None,
+            sampling: SamplingParameters::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            credential: None,
+            max_tokens: None,
+            response_format: None,
+        };
+        let mut answers = Vec::new();
+        mock_chat
+            .do_chat_samples(&settings, 3, &mut answers)
+            .await
+            .unwrap();
+        assert_eq!(
+            answers,
+            vec![
+                "Answer 0".to_string(),
+                "Answer 1".to_string(),
+                "Answer 2".to_string()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_do_chat_sampling_parameters() {
+        struct MockOpenAIClient;
+
+        #[async_trait]
+        impl AIClient for MockOpenAIClient {
+            async fn create_chat(
+                &self,
+                req: CreateChatCompletionRequest,
+            ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+                // サンプリングパラメータがリクエストに設定されていること
+                assert_eq!(req.top_p, Some(0.9));
+                assert_eq!(req.frequency_penalty, Some(0.5));
+                assert_eq!(req.presence_penalty, None);
+                assert_eq!(req.stop, Some(Stop::StringArray(vec!["END".to_string()])));
+                assert_eq!(req.seed, Some(42));
+                assert_eq!(
+                    req.logit_bias.unwrap().get("50256"),
+                    Some(&serde_json::Value::from(-100))
+                );
+                assert_eq!(req.user, Some("test_user".to_string()));
+                assert_eq!(
+                    req.response_format,
+                    Some(ChatCompletionResponseFormat {
+                        r#type: ChatCompletionResponseFormatType::JsonObject
+                    })
+                );
+                Ok(CreateChatCompletionResponse {
+                    id: "test".to_string(),
+                    object: "chat.completion".to_string(),
+                    created: 0,
+                    model: "gpt-4-1106-preview".to_string(),
+                    usage: None,
+                    choices: vec![ChatChoice {
+                        message: ChatCompletionResponseMessage {
+                            role: Role::Assistant,
+                            content: Some("Test message".to_string()),
+                            tool_calls: None,
+                            function_call: None, // NOTE: function_callが完全に廃止されたら削除する
+                        },
+                        finish_reason: Option::from(FinishReason::Stop),
+                        index: 0,
+                    }],
+                    system_fingerprint: None,
+                })
+            }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+        }
+
+        let mock_chat = OpenAIChat {
+            client: Arc::new(MockOpenAIClient),
+            azure_client: Arc::new(MockOpenAIClient),
+        };
+        let settings = ChatSettings {
+            id: 0,
+            provider_type: ProviderType::OpenAI,
+            system_prompt: "System prompt".to_string(),
+            user_prompt: "User prompt".to_string(),
+            model: "gpt-4-1106-preview".to_string(),
+            temperature: 0.0,
+            top_p: Some(0.9),
+            sampling: SamplingParameters {
+                frequency_penalty: Some(0.5),
+                presence_penalty: None,
+                stop: Some(vec!["END".to_string()]),
+                seed: Some(42),
+                logit_bias: Some([("50256".to_string(), -100)].into()),
+                user: Some("test_user".to_string()),
+            },
+            tools: Vec::new(),
+            tool_choice: None,
+            credential: None,
+            max_tokens: None,
+            response_format: Some("json_object".to_string()),
+        };
+        let result = mock_chat.do_chat(&settings).await;
+        assert_eq!(result.unwrap(), "Test message");
+
+        // 対応していないresponse_formatは送らずにエラーにする
+        let settings = ChatSettings {
+            response_format: Some("yaml".to_string()),
+            ..settings
+        };
+        let result = mock_chat.do_chat(&settings).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_do_chat_tool_calls() {
+        struct MockOpenAIClient;
+
+        #[async_trait]
+        impl AIClient for MockOpenAIClient {
+            async fn create_chat(
+                &self,
+                req: CreateChatCompletionRequest,
+            ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+                // ツール定義と選択方針がリクエストに設定されていること
+                let tools = req.tools.unwrap();
+                assert_eq!(tools.len(), 1);
+                assert_eq!(tools[0].function.name, "get_weather");
+                assert_eq!(
+                    req.tool_choice,
+                    Some(ChatCompletionToolChoiceOption::Named(
+                        ChatCompletionNamedToolChoice {
+                            r#type: ChatCompletionToolType::Function,
+                            function: FunctionName {
+                                name: "get_weather".to_string()
+                            },
+                        }
+                    ))
+                );
+                Ok(CreateChatCompletionResponse {
+                    id: "test".to_string(),
+                    object: "chat.completion".to_string(),
+                    created: 0,
+                    model: "gpt-4-1106-preview".to_string(),
+                    usage: None,
+                    choices: vec![ChatChoice {
+                        message: ChatCompletionResponseMessage {
+                            role: Role::Assistant,
+                            content: None,
+                            tool_calls: Some(vec![ChatCompletionMessageToolCall {
+                                id: "call_1".to_string(),
+                                r#type: ChatCompletionToolType::Function,
+                                function: FunctionCall {
+                                    name: "get_weather".to_string(),
+                                    arguments: r#"{"city": "Tokyo"}"#.to_string(),
+                                },
+                            }]),
+                            function_call: None, // NOTE: function_callが完全に廃止されたら削除する
+                        },
+                        finish_reason: Option::from(FinishReason::ToolCalls),
+                        index: 0,
+                    }],
+                    system_fingerprint: None,
+                })
+            }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+        }
+
+        let mock_chat = OpenAIChat {
+            client: Arc::new(MockOpenAIClient),
+            azure_client: Arc::new(MockOpenAIClient),
+        };
+        let settings = ChatSettings {
+            id: 0,
+            provider_type: ProviderType::OpenAI,
+            system_prompt: "System prompt".to_string(),
+            user_prompt: "User prompt".to_string(),
+            model: "gpt-4-1106-preview".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            sampling: SamplingParameters::default(),
+            tools: vec![ToolDefinition {
+                name: "get_weather".to_string(),
+                description: None,
+                parameters: serde_json::json!({ "type": "object" }),
+            }],
+            tool_choice: Some(ToolChoice::Function {
+                name: "get_weather".to_string(),
+            }),
+            max_tokens: None,
+            response_format: None,
+            credential: None,
+        };
+        // contentが無くてもツール呼び出しをレスポンスとして返す
+        let result = mock_chat.do_chat(&settings).await;
+        let response: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(
+            response,
+            serde_json::json!({
+                "content": null,
+                "toolCalls": [{ "name": "get_weather", "arguments": { "city": "Tokyo" } }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_do_chat_messages() {
+        struct MockOpenAIClient;
+
+        #[async_trait]
+        impl AIClient for MockOpenAIClient {
+            async fn create_chat(
+                &self,
+                req: CreateChatCompletionRequest,
+            ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+                // システムプロンプトに続いて会話の履歴がそのまま送られること
+                assert_eq!(req.messages.len(), 4);
+                assert!(matches!(
+                    req.messages[0],
+                    ChatCompletionRequestMessage::System(_)
+                ));
+                match &req.messages[2] {
+                    ChatCompletionRequestMessage::Assistant(message) => {
+                        let tool_calls = message.tool_calls.as_ref().unwrap();
+                        assert_eq!(tool_calls[0].id, "call_1");
+                        assert_eq!(tool_calls[0].function.name, "get_weather");
+                    }
+                    _ => panic!("assistant message expected"),
+                }
+                match &req.messages[3] {
+                    ChatCompletionRequestMessage::Tool(message) => {
+                        assert_eq!(message.tool_call_id, "call_1");
+                        assert_eq!(message.content.as_deref(), Some("sunny"));
+                    }
+                    _ => panic!("tool message expected"),
+                }
+                Ok(CreateChatCompletionResponse {
+                    id: "test".to_string(),
+                    object: "chat.completion".to_string(),
+                    created: 0,
+                    model: "gpt-4-1106-preview".to_string(),
+                    usage: None,
+                    choices: vec![ChatChoice {
+                        message: ChatCompletionResponseMessage {
+                            role: Role::Assistant,
+                            content: Some("It is sunny in Tokyo.".to_string()),
+                            tool_calls: None,
+                            function_call: None, // NOTE: function_callが完全に廃止されたら削除する
+                        },
+                        finish_reason: Option::from(FinishReason::Stop),
+                        index: 0,
+                    }],
+                    system_fingerprint: None,
+                })
+            }
+
+            async fn create_embedding(
+                &self,
+                _req: CreateEmbeddingRequest,
+            ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+
+            async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+                Err(OpenAIError::InvalidArgument("not used".to_string()))
+            }
+        }
+
+        let mock_chat = OpenAIChat {
+            client: Arc::new(MockOpenAIClient),
+            azure_client: Arc::new(MockOpenAIClient),
+        };
+        let settings = ChatSettings {
+            id: 0,
+            provider_type: ProviderType::OpenAI,
+            system_prompt: "System prompt".to_string(),
+            user_prompt: "User prompt".to_string(),
+            model: "gpt-4-1106-preview".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            sampling: SamplingParameters::default(),
+            tools: vec![],
+            tool_choice: None,
+            credential: None,
+            max_tokens: None,
+            response_format: None,
+        };
+        let messages = vec![
+            ChatMessage::User {
+                content: "User prompt".to_string(),
+            },
+            ChatMessage::Assistant {
+                content: None,
+                tool_calls: vec![ToolCall {
+                    id: "call_1".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: r#"{"city": "Tokyo"}"#.to_string(),
+                }],
+            },
+            ChatMessage::Tool {
+                tool_call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                content: "sunny".to_string(),
+            },
+        ];
+        let result = mock_chat.do_chat_messages(&settings, &messages).await;
+        assert_eq!(
+            result.unwrap(),
+            ChatAnswer {
+                content: Some("It is sunny in Tokyo.".to_string()),
+                tool_calls: vec![],
+            }
+        );
+    }
 }
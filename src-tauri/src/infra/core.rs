@@ -1,2 +1,7 @@
+pub mod azure_openai;
+pub mod cassette;
+pub mod http;
 pub mod openai;
+pub mod rate_limit;
 pub mod seaorm;
+pub mod secret;
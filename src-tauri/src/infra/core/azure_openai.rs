@@ -0,0 +1,281 @@
+use std::env;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use async_openai::config::AzureConfig;
+use async_openai::error::OpenAIError;
+use async_openai::types::{
+    CreateChatCompletionRequest, CreateChatCompletionResponse, CreateEmbeddingRequest,
+    CreateEmbeddingResponse, ListModelResponse, Model,
+};
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+
+use crate::domain::app_setting::{
+    ApiKeyUpdater, AzureOpenAISettings, ProviderCredential, RateLimitSettings,
+};
+use crate::domain::comparing_prompt::ProviderType;
+use crate::infra::core::http::SharedHttpClient;
+use crate::infra::core::openai::{
+    estimate_chat_tokens, estimate_embedding_tokens, AIClient, RateLimitedHttpClient,
+};
+use crate::infra::core::rate_limit::{RateLimitConfig, RateLimitEndpoint};
+
+/// 設定画面でAPIキーが保存されていない場合に使う環境変数
+const AZURE_OPENAI_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+
+/// Azure OpenAIのクライアント
+/// リクエストのモデル名をデプロイメント名として、デプロイメントのURLにapi-keyヘッダーを付けて送信する
+pub struct AzureOpenAIClient {
+    settings: RwLock<Option<AzureOpenAISettings>>,
+    api_key: RwLock<Option<String>>, // Noneの場合は環境変数のAPIキーを使う
+    http: RateLimitedHttpClient,
+}
+
+/// APIキーは出力しない
+impl fmt::Debug for AzureOpenAIClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AzureOpenAIClient")
+            .field("settings", &self.settings)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AIClient for AzureOpenAIClient {
+    async fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        self.create_chat_with_credential(request, None).await
+    }
+
+    async fn create_chat_with_credential(
+        &self,
+        request: CreateChatCompletionRequest,
+        credential: Option<&ProviderCredential>,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        let config = self.config(&request.model, credential)?;
+        // リクエストのモデル名はデプロイメント名なので、デプロイメントのモデルで見積もる
+        let model = self.model_for(&request.model)?;
+        let estimated_tokens = estimate_chat_tokens(&request, &model);
+        self.http
+            .post(
+                RateLimitEndpoint::Chat,
+                &config,
+                HeaderMap::new(),
+                "/chat/completions",
+                &request,
+                estimated_tokens,
+            )
+            .await
+    }
+
+    async fn create_embedding(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+        let config = self.config(&request.model, None)?;
+        let estimated_tokens = estimate_embedding_tokens(&request);
+        self.http
+            .post(
+                RateLimitEndpoint::Embedding,
+                &config,
+                HeaderMap::new(),
+                "/embeddings",
+                &request,
+                estimated_tokens,
+            )
+            .await
+    }
+
+    /// 設定されたデプロイメントをモデルの一覧として返す（設定されていない場合は空）
+    /// owned_byにはデプロイメントのモデル名を入れる
+    async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+        let deployments = self
+            .settings
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|settings| settings.deployments.clone())
+            .unwrap_or_default();
+        Ok(ListModelResponse {
+            object: "list".to_string(),
+            data: deployments
+                .into_iter()
+                .map(|item| Model {
+                    id: item.deployment,
+                    object: "model".to_string(),
+                    created: 0,
+                    owned_by: item.model,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl ApiKeyUpdater for AzureOpenAIClient {
+    fn update_api_key(&self, provider_type: &ProviderType, api_key: Option<&str>) {
+        if *provider_type != ProviderType::AzureOpenAI {
+            return;
+        }
+        *self.api_key.write().unwrap() = api_key.map(|api_key| api_key.to_string());
+    }
+
+    fn update_azure_openai_settings(&self, settings: Option<&AzureOpenAISettings>) {
+        *self.settings.write().unwrap() = settings.cloned();
+    }
+
+    fn update_rate_limit(
+        &self,
+        provider_type: &ProviderType,
+        settings: Option<&RateLimitSettings>,
+    ) {
+        if *provider_type != ProviderType::AzureOpenAI {
+            return;
+        }
+        self.http.update_rate_limit(settings);
+    }
+}
+
+impl AzureOpenAIClient {
+    pub fn new(rate_limit_config: RateLimitConfig, http_client: Arc<SharedHttpClient>) -> Self {
+        AzureOpenAIClient {
+            settings: RwLock::new(None),
+            api_key: RwLock::new(None),
+            http: RateLimitedHttpClient::new(rate_limit_config, http_client),
+        }
+    }
+
+    fn settings(&self) -> Result<AzureOpenAISettings, OpenAIError> {
+        self.settings
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| OpenAIError::InvalidArgument("Azure OpenAI is not configured".into()))
+    }
+
+    /// デプロイメントのモデル名を返す
+    fn model_for(&self, deployment: &str) -> Result<String, OpenAIError> {
+        self.settings()?
+            .model_for(deployment)
+            .map(|model| model.to_string())
+            .ok_or_else(|| {
+                OpenAIError::InvalidArgument(format!(
+                    "unknown Azure OpenAI deployment: {}",
+                    deployment
+                ))
+            })
+    }
+
+    /// デプロイメントのURLとAPIキーを設定する（プロファイルが指定された場合はプロファイルのAPIキーを使う）
+    fn config(
+        &self,
+        deployment: &str,
+        credential: Option<&ProviderCredential>,
+    ) -> Result<AzureConfig, OpenAIError> {
+        self.model_for(deployment)?;
+        let settings = self.settings()?;
+        let api_key = match credential {
+            Some(credential) => credential.api_key.expose().to_string(),
+            None => self
+                .api_key
+                .read()
+                .unwrap()
+                .clone()
+                .or_else(|| env::var(AZURE_OPENAI_API_KEY_ENV).ok())
+                .unwrap_or_default(),
+        };
+        Ok(AzureConfig::new()
+            .with_api_base(settings.endpoint.trim_end_matches('/'))
+            .with_api_version(settings.api_version)
+            .with_deployment_id(deployment)
+            .with_api_key(api_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_openai::config::Config;
+
+    use crate::common::secret::SecretString;
+    use crate::domain::app_setting::AzureOpenAIDeployment;
+    use crate::infra::core::http::HttpClientConfig;
+
+    use super::*;
+
+    fn client() -> AzureOpenAIClient {
+        let client = AzureOpenAIClient::new(
+            RateLimitConfig::default(),
+            Arc::new(SharedHttpClient::new(HttpClientConfig::default()).unwrap()),
+        );
+        client.update_azure_openai_settings(Some(&AzureOpenAISettings {
+            endpoint: "https://example.openai.azure.com/".to_string(),
+            api_version: "2024-02-01".to_string(),
+            deployments: vec![AzureOpenAIDeployment {
+                deployment: "prod-gpt4o".to_string(),
+                model: "gpt-4o".to_string(),
+            }],
+        }));
+        client.update_api_key(&ProviderType::AzureOpenAI, Some("azure-key"));
+        client
+    }
+
+    #[test]
+    fn test_config() {
+        let client = client();
+        // 他のプロバイダーのAPIキーは反映しない
+        client.update_api_key(&ProviderType::OpenAI, Some("openai-key"));
+
+        let config = client.config("prod-gpt4o", None).unwrap();
+        assert_eq!(
+            config.url("/chat/completions"),
+            "https://example.openai.azure.com/openai/deployments/prod-gpt4o/chat/completions"
+        );
+        assert_eq!(config.query(), vec![("api-version", "2024-02-01")]);
+        assert_eq!(config.headers().get("api-key").unwrap(), "azure-key");
+
+        // プロファイルのAPIキーを優先する
+        let credential = ProviderCredential {
+            api_key: SecretString::from("profile-key"),
+            organization_id: None,
+            project_id: None,
+        };
+        let config = client.config("prod-gpt4o", Some(&credential)).unwrap();
+        assert_eq!(config.headers().get("api-key").unwrap(), "profile-key");
+
+        // 設定されていないデプロイメント
+        let result = client.config("gpt-4o", None);
+        assert!(matches!(result, Err(OpenAIError::InvalidArgument(_))));
+
+        // トークン数の見積もりにはデプロイメントのモデルを使う
+        assert_eq!(client.model_for("prod-gpt4o").unwrap(), "gpt-4o");
+        assert!(client.model_for("gpt-4o").is_err());
+
+        // 設定が削除された場合は使えない
+        client.update_azure_openai_settings(None);
+        let result = client.config("prod-gpt4o", None);
+        assert!(matches!(result, Err(OpenAIError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let client = client();
+        let result = client.list_models().await.unwrap();
+        let models: Vec<(String, String)> = result
+            .data
+            .into_iter()
+            .map(|model| (model.id, model.owned_by))
+            .collect();
+        assert_eq!(
+            models,
+            vec![("prod-gpt4o".to_string(), "gpt-4o".to_string())]
+        );
+
+        // 設定されていない場合は空
+        client.update_azure_openai_settings(None);
+        let result = client.list_models().await.unwrap();
+        assert!(result.data.is_empty());
+    }
+}
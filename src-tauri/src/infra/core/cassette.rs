@@ -0,0 +1,665 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_openai::error::OpenAIError;
+use async_openai::types::{
+    CreateChatCompletionRequest, CreateChatCompletionResponse, CreateEmbeddingRequest,
+    CreateEmbeddingResponse, ListModelResponse,
+};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::common;
+use crate::common::errors::ApplicationError;
+use crate::domain::app_setting::{
+    ApiKeyUpdater, AzureOpenAISettings, ProviderCredential, RateLimitSettings,
+};
+use crate::domain::comparing_prompt::ProviderType;
+use crate::infra::core::openai::AIClient;
+
+/// カセットのモードを指定する環境変数（record / replay、未指定の場合は実際に通信する）
+const CASSETTE_MODE_ENV: &str = "APP_CASSETTE_MODE";
+/// カセットファイルのパスを指定する環境変数（未指定の場合はappデータのcassettes/cassette.json）
+const CASSETTE_PATH_ENV: &str = "APP_CASSETTE_PATH";
+
+/// 記録したAPIの種類
+#[derive(Clone, Copy, Debug, Display, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CassetteEndpoint {
+    Chat,
+    Embedding,
+    ListModels,
+}
+
+/// 1回のリクエストとレスポンスの組
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CassetteInteraction {
+    /// プロバイダーを記録する前のカセットはOpenAIの記録として扱う
+    #[serde(default = "default_provider_type")]
+    pub provider_type: ProviderType,
+    pub endpoint: CassetteEndpoint,
+    pub request: serde_json::Value,
+    pub response: serde_json::Value,
+}
+
+fn default_provider_type() -> ProviderType {
+    ProviderType::OpenAI
+}
+
+/// 記録したリクエストとレスポンスの一覧（記録した順）
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Cassette {
+    pub interactions: Vec<CassetteInteraction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self, ApplicationError> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            ApplicationError::UnknownError(format!(
+                "Cannot read cassette '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| ApplicationError::ParseError(e.to_string()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ApplicationError> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| ApplicationError::UnknownError("Invalid cassette path".to_string()))?;
+        common::dir::make_parent_dir_if_not_exists(path_str)?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+        fs::write(path, content).map_err(|e| {
+            ApplicationError::UnknownError(format!(
+                "Cannot write cassette '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// 記録中のカセット（プロバイダーごとのclientで1つのファイルを共有する）
+pub struct CassetteRecorder {
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl CassetteRecorder {
+    /// 記録は空のカセットから始め、既存のファイルは上書きする
+    pub fn new(path: PathBuf) -> Self {
+        CassetteRecorder {
+            path,
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+
+    /// 記録のたびにファイルに書き出す
+    fn record(&self, interaction: CassetteInteraction) -> Result<(), ApplicationError> {
+        let mut cassette = self.cassette.lock().unwrap();
+        cassette.interactions.push(interaction);
+        cassette.save(&self.path)
+    }
+}
+
+/// 再生中のカセット（プロバイダーごとのclientで共有する）
+pub struct CassettePlayer {
+    cassette: Cassette,
+    replayed: Mutex<Vec<bool>>, // 記録ごとに返却済みか
+}
+
+impl CassettePlayer {
+    pub fn new(cassette: Cassette) -> Self {
+        let replayed = vec![false; cassette.interactions.len()];
+        CassettePlayer {
+            cassette,
+            replayed: Mutex::new(replayed),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ApplicationError> {
+        Ok(CassettePlayer::new(Cassette::load(path)?))
+    }
+
+    /// プロバイダーとAPIとリクエストが一致する記録のレスポンスを返す
+    /// 同じリクエストの記録は記録した順に一度ずつ返し、一致する記録がない場合や全て返却済みの場合はエラーにする
+    fn replay(
+        &self,
+        provider_type: &ProviderType,
+        endpoint: CassetteEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<serde_json::Value, OpenAIError> {
+        let matched: Vec<usize> = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| {
+                interaction.provider_type == *provider_type
+                    && interaction.endpoint == endpoint
+                    && interaction.request == *request
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let mut replayed = self.replayed.lock().unwrap();
+        let Some(index) = matched.iter().find(|index| !replayed[**index]).copied() else {
+            let message = if matched.is_empty() {
+                format!(
+                    "no matching interaction: no recorded {} {} interaction in cassette for request: {}",
+                    provider_type, endpoint, request
+                )
+            } else {
+                format!(
+                    "cassette exhausted: all {} recorded {} {} interactions for request have been replayed: {}",
+                    matched.len(),
+                    provider_type,
+                    endpoint,
+                    request
+                )
+            };
+            log::error!("{}", message);
+            return Err(OpenAIError::InvalidArgument(message));
+        };
+        replayed[index] = true;
+        Ok(self.cassette.interactions[index].response.clone())
+    }
+}
+
+/// 実際のclientへのリクエストとレスポンスをカセットファイルに記録するAIClient
+pub struct RecordingAIClient<T: AIClient> {
+    inner: T,
+    provider_type: ProviderType,
+    recorder: Arc<CassetteRecorder>,
+}
+
+impl<T: AIClient> RecordingAIClient<T> {
+    pub fn new(inner: T, provider_type: ProviderType, recorder: Arc<CassetteRecorder>) -> Self {
+        RecordingAIClient {
+            inner,
+            provider_type,
+            recorder,
+        }
+    }
+
+    /// 成功したレスポンスのみ記録する
+    fn record<Req: Serialize, Res: Serialize>(
+        &self,
+        endpoint: CassetteEndpoint,
+        request: &Req,
+        response: &Res,
+    ) -> Result<(), OpenAIError> {
+        let to_error = |e: serde_json::Error| OpenAIError::InvalidArgument(e.to_string());
+        let interaction = CassetteInteraction {
+            provider_type: self.provider_type.clone(),
+            endpoint,
+            request: serde_json::to_value(request).map_err(to_error)?,
+            response: serde_json::to_value(response).map_err(to_error)?,
+        };
+        self.recorder
+            .record(interaction)
+            .map_err(|e| OpenAIError::FileSaveError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl<T: AIClient> AIClient for RecordingAIClient<T> {
+    async fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        self.create_chat_with_credential(request, None).await
+    }
+
+    /// 認証情報はカセットに記録しない
+    async fn create_chat_with_credential(
+        &self,
+        request: CreateChatCompletionRequest,
+        credential: Option<&ProviderCredential>,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        let response = self
+            .inner
+            .create_chat_with_credential(request.clone(), credential)
+            .await?;
+        self.record(CassetteEndpoint::Chat, &request, &response)?;
+        Ok(response)
+    }
+
+    async fn create_embedding(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+        let response = self.inner.create_embedding(request.clone()).await?;
+        self.record(CassetteEndpoint::Embedding, &request, &response)?;
+        Ok(response)
+    }
+
+    async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+        let response = self.inner.list_models().await?;
+        self.record(CassetteEndpoint::ListModels, &(), &response)?;
+        Ok(response)
+    }
+}
+
+/// カセットファイルに記録したレスポンスを返すAIClient（通信は行わない）
+pub struct ReplayingAIClient {
+    provider_type: ProviderType,
+    player: Arc<CassettePlayer>,
+}
+
+impl ReplayingAIClient {
+    pub fn new(provider_type: ProviderType, player: Arc<CassettePlayer>) -> Self {
+        ReplayingAIClient {
+            provider_type,
+            player,
+        }
+    }
+
+    /// 一致する記録がない場合や、記録を使い切った場合はエラーにする
+    fn replay<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        endpoint: CassetteEndpoint,
+        request: &Req,
+    ) -> Result<Res, OpenAIError> {
+        let request = serde_json::to_value(request)
+            .map_err(|e| OpenAIError::InvalidArgument(e.to_string()))?;
+        let response = self
+            .player
+            .replay(&self.provider_type, endpoint, &request)?;
+        serde_json::from_value(response).map_err(OpenAIError::JSONDeserialize)
+    }
+}
+
+#[async_trait]
+impl AIClient for ReplayingAIClient {
+    async fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        self.replay(CassetteEndpoint::Chat, &request)
+    }
+
+    async fn create_embedding(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+        self.replay(CassetteEndpoint::Embedding, &request)
+    }
+
+    async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+        self.replay(CassetteEndpoint::ListModels, &())
+    }
+}
+
+/// 環境変数で選択したカセットのモード（全てのプロバイダーのclientで共有する）
+pub enum CassetteMode {
+    Live,
+    Record(Arc<CassetteRecorder>),
+    Replay(Arc<CassettePlayer>),
+}
+
+impl CassetteMode {
+    pub fn from_env() -> Result<Self, ApplicationError> {
+        let mode = env::var(CASSETTE_MODE_ENV).unwrap_or_default();
+        if mode.is_empty() {
+            return Ok(CassetteMode::Live);
+        }
+        let path = match env::var(CASSETTE_PATH_ENV) {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => PathBuf::from(common::dir::get_app_home_path()?)
+                .join("cassettes")
+                .join("cassette.json"),
+        };
+        match mode.as_str() {
+            "record" => {
+                println!("cassette mode: record to {}", path.display());
+                Ok(CassetteMode::Record(Arc::new(CassetteRecorder::new(path))))
+            }
+            "replay" => {
+                println!("cassette mode: replay from {}", path.display());
+                Ok(CassetteMode::Replay(Arc::new(CassettePlayer::load(&path)?)))
+            }
+            _ => Err(ApplicationError::ValidationError(format!(
+                "{} must be record or replay: {}",
+                CASSETTE_MODE_ENV, mode
+            ))),
+        }
+    }
+}
+
+/// カセットのモードに応じて動作するAIClient
+pub enum CassetteAIClient<T: AIClient> {
+    Live(T),
+    Record(RecordingAIClient<T>),
+    Replay(ReplayingAIClient),
+}
+
+impl<T: AIClient> CassetteAIClient<T> {
+    /// 記録と再生はプロバイダーごとに区別する
+    pub fn new(mode: &CassetteMode, provider_type: ProviderType, inner: T) -> Self {
+        match mode {
+            CassetteMode::Live => CassetteAIClient::Live(inner),
+            CassetteMode::Record(recorder) => CassetteAIClient::Record(RecordingAIClient::new(
+                inner,
+                provider_type,
+                Arc::clone(recorder),
+            )),
+            CassetteMode::Replay(player) => {
+                CassetteAIClient::Replay(ReplayingAIClient::new(provider_type, Arc::clone(player)))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: AIClient> AIClient for CassetteAIClient<T> {
+    async fn create_chat(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        match self {
+            CassetteAIClient::Live(client) => client.create_chat(request).await,
+            CassetteAIClient::Record(client) => client.create_chat(request).await,
+            CassetteAIClient::Replay(client) => client.create_chat(request).await,
+        }
+    }
+
+    async fn create_chat_with_credential(
+        &self,
+        request: CreateChatCompletionRequest,
+        credential: Option<&ProviderCredential>,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        match self {
+            CassetteAIClient::Live(client) => {
+                client
+                    .create_chat_with_credential(request, credential)
+                    .await
+            }
+            CassetteAIClient::Record(client) => {
+                client
+                    .create_chat_with_credential(request, credential)
+                    .await
+            }
+            // 再生時は通信しないので認証情報は使わない
+            CassetteAIClient::Replay(client) => client.create_chat(request).await,
+        }
+    }
+
+    async fn create_embedding(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+        match self {
+            CassetteAIClient::Live(client) => client.create_embedding(request).await,
+            CassetteAIClient::Record(client) => client.create_embedding(request).await,
+            CassetteAIClient::Replay(client) => client.create_embedding(request).await,
+        }
+    }
+
+    async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+        match self {
+            CassetteAIClient::Live(client) => client.list_models().await,
+            CassetteAIClient::Record(client) => client.list_models().await,
+            CassetteAIClient::Replay(client) => client.list_models().await,
+        }
+    }
+}
+
+/// 再生時は通信しないのでAPIキーとリソース、レート制限の設定は使わない
+impl<T: AIClient + ApiKeyUpdater> ApiKeyUpdater for CassetteAIClient<T> {
+    fn update_api_key(&self, provider_type: &ProviderType, api_key: Option<&str>) {
+        match self {
+            CassetteAIClient::Live(client) => client.update_api_key(provider_type, api_key),
+            CassetteAIClient::Record(client) => client.inner.update_api_key(provider_type, api_key),
+            CassetteAIClient::Replay(_) => {}
+        }
+    }
+
+    fn update_azure_openai_settings(&self, settings: Option<&AzureOpenAISettings>) {
+        match self {
+            CassetteAIClient::Live(client) => client.update_azure_openai_settings(settings),
+            CassetteAIClient::Record(client) => client.inner.update_azure_openai_settings(settings),
+            CassetteAIClient::Replay(_) => {}
+        }
+    }
+
+    fn update_rate_limit(
+        &self,
+        provider_type: &ProviderType,
+        settings: Option<&RateLimitSettings>,
+    ) {
+        match self {
+            CassetteAIClient::Live(client) => client.update_rate_limit(provider_type, settings),
+            CassetteAIClient::Record(client) => {
+                client.inner.update_rate_limit(provider_type, settings)
+            }
+            CassetteAIClient::Replay(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_openai::types::{
+        ChatCompletionRequestMessage, ChatCompletionRequestUserMessageArgs, Model,
+    };
+
+    use crate::domain::app_setting::AzureOpenAIDeployment;
+    use crate::infra::core::azure_openai::AzureOpenAIClient;
+    use crate::infra::core::http::{HttpClientConfig, SharedHttpClient};
+    use crate::infra::core::rate_limit::RateLimitConfig;
+
+    use super::*;
+
+    /// 呼び出し回数をモデル名にして返す
+    #[derive(Default)]
+    struct MockOpenAIClient {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl AIClient for MockOpenAIClient {
+        async fn create_chat(
+            &self,
+            _request: CreateChatCompletionRequest,
+        ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+            Err(OpenAIError::InvalidArgument("not used".to_string()))
+        }
+
+        async fn create_embedding(
+            &self,
+            _request: CreateEmbeddingRequest,
+        ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+            Err(OpenAIError::InvalidArgument("not used".to_string()))
+        }
+
+        async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ListModelResponse {
+                object: "list".to_string(),
+                data: vec![Model {
+                    id: format!("model-{}", calls),
+                    object: "model".to_string(),
+                    created: 0,
+                    owned_by: "openai".to_string(),
+                }],
+            })
+        }
+    }
+
+    fn chat_request() -> CreateChatCompletionRequest {
+        CreateChatCompletionRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![ChatCompletionRequestMessage::User(
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content("Hello")
+                    .build()
+                    .unwrap(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    fn model_ids(response: ListModelResponse) -> Vec<String> {
+        response.data.into_iter().map(|model| model.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path = PathBuf::from(common::dir::get_test_home_path().unwrap())
+            .join("cassettes")
+            .join("test_record_and_replay.json");
+
+        // 記録
+        let recording = RecordingAIClient::new(
+            MockOpenAIClient::default(),
+            ProviderType::OpenAI,
+            Arc::new(CassetteRecorder::new(path.clone())),
+        );
+        recording.list_models().await.unwrap();
+        recording.list_models().await.unwrap();
+
+        // 再生
+        let player = Arc::new(CassettePlayer::load(&path).unwrap());
+        let replaying = ReplayingAIClient::new(ProviderType::OpenAI, player);
+        // 同じリクエストは記録した順に返し、使い切った後はエラーにする
+        let ids = model_ids(replaying.list_models().await.unwrap());
+        assert_eq!(ids, vec!["model-1"]);
+        let ids = model_ids(replaying.list_models().await.unwrap());
+        assert_eq!(ids, vec!["model-2"]);
+        let result = replaying.list_models().await;
+        match result {
+            Err(OpenAIError::InvalidArgument(message)) => {
+                assert!(message.starts_with("cassette exhausted"))
+            }
+            _ => panic!("InvalidArgument expected"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay_by_provider() {
+        let path = PathBuf::from(common::dir::get_test_home_path().unwrap())
+            .join("cassettes")
+            .join("test_record_and_replay_by_provider.json");
+
+        // 記録（1つのカセットを共有する）
+        let recorder = CassetteMode::Record(Arc::new(CassetteRecorder::new(path.clone())));
+        let openai =
+            CassetteAIClient::new(&recorder, ProviderType::OpenAI, MockOpenAIClient::default());
+        let azure_openai = CassetteAIClient::new(
+            &recorder,
+            ProviderType::AzureOpenAI,
+            AzureOpenAIClient::new(
+                RateLimitConfig::default(),
+                Arc::new(SharedHttpClient::new(HttpClientConfig::default()).unwrap()),
+            ),
+        );
+        azure_openai.update_azure_openai_settings(Some(&AzureOpenAISettings {
+            endpoint: "https://example.openai.azure.com".to_string(),
+            api_version: "2024-02-01".to_string(),
+            deployments: vec![AzureOpenAIDeployment {
+                deployment: "prod-gpt4o".to_string(),
+                model: "gpt-4o".to_string(),
+            }],
+        }));
+        openai.list_models().await.unwrap();
+        azure_openai.list_models().await.unwrap();
+
+        // 再生（同じリクエストでもプロバイダーごとの記録を返す）
+        let player = CassetteMode::Replay(Arc::new(CassettePlayer::load(&path).unwrap()));
+        let openai =
+            CassetteAIClient::new(&player, ProviderType::OpenAI, MockOpenAIClient::default());
+        let azure_openai = CassetteAIClient::new(
+            &player,
+            ProviderType::AzureOpenAI,
+            AzureOpenAIClient::new(
+                RateLimitConfig::default(),
+                Arc::new(SharedHttpClient::new(HttpClientConfig::default()).unwrap()),
+            ),
+        );
+        let ids = model_ids(openai.list_models().await.unwrap());
+        assert_eq!(ids, vec!["model-1"]);
+        let ids = model_ids(azure_openai.list_models().await.unwrap());
+        assert_eq!(ids, vec!["prod-gpt4o"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_azure_openai_chat() {
+        let request = chat_request();
+        let response = serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "Hi from Azure" },
+                "finish_reason": "stop"
+            }]
+        });
+        let player = CassetteMode::Replay(Arc::new(CassettePlayer::new(Cassette {
+            interactions: vec![CassetteInteraction {
+                provider_type: ProviderType::AzureOpenAI,
+                endpoint: CassetteEndpoint::Chat,
+                request: serde_json::to_value(&request).unwrap(),
+                response,
+            }],
+        })));
+        // Azure OpenAIのリソースを設定していないので、通信した場合はエラーになる
+        let azure_openai = CassetteAIClient::new(
+            &player,
+            ProviderType::AzureOpenAI,
+            AzureOpenAIClient::new(
+                RateLimitConfig::default(),
+                Arc::new(SharedHttpClient::new(HttpClientConfig::default()).unwrap()),
+            ),
+        );
+        let result = azure_openai
+            .create_chat_with_credential(request.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.choices[0].message.content,
+            Some("Hi from Azure".to_string())
+        );
+
+        // 他のプロバイダーの記録は返さない
+        let openai =
+            CassetteAIClient::new(&player, ProviderType::OpenAI, MockOpenAIClient::default());
+        let result = openai.create_chat(request).await;
+        assert!(matches!(result, Err(OpenAIError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_load_cassette_without_provider_type() {
+        // プロバイダーを記録する前のカセットはOpenAIの記録として読み込む
+        let cassette: Cassette = serde_json::from_value(serde_json::json!({
+            "interactions": [{ "endpoint": "list_models", "request": null, "response": {} }]
+        }))
+        .unwrap();
+        assert_eq!(cassette.interactions[0].provider_type, ProviderType::OpenAI);
+    }
+
+    #[tokio::test]
+    async fn test_replay_unmatched_request() {
+        let replaying = ReplayingAIClient::new(
+            ProviderType::OpenAI,
+            Arc::new(CassettePlayer::new(Cassette::default())),
+        );
+        let result = replaying.create_chat(chat_request()).await;
+        match result {
+            Err(OpenAIError::InvalidArgument(message)) => {
+                assert!(message.starts_with("no matching interaction"))
+            }
+            _ => panic!("InvalidArgument expected"),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, NoProxy, Proxy};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::app_setting::{ApiKeyUpdater, HttpClientSettings};
+use crate::domain::comparing_prompt::ProviderType;
+
+/// プロバイダーへの通信に使うプロキシのURLを指定する環境変数
+const PROXY_ENV: &str = "APP_HTTP_PROXY";
+/// プロキシを経由しないホストをカンマ区切りで指定する環境変数
+const NO_PROXY_ENV: &str = "APP_HTTP_NO_PROXY";
+/// 追加で信頼するルート証明書（PEM形式）のパスをカンマ区切りで指定する環境変数
+const CA_CERTS_ENV: &str = "APP_HTTP_CA_CERTS";
+/// 接続のタイムアウト（秒）を指定する環境変数
+const CONNECT_TIMEOUT_ENV: &str = "APP_HTTP_CONNECT_TIMEOUT_SECS";
+/// リクエストのタイムアウト（秒）を指定する環境変数
+const REQUEST_TIMEOUT_ENV: &str = "APP_HTTP_REQUEST_TIMEOUT_SECS";
+/// 全てのリクエストに付けるヘッダーをJSONのオブジェクトで指定する環境変数
+const HEADERS_ENV: &str = "APP_HTTP_HEADERS";
+
+const PEM_CERTIFICATE_HEADER: &str = "-----BEGIN CERTIFICATE-----";
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// 応答の遅いモデルでも待てるように長めにする
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// プロバイダーのHTTPクライアントの通信の設定
+/// プロキシが未指定の場合は、reqwestの既定どおりHTTPS_PROXYなどの環境変数に従う
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpClientConfig {
+    pub proxy: Option<String>,
+    pub no_proxy: Option<String>,
+    pub ca_certs: Vec<PathBuf>,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub headers: HeaderMap,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        HttpClientConfig {
+            proxy: None,
+            no_proxy: None,
+            ca_certs: Vec::new(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// 環境変数の設定（設定画面で指定されていない項目の既定値）
+    pub fn from_env() -> Result<Self, ApplicationError> {
+        fn var(name: &str) -> Option<String> {
+            env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        }
+        fn timeout(name: &str, default: Duration) -> Result<Duration, ApplicationError> {
+            match var(name) {
+                Some(value) => match value.parse::<u64>() {
+                    Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+                    _ => Err(ApplicationError::ValidationError(format!(
+                        "{} must be a positive integer: {}",
+                        name, value
+                    ))),
+                },
+                None => Ok(default),
+            }
+        }
+        Ok(HttpClientConfig {
+            proxy: var(PROXY_ENV),
+            no_proxy: var(NO_PROXY_ENV),
+            ca_certs: var(CA_CERTS_ENV)
+                .map(|paths| {
+                    paths
+                        .split(',')
+                        .map(|path| path.trim())
+                        .filter(|path| !path.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            connect_timeout: timeout(CONNECT_TIMEOUT_ENV, DEFAULT_CONNECT_TIMEOUT)?,
+            request_timeout: timeout(REQUEST_TIMEOUT_ENV, DEFAULT_REQUEST_TIMEOUT)?,
+            headers: var(HEADERS_ENV)
+                .map(|headers| parse_headers(&headers))
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+
+    /// 設定画面で保存された通信の設定を既定値に重ねる
+    pub fn with_settings(
+        &self,
+        settings: Option<&HttpClientSettings>,
+    ) -> Result<Self, ApplicationError> {
+        let settings = match settings {
+            Some(settings) => settings,
+            None => return Ok(self.clone()),
+        };
+        let timeout =
+            |secs: Option<u64>, default: Duration| secs.map(Duration::from_secs).unwrap_or(default);
+        Ok(HttpClientConfig {
+            proxy: settings.proxy.clone().or_else(|| self.proxy.clone()),
+            no_proxy: settings.no_proxy.clone().or_else(|| self.no_proxy.clone()),
+            ca_certs: if settings.ca_certs.is_empty() {
+                self.ca_certs.clone()
+            } else {
+                settings.ca_certs.iter().map(PathBuf::from).collect()
+            },
+            connect_timeout: timeout(settings.connect_timeout_secs, self.connect_timeout),
+            request_timeout: timeout(settings.request_timeout_secs, self.request_timeout),
+            headers: if settings.headers.is_empty() {
+                self.headers.clone()
+            } else {
+                header_map("headers", &settings.headers)?
+            },
+        })
+    }
+
+    /// 設定を反映したHTTPクライアントを作成する（全てのプロバイダーのクライアントで共有する）
+    pub fn build_client(&self) -> Result<reqwest::Client, ApplicationError> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .default_headers(self.headers.clone());
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| {
+                ApplicationError::ValidationError(format!("invalid {}: {}", PROXY_ENV, e))
+            })?;
+            builder = builder
+                .proxy(proxy.no_proxy(self.no_proxy.as_deref().and_then(NoProxy::from_string)));
+        }
+        for path in &self.ca_certs {
+            let pem = fs::read(path).map_err(|e| {
+                ApplicationError::ValidationError(format!(
+                    "cannot read certificate {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            // rustlsは証明書を含まないファイルを読み込んでもエラーにしないので、ここで確認する
+            if !String::from_utf8_lossy(&pem).contains(PEM_CERTIFICATE_HEADER) {
+                return Err(ApplicationError::ValidationError(format!(
+                    "no certificate found in {}",
+                    path.display()
+                )));
+            }
+            let certificate = Certificate::from_pem(&pem).map_err(|e| {
+                ApplicationError::ValidationError(format!(
+                    "invalid certificate {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder.build().map_err(|e| {
+            ApplicationError::ValidationError(format!("cannot build http client: {}", e))
+        })
+    }
+}
+
+/// {"ヘッダー名": "値"}の形式のJSONをヘッダーにする
+fn parse_headers(value: &str) -> Result<HeaderMap, ApplicationError> {
+    let headers: BTreeMap<String, String> = serde_json::from_str(value).map_err(|e| {
+        ApplicationError::ValidationError(format!("invalid {}: {}", HEADERS_ENV, e))
+    })?;
+    header_map(HEADERS_ENV, &headers)
+}
+
+fn header_map(
+    label: &str,
+    headers: &BTreeMap<String, String>,
+) -> Result<HeaderMap, ApplicationError> {
+    let invalid =
+        |e: String| ApplicationError::ValidationError(format!("invalid {}: {}", label, e));
+    headers
+        .iter()
+        .map(|(name, value)| {
+            Ok((
+                HeaderName::from_str(name).map_err(|e| invalid(e.to_string()))?,
+                HeaderValue::from_str(value).map_err(|e| invalid(e.to_string()))?,
+            ))
+        })
+        .collect()
+}
+
+/// 全てのプロバイダーのクライアントで共有するHTTPクライアント
+/// 設定画面で通信の設定が変更された場合は作り直す（送信中のリクエストは古いクライアントのまま完了する）
+#[derive(Debug)]
+pub struct SharedHttpClient {
+    default_config: HttpClientConfig, // 環境変数の設定
+    client: RwLock<reqwest::Client>,
+}
+
+impl SharedHttpClient {
+    pub fn new(default_config: HttpClientConfig) -> Result<Self, ApplicationError> {
+        Ok(SharedHttpClient {
+            client: RwLock::new(default_config.build_client()?),
+            default_config,
+        })
+    }
+
+    /// reqwest::Clientは内部でArcを共有しているので、複製してリクエストごとに使う
+    pub fn client(&self) -> reqwest::Client {
+        self.client.read().unwrap().clone()
+    }
+}
+
+impl ApiKeyUpdater for SharedHttpClient {
+    fn update_api_key(&self, _provider_type: &ProviderType, _api_key: Option<&str>) {}
+
+    fn update_http_client(
+        &self,
+        settings: Option<&HttpClientSettings>,
+    ) -> Result<(), ApplicationError> {
+        let client = self
+            .default_config
+            .with_settings(settings)?
+            .build_client()?;
+        *self.client.write().unwrap() = client;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_headers() {
+        let headers = parse_headers(r#"{"X-Tenant": "team-a", "X-Trace": "1"}"#).unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.get("x-tenant").unwrap(), "team-a");
+
+        // 文字列以外の値や不正なヘッダー名はエラーにする
+        let result = parse_headers(r#"{"X-Retry": 1}"#);
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let result = parse_headers(r#"{"X Tenant": "team-a"}"#);
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let result = parse_headers("X-Tenant: team-a");
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_with_settings() {
+        let config = HttpClientConfig {
+            proxy: Some("http://env-proxy.example.com:8080".to_string()),
+            ca_certs: vec![PathBuf::from("env.pem")],
+            ..Default::default()
+        };
+
+        // 指定された項目のみ上書きし、それ以外は既定値を使う
+        let result = config
+            .with_settings(Some(&HttpClientSettings {
+                proxy: Some("http://proxy.example.com:8080".to_string()),
+                request_timeout_secs: Some(60),
+                headers: BTreeMap::from([("X-Tenant".to_string(), "team-a".to_string())]),
+                ..Default::default()
+            }))
+            .unwrap();
+        assert_eq!(
+            result.proxy.as_deref(),
+            Some("http://proxy.example.com:8080")
+        );
+        assert_eq!(result.ca_certs, vec![PathBuf::from("env.pem")]);
+        assert_eq!(result.connect_timeout, DEFAULT_CONNECT_TIMEOUT);
+        assert_eq!(result.request_timeout, Duration::from_secs(60));
+        assert_eq!(result.headers.get("x-tenant").unwrap(), "team-a");
+
+        // 設定がない場合は既定値のまま
+        assert_eq!(config.with_settings(None).unwrap(), config);
+
+        // 不正なヘッダー名
+        let result = config.with_settings(Some(&HttpClientSettings {
+            headers: BTreeMap::from([("X Tenant".to_string(), "team-a".to_string())]),
+            ..Default::default()
+        }));
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_update_http_client() {
+        let client = SharedHttpClient::new(HttpClientConfig::default()).unwrap();
+        let result = client.update_http_client(Some(&HttpClientSettings {
+            proxy: Some("http://proxy.example.com:8080".to_string()),
+            ..Default::default()
+        }));
+        assert!(result.is_ok());
+
+        // クライアントを作れない設定はエラーにする
+        let result = client.update_http_client(Some(&HttpClientSettings {
+            ca_certs: vec!["not-found.pem".to_string()],
+            ..Default::default()
+        }));
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        assert!(client.update_http_client(None).is_ok());
+    }
+
+    #[test]
+    fn test_build_client() {
+        let config = HttpClientConfig {
+            proxy: Some("http://proxy.example.com:8080".to_string()),
+            no_proxy: Some("localhost,.internal.example.com".to_string()),
+            headers: parse_headers(r#"{"X-Tenant": "team-a"}"#).unwrap(),
+            ..Default::default()
+        };
+        assert!(config.build_client().is_ok());
+
+        // 不正なプロキシのURL
+        let config = HttpClientConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        let result = config.build_client();
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+
+        // 存在しない証明書と、PEM形式でない証明書
+        let config = HttpClientConfig {
+            ca_certs: vec![PathBuf::from("not-found.pem")],
+            ..Default::default()
+        };
+        let result = config.build_client();
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let path = env::temp_dir().join("promptory_test_invalid_ca.pem");
+        fs::write(&path, "not a certificate").unwrap();
+        let config = HttpClientConfig {
+            ca_certs: vec![path],
+            ..Default::default()
+        };
+        let result = config.build_client();
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+}
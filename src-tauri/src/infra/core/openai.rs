@@ -1,8 +1,34 @@
-use async_openai::config::OpenAIConfig;
-use async_openai::error::OpenAIError;
-use async_openai::types::{CreateChatCompletionRequest, CreateChatCompletionResponse};
-use async_openai::Client;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use async_openai::config::{Config, OpenAIConfig};
+use async_openai::error::{ApiError, OpenAIError};
+use async_openai::types::{
+    CreateChatCompletionRequest, CreateChatCompletionResponse, CreateEmbeddingRequest,
+    CreateEmbeddingResponse, ListModelResponse,
+};
 use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::app_setting::{
+    ApiKeyUpdater, AzureOpenAISettings, HttpClientSettings, ProviderCredential, RateLimitSettings,
+};
+use crate::domain::comparing_prompt::ProviderType;
+use crate::domain::tokenizer::Tokenizer;
+use crate::infra::core::http::SharedHttpClient;
+use crate::infra::core::rate_limit::{
+    RateLimitConfig, RateLimitEndpoint, RateLimitHeaders, RateLimiters,
+};
+use crate::infra::tokenizer::TiktokenTokenizer;
+
+/// レート制限された（429）場合に再送する回数
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+
+/// プロジェクトを指定するヘッダー（async-openaiの設定では指定できない）
+const OPENAI_PROJECT_HEADER: &str = "OpenAI-Project";
 
 /// open aiのclientのラッパーtrait
 /// crate内の実装がstructなのでテストでモックを使うための対応
@@ -12,11 +38,53 @@ pub trait AIClient: Send + Sync {
         &self,
         request: CreateChatCompletionRequest,
     ) -> Result<CreateChatCompletionResponse, OpenAIError>;
+
+    /// 認証情報のプロファイルを指定してチャットを実行する（Noneの場合はcreate_chatと同じ）
+    async fn create_chat_with_credential(
+        &self,
+        request: CreateChatCompletionRequest,
+        _credential: Option<&ProviderCredential>,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        self.create_chat(request).await
+    }
+
+    async fn create_embedding(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse, OpenAIError>;
+
+    async fn list_models(&self) -> Result<ListModelResponse, OpenAIError>;
 }
 
-#[derive(Clone, Debug)]
+/// x-ratelimit-*ヘッダーを読むため、リクエストはasync-openaiの設定と型を使って直接送信する
+/// APIキーは設定画面から変更されるので、リクエストごとに最新の設定を使う
 pub struct OpenAIClient {
-    client: Client<OpenAIConfig>,
+    config: RwLock<OpenAIConfig>,
+    http: RateLimitedHttpClient,
+}
+
+/// OpenAIConfigのDebugはAPIキーを含むので出力しない
+impl fmt::Debug for OpenAIClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenAIClient")
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// レート制限の枠を待ってからリクエストを送信するHTTPクライアント
+/// Azure OpenAIも同じx-ratelimit-*ヘッダーを返すので、async-openaiの設定を差し替えて共通で使う
+#[derive(Debug)]
+pub struct RateLimitedHttpClient {
+    http_client: Arc<SharedHttpClient>,
+    default_config: RateLimitConfig, // 環境変数の上限
+    // 設定画面で上限が変更された場合は作り直す（送信中のリクエストは古い制限のまま完了する）
+    rate_limiters: RwLock<Arc<RateLimiters>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorResponse {
+    error: ApiError,
 }
 
 #[async_trait]
@@ -25,13 +93,289 @@ impl AIClient for OpenAIClient {
         &self,
         request: CreateChatCompletionRequest,
     ) -> Result<CreateChatCompletionResponse, OpenAIError> {
-        self.client.chat().create(request).await
+        self.create_chat_with_credential(request, None).await
+    }
+
+    async fn create_chat_with_credential(
+        &self,
+        request: CreateChatCompletionRequest,
+        credential: Option<&ProviderCredential>,
+    ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+        let estimated_tokens = estimate_chat_tokens(&request, &request.model);
+        self.post(
+            RateLimitEndpoint::Chat,
+            "/chat/completions",
+            &request,
+            estimated_tokens,
+            credential,
+        )
+        .await
+    }
+
+    async fn create_embedding(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+        let estimated_tokens = estimate_embedding_tokens(&request);
+        self.post(
+            RateLimitEndpoint::Embedding,
+            "/embeddings",
+            &request,
+            estimated_tokens,
+            None,
+        )
+        .await
+    }
+
+    async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+        self.http.get(&self.config(), "/models").await
+    }
+}
+
+impl ApiKeyUpdater for OpenAIClient {
+    fn update_api_key(&self, provider_type: &ProviderType, api_key: Option<&str>) {
+        if *provider_type != ProviderType::OpenAI {
+            return;
+        }
+        // OpenAIConfig::new()は環境変数OPENAI_API_KEYを読み込む
+        let config = match api_key {
+            Some(api_key) => OpenAIConfig::new().with_api_key(api_key),
+            None => OpenAIConfig::new(),
+        };
+        *self.config.write().unwrap() = config;
+    }
+
+    fn update_rate_limit(
+        &self,
+        provider_type: &ProviderType,
+        settings: Option<&RateLimitSettings>,
+    ) {
+        if *provider_type != ProviderType::OpenAI {
+            return;
+        }
+        self.http.update_rate_limit(settings);
+    }
+}
+
+/// 複数のプロバイダーのクライアントにまとめてAPIキーと設定を反映する
+/// 各クライアントは自分のプロバイダー以外の値を無視する
+pub struct ApiKeyUpdaters(pub Vec<Arc<dyn ApiKeyUpdater>>);
+
+impl ApiKeyUpdater for ApiKeyUpdaters {
+    fn update_api_key(&self, provider_type: &ProviderType, api_key: Option<&str>) {
+        for updater in &self.0 {
+            updater.update_api_key(provider_type, api_key);
+        }
+    }
+
+    fn update_azure_openai_settings(&self, settings: Option<&AzureOpenAISettings>) {
+        for updater in &self.0 {
+            updater.update_azure_openai_settings(settings);
+        }
+    }
+
+    fn update_rate_limit(
+        &self,
+        provider_type: &ProviderType,
+        settings: Option<&RateLimitSettings>,
+    ) {
+        for updater in &self.0 {
+            updater.update_rate_limit(provider_type, settings);
+        }
+    }
+
+    fn update_http_client(
+        &self,
+        settings: Option<&HttpClientSettings>,
+    ) -> Result<(), ApplicationError> {
+        for updater in &self.0 {
+            updater.update_http_client(settings)?;
+        }
+        Ok(())
     }
 }
 
 impl OpenAIClient {
-    pub fn new() -> Self {
-        let client = Client::new();
-        OpenAIClient { client }
+    pub fn new(rate_limit_config: RateLimitConfig, http_client: Arc<SharedHttpClient>) -> Self {
+        OpenAIClient {
+            config: RwLock::new(OpenAIConfig::new()),
+            http: RateLimitedHttpClient::new(rate_limit_config, http_client),
+        }
+    }
+
+    async fn post<I, O>(
+        &self,
+        endpoint: RateLimitEndpoint,
+        path: &str,
+        request: &I,
+        estimated_tokens: u32,
+        credential: Option<&ProviderCredential>,
+    ) -> Result<O, OpenAIError>
+    where
+        I: Serialize + Sync,
+        O: DeserializeOwned,
+    {
+        let mut headers = HeaderMap::new();
+        if let Some(project_id) = credential.and_then(|c| c.project_id.as_deref()) {
+            let value = HeaderValue::from_str(project_id)
+                .map_err(|e| OpenAIError::InvalidArgument(e.to_string()))?;
+            headers.insert(OPENAI_PROJECT_HEADER, value);
+        }
+        self.http
+            .post(
+                endpoint,
+                &self.config_with_credential(credential),
+                headers,
+                path,
+                request,
+                estimated_tokens,
+            )
+            .await
+    }
+
+    fn config(&self) -> OpenAIConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// プロファイルが指定された場合は、設定画面のAPIキーと組織の代わりにプロファイルの値を使う
+    fn config_with_credential(&self, credential: Option<&ProviderCredential>) -> OpenAIConfig {
+        match credential {
+            Some(credential) => self
+                .config()
+                .with_api_key(credential.api_key.expose())
+                .with_org_id(credential.organization_id.as_deref().unwrap_or_default()),
+            None => self.config(),
+        }
+    }
+}
+
+impl RateLimitedHttpClient {
+    /// HTTPクライアントは全てのプロバイダーで共有する
+    pub fn new(rate_limit_config: RateLimitConfig, http_client: Arc<SharedHttpClient>) -> Self {
+        RateLimitedHttpClient {
+            http_client,
+            rate_limiters: RwLock::new(Arc::new(RateLimiters::new(rate_limit_config.clone()))),
+            default_config: rate_limit_config,
+        }
+    }
+
+    /// 設定画面で保存された上限を反映する（Noneの場合は環境変数の上限に戻す）
+    /// 上限が変わらない場合はヘッダーから分かった残りを保つため作り直さない
+    pub fn update_rate_limit(&self, settings: Option<&RateLimitSettings>) {
+        let config = self.default_config.with_settings(settings);
+        let mut rate_limiters = self.rate_limiters.write().unwrap();
+        if rate_limiters.config() != &config {
+            log::info!("update rate limit: {:?}", config);
+            *rate_limiters = Arc::new(RateLimiters::new(config));
+        }
+    }
+
+    /// レート制限の枠が空くまで待ってから送信し、レスポンスのヘッダーで制限を更新する
+    /// レート制限された場合はリセットまで待って再送する
+    pub async fn post<C, I, O>(
+        &self,
+        endpoint: RateLimitEndpoint,
+        config: &C,
+        extra_headers: HeaderMap,
+        path: &str,
+        request: &I,
+        estimated_tokens: u32,
+    ) -> Result<O, OpenAIError>
+    where
+        C: Config,
+        I: Serialize + Sync,
+        O: DeserializeOwned,
+    {
+        let rate_limiters = Arc::clone(&self.rate_limiters.read().unwrap());
+        let limiter = rate_limiters.get(endpoint);
+        let mut retries = 0;
+        loop {
+            let permit = limiter.acquire(estimated_tokens).await;
+            let response = self
+                .http_client
+                .client()
+                .post(config.url(path))
+                .query(&config.query())
+                .headers(config.headers())
+                .headers(extra_headers.clone())
+                .json(request)
+                .send()
+                .await?;
+            let status = response.status();
+            let headers = RateLimitHeaders::from_headers(response.headers());
+            let bytes = response.bytes().await?;
+            drop(permit);
+
+            let result = parse_response(status, &bytes);
+            let rate_limited = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                && !matches!(
+                    &result,
+                    Err(OpenAIError::ApiError(error))
+                        if error.r#type.as_deref() == Some("insufficient_quota")
+                );
+            if !rate_limited {
+                limiter.observe(&headers);
+                return result;
+            }
+            limiter.rate_limited(&headers);
+            if retries >= MAX_RATE_LIMIT_RETRIES {
+                return result;
+            }
+            retries += 1;
+            log::warn!("rate limited: {} (retry {})", endpoint, retries);
+        }
+    }
+
+    /// レート制限の対象外のAPI（モデル一覧など）を呼び出す
+    pub async fn get<C, O>(&self, config: &C, path: &str) -> Result<O, OpenAIError>
+    where
+        C: Config,
+        O: DeserializeOwned,
+    {
+        let response = self
+            .http_client
+            .client()
+            .get(config.url(path))
+            .query(&config.query())
+            .headers(config.headers())
+            .send()
+            .await?;
+        let status = response.status();
+        let bytes = response.bytes().await?;
+        parse_response(status, &bytes)
+    }
+}
+
+/// プロンプトのトークン数を見積もり、生成されうる最大トークン数を加える
+/// プロンプトはmodelのエンコーディングで数え、トークン数を数えられないモデルの場合は4文字を1トークンとして見積もる
+/// Azure OpenAIのリクエストのモデル名はデプロイメント名なので、modelにはデプロイメントのモデルを指定する
+pub fn estimate_chat_tokens(request: &CreateChatCompletionRequest, model: &str) -> u32 {
+    let tokenizer = TiktokenTokenizer::new();
+    let prompt_tokens = serde_json::to_string(&request.messages)
+        .map(|messages| match tokenizer.model_limit(model) {
+            Some(limit) => tokenizer.count_tokens(limit.encoding, &messages),
+            None => messages.len() / 4,
+        })
+        .unwrap_or_default() as u32;
+    let completion_tokens =
+        request.max_tokens.unwrap_or_default() as u32 * request.n.unwrap_or(1) as u32;
+    prompt_tokens + completion_tokens
+}
+
+pub fn estimate_embedding_tokens(request: &CreateEmbeddingRequest) -> u32 {
+    serde_json::to_string(&request.input)
+        .map(|input| input.len() / 4)
+        .unwrap_or_default() as u32
+}
+
+fn parse_response<O: DeserializeOwned>(
+    status: reqwest::StatusCode,
+    bytes: &[u8],
+) -> Result<O, OpenAIError> {
+    if !status.is_success() {
+        let response: ApiErrorResponse =
+            serde_json::from_slice(bytes).map_err(OpenAIError::JSONDeserialize)?;
+        return Err(OpenAIError::ApiError(response.error));
     }
+    serde_json::from_slice(bytes).map_err(OpenAIError::JSONDeserialize)
 }
@@ -0,0 +1,466 @@
+use std::env;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use strum_macros::Display;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::app_setting::RateLimitSettings;
+
+/// 同時リクエスト数の上限を指定する環境変数
+const MAX_CONCURRENT_ENV: &str = "APP_RATE_LIMIT_MAX_CONCURRENT";
+/// 1分あたりのリクエスト数の上限を指定する環境変数
+const REQUESTS_PER_MINUTE_ENV: &str = "APP_RATE_LIMIT_REQUESTS_PER_MINUTE";
+/// 1分あたりのトークン数の上限を指定する環境変数
+const TOKENS_PER_MINUTE_ENV: &str = "APP_RATE_LIMIT_TOKENS_PER_MINUTE";
+
+const DEFAULT_MAX_CONCURRENT: usize = 8;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// レート制限された（429）が待ち時間が分からない場合の待ち時間
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// 制限をかけるAPIの種類
+#[derive(Clone, Copy, Debug, Display, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum RateLimitEndpoint {
+    Chat,
+    Embedding,
+}
+
+/// プロバイダーのAPIごとの制限（上限が未指定(None)の場合は制限しない）
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitConfig {
+    pub max_concurrent: usize,
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// 環境変数の上限（設定画面でプロバイダーごとに指定されていない項目の既定値）
+    pub fn from_env() -> Result<Self, ApplicationError> {
+        // 上限が0の場合はリクエストを送れず待ち続けるので、1以上のみ受け付ける
+        fn parse<T>(name: &str) -> Result<Option<T>, ApplicationError>
+        where
+            T: std::str::FromStr + Default + PartialEq,
+        {
+            match env::var(name) {
+                Ok(value) if !value.is_empty() => match value.parse::<T>() {
+                    Ok(parsed) if parsed != T::default() => Ok(Some(parsed)),
+                    _ => Err(ApplicationError::ValidationError(format!(
+                        "{} must be a positive integer: {}",
+                        name, value
+                    ))),
+                },
+                _ => Ok(None),
+            }
+        }
+        Ok(RateLimitConfig {
+            max_concurrent: parse(MAX_CONCURRENT_ENV)?.unwrap_or(DEFAULT_MAX_CONCURRENT),
+            requests_per_minute: parse(REQUESTS_PER_MINUTE_ENV)?,
+            tokens_per_minute: parse(TOKENS_PER_MINUTE_ENV)?,
+        })
+    }
+
+    /// 設定画面で保存されたプロバイダーの上限を既定値に重ねる
+    pub fn with_settings(&self, settings: Option<&RateLimitSettings>) -> Self {
+        let settings = match settings {
+            Some(settings) => settings,
+            None => return self.clone(),
+        };
+        RateLimitConfig {
+            max_concurrent: settings
+                .max_concurrent
+                .map(|max_concurrent| max_concurrent as usize)
+                .unwrap_or(self.max_concurrent),
+            requests_per_minute: settings.requests_per_minute.or(self.requests_per_minute),
+            tokens_per_minute: settings.tokens_per_minute.or(self.tokens_per_minute),
+        }
+    }
+}
+
+/// プロバイダーのレスポンスのx-ratelimit-*ヘッダー
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateLimitHeaders {
+    pub limit_requests: Option<u32>,
+    pub limit_tokens: Option<u32>,
+    pub remaining_requests: Option<u32>,
+    pub remaining_tokens: Option<u32>,
+    pub reset_requests: Option<Duration>,
+    pub reset_tokens: Option<Duration>,
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitHeaders {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let number = |name: &str| get(name).and_then(|value| value.parse().ok());
+        let duration = |name: &str| get(name).and_then(parse_reset_duration);
+        RateLimitHeaders {
+            limit_requests: number("x-ratelimit-limit-requests"),
+            limit_tokens: number("x-ratelimit-limit-tokens"),
+            remaining_requests: number("x-ratelimit-remaining-requests"),
+            remaining_tokens: number("x-ratelimit-remaining-tokens"),
+            reset_requests: duration("x-ratelimit-reset-requests"),
+            reset_tokens: duration("x-ratelimit-reset-tokens"),
+            retry_after: get("retry-after")
+                .and_then(|value| value.parse::<f64>().ok())
+                .map(Duration::from_secs_f64),
+        }
+    }
+}
+
+/// リセットまでの時間（"1s", "6m0s", "20ms", "1h2m3.5s"など）をパースする
+fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut chars = value.trim().chars().peekable();
+    let mut parsed = false;
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let amount: f64 = number.parse().ok()?;
+        number.clear();
+        let seconds = match c {
+            'h' => 3600.0,
+            'm' if chars.peek() == Some(&'s') => {
+                chars.next();
+                0.001
+            }
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return None,
+        };
+        total += amount * seconds;
+        parsed = true;
+    }
+    if !number.is_empty() || !parsed {
+        return None;
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+#[derive(Debug)]
+struct RateLimitState {
+    // 設定の上限に対する直近1分間の使用量
+    window_start: Instant,
+    requests: u32,
+    tokens: u32,
+    // プロバイダーのヘッダーから分かった上限と残り
+    limit_requests: Option<u32>,
+    limit_tokens: Option<u32>,
+    remaining_requests: Option<(u32, Instant)>, // (残り, リセットされる時刻)
+    remaining_tokens: Option<(u32, Instant)>,
+}
+
+/// プロバイダーのAPIごとの同時リクエスト数とリクエスト数・トークン数/分の制限
+/// 上限に達した場合はエラーにせず、枠が空くまで待つ
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    semaphore: Semaphore,
+    state: Mutex<RateLimitState>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            semaphore: Semaphore::new(config.max_concurrent),
+            config,
+            state: Mutex::new(RateLimitState {
+                window_start: Instant::now(),
+                requests: 0,
+                tokens: 0,
+                limit_requests: None,
+                limit_tokens: None,
+                remaining_requests: None,
+                remaining_tokens: None,
+            }),
+        }
+    }
+
+    /// リクエストを送信できるまで待ち、送信中は保持するpermitを返す
+    pub async fn acquire(&self, estimated_tokens: u32) -> SemaphorePermit<'_> {
+        let permit = self.semaphore.acquire().await.expect("semaphore closed");
+        while let Some(wait) = self.reserve(estimated_tokens, Instant::now()) {
+            log::info!("rate limit: waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+        permit
+    }
+
+    /// 枠がある場合は予約してNone、ない場合は待つ時間を返す
+    fn reserve(&self, estimated_tokens: u32, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        if now.duration_since(state.window_start) >= WINDOW {
+            state.window_start = now;
+            state.requests = 0;
+            state.tokens = 0;
+        }
+        let window_end = state.window_start + WINDOW;
+
+        // 設定とヘッダーの上限のうち小さい方を使う
+        let requests_per_minute = min_option(self.config.requests_per_minute, state.limit_requests);
+        let tokens_per_minute = min_option(self.config.tokens_per_minute, state.limit_tokens);
+        if requests_per_minute.is_some_and(|limit| state.requests >= limit) {
+            return Some(window_end - now);
+        }
+        // 1リクエストで上限を超える場合は、枠が空いていれば送信する
+        if tokens_per_minute.is_some_and(|limit| {
+            state.tokens > 0 && state.tokens.saturating_add(estimated_tokens) > limit
+        }) {
+            return Some(window_end - now);
+        }
+        if let Some((remaining, reset_at)) = state.remaining_requests {
+            if remaining == 0 && reset_at > now {
+                return Some(reset_at - now);
+            }
+        }
+        if let Some((remaining, reset_at)) = state.remaining_tokens {
+            if remaining < estimated_tokens && reset_at > now {
+                return Some(reset_at - now);
+            }
+        }
+
+        state.requests += 1;
+        state.tokens = state.tokens.saturating_add(estimated_tokens);
+        if let Some((remaining, reset_at)) = state.remaining_requests {
+            state.remaining_requests = Some((remaining.saturating_sub(1), reset_at));
+        }
+        if let Some((remaining, reset_at)) = state.remaining_tokens {
+            state.remaining_tokens = Some((remaining.saturating_sub(estimated_tokens), reset_at));
+        }
+        None
+    }
+
+    /// レスポンスのヘッダーから上限と残りを更新する
+    pub fn observe(&self, headers: &RateLimitHeaders) {
+        self.observe_at(headers, Instant::now());
+    }
+
+    fn observe_at(&self, headers: &RateLimitHeaders, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if headers.limit_requests.is_some() {
+            state.limit_requests = headers.limit_requests;
+        }
+        if headers.limit_tokens.is_some() {
+            state.limit_tokens = headers.limit_tokens;
+        }
+        if let Some(remaining) = headers.remaining_requests {
+            let reset = headers.reset_requests.unwrap_or(WINDOW);
+            state.remaining_requests = Some((remaining, now + reset));
+        }
+        if let Some(remaining) = headers.remaining_tokens {
+            let reset = headers.reset_tokens.unwrap_or(WINDOW);
+            state.remaining_tokens = Some((remaining, now + reset));
+        }
+    }
+
+    /// レート制限された（429）場合は、リセットされるまで次のリクエストを待たせる
+    pub fn rate_limited(&self, headers: &RateLimitHeaders) {
+        let now = Instant::now();
+        self.observe_at(headers, now);
+        let wait = headers
+            .retry_after
+            .or(headers.reset_requests)
+            .unwrap_or(DEFAULT_RETRY_AFTER);
+        let mut state = self.state.lock().unwrap();
+        state.remaining_requests = Some((0, now + wait));
+    }
+}
+
+fn min_option(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// プロバイダーのAPIごとのRateLimiter
+#[derive(Debug)]
+pub struct RateLimiters {
+    config: RateLimitConfig,
+    chat: RateLimiter,
+    embedding: RateLimiter,
+}
+
+impl RateLimiters {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiters {
+            chat: RateLimiter::new(config.clone()),
+            embedding: RateLimiter::new(config.clone()),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    pub fn get(&self, endpoint: RateLimitEndpoint) -> &RateLimiter {
+        match endpoint {
+            RateLimitEndpoint::Chat => &self.chat,
+            RateLimitEndpoint::Embedding => &self.embedding,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_reset_duration() {
+        assert_eq!(parse_reset_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(
+            parse_reset_duration("20ms"),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            parse_reset_duration("1h2m3.5s"),
+            Some(Duration::from_secs_f64(3723.5))
+        );
+        assert_eq!(parse_reset_duration(""), None);
+        assert_eq!(parse_reset_duration("10"), None);
+        assert_eq!(parse_reset_duration("1d"), None);
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-limit-requests",
+            HeaderValue::from_static("500"),
+        );
+        headers.insert(
+            "x-ratelimit-remaining-tokens",
+            HeaderValue::from_static("9000"),
+        );
+        headers.insert("x-ratelimit-reset-tokens", HeaderValue::from_static("6ms"));
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+        let headers = RateLimitHeaders::from_headers(&headers);
+        assert_eq!(headers.limit_requests, Some(500));
+        assert_eq!(headers.remaining_tokens, Some(9000));
+        assert_eq!(headers.reset_tokens, Some(Duration::from_millis(6)));
+        assert_eq!(headers.retry_after, Some(Duration::from_secs(2)));
+        assert_eq!(headers.remaining_requests, None);
+    }
+
+    #[test]
+    fn test_from_env_zero() {
+        // 上限が0の場合はエラーにする
+        for name in [
+            MAX_CONCURRENT_ENV,
+            REQUESTS_PER_MINUTE_ENV,
+            TOKENS_PER_MINUTE_ENV,
+        ] {
+            env::set_var(name, "0");
+            let result = RateLimitConfig::from_env();
+            env::remove_var(name);
+            assert!(result.is_err(), "{}", name);
+        }
+
+        // 未指定の場合は既定値を使う
+        assert_eq!(
+            RateLimitConfig::from_env().unwrap(),
+            RateLimitConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_with_settings() {
+        let config = RateLimitConfig {
+            max_concurrent: 4,
+            requests_per_minute: Some(100),
+            tokens_per_minute: None,
+        };
+
+        // 指定された項目のみ上書きし、それ以外は既定値を使う
+        let result = config.with_settings(Some(&RateLimitSettings {
+            max_concurrent: Some(2),
+            requests_per_minute: None,
+            tokens_per_minute: Some(1000),
+        }));
+        assert_eq!(
+            result,
+            RateLimitConfig {
+                max_concurrent: 2,
+                requests_per_minute: Some(100),
+                tokens_per_minute: Some(1000),
+            }
+        );
+
+        // 設定がない場合は既定値のまま
+        assert_eq!(config.with_settings(None), config);
+    }
+
+    #[test]
+    fn test_reserve() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_concurrent: 1,
+            requests_per_minute: Some(2),
+            tokens_per_minute: Some(100),
+        });
+        let now = Instant::now();
+
+        // リクエスト数の上限
+        assert_eq!(limiter.reserve(10, now), None);
+        assert_eq!(limiter.reserve(10, now), None);
+        assert!(limiter.reserve(10, now).is_some());
+
+        // 1分経過すると枠が空く
+        let now = now + WINDOW;
+        assert_eq!(limiter.reserve(60, now), None);
+        // トークン数の上限
+        assert!(limiter.reserve(60, now).is_some());
+    }
+
+    #[test]
+    fn test_reserve_with_headers() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let now = Instant::now();
+
+        // ヘッダーの残りが0の場合はリセットまで待つ
+        limiter.observe_at(
+            &RateLimitHeaders {
+                remaining_requests: Some(1),
+                reset_requests: Some(Duration::from_secs(5)),
+                ..Default::default()
+            },
+            now,
+        );
+        assert_eq!(limiter.reserve(10, now), None);
+        assert_eq!(limiter.reserve(10, now), Some(Duration::from_secs(5)));
+        assert_eq!(limiter.reserve(10, now + Duration::from_secs(5)), None);
+
+        // ヘッダーの上限が設定より小さい場合はヘッダーの上限を使う
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        limiter.observe_at(
+            &RateLimitHeaders {
+                limit_requests: Some(1),
+                ..Default::default()
+            },
+            now,
+        );
+        assert_eq!(limiter.reserve(10, now), None);
+        assert!(limiter.reserve(10, now).is_some());
+    }
+}
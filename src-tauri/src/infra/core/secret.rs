@@ -0,0 +1,473 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::{env, fmt};
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use sha2::{Digest, Sha256};
+
+use crate::common;
+use crate::common::errors::ApplicationError;
+use crate::common::secret::SecretString;
+use crate::domain::app_setting::SecretCipher;
+
+/// 鍵ファイルの代わりにパスフレーズから鍵を導出する場合に指定する環境変数
+const SECRET_PASSPHRASE_ENV: &str = "APP_SECRET_PASSPHRASE";
+
+/// 暗号化した値の接頭辞（形式: enc:v1:<鍵ID>:<base64(nonce + 暗号文)>）
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+const KEY_FILE_NAME: &str = "secret.key";
+const SALT_FILE_NAME: &str = "secret.salt";
+
+/// ローテーション中の新しい鍵（ソルト）のファイルにつける拡張子
+const PENDING_EXTENSION: &str = "new";
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 鍵IDとして使う鍵のハッシュの文字数
+const KEY_ID_LEN: usize = 8;
+
+/// 鍵の導出元
+enum KeySource {
+    /// ランダムな鍵をファイルに保存する
+    KeyFile,
+    /// パスフレーズとファイルに保存したソルトから鍵を導出する
+    Passphrase(SecretString),
+}
+
+struct SecretKey {
+    id: String,
+    cipher: ChaCha20Poly1305,
+}
+
+struct SecretKeys {
+    current: SecretKey,
+    /// begin_key_rotationで作成した新しい鍵（確定するまで暗号化に使う）
+    pending: Option<SecretKey>,
+    /// 前回のローテーションが中断されて残っていた鍵（復号のみに使う）
+    stale: Option<SecretKey>,
+}
+
+/// appデータのディレクトリに置いた鍵ファイル（またはパスフレーズ）で
+/// ChaCha20-Poly1305により暗号化する
+pub struct LocalSecretCipher {
+    dir: PathBuf,
+    source: KeySource,
+    keys: RwLock<SecretKeys>,
+}
+
+impl fmt::Debug for LocalSecretCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSecretCipher")
+            .field("dir", &self.dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretCipher for LocalSecretCipher {
+    fn encrypt(&self, plaintext: &str) -> Result<String, ApplicationError> {
+        let keys = self.keys.read().unwrap();
+        let key = keys.pending.as_ref().unwrap_or(&keys.current);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = key
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| ApplicationError::UnknownError("Cannot encrypt secret".to_string()))?;
+        let mut data = nonce.to_vec();
+        data.extend(ciphertext);
+        Ok(format!(
+            "{}{}:{}",
+            ENCRYPTED_PREFIX,
+            key.id,
+            STANDARD.encode(data)
+        ))
+    }
+
+    fn decrypt(&self, value: &str) -> Result<String, ApplicationError> {
+        let (key_id, data) = match value.strip_prefix(ENCRYPTED_PREFIX) {
+            Some(encrypted) => encrypted.split_once(':').ok_or_else(|| {
+                ApplicationError::ParseError("invalid encrypted secret".to_string())
+            })?,
+            None => return Ok(value.to_string()),
+        };
+        let keys = self.keys.read().unwrap();
+        let key = [
+            Some(&keys.current),
+            keys.pending.as_ref(),
+            keys.stale.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|key| key.id == key_id)
+        .ok_or_else(|| {
+            ApplicationError::ValidationError(format!(
+                "secret is encrypted with an unknown key: {}",
+                key_id
+            ))
+        })?;
+        let data = STANDARD.decode(data).map_err(|e| {
+            ApplicationError::ParseError(format!("invalid encrypted secret: {}", e))
+        })?;
+        if data.len() < NONCE_LEN {
+            return Err(ApplicationError::ParseError(
+                "invalid encrypted secret".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = key
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                ApplicationError::ValidationError(
+                    "Cannot decrypt secret: the key is wrong or the value is tampered".to_string(),
+                )
+            })?;
+        String::from_utf8(plaintext).map_err(|e| ApplicationError::ParseError(e.to_string()))
+    }
+
+    fn needs_reencryption(&self, value: &str) -> bool {
+        let keys = self.keys.read().unwrap();
+        let key = keys.pending.as_ref().unwrap_or(&keys.current);
+        match value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .and_then(|encrypted| encrypted.split_once(':'))
+        {
+            Some((key_id, _)) => key_id != key.id,
+            None => true,
+        }
+    }
+
+    fn begin_key_rotation(&self) -> Result<(), ApplicationError> {
+        let mut keys = self.keys.write().unwrap();
+        if keys.pending.is_some() {
+            return Err(ApplicationError::ValidationError(
+                "key rotation is already in progress".to_string(),
+            ));
+        }
+        // 中断されたローテーションの鍵が残っている場合は作り直す
+        let path = self.pending_file_path();
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| file_error(&path, e))?;
+        }
+        keys.pending = Some(create_key(&self.source, &path)?);
+        Ok(())
+    }
+
+    fn commit_key_rotation(&self) -> Result<(), ApplicationError> {
+        let mut keys = self.keys.write().unwrap();
+        let pending = keys.pending.take().ok_or_else(|| {
+            ApplicationError::ValidationError("key rotation is not in progress".to_string())
+        })?;
+        let pending_path = self.pending_file_path();
+        if let Err(e) = fs::rename(&pending_path, self.file_path()) {
+            // 新しい鍵で暗号化した値を復号できるように残しておく
+            keys.pending = Some(pending);
+            return Err(file_error(&pending_path, e));
+        }
+        keys.current = pending;
+        keys.stale = None;
+        Ok(())
+    }
+
+    fn abort_key_rotation(&self) -> Result<(), ApplicationError> {
+        let mut keys = self.keys.write().unwrap();
+        let path = self.pending_file_path();
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| file_error(&path, e))?;
+        }
+        keys.pending = None;
+        keys.stale = None;
+        Ok(())
+    }
+}
+
+impl LocalSecretCipher {
+    /// APP_SECRET_PASSPHRASEが指定されている場合はパスフレーズから鍵を導出する
+    pub fn from_env() -> Result<Self, ApplicationError> {
+        let dir = PathBuf::from(common::dir::get_app_home_path()?);
+        let passphrase = env::var(SECRET_PASSPHRASE_ENV)
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty())
+            .map(SecretString::new);
+        LocalSecretCipher::new(dir, passphrase)
+    }
+
+    /// 鍵ファイル（ソルトのファイル）がない場合は作成する
+    pub fn new(dir: PathBuf, passphrase: Option<SecretString>) -> Result<Self, ApplicationError> {
+        let source = match passphrase {
+            Some(passphrase) => KeySource::Passphrase(passphrase),
+            None => KeySource::KeyFile,
+        };
+        fs::create_dir_all(&dir).map_err(|e| file_error(&dir, e))?;
+        let path = secret_file_path(&dir, &source);
+        let current = match load_key(&source, &path)? {
+            Some(key) => key,
+            None => create_key(&source, &path)?,
+        };
+        let stale = load_key(&source, &pending_file_path(&path))?;
+        Ok(LocalSecretCipher {
+            dir,
+            source,
+            keys: RwLock::new(SecretKeys {
+                current,
+                pending: None,
+                stale,
+            }),
+        })
+    }
+
+    fn file_path(&self) -> PathBuf {
+        secret_file_path(&self.dir, &self.source)
+    }
+
+    fn pending_file_path(&self) -> PathBuf {
+        pending_file_path(&self.file_path())
+    }
+}
+
+fn secret_file_path(dir: &Path, source: &KeySource) -> PathBuf {
+    match source {
+        KeySource::KeyFile => dir.join(KEY_FILE_NAME),
+        KeySource::Passphrase(_) => dir.join(SALT_FILE_NAME),
+    }
+}
+
+/// ローテーション中の新しい鍵（ソルト）のファイルのパス
+fn pending_file_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}", PENDING_EXTENSION));
+    path.with_file_name(file_name)
+}
+
+/// ファイルから鍵（ソルト）を読み込む（ファイルがない場合はNone）
+fn load_key(source: &KeySource, path: &Path) -> Result<Option<SecretKey>, ApplicationError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    restrict_permissions(path)?;
+    let content = fs::read_to_string(path).map_err(|e| file_error(path, e))?;
+    let material = STANDARD.decode(content.trim()).map_err(|e| {
+        ApplicationError::ParseError(format!(
+            "invalid secret key file '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+    derive_key(source, &material).map(Some)
+}
+
+/// ランダムな鍵（ソルト）を作成し、本人のみ読み書きできるファイルに保存する
+fn create_key(source: &KeySource, path: &Path) -> Result<SecretKey, ApplicationError> {
+    let mut material = vec![
+        0u8;
+        match source {
+            KeySource::KeyFile => KEY_LEN,
+            KeySource::Passphrase(_) => SALT_LEN,
+        }
+    ];
+    OsRng.fill_bytes(&mut material);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // windowsではユーザーごとのappデータのディレクトリの権限に従う
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| file_error(path, e))?;
+    file.write_all(STANDARD.encode(&material).as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| file_error(path, e))?;
+    derive_key(source, &material)
+}
+
+fn derive_key(source: &KeySource, material: &[u8]) -> Result<SecretKey, ApplicationError> {
+    let mut key = [0u8; KEY_LEN];
+    match source {
+        KeySource::KeyFile => {
+            if material.len() != KEY_LEN {
+                return Err(ApplicationError::ParseError(format!(
+                    "secret key must be {} bytes",
+                    KEY_LEN
+                )));
+            }
+            key.copy_from_slice(material);
+        }
+        KeySource::Passphrase(passphrase) => {
+            Argon2::default()
+                .hash_password_into(passphrase.expose().as_bytes(), material, &mut key)
+                .map_err(|e| {
+                    ApplicationError::UnknownError(format!("Cannot derive secret key: {}", e))
+                })?;
+        }
+    }
+    let id = Sha256::digest(key)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()[..KEY_ID_LEN]
+        .to_string();
+    Ok(SecretKey {
+        id,
+        cipher: ChaCha20Poly1305::new(&key.into()),
+    })
+}
+
+/// 他のユーザーが読めるようになっている鍵ファイルの権限を戻す
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<(), ApplicationError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)
+        .map_err(|e| file_error(path, e))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        log::warn!(
+            "secret key file '{}' is accessible by other users, restrict to 0600",
+            path.display()
+        );
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| file_error(path, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<(), ApplicationError> {
+    Ok(())
+}
+
+fn file_error(path: &Path, e: std::io::Error) -> ApplicationError {
+    ApplicationError::UnknownError(format!(
+        "Cannot access secret key file '{}': {}",
+        path.display(),
+        e
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(test_name: &str) -> PathBuf {
+        let dir = PathBuf::from(common::dir::get_test_home_path().unwrap())
+            .join("secret")
+            .join(test_name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let dir = test_dir("test_encrypt_and_decrypt");
+        let cipher = LocalSecretCipher::new(dir.clone(), None).unwrap();
+
+        let encrypted = cipher.encrypt("sk-test-1234567890").unwrap();
+        assert!(encrypted.starts_with(ENCRYPTED_PREFIX));
+        assert!(!encrypted.contains("sk-test"));
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), "sk-test-1234567890");
+        assert!(!cipher.needs_reencryption(&encrypted));
+
+        // 作成した鍵ファイルで復号できる
+        let reopened = LocalSecretCipher::new(dir.clone(), None).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), "sk-test-1234567890");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.join(KEY_FILE_NAME))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // 改ざんされた値は復号できない
+        let mut data = STANDARD
+            .decode(encrypted.rsplit_once(':').unwrap().1)
+            .unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        let tampered = format!(
+            "{}:{}",
+            encrypted.rsplit_once(':').unwrap().0,
+            STANDARD.encode(data)
+        );
+        assert!(cipher.decrypt(&tampered).is_err());
+
+        // 暗号化される前の平文はそのまま返し、再暗号化の対象にする
+        assert_eq!(cipher.decrypt("sk-plain").unwrap(), "sk-plain");
+        assert!(cipher.needs_reencryption("sk-plain"));
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let dir = test_dir("test_passphrase");
+        let cipher =
+            LocalSecretCipher::new(dir.clone(), Some(SecretString::new("passphrase"))).unwrap();
+        let encrypted = cipher.encrypt("sk-test").unwrap();
+        assert!(dir.join(SALT_FILE_NAME).exists());
+        assert!(!dir.join(KEY_FILE_NAME).exists());
+
+        let reopened =
+            LocalSecretCipher::new(dir.clone(), Some(SecretString::new("passphrase"))).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), "sk-test");
+        let wrong = LocalSecretCipher::new(dir, Some(SecretString::new("wrong"))).unwrap();
+        assert!(wrong.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let dir = test_dir("test_key_rotation");
+        let cipher = LocalSecretCipher::new(dir.clone(), None).unwrap();
+        let old = cipher.encrypt("old").unwrap();
+
+        // 確定するまでは古い鍵と新しい鍵の両方で復号できる
+        cipher.begin_key_rotation().unwrap();
+        let aborted = cipher.encrypt("aborted").unwrap();
+        assert!(cipher.needs_reencryption(&old));
+        assert_eq!(cipher.decrypt(&old).unwrap(), "old");
+        assert_eq!(cipher.decrypt(&aborted).unwrap(), "aborted");
+        cipher.abort_key_rotation().unwrap();
+        assert!(cipher.decrypt(&aborted).is_err());
+        assert!(!cipher.needs_reencryption(&old));
+
+        cipher.begin_key_rotation().unwrap();
+        let new = cipher.encrypt("new").unwrap();
+        cipher.commit_key_rotation().unwrap();
+        assert!(!dir
+            .join(format!("{}.{}", KEY_FILE_NAME, PENDING_EXTENSION))
+            .exists());
+
+        let reopened = LocalSecretCipher::new(dir, None).unwrap();
+        assert_eq!(reopened.decrypt(&new).unwrap(), "new");
+        assert!(reopened.decrypt(&old).is_err());
+    }
+
+    #[test]
+    fn test_interrupted_key_rotation() {
+        let dir = test_dir("test_interrupted_key_rotation");
+        let cipher = LocalSecretCipher::new(dir.clone(), None).unwrap();
+        cipher.begin_key_rotation().unwrap();
+        let new = cipher.encrypt("new").unwrap();
+
+        // 確定前に終了した場合は残っていた新しい鍵でも復号でき、再暗号化の対象になる
+        let reopened = LocalSecretCipher::new(dir.clone(), None).unwrap();
+        assert_eq!(reopened.decrypt(&new).unwrap(), "new");
+        assert!(reopened.needs_reencryption(&new));
+        reopened.abort_key_rotation().unwrap();
+        assert!(!dir
+            .join(format!("{}.{}", KEY_FILE_NAME, PENDING_EXTENSION))
+            .exists());
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use async_openai::types::{CreateEmbeddingRequestArgs, EmbeddingInput};
+use async_trait::async_trait;
+
+use crate::common::errors::ApplicationError;
+use crate::domain::embedding::AIEmbedding;
+use crate::infra::core::openai::AIClient;
+
+/// ドリフト判定などで使用するembeddingモデル
+const EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+#[derive(Clone, Debug)]
+pub struct OpenAIEmbedding<T>
+where
+    T: AIClient,
+{
+    client: Arc<T>,
+}
+
+#[async_trait]
+impl<T> AIEmbedding for OpenAIEmbedding<T>
+where
+    T: AIClient,
+{
+    async fn embed(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>, ApplicationError> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input_len = inputs.len();
+        let req = CreateEmbeddingRequestArgs::default()
+            .model(EMBEDDING_MODEL)
+            .input(EmbeddingInput::StringArray(inputs))
+            .build()
+            .map_err(|e| ApplicationError::UnknownError(e.to_string()))?;
+
+        match self.client.create_embedding(req).await {
+            Ok(response) => {
+                if response.data.len() != input_len {
+                    return Err(ApplicationError::EmptyResult);
+                }
+                // indexの順序は保証されていないので並び替える
+                let mut data = response.data;
+                data.sort_by_key(|d| d.index);
+                Ok(data.into_iter().map(|d| d.embedding).collect())
+            }
+            Err(err) => {
+                log::error!("OpenAI embedding error: {}", err);
+                Err(ApplicationError::OpenAPIError(err.to_string()))
+            }
+        }
+    }
+}
+
+impl<T> OpenAIEmbedding<T>
+where
+    T: AIClient,
+{
+    pub fn new(client: Arc<T>) -> Self {
+        OpenAIEmbedding { client }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_openai::error::OpenAIError;
+    use async_openai::types::{
+        CreateChatCompletionRequest, CreateChatCompletionResponse, CreateEmbeddingRequest,
+        CreateEmbeddingResponse, Embedding, EmbeddingUsage, ListModelResponse,
+    };
+
+    use super::*;
+
+    struct MockOpenAIClient {}
+
+    #[async_trait]
+    impl AIClient for MockOpenAIClient {
+        async fn create_chat(
+            &self,
+            _request: CreateChatCompletionRequest,
+        ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+            Err(OpenAIError::InvalidArgument("not used".to_string()))
+        }
+
+        async fn create_embedding(
+            &self,
+            _request: CreateEmbeddingRequest,
+        ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+            // 逆順で返しても入力順に並び替えられることを確認する
+            Ok(CreateEmbeddingResponse {
+                object: "list".to_string(),
+                model: EMBEDDING_MODEL.to_string(),
+                data: vec![
+                    Embedding {
+                        index: 1,
+                        object: "embedding".to_string(),
+                        embedding: vec![0.0, 1.0],
+                    },
+                    Embedding {
+                        index: 0,
+                        object: "embedding".to_string(),
+                        embedding: vec![1.0, 0.0],
+                    },
+                ],
+                usage: EmbeddingUsage {
+                    prompt_tokens: 0,
+                    total_tokens: 0,
+                },
+            })
+        }
+
+        async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+            Err(OpenAIError::InvalidArgument("not used".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embed() {
+        let embedding = OpenAIEmbedding::new(Arc::new(MockOpenAIClient {}));
+        let result = embedding
+            .embed(vec!["first".to_string(), "second".to_string()])
+            .await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result[0], vec![1.0, 0.0]);
+        assert_eq!(result[1], vec![0.0, 1.0]);
+    }
+}
@@ -0,0 +1,37 @@
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::domain::event::EventEmitter;
+
+/// tauriのイベントとして全てのウィンドウに通知するEventEmitter
+/// AppHandleはtauriの起動後（setup）に設定されるため、それまでのイベントは破棄する
+#[derive(Debug, Default)]
+pub struct TauriEventEmitter {
+    app_handle: OnceCell<AppHandle>,
+}
+
+impl EventEmitter for TauriEventEmitter {
+    fn emit<T: Serialize + Clone>(&self, event: &str, payload: T) {
+        let app_handle = match self.app_handle.get() {
+            Some(app_handle) => app_handle,
+            None => {
+                log::debug!("app is not ready, drop event: {}", event);
+                return;
+            }
+        };
+        if let Err(err) = app_handle.emit_all(event, payload) {
+            log::error!("emit event error: {}: {}", event, err);
+        }
+    }
+}
+
+impl TauriEventEmitter {
+    pub fn new() -> Self {
+        TauriEventEmitter::default()
+    }
+
+    pub fn set_app_handle(&self, app_handle: AppHandle) {
+        let _ = self.app_handle.set(app_handle);
+    }
+}
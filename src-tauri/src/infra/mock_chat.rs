@@ -0,0 +1,399 @@
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use strum_macros::{Display, EnumString};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::chat::{AIChat, ChatAnswer, ChatMessage, ChatSettings};
+
+/// モックプロバイダーの動作を指定する環境変数（echo / canned / lorem / fail、未指定の場合は実際のプロバイダーを使う）
+const MOCK_CHAT_ENV: &str = "APP_MOCK_CHAT";
+/// cannedで返すテキスト
+const MOCK_CHAT_TEXT_ENV: &str = "APP_MOCK_CHAT_TEXT";
+/// 回答を返すまでの待ち時間（ミリ秒）
+const MOCK_CHAT_LATENCY_ENV: &str = "APP_MOCK_CHAT_LATENCY_MS";
+/// failで返すエラーの種類（openai / empty / parse / validation / unknown）
+const MOCK_CHAT_ERROR_ENV: &str = "APP_MOCK_CHAT_ERROR";
+/// モックプロバイダーを使える実行モードを判定する環境変数
+const EXECUTION_MODE_ENV: &str = "APP_EXECUTION_MODE";
+
+const DEFAULT_CANNED_TEXT: &str = "This is a mock response.";
+
+/// loremで生成する単語数
+const LOREM_WORDS: usize = 30;
+
+const LOREM_IPSUM: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "ad",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+    "aliquip",
+];
+
+/// failで返すエラーの種類
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum MockChatError {
+    #[strum(serialize = "openai")]
+    OpenAI,
+    Empty,
+    Parse,
+    Validation,
+    Unknown,
+}
+
+impl MockChatError {
+    fn to_application_error(self) -> ApplicationError {
+        match self {
+            MockChatError::OpenAI => {
+                ApplicationError::OpenAPIError("mock: rate limit exceeded".to_string())
+            }
+            MockChatError::Empty => ApplicationError::EmptyResult,
+            MockChatError::Parse => ApplicationError::ParseError("mock: invalid json".to_string()),
+            MockChatError::Validation => {
+                ApplicationError::ValidationError("mock: invalid request".to_string())
+            }
+            MockChatError::Unknown => ApplicationError::UnknownError("mock: failure".to_string()),
+        }
+    }
+}
+
+/// モックプロバイダーの回答の生成方法
+#[derive(Clone, Debug, PartialEq)]
+pub enum MockChatBehavior {
+    /// ユーザープロンプトをそのまま返す
+    Echo,
+    /// 指定したテキストを返す
+    Canned(String),
+    /// ランダムなlorem ipsumを返す（seedが指定されている場合は同じ文章になる）
+    Lorem,
+    /// 指定した種類のエラーを返す
+    Fail(MockChatError),
+}
+
+/// 通信を行わずに回答を返すAIChat（オフラインでの開発用）
+#[derive(Clone, Debug)]
+pub struct MockChat {
+    behavior: MockChatBehavior,
+    latency: Duration,
+}
+
+#[async_trait]
+impl AIChat for MockChat {
+    async fn do_chat(&self, settings: &ChatSettings) -> Result<String, ApplicationError> {
+        self.answer(&settings.user_prompt, settings.sampling.seed, 0)
+            .await
+    }
+
+    async fn do_chat_samples(
+        &self,
+        settings: &ChatSettings,
+        n: u8,
+        answers: &mut Vec<String>,
+    ) -> Result<(), ApplicationError> {
+        for index in 0..n {
+            answers.push(
+                self.answer(&settings.user_prompt, settings.sampling.seed, index as u64)
+                    .await?,
+            );
+        }
+        Ok(())
+    }
+
+    /// 最後のメッセージに対して回答する（ツールは呼び出さない）
+    async fn do_chat_messages(
+        &self,
+        settings: &ChatSettings,
+        messages: &[ChatMessage],
+    ) -> Result<ChatAnswer, ApplicationError> {
+        let prompt = match messages.last() {
+            Some(ChatMessage::User { content }) => content.as_str(),
+            Some(ChatMessage::Tool { content, .. }) => content.as_str(),
+            Some(ChatMessage::Assistant { content, .. }) => content.as_deref().unwrap_or_default(),
+            None => "",
+        };
+        let content = self
+            .answer(prompt, settings.sampling.seed, messages.len() as u64)
+            .await?;
+        Ok(ChatAnswer {
+            content: Some(content),
+            tool_calls: Vec::new(),
+        })
+    }
+}
+
+impl MockChat {
+    pub fn new(behavior: MockChatBehavior, latency: Duration) -> Self {
+        MockChat { behavior, latency }
+    }
+
+    /// 環境変数から動作を読み込む（未指定の場合はNone）
+    pub fn from_env() -> Result<Option<Self>, ApplicationError> {
+        let behavior = env::var(MOCK_CHAT_ENV).unwrap_or_default();
+        let behavior = match behavior.as_str() {
+            "" => return Ok(None),
+            "echo" => MockChatBehavior::Echo,
+            "canned" => MockChatBehavior::Canned(
+                env::var(MOCK_CHAT_TEXT_ENV).unwrap_or(DEFAULT_CANNED_TEXT.to_string()),
+            ),
+            "lorem" => MockChatBehavior::Lorem,
+            "fail" => {
+                let error = env::var(MOCK_CHAT_ERROR_ENV).unwrap_or("openai".to_string());
+                MockChatBehavior::Fail(MockChatError::from_str(&error).map_err(|_| {
+                    ApplicationError::ValidationError(format!(
+                        "{} must be openai, empty, parse, validation or unknown: {}",
+                        MOCK_CHAT_ERROR_ENV, error
+                    ))
+                })?)
+            }
+            _ => {
+                return Err(ApplicationError::ValidationError(format!(
+                    "{} must be echo, canned, lorem or fail: {}",
+                    MOCK_CHAT_ENV, behavior
+                )))
+            }
+        };
+        let latency = match env::var(MOCK_CHAT_LATENCY_ENV) {
+            Ok(latency) => latency.parse::<u64>().map_err(|e| {
+                ApplicationError::ValidationError(format!("{}: {}", MOCK_CHAT_LATENCY_ENV, e))
+            })?,
+            Err(_) => 0,
+        };
+        Ok(Some(MockChat::new(
+            behavior,
+            Duration::from_millis(latency),
+        )))
+    }
+
+    async fn answer(
+        &self,
+        prompt: &str,
+        seed: Option<i64>,
+        sample_index: u64,
+    ) -> Result<String, ApplicationError> {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        match &self.behavior {
+            MockChatBehavior::Echo => Ok(prompt.to_string()),
+            MockChatBehavior::Canned(text) => Ok(text.clone()),
+            MockChatBehavior::Lorem => {
+                let seed = match seed {
+                    Some(seed) => seed as u64,
+                    None => chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64,
+                };
+                Ok(lorem_ipsum(seed.wrapping_add(sample_index), LOREM_WORDS))
+            }
+            MockChatBehavior::Fail(error) => Err(error.to_application_error()),
+        }
+    }
+}
+
+/// seedから疑似乱数（xorshift）で単語を選んでlorem ipsumを生成する
+fn lorem_ipsum(seed: u64, words: usize) -> String {
+    // xorshiftは0から抜け出せないので0以外の値にする
+    let mut state = seed ^ 0x9E37_79B9_7F4A_7C15;
+    let mut text = Vec::with_capacity(words);
+    for _ in 0..words {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        text.push(LOREM_IPSUM[(state % LOREM_IPSUM.len() as u64) as usize]);
+    }
+    let mut text = text.join(" ");
+    if let Some(first) = text.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    text + "."
+}
+
+/// 環境変数でモックプロバイダーが指定されている場合はモックを使うAIChat
+/// モックは全てのリクエストを置き換えるので、開発モード（APP_EXECUTION_MODE=dev）でのみ使える
+pub enum MockableChat<T: AIChat> {
+    Live(T),
+    Mock(MockChat),
+}
+
+impl<T: AIChat> MockableChat<T> {
+    pub fn from_env(live: T) -> Result<Self, ApplicationError> {
+        let dev_mode = env::var(EXECUTION_MODE_ENV).unwrap_or_default() == "dev";
+        Self::new(live, MockChat::from_env()?, dev_mode)
+    }
+
+    fn new(live: T, mock: Option<MockChat>, dev_mode: bool) -> Result<Self, ApplicationError> {
+        Ok(match mock {
+            Some(_) if !dev_mode => {
+                return Err(ApplicationError::ValidationError(format!(
+                    "{} is only available when {}=dev",
+                    MOCK_CHAT_ENV, EXECUTION_MODE_ENV
+                )))
+            }
+            Some(mock) => {
+                log::warn!(
+                    "mock chat provider is active, all chats return mock responses: {:?}",
+                    mock.behavior
+                );
+                MockableChat::Mock(mock)
+            }
+            None => MockableChat::Live(live),
+        })
+    }
+}
+
+#[async_trait]
+impl<T: AIChat> AIChat for MockableChat<T> {
+    async fn do_chat(&self, settings: &ChatSettings) -> Result<String, ApplicationError> {
+        match self {
+            MockableChat::Live(chat) => chat.do_chat(settings).await,
+            MockableChat::Mock(chat) => chat.do_chat(settings).await,
+        }
+    }
+
+    async fn do_chat_samples(
+        &self,
+        settings: &ChatSettings,
+        n: u8,
+        answers: &mut Vec<String>,
+    ) -> Result<(), ApplicationError> {
+        match self {
+            MockableChat::Live(chat) => chat.do_chat_samples(settings, n, answers).await,
+            MockableChat::Mock(chat) => chat.do_chat_samples(settings, n, answers).await,
+        }
+    }
+
+    async fn do_chat_messages(
+        &self,
+        settings: &ChatSettings,
+        messages: &[ChatMessage],
+    ) -> Result<ChatAnswer, ApplicationError> {
+        match self {
+            MockableChat::Live(chat) => chat.do_chat_messages(settings, messages).await,
+            MockableChat::Mock(chat) => chat.do_chat_messages(settings, messages).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::chat::SamplingParameters;
+    use crate::domain::comparing_prompt::ProviderType;
+
+    use super::*;
+
+    fn settings(seed: Option<i64>) -> ChatSettings {
+        ChatSettings {
+            id: 0,
+            provider_type: ProviderType::OpenAI,
+            user_prompt: "Hello".to_string(),
+            system_prompt: "You are a helpful assistant.".to_string(),
+            model: "mock".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            sampling: SamplingParameters {
+                seed,
+                ..Default::default()
+            },
+            tools: Vec::new(),
+            tool_choice: None,
+            credential: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_do_chat() {
+        let chat = MockChat::new(MockChatBehavior::Echo, Duration::ZERO);
+        assert_eq!(chat.do_chat(&settings(None)).await.unwrap(), "Hello");
+
+        let chat = MockChat::new(
+            MockChatBehavior::Canned("canned".to_string()),
+            Duration::from_millis(1),
+        );
+        assert_eq!(chat.do_chat(&settings(None)).await.unwrap(), "canned");
+
+        let chat = MockChat::new(
+            MockChatBehavior::Fail(MockChatError::Validation),
+            Duration::ZERO,
+        );
+        let result = chat.do_chat(&settings(None)).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_do_chat_samples_lorem() {
+        let chat = MockChat::new(MockChatBehavior::Lorem, Duration::ZERO);
+        let mut answers = Vec::new();
+        chat.do_chat_samples(&settings(Some(1)), 2, &mut answers)
+            .await
+            .unwrap();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0].split(' ').count(), LOREM_WORDS);
+        assert!(answers[0].ends_with('.'));
+        // サンプルごとに異なり、seedが同じなら同じ文章になる
+        assert_ne!(answers[0], answers[1]);
+        let mut again = Vec::new();
+        chat.do_chat_samples(&settings(Some(1)), 2, &mut again)
+            .await
+            .unwrap();
+        assert_eq!(answers, again);
+    }
+
+    #[tokio::test]
+    async fn test_mockable_chat_dev_mode() {
+        let live = || MockChat::new(MockChatBehavior::Canned("live".to_string()), Duration::ZERO);
+        let mock = || Some(MockChat::new(MockChatBehavior::Echo, Duration::ZERO));
+
+        // 開発モードの場合はモックを使う
+        let chat = MockableChat::new(live(), mock(), true).unwrap();
+        assert_eq!(chat.do_chat(&settings(None)).await.unwrap(), "Hello");
+
+        // 開発モード以外ではモックを指定できない
+        let result = MockableChat::new(live(), mock(), false);
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+
+        // モックが指定されていない場合は実際のプロバイダーを使う
+        let chat = MockableChat::new(live(), None, false).unwrap();
+        assert_eq!(chat.do_chat(&settings(None)).await.unwrap(), "live");
+    }
+
+    #[test]
+    fn test_mock_chat_error_from_str() {
+        assert_eq!(
+            MockChatError::from_str("openai").unwrap(),
+            MockChatError::OpenAI
+        );
+        assert_eq!(
+            MockChatError::from_str("empty").unwrap(),
+            MockChatError::Empty
+        );
+        assert!(MockChatError::from_str("timeout").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::ProviderType;
+use crate::domain::model_catalog::{AIModelList, ListedModel};
+use crate::infra::core::openai::AIClient;
+
+#[derive(Clone, Debug)]
+pub struct ModelListImpl<T, Z>
+where
+    T: AIClient,
+    Z: AIClient,
+{
+    openai_client: Arc<T>,
+    azure_openai_client: Arc<Z>,
+}
+
+#[async_trait]
+impl<T, Z> AIModelList for ModelListImpl<T, Z>
+where
+    T: AIClient,
+    Z: AIClient,
+{
+    async fn list_models(
+        &self,
+        provider_type: &ProviderType,
+    ) -> Result<Vec<ListedModel>, ApplicationError> {
+        match provider_type {
+            ProviderType::OpenAI => match self.openai_client.list_models().await {
+                Ok(response) => {
+                    let mut models: Vec<ListedModel> = response
+                        .data
+                        .into_iter()
+                        .map(|model| ListedModel::new(model.id))
+                        .collect();
+                    models.sort_by(|a, b| a.model.cmp(&b.model));
+                    Ok(models)
+                }
+                Err(err) => {
+                    log::error!("OpenAI list models error: {}", err);
+                    Err(ApplicationError::OpenAPIError(err.to_string()))
+                }
+            },
+            // Azure OpenAIはモデルの一覧ではなく、設定されたデプロイメントを返す
+            ProviderType::AzureOpenAI => match self.azure_openai_client.list_models().await {
+                Ok(response) => Ok(response
+                    .data
+                    .into_iter()
+                    .map(|model| ListedModel {
+                        model: model.id,
+                        base_model: Some(model.owned_by),
+                    })
+                    .collect()),
+                Err(err) => {
+                    log::error!("Azure OpenAI list models error: {}", err);
+                    Err(ApplicationError::OpenAPIError(err.to_string()))
+                }
+            },
+            // Geminiのクライアントは未実装なので、モデル一覧は取得できない
+            ProviderType::Gemini => Err(ApplicationError::ValidationError(format!(
+                "listing models is not supported for provider: {}",
+                provider_type
+            ))),
+        }
+    }
+}
+
+impl<T, Z> ModelListImpl<T, Z>
+where
+    T: AIClient,
+    Z: AIClient,
+{
+    pub fn new(openai_client: Arc<T>, azure_openai_client: Arc<Z>) -> Self {
+        ModelListImpl {
+            openai_client,
+            azure_openai_client,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_openai::error::OpenAIError;
+    use async_openai::types::{
+        CreateChatCompletionRequest, CreateChatCompletionResponse, CreateEmbeddingRequest,
+        CreateEmbeddingResponse, ListModelResponse, Model,
+    };
+
+    use crate::domain::app_setting::{ApiKeyUpdater, AzureOpenAIDeployment, AzureOpenAISettings};
+    use crate::infra::core::azure_openai::AzureOpenAIClient;
+    use crate::infra::core::http::{HttpClientConfig, SharedHttpClient};
+    use crate::infra::core::rate_limit::RateLimitConfig;
+
+    use super::*;
+
+    struct MockOpenAIClient;
+
+    #[async_trait]
+    impl AIClient for MockOpenAIClient {
+        async fn create_chat(
+            &self,
+            _request: CreateChatCompletionRequest,
+        ) -> Result<CreateChatCompletionResponse, OpenAIError> {
+            Err(OpenAIError::InvalidArgument("not used".to_string()))
+        }
+
+        async fn create_embedding(
+            &self,
+            _request: CreateEmbeddingRequest,
+        ) -> Result<CreateEmbeddingResponse, OpenAIError> {
+            Err(OpenAIError::InvalidArgument("not used".to_string()))
+        }
+
+        async fn list_models(&self) -> Result<ListModelResponse, OpenAIError> {
+            let model = |id: &str| Model {
+                id: id.to_string(),
+                object: "model".to_string(),
+                created: 0,
+                owned_by: "openai".to_string(),
+            };
+            Ok(ListModelResponse {
+                object: "list".to_string(),
+                data: vec![model("gpt-4o"), model("gpt-3.5-turbo")],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let azure_openai_client = AzureOpenAIClient::new(
+            RateLimitConfig::default(),
+            Arc::new(SharedHttpClient::new(HttpClientConfig::default()).unwrap()),
+        );
+        azure_openai_client.update_azure_openai_settings(Some(&AzureOpenAISettings {
+            endpoint: "https://example.openai.azure.com".to_string(),
+            api_version: "2024-02-01".to_string(),
+            deployments: vec![AzureOpenAIDeployment {
+                deployment: "prod-gpt4o".to_string(),
+                model: "gpt-4o".to_string(),
+            }],
+        }));
+        let model_list =
+            ModelListImpl::new(Arc::new(MockOpenAIClient), Arc::new(azure_openai_client));
+        let result = model_list.list_models(&ProviderType::OpenAI).await;
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                ListedModel::new("gpt-3.5-turbo"),
+                ListedModel::new("gpt-4o")
+            ]
+        );
+
+        let result = model_list.list_models(&ProviderType::AzureOpenAI).await;
+        assert_eq!(
+            result.unwrap(),
+            vec![ListedModel {
+                model: "prod-gpt4o".to_string(),
+                base_model: Some("gpt-4o".to_string()),
+            }]
+        );
+
+        // 未対応のプロバイダー
+        let result = model_list.list_models(&ProviderType::Gemini).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+}
@@ -1,5 +1,14 @@
+pub mod app_setting;
+pub mod comparing_prompt_baseline;
 pub mod comparing_prompt_run;
 pub mod comparing_prompt_setting;
 mod entities;
+pub mod job;
+pub mod model_catalog;
 pub mod prompt_manager;
 mod relation;
+pub mod response_cache;
+pub mod search;
+pub mod tag;
+pub mod trash;
+mod visibility;
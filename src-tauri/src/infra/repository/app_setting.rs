@@ -0,0 +1,617 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, LoaderTrait, QueryFilter,
+    QueryOrder, TransactionTrait,
+};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::app_setting::{
+    AppSettingModel, AppSettingName, AppSettingRepository, CredentialProfileModel,
+    CredentialProfileUsageModel, SecretRef,
+};
+use crate::domain::comparing_prompt::ProviderType;
+use crate::infra::repository::entities::prelude::{
+    AppSettings, ComparingPromptRunHistories, ComparingPromptRuns, CredentialProfiles,
+    PromptManagerCredentialProfiles,
+};
+use crate::infra::repository::entities::{
+    app_settings, comparing_prompt_run_histories, comparing_prompt_runs, credential_profiles,
+    prompt_manager_credential_profiles,
+};
+use crate::infra::repository::visibility::{active_manager_ids, active_version_ids};
+
+#[derive(Clone, Debug)]
+pub struct AppSettingRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl AppSettingRepository for AppSettingRepositoryImpl {
+    async fn find_app_settings(&self) -> Result<Vec<AppSettingModel>, ApplicationError> {
+        let res = AppSettings::find()
+            .order_by_asc(app_settings::Column::Name)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.into_iter()
+            .map(|setting| {
+                Ok(AppSettingModel {
+                    name: AppSettingName::from_str(&setting.name)?,
+                    value: setting.value,
+                })
+            })
+            .collect()
+    }
+
+    async fn save_app_settings(
+        &self,
+        settings: &[(AppSettingName, Option<String>)],
+    ) -> Result<(), ApplicationError> {
+        let now = chrono::Utc::now().to_string();
+        let txn = self.db.begin().await?;
+        for (name, value) in settings {
+            match value {
+                Some(value) => {
+                    let setting = app_settings::ActiveModel {
+                        id: Default::default(),
+                        name: ActiveValue::Set(name.to_string()),
+                        value: ActiveValue::Set(value.clone()),
+                        updated_at: ActiveValue::Set(now.clone()),
+                    };
+                    AppSettings::insert(setting)
+                        .on_conflict(
+                            OnConflict::column(app_settings::Column::Name)
+                                .update_columns([
+                                    app_settings::Column::Value,
+                                    app_settings::Column::UpdatedAt,
+                                ])
+                                .to_owned(),
+                        )
+                        .exec(&txn)
+                        .await?;
+                }
+                None => {
+                    AppSettings::delete_many()
+                        .filter(app_settings::Column::Name.eq(name.to_string()))
+                        .exec(&txn)
+                        .await?;
+                }
+            }
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn find_secrets(&self) -> Result<Vec<(SecretRef, String)>, ApplicationError> {
+        let mut secrets = self
+            .find_app_settings()
+            .await?
+            .into_iter()
+            .filter(|setting| setting.name.is_secret())
+            .map(|setting| (SecretRef::AppSetting(setting.name), setting.value))
+            .collect::<Vec<_>>();
+        let profiles = CredentialProfiles::find()
+            .filter(credential_profiles::Column::DeletedAt.is_null())
+            .order_by_asc(credential_profiles::Column::Id)
+            .all(self.db.as_ref())
+            .await?;
+        secrets.extend(
+            profiles
+                .into_iter()
+                .map(|profile| (SecretRef::CredentialProfile(profile.id), profile.api_key)),
+        );
+        Ok(secrets)
+    }
+
+    async fn save_secrets(&self, secrets: &[(SecretRef, String)]) -> Result<(), ApplicationError> {
+        let now = chrono::Utc::now().to_string();
+        let txn = self.db.begin().await?;
+        for (secret_ref, value) in secrets {
+            match secret_ref {
+                SecretRef::AppSetting(name) => {
+                    AppSettings::update_many()
+                        .col_expr(app_settings::Column::Value, value.clone().into())
+                        .col_expr(app_settings::Column::UpdatedAt, now.clone().into())
+                        .filter(app_settings::Column::Name.eq(name.to_string()))
+                        .exec(&txn)
+                        .await?;
+                }
+                SecretRef::CredentialProfile(id) => {
+                    CredentialProfiles::update_many()
+                        .col_expr(credential_profiles::Column::ApiKey, value.clone().into())
+                        .col_expr(credential_profiles::Column::UpdatedAt, now.clone().into())
+                        .filter(credential_profiles::Column::Id.eq(*id))
+                        .exec(&txn)
+                        .await?;
+                }
+            }
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn find_credential_profiles(
+        &self,
+    ) -> Result<Vec<CredentialProfileModel>, ApplicationError> {
+        let res = CredentialProfiles::find()
+            .order_by_asc(credential_profiles::Column::Id)
+            .all(self.db.as_ref())
+            .await?;
+        res.into_iter().map(to_credential_profile_model).collect()
+    }
+
+    async fn find_credential_profile_by_id(
+        &self,
+        id: i32,
+    ) -> Result<CredentialProfileModel, ApplicationError> {
+        let res = CredentialProfiles::find_by_id(id)
+            .one(self.db.as_ref())
+            .await?
+            .ok_or(ApplicationError::EmptyResult)?;
+        to_credential_profile_model(res)
+    }
+
+    async fn create_credential_profile(
+        &self,
+        param: CredentialProfileModel,
+    ) -> Result<i32, ApplicationError> {
+        let now = chrono::Utc::now().to_string();
+        let profile = credential_profiles::ActiveModel {
+            id: Default::default(),
+            provider_type: ActiveValue::Set(param.provider_type.to_string()),
+            label: ActiveValue::Set(param.label),
+            api_key: ActiveValue::Set(param.api_key),
+            organization_id: ActiveValue::Set(param.organization_id),
+            project_id: ActiveValue::Set(param.project_id),
+            created_at: ActiveValue::Set(now.clone()),
+            updated_at: ActiveValue::Set(now),
+            deleted_at: ActiveValue::Set(None),
+        };
+        let res = CredentialProfiles::insert(profile)
+            .exec(self.db.as_ref())
+            .await?;
+        Ok(res.last_insert_id)
+    }
+
+    async fn update_credential_profile(
+        &self,
+        param: CredentialProfileModel,
+    ) -> Result<(), ApplicationError> {
+        let profile = credential_profiles::ActiveModel {
+            id: ActiveValue::Unchanged(param.id),
+            label: ActiveValue::Set(param.label),
+            api_key: ActiveValue::Set(param.api_key),
+            organization_id: ActiveValue::Set(param.organization_id),
+            project_id: ActiveValue::Set(param.project_id),
+            updated_at: ActiveValue::Set(chrono::Utc::now().to_string()),
+            ..Default::default()
+        };
+        CredentialProfiles::update(profile)
+            .exec(self.db.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    async fn logical_delete_credential_profile(&self, id: i32) -> Result<(), ApplicationError> {
+        let now = chrono::Utc::now().to_string();
+        let txn = self.db.begin().await?;
+        let profile = credential_profiles::ActiveModel {
+            id: ActiveValue::Unchanged(id),
+            api_key: ActiveValue::Set("".to_string()),
+            updated_at: ActiveValue::Set(now.clone()),
+            deleted_at: ActiveValue::Set(Some(now)),
+            ..Default::default()
+        };
+        CredentialProfiles::update(profile).exec(&txn).await?;
+        PromptManagerCredentialProfiles::delete_many()
+            .filter(prompt_manager_credential_profiles::Column::CredentialProfileId.eq(id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn find_manager_credential_profiles(
+        &self,
+        manager_id: i32,
+    ) -> Result<Vec<(ProviderType, i32)>, ApplicationError> {
+        let res = PromptManagerCredentialProfiles::find()
+            .filter(prompt_manager_credential_profiles::Column::ManagerId.eq(manager_id))
+            .order_by_asc(prompt_manager_credential_profiles::Column::ProviderType)
+            .all(self.db.as_ref())
+            .await?;
+        res.into_iter()
+            .map(|selection| {
+                Ok((
+                    parse_provider_type(&selection.provider_type)?,
+                    selection.credential_profile_id,
+                ))
+            })
+            .collect()
+    }
+
+    async fn save_manager_credential_profile(
+        &self,
+        manager_id: i32,
+        provider_type: &ProviderType,
+        credential_profile_id: Option<i32>,
+    ) -> Result<(), ApplicationError> {
+        match credential_profile_id {
+            Some(credential_profile_id) => {
+                let selection = prompt_manager_credential_profiles::ActiveModel {
+                    id: Default::default(),
+                    manager_id: ActiveValue::Set(manager_id),
+                    provider_type: ActiveValue::Set(provider_type.to_string()),
+                    credential_profile_id: ActiveValue::Set(credential_profile_id),
+                };
+                PromptManagerCredentialProfiles::insert(selection)
+                    .on_conflict(
+                        OnConflict::columns([
+                            prompt_manager_credential_profiles::Column::ManagerId,
+                            prompt_manager_credential_profiles::Column::ProviderType,
+                        ])
+                        .update_column(
+                            prompt_manager_credential_profiles::Column::CredentialProfileId,
+                        )
+                        .to_owned(),
+                    )
+                    .exec(self.db.as_ref())
+                    .await?;
+            }
+            None => {
+                PromptManagerCredentialProfiles::delete_many()
+                    .filter(prompt_manager_credential_profiles::Column::ManagerId.eq(manager_id))
+                    .filter(
+                        prompt_manager_credential_profiles::Column::ProviderType
+                            .eq(provider_type.to_string()),
+                    )
+                    .exec(self.db.as_ref())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn find_credential_profile_usages(
+        &self,
+    ) -> Result<Vec<CredentialProfileUsageModel>, ApplicationError> {
+        // 削除したマネージャーの実行と、削除した設定の履歴は集計しない
+        let runs = ComparingPromptRuns::find()
+            .filter(comparing_prompt_runs::Column::ManagerId.in_subquery(active_manager_ids()))
+            .order_by_asc(comparing_prompt_runs::Column::Id)
+            .all(self.db.as_ref())
+            .await?;
+        let histories = runs
+            .load_many(
+                ComparingPromptRunHistories::find().filter(
+                    comparing_prompt_run_histories::Column::VersionId
+                        .in_subquery(active_version_ids()),
+                ),
+                self.db.as_ref(),
+            )
+            .await?;
+
+        // プロファイル・プロバイダー・モデルの組み合わせごとに集計する（初めて出現した順）
+        let mut usages: Vec<CredentialProfileUsageModel> = vec![];
+        for (run, histories) in runs.into_iter().zip(histories) {
+            let provider_type = parse_provider_type(&run.provider_type)?;
+            let index = match usages.iter().position(|usage| {
+                usage.credential_profile_id == run.credential_profile_id
+                    && usage.provider_type == provider_type
+                    && usage.model == run.model
+            }) {
+                Some(index) => index,
+                None => {
+                    usages.push(CredentialProfileUsageModel {
+                        credential_profile_id: run.credential_profile_id,
+                        provider_type,
+                        model: run.model.clone(),
+                        runs: 0,
+                        responses: 0,
+                        cached_responses: 0,
+                    });
+                    usages.len() - 1
+                }
+            };
+            let usage = &mut usages[index];
+            usage.runs += 1;
+            for history in histories {
+                if history.cache_hit {
+                    usage.cached_responses += 1;
+                } else if !history.cancelled {
+                    usage.responses += 1;
+                }
+            }
+        }
+        Ok(usages)
+    }
+}
+
+fn parse_provider_type(provider_type: &str) -> Result<ProviderType, ApplicationError> {
+    ProviderType::from_str(provider_type).map_err(|e| ApplicationError::ParseError(e.to_string()))
+}
+
+fn to_credential_profile_model(
+    profile: credential_profiles::Model,
+) -> Result<CredentialProfileModel, ApplicationError> {
+    Ok(CredentialProfileModel {
+        id: profile.id,
+        provider_type: parse_provider_type(&profile.provider_type)?,
+        label: profile.label,
+        api_key: profile.api_key,
+        organization_id: profile.organization_id,
+        project_id: profile.project_id,
+        deleted: profile.deleted_at.is_some(),
+    })
+}
+
+impl AppSettingRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        AppSettingRepositoryImpl { db }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::thelper::db::setup_db;
+    use crate::infra::repository::entities::prelude::{
+        ComparingPromptManager, ComparingPromptSettingVersions, ComparingPromptSettings,
+        PromptManager,
+    };
+    use crate::infra::repository::entities::{
+        comparing_prompt_manager, comparing_prompt_setting_versions, comparing_prompt_settings,
+        prompt_manager,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_app_settings() {
+        let db = setup_db("test_save_app_settings").await;
+        let repository = AppSettingRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        repository
+            .save_app_settings(&[
+                (
+                    AppSettingName::ApiKey(ProviderType::OpenAI),
+                    Some("old-key".to_string()),
+                ),
+                (AppSettingName::DefaultModel, Some("gpt-4".to_string())),
+            ])
+            .await
+            .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        repository
+            .save_app_settings(&[
+                (
+                    AppSettingName::ApiKey(ProviderType::OpenAI),
+                    Some("new-key".to_string()),
+                ),
+                (AppSettingName::DefaultModel, None),
+                (AppSettingName::Theme, Some("dark".to_string())),
+            ])
+            .await
+            .unwrap();
+
+        // assert
+        // 値は上書きされ、Noneの設定は削除される
+        let settings = repository.find_app_settings().await.unwrap();
+        assert_eq!(
+            settings,
+            vec![
+                AppSettingModel {
+                    name: AppSettingName::ApiKey(ProviderType::OpenAI),
+                    value: "new-key".to_string(),
+                },
+                AppSettingModel {
+                    name: AppSettingName::Theme,
+                    value: "dark".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_logical_delete_credential_profile() {
+        let db = setup_db("test_logical_delete_credential_profile").await;
+        let repository = AppSettingRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager = prompt_manager::ActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set("test_title".to_string()),
+            action_type: ActiveValue::Set(None),
+            api_type: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(None),
+        };
+        let manager_id = PromptManager::insert(manager)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert prompt manager")
+            .last_insert_id;
+        let profile = |label: &str, api_key: &str| CredentialProfileModel {
+            id: 0,
+            provider_type: ProviderType::OpenAI,
+            label: label.to_string(),
+            api_key: api_key.to_string(),
+            organization_id: Some("org-test".to_string()),
+            project_id: None,
+            deleted: false,
+        };
+        let team_id = repository
+            .create_credential_profile(profile("team", "enc-team"))
+            .await
+            .unwrap();
+        let personal_id = repository
+            .create_credential_profile(profile("personal", "enc-personal"))
+            .await
+            .unwrap();
+        repository
+            .save_manager_credential_profile(manager_id, &ProviderType::OpenAI, Some(personal_id))
+            .await
+            .unwrap();
+        repository
+            .save_manager_credential_profile(manager_id, &ProviderType::OpenAI, Some(team_id))
+            .await
+            .unwrap();
+        assert_eq!(
+            repository
+                .find_manager_credential_profiles(manager_id)
+                .await
+                .unwrap(),
+            vec![(ProviderType::OpenAI, team_id)]
+        );
+
+        // テスト対象のメソッドを呼び出し
+        repository
+            .logical_delete_credential_profile(team_id)
+            .await
+            .unwrap();
+
+        // assert
+        // APIキーは消去され、ラベルは残る
+        let deleted = repository
+            .find_credential_profile_by_id(team_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            deleted,
+            CredentialProfileModel {
+                id: team_id,
+                api_key: "".to_string(),
+                deleted: true,
+                ..profile("team", "")
+            }
+        );
+        // 選択は解除され、暗号化し直す対象からも外れる
+        assert!(repository
+            .find_manager_credential_profiles(manager_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            repository.find_secrets().await.unwrap(),
+            vec![(
+                SecretRef::CredentialProfile(personal_id),
+                "enc-personal".to_string()
+            )]
+        );
+    }
+
+    /// マネージャー、設定、実行を作成し、設定ごとに1つずつ履歴を作成する
+    async fn seed_run(
+        db: Arc<DatabaseConnection>,
+        manager_deleted: bool,
+        settings_deleted: &[bool],
+    ) {
+        let deleted_at = || Some(chrono::Utc::now().to_string());
+        let manager_id = PromptManager::insert(prompt_manager::ActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set("test_title".to_string()),
+            action_type: ActiveValue::Set(None),
+            api_type: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(manager_deleted.then(deleted_at).flatten()),
+        })
+        .exec(db.as_ref())
+        .await
+        .unwrap()
+        .last_insert_id;
+        ComparingPromptManager::insert(comparing_prompt_manager::ActiveModel {
+            manager_id: ActiveValue::Set(manager_id),
+        })
+        .exec(db.as_ref())
+        .await
+        .unwrap();
+        let run_id = ComparingPromptRuns::insert(comparing_prompt_runs::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            provider_type: ActiveValue::Set(ProviderType::OpenAI.to_string()),
+            user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+            model: ActiveValue::Set("gpt-4".to_string()),
+            temperature: ActiveValue::Set(0.0),
+            max_token: ActiveValue::Set(None),
+            repetitions: ActiveValue::Set(1),
+            top_p: ActiveValue::Set(None),
+            sweep_id: ActiveValue::Set(None),
+            frequency_penalty: ActiveValue::Set(None),
+            presence_penalty: ActiveValue::Set(None),
+            stop: ActiveValue::Set(None),
+            seed: ActiveValue::Set(None),
+            logit_bias: ActiveValue::Set(None),
+            user: ActiveValue::Set(None),
+            credential_profile_id: ActiveValue::Set(None),
+        })
+        .exec(db.as_ref())
+        .await
+        .unwrap()
+        .last_insert_id;
+        for setting_deleted in settings_deleted {
+            let setting_id =
+                ComparingPromptSettings::insert(comparing_prompt_settings::ActiveModel {
+                    id: Default::default(),
+                    manager_id: ActiveValue::Set(manager_id),
+                    current_version: ActiveValue::Set(1),
+                    deleted_at: ActiveValue::Set(setting_deleted.then(deleted_at).flatten()),
+                })
+                .exec(db.as_ref())
+                .await
+                .unwrap()
+                .last_insert_id;
+            let version_id = ComparingPromptSettingVersions::insert(
+                comparing_prompt_setting_versions::ActiveModel {
+                    id: Default::default(),
+                    setting_id: ActiveValue::Set(setting_id),
+                    version: ActiveValue::Set(1),
+                    system_prompt: ActiveValue::Set("".to_string()),
+                    tools: ActiveValue::Set(None),
+                    tool_choice: ActiveValue::Set(None),
+                    tool_scripts: ActiveValue::Set(None),
+                },
+            )
+            .exec(db.as_ref())
+            .await
+            .unwrap()
+            .last_insert_id;
+            ComparingPromptRunHistories::insert(comparing_prompt_run_histories::ActiveModel {
+                id: Default::default(),
+                run_id: ActiveValue::Set(run_id),
+                version_id: ActiveValue::Set(version_id),
+                response: ActiveValue::Set("test_response".to_string()),
+                sample_index: ActiveValue::Set(0),
+                transcript: ActiveValue::Set(None),
+                cache_hit: ActiveValue::Set(false),
+                cancelled: ActiveValue::Set(false),
+                job_item_id: ActiveValue::Set(None),
+            })
+            .exec(db.as_ref())
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_credential_profile_usages() {
+        let db = setup_db("test_find_credential_profile_usages").await;
+        let repository = AppSettingRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        seed_run(Arc::clone(&db), false, &[false, true]).await;
+        seed_run(Arc::clone(&db), true, &[false]).await;
+
+        // テスト対象のメソッドを呼び出し
+        let usages = repository.find_credential_profile_usages().await.unwrap();
+
+        // assert
+        // 削除したマネージャーの実行と、削除した設定の履歴は集計しない
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].model, "gpt-4");
+        assert_eq!(usages[0].runs, 1);
+        assert_eq!(usages[0].responses, 1);
+    }
+}
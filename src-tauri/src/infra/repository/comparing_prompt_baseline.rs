@@ -0,0 +1,358 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, EntityTrait,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, TransactionTrait,
+};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::{
+    ComparingPromptBaselineModel, ComparingPromptBaselineRepository, DriftMeasure,
+};
+use crate::infra::repository::entities::prelude::ComparingPromptBaselines;
+use crate::infra::repository::entities::{comparing_prompt_baselines, comparing_prompt_settings};
+use crate::infra::repository::visibility::active_setting_ids;
+
+#[derive(Clone, Debug)]
+pub struct ComparingPromptBaselineRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl ComparingPromptBaselineRepository for ComparingPromptBaselineRepositoryImpl {
+    async fn find_comparing_prompt_baselines_by_setting_id(
+        &self,
+        setting_id: i32,
+    ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError> {
+        let res = ComparingPromptBaselines::find()
+            .filter(comparing_prompt_baselines::Column::SettingId.eq(setting_id))
+            .filter(comparing_prompt_baselines::Column::SettingId.in_subquery(active_setting_ids()))
+            .order_by_asc(comparing_prompt_baselines::Column::Id)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.into_iter().map(to_baseline_model).collect()
+    }
+
+    async fn find_comparing_prompt_baselines_by_manager_id_and_user_prompt(
+        &self,
+        manager_id: i32,
+        user_prompt: &str,
+    ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError> {
+        let res = ComparingPromptBaselines::find()
+            .join(
+                JoinType::InnerJoin,
+                comparing_prompt_baselines::Relation::ComparingPromptSettings.def(),
+            )
+            .filter(
+                Condition::all()
+                    .add(comparing_prompt_settings::Column::ManagerId.eq(manager_id))
+                    .add(comparing_prompt_baselines::Column::UserPrompt.eq(user_prompt))
+                    .add(
+                        comparing_prompt_baselines::Column::SettingId
+                            .in_subquery(active_setting_ids()),
+                    ),
+            )
+            .order_by_asc(comparing_prompt_baselines::Column::Id)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.into_iter().map(to_baseline_model).collect()
+    }
+
+    async fn upsert_comparing_prompt_baseline(
+        &self,
+        param: ComparingPromptBaselineModel,
+    ) -> Result<i32, ApplicationError> {
+        let txn = self
+            .db
+            .as_ref()
+            .begin()
+            .await
+            .map_err(ApplicationError::DBError)?;
+
+        let existing = ComparingPromptBaselines::find()
+            .filter(
+                Condition::all()
+                    .add(comparing_prompt_baselines::Column::SettingId.eq(param.setting_id))
+                    .add(
+                        comparing_prompt_baselines::Column::UserPrompt
+                            .eq(param.user_prompt.clone()),
+                    ),
+            )
+            .one(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+
+        let id = match existing {
+            Some(existing) => {
+                let id = existing.id;
+                let mut baseline: comparing_prompt_baselines::ActiveModel = existing.into();
+                baseline.history_id = ActiveValue::Set(param.history_id);
+                baseline.response = ActiveValue::Set(param.response);
+                baseline.drift_measure = ActiveValue::Set(param.drift_measure.to_string());
+                baseline.threshold = ActiveValue::Set(param.threshold);
+                baseline.created_at = ActiveValue::Set(chrono::Utc::now().to_string());
+                let _ = baseline
+                    .update(&txn)
+                    .await
+                    .map_err(ApplicationError::DBError)?;
+                id
+            }
+            None => {
+                let baseline = comparing_prompt_baselines::ActiveModel {
+                    id: Default::default(),
+                    setting_id: ActiveValue::Set(param.setting_id),
+                    history_id: ActiveValue::Set(param.history_id),
+                    user_prompt: ActiveValue::Set(param.user_prompt),
+                    response: ActiveValue::Set(param.response),
+                    drift_measure: ActiveValue::Set(param.drift_measure.to_string()),
+                    threshold: ActiveValue::Set(param.threshold),
+                    created_at: ActiveValue::Set(chrono::Utc::now().to_string()),
+                };
+                ComparingPromptBaselines::insert(baseline)
+                    .exec(&txn)
+                    .await
+                    .map_err(ApplicationError::DBError)?
+                    .last_insert_id
+            }
+        };
+
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(id)
+    }
+
+    async fn delete_comparing_prompt_baseline(&self, id: i32) -> Result<(), ApplicationError> {
+        let res = ComparingPromptBaselines::delete_by_id(id)
+            .exec(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        if res.rows_affected == 0 {
+            return Err(ApplicationError::EmptyResult);
+        }
+        Ok(())
+    }
+}
+
+fn to_baseline_model(
+    baseline: comparing_prompt_baselines::Model,
+) -> Result<ComparingPromptBaselineModel, ApplicationError> {
+    let drift_measure = DriftMeasure::from_str(&baseline.drift_measure).map_err(|_| {
+        ApplicationError::ParseError("failed to convert string to enum DriftMeasure".to_string())
+    })?;
+    Ok(ComparingPromptBaselineModel {
+        id: baseline.id,
+        setting_id: baseline.setting_id,
+        history_id: baseline.history_id,
+        user_prompt: baseline.user_prompt,
+        response: baseline.response,
+        drift_measure,
+        threshold: baseline.threshold,
+    })
+}
+
+impl ComparingPromptBaselineRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        ComparingPromptBaselineRepositoryImpl { db }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::thelper::db::setup_db;
+    use crate::domain::comparing_prompt::ProviderType;
+    use crate::infra::repository::entities::prelude::{
+        ComparingPromptManager, ComparingPromptRunHistories, ComparingPromptRuns,
+        ComparingPromptSettingVersions, ComparingPromptSettings, PromptManager,
+    };
+    use crate::infra::repository::entities::{
+        comparing_prompt_manager, comparing_prompt_run_histories, comparing_prompt_runs,
+        comparing_prompt_setting_versions, prompt_manager,
+    };
+
+    use super::*;
+
+    /// manager, setting, version, run, historyを作成し、(manager_id, setting_id, history_id)を返す
+    async fn seed_history(db: Arc<DatabaseConnection>) -> (i32, i32, i32) {
+        let manager = prompt_manager::ActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set("test_title".to_string()),
+            action_type: ActiveValue::Set(None),
+            api_type: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(None),
+        };
+        let manager_id = PromptManager::insert(manager)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert prompt manager")
+            .last_insert_id;
+        let comparing_prompt_manager = comparing_prompt_manager::ActiveModel {
+            manager_id: ActiveValue::Set(manager_id),
+        };
+        let _ = ComparingPromptManager::insert(comparing_prompt_manager)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_manager");
+        let setting = comparing_prompt_settings::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            current_version: ActiveValue::Set(1),
+            deleted_at: ActiveValue::Set(None),
+        };
+        let setting_id = ComparingPromptSettings::insert(setting)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_setting")
+            .last_insert_id;
+        let version = comparing_prompt_setting_versions::ActiveModel {
+            id: Default::default(),
+            setting_id: ActiveValue::Set(setting_id),
+            version: ActiveValue::Set(1),
+            system_prompt: ActiveValue::Set("".to_string()),
+            tools: ActiveValue::Set(None),
+            tool_choice: ActiveValue::Set(None),
+            tool_scripts: ActiveValue::Set(None),
+        };
+        let version_id = ComparingPromptSettingVersions::insert(version)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_setting_version")
+            .last_insert_id;
+        let run = comparing_prompt_runs::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            provider_type: ActiveValue::Set(ProviderType::OpenAI.to_string()),
+            user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+            model: ActiveValue::Set("test_model".to_string()),
+            temperature: ActiveValue::Set(0.0),
+            max_token: ActiveValue::Set(None),
+            repetitions: ActiveValue::Set(1),
+            top_p: ActiveValue::Set(None),
+            sweep_id: ActiveValue::Set(None),
+            frequency_penalty: ActiveValue::Set(None),
+            presence_penalty: ActiveValue::Set(None),
+            stop: ActiveValue::Set(None),
+            seed: ActiveValue::Set(None),
+            logit_bias: ActiveValue::Set(None),
+            user: ActiveValue::Set(None),
+            credential_profile_id: ActiveValue::Set(None),
+        };
+        let run_id = ComparingPromptRuns::insert(run)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_run")
+            .last_insert_id;
+        let history = comparing_prompt_run_histories::ActiveModel {
+            id: Default::default(),
+            run_id: ActiveValue::Set(run_id),
+            version_id: ActiveValue::Set(version_id),
+            response: ActiveValue::Set("test_response".to_string()),
+            sample_index: ActiveValue::Set(0),
+            transcript: ActiveValue::Set(None),
+            cache_hit: ActiveValue::Set(false),
+            cancelled: ActiveValue::Set(false),
+            job_item_id: ActiveValue::Set(None),
+        };
+        let history_id = ComparingPromptRunHistories::insert(history)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_run_history")
+            .last_insert_id;
+        (manager_id, setting_id, history_id)
+    }
+
+    fn new_baseline(
+        setting_id: i32,
+        history_id: i32,
+        response: &str,
+    ) -> ComparingPromptBaselineModel {
+        ComparingPromptBaselineModel {
+            id: 0,
+            setting_id,
+            history_id,
+            user_prompt: "test_user_prompt".to_string(),
+            response: response.to_string(),
+            drift_measure: DriftMeasure::Exact,
+            threshold: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_comparing_prompt_baseline() {
+        let db = setup_db("test_upsert_comparing_prompt_baseline").await;
+        let repository = ComparingPromptBaselineRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let (manager_id, setting_id, history_id) = seed_history(Arc::clone(&db)).await;
+
+        // 新規作成
+        let first_id = repository
+            .upsert_comparing_prompt_baseline(new_baseline(setting_id, history_id, "first"))
+            .await
+            .unwrap();
+
+        // 同じ設定と入力の場合は上書きされる
+        let mut param = new_baseline(setting_id, history_id, "second");
+        param.drift_measure = DriftMeasure::Semantic;
+        param.threshold = Some(0.9);
+        let second_id = repository
+            .upsert_comparing_prompt_baseline(param)
+            .await
+            .unwrap();
+        assert_eq!(first_id, second_id);
+
+        // assert
+        let baselines = repository
+            .find_comparing_prompt_baselines_by_setting_id(setting_id)
+            .await
+            .unwrap();
+        assert_eq!(baselines.len(), 1);
+        assert_eq!(baselines[0].response, "second");
+        assert_eq!(baselines[0].drift_measure, DriftMeasure::Semantic);
+        assert_eq!(baselines[0].threshold, Some(0.9));
+
+        let baselines = repository
+            .find_comparing_prompt_baselines_by_manager_id_and_user_prompt(
+                manager_id,
+                "test_user_prompt",
+            )
+            .await
+            .unwrap();
+        assert_eq!(baselines.len(), 1);
+
+        // 入力が異なるものは含まれない
+        let baselines = repository
+            .find_comparing_prompt_baselines_by_manager_id_and_user_prompt(manager_id, "other")
+            .await
+            .unwrap();
+        assert!(baselines.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_comparing_prompt_baseline() {
+        let db = setup_db("test_delete_comparing_prompt_baseline").await;
+        let repository = ComparingPromptBaselineRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let (_, setting_id, history_id) = seed_history(Arc::clone(&db)).await;
+        let id = repository
+            .upsert_comparing_prompt_baseline(new_baseline(setting_id, history_id, "first"))
+            .await
+            .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository.delete_comparing_prompt_baseline(id).await;
+        assert!(result.is_ok());
+        let baselines = repository
+            .find_comparing_prompt_baselines_by_setting_id(setting_id)
+            .await
+            .unwrap();
+        assert!(baselines.is_empty());
+
+        // 存在しないIDはエラー
+        let result = repository.delete_comparing_prompt_baseline(id).await;
+        assert!(result.is_err());
+    }
+}
@@ -1,17 +1,27 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use sea_orm::sea_query::Query;
 use sea_orm::{
-    ActiveValue, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter, QuerySelect,
-    RelationTrait, TransactionTrait,
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait, TransactionTrait,
 };
 
 use crate::common::errors::ApplicationError;
+use crate::domain::chat::SamplingParameters;
 use crate::domain::comparing_prompt::{
-    ComparingPromptRunRepository, ComparingPromptSettingRunModel,
+    ComparingPromptRunHistoryModel, ComparingPromptRunRepository, ComparingPromptSettingRunModel,
+    ComparingPromptSweepModel, RunHistoryTarget, SweepDefinition,
 };
-use crate::infra::repository::entities::comparing_prompt_runs;
-use crate::infra::repository::entities::prelude::ComparingPromptRuns;
+use crate::infra::repository::entities::prelude::{
+    ComparingPromptBaselines, ComparingPromptRunHistories, ComparingPromptRuns,
+    ComparingPromptSettingVersions, ComparingPromptSweeps,
+};
+use crate::infra::repository::entities::{
+    comparing_prompt_baselines, comparing_prompt_run_histories, comparing_prompt_runs,
+    comparing_prompt_setting_versions, comparing_prompt_sweeps,
+};
+use crate::infra::repository::visibility::{active_manager_ids, active_version_ids};
 
 #[derive(Clone, Debug)]
 pub struct ComparingPromptRunRepositoryImpl {
@@ -25,42 +35,297 @@ impl ComparingPromptRunRepository for ComparingPromptRunRepositoryImpl {
         id: i32,
     ) -> Result<ComparingPromptSettingRunModel, ApplicationError> {
         let comparing_prompt_run = ComparingPromptRuns::find_by_id(id)
+            .filter(comparing_prompt_runs::Column::ManagerId.in_subquery(active_manager_ids()))
             .one(self.db.as_ref())
             .await
             .map_err(ApplicationError::DBError)?;
         let comparing_prompt_run = comparing_prompt_run.ok_or(ApplicationError::EmptyResult)?;
-        Ok(ComparingPromptSettingRunModel {
-            id: comparing_prompt_run.id,
-            manager_id: comparing_prompt_run.manager_id,
-            user_prompt: comparing_prompt_run.user_prompt,
-            provider_type: comparing_prompt_run.provider_type.parse().unwrap(),
-            model: comparing_prompt_run.model,
-            temperature: comparing_prompt_run.temperature,
-            max_tokens: comparing_prompt_run.max_token,
-            response_format: None,
-        })
+        to_run_model(comparing_prompt_run)
     }
 
     async fn create_comparing_prompt_run(
         &self,
         param: ComparingPromptSettingRunModel,
     ) -> Result<i32, ApplicationError> {
-        let comparing_prompt_run = comparing_prompt_runs::ActiveModel {
+        let inserted_comparing_prompt_run =
+            ComparingPromptRuns::insert(to_run_active_model(param)?)
+                .exec(self.db.as_ref())
+                .await?;
+        let comparing_prompt_run_id = inserted_comparing_prompt_run.last_insert_id;
+
+        Ok(comparing_prompt_run_id)
+    }
+
+    async fn find_comparing_prompt_sweep_by_id(
+        &self,
+        id: i32,
+    ) -> Result<ComparingPromptSweepModel, ApplicationError> {
+        let sweep = ComparingPromptSweeps::find_by_id(id)
+            .filter(comparing_prompt_sweeps::Column::ManagerId.in_subquery(active_manager_ids()))
+            .one(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        let sweep = sweep.ok_or(ApplicationError::EmptyResult)?;
+        let definition = serde_json::from_str(&sweep.definition)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+        Ok(ComparingPromptSweepModel {
+            id: sweep.id,
+            manager_id: sweep.manager_id,
+            definition,
+        })
+    }
+
+    async fn find_comparing_prompt_runs_by_sweep_id(
+        &self,
+        sweep_id: i32,
+    ) -> Result<Vec<ComparingPromptSettingRunModel>, ApplicationError> {
+        let runs = ComparingPromptRuns::find()
+            .filter(comparing_prompt_runs::Column::SweepId.eq(sweep_id))
+            .filter(comparing_prompt_runs::Column::ManagerId.in_subquery(active_manager_ids()))
+            .order_by_asc(comparing_prompt_runs::Column::Id)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        runs.into_iter().map(to_run_model).collect()
+    }
+
+    async fn create_comparing_prompt_sweep(
+        &self,
+        manager_id: i32,
+        definition: &SweepDefinition,
+        runs: Vec<ComparingPromptSettingRunModel>,
+    ) -> Result<(i32, Vec<i32>), ApplicationError> {
+        let definition = serde_json::to_string(definition)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+
+        let txn = self.db.begin().await?;
+        let sweep = comparing_prompt_sweeps::ActiveModel {
             id: Default::default(),
-            manager_id: ActiveValue::Set(param.manager_id),
-            provider_type: ActiveValue::Set(param.provider_type.to_string()),
-            user_prompt: ActiveValue::Set(param.user_prompt),
-            model: ActiveValue::Set(param.model),
-            temperature: ActiveValue::Set(param.temperature),
-            max_token: ActiveValue::Set(param.max_tokens),
-            // response_format: ActiveValue::Set(param.response_format),
+            manager_id: ActiveValue::Set(manager_id),
+            definition: ActiveValue::Set(definition),
+            created_at: ActiveValue::Set(chrono::Utc::now().to_string()),
         };
-        let inserted_comparing_prompt_run = ComparingPromptRuns::insert(comparing_prompt_run)
+        let sweep_id = ComparingPromptSweeps::insert(sweep)
+            .exec(&txn)
+            .await?
+            .last_insert_id;
+
+        let mut run_ids = Vec::with_capacity(runs.len());
+        for run in runs {
+            let run = ComparingPromptSettingRunModel {
+                sweep_id: Some(sweep_id),
+                ..run
+            };
+            let run_id = ComparingPromptRuns::insert(to_run_active_model(run)?)
+                .exec(&txn)
+                .await?
+                .last_insert_id;
+            run_ids.push(run_id);
+        }
+        txn.commit().await?;
+
+        Ok((sweep_id, run_ids))
+    }
+
+    async fn find_comparing_prompt_run_history_by_id(
+        &self,
+        id: i32,
+    ) -> Result<ComparingPromptRunHistoryModel, ApplicationError> {
+        let res = ComparingPromptRunHistories::find_by_id(id)
+            .filter(
+                comparing_prompt_run_histories::Column::VersionId.in_subquery(active_version_ids()),
+            )
+            .find_also_related(ComparingPromptSettingVersions)
+            .one(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        let (history, version) = res.ok_or(ApplicationError::EmptyResult)?;
+        let version = version.ok_or(ApplicationError::EmptyResult)?;
+        Ok(to_history_model(history, version))
+    }
+
+    async fn find_comparing_prompt_run_histories_by_run_id(
+        &self,
+        run_id: i32,
+    ) -> Result<Vec<ComparingPromptRunHistoryModel>, ApplicationError> {
+        let res = ComparingPromptRunHistories::find()
+            .filter(comparing_prompt_run_histories::Column::RunId.eq(run_id))
+            .filter(
+                comparing_prompt_run_histories::Column::VersionId.in_subquery(active_version_ids()),
+            )
+            .order_by_asc(comparing_prompt_run_histories::Column::Id)
+            .find_also_related(ComparingPromptSettingVersions)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+
+        res.into_iter()
+            .map(|(history, version)| {
+                let version = version.ok_or(ApplicationError::EmptyResult)?;
+                Ok(to_history_model(history, version))
+            })
+            .collect()
+    }
+
+    async fn create_comparing_prompt_run_history(
+        &self,
+        target: &RunHistoryTarget,
+        sample_index: i32,
+        response: &str,
+        transcript: Option<&str>,
+        cache_hit: bool,
+    ) -> Result<i32, ApplicationError> {
+        let history = comparing_prompt_run_histories::ActiveModel {
+            id: Default::default(),
+            run_id: ActiveValue::Set(target.run_id),
+            version_id: ActiveValue::Set(target.version_id),
+            response: ActiveValue::Set(response.to_string()),
+            sample_index: ActiveValue::Set(sample_index),
+            transcript: ActiveValue::Set(transcript.map(|t| t.to_string())),
+            cache_hit: ActiveValue::Set(cache_hit),
+            cancelled: ActiveValue::Set(false),
+            job_item_id: ActiveValue::Set(target.job_item_id),
+        };
+        let res = ComparingPromptRunHistories::insert(history)
             .exec(self.db.as_ref())
-            .await?;
-        let comparing_prompt_run_id = inserted_comparing_prompt_run.last_insert_id;
+            .await
+            .map_err(ApplicationError::DBError)?;
+        Ok(res.last_insert_id)
+    }
 
-        Ok(comparing_prompt_run_id)
+    async fn create_cancelled_comparing_prompt_run_history(
+        &self,
+        target: &RunHistoryTarget,
+        sample_index: i32,
+        transcript: Option<&str>,
+    ) -> Result<i32, ApplicationError> {
+        let history = comparing_prompt_run_histories::ActiveModel {
+            id: Default::default(),
+            run_id: ActiveValue::Set(target.run_id),
+            version_id: ActiveValue::Set(target.version_id),
+            response: ActiveValue::Set("".to_string()),
+            sample_index: ActiveValue::Set(sample_index),
+            transcript: ActiveValue::Set(transcript.map(|t| t.to_string())),
+            cache_hit: ActiveValue::Set(false),
+            cancelled: ActiveValue::Set(true),
+            job_item_id: ActiveValue::Set(target.job_item_id),
+        };
+        let res = ComparingPromptRunHistories::insert(history)
+            .exec(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        Ok(res.last_insert_id)
+    }
+
+    async fn delete_comparing_prompt_run_histories_by_job_item_id(
+        &self,
+        job_item_id: i32,
+    ) -> Result<u64, ApplicationError> {
+        let res = ComparingPromptRunHistories::delete_many()
+            .filter(comparing_prompt_run_histories::Column::JobItemId.eq(job_item_id))
+            .filter(
+                comparing_prompt_run_histories::Column::Id.not_in_subquery(
+                    Query::select()
+                        .column(comparing_prompt_baselines::Column::HistoryId)
+                        .from(ComparingPromptBaselines)
+                        .to_owned(),
+                ),
+            )
+            .exec(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        Ok(res.rows_affected)
+    }
+}
+
+fn to_run_model(
+    run: comparing_prompt_runs::Model,
+) -> Result<ComparingPromptSettingRunModel, ApplicationError> {
+    // stopとlogit_biasはJSON文字列で保持している
+    let stop = run
+        .stop
+        .map(|stop| serde_json::from_str(&stop))
+        .transpose()
+        .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+    let logit_bias = run
+        .logit_bias
+        .map(|logit_bias| serde_json::from_str(&logit_bias))
+        .transpose()
+        .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+    Ok(ComparingPromptSettingRunModel {
+        id: run.id,
+        manager_id: run.manager_id,
+        user_prompt: run.user_prompt,
+        provider_type: run.provider_type.parse().unwrap(),
+        model: run.model,
+        temperature: run.temperature,
+        max_tokens: run.max_token,
+        response_format: None,
+        repetitions: run.repetitions,
+        top_p: run.top_p,
+        sweep_id: run.sweep_id,
+        sampling: SamplingParameters {
+            frequency_penalty: run.frequency_penalty.map(|v| v as f32),
+            presence_penalty: run.presence_penalty.map(|v| v as f32),
+            stop,
+            seed: run.seed,
+            logit_bias,
+            user: run.user,
+        },
+        credential_profile_id: run.credential_profile_id,
+    })
+}
+
+fn to_run_active_model(
+    param: ComparingPromptSettingRunModel,
+) -> Result<comparing_prompt_runs::ActiveModel, ApplicationError> {
+    let sampling = param.sampling;
+    let stop = sampling
+        .stop
+        .map(|stop| serde_json::to_string(&stop))
+        .transpose()
+        .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+    let logit_bias = sampling
+        .logit_bias
+        .map(|logit_bias| serde_json::to_string(&logit_bias))
+        .transpose()
+        .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+    Ok(comparing_prompt_runs::ActiveModel {
+        id: Default::default(),
+        manager_id: ActiveValue::Set(param.manager_id),
+        provider_type: ActiveValue::Set(param.provider_type.to_string()),
+        user_prompt: ActiveValue::Set(param.user_prompt),
+        model: ActiveValue::Set(param.model),
+        temperature: ActiveValue::Set(param.temperature),
+        max_token: ActiveValue::Set(param.max_tokens),
+        repetitions: ActiveValue::Set(param.repetitions),
+        top_p: ActiveValue::Set(param.top_p),
+        sweep_id: ActiveValue::Set(param.sweep_id),
+        frequency_penalty: ActiveValue::Set(sampling.frequency_penalty.map(|v| v as f64)),
+        presence_penalty: ActiveValue::Set(sampling.presence_penalty.map(|v| v as f64)),
+        stop: ActiveValue::Set(stop),
+        seed: ActiveValue::Set(sampling.seed),
+        logit_bias: ActiveValue::Set(logit_bias),
+        user: ActiveValue::Set(sampling.user),
+        credential_profile_id: ActiveValue::Set(param.credential_profile_id),
+        // response_format: ActiveValue::Set(param.response_format),
+    })
+}
+
+fn to_history_model(
+    history: comparing_prompt_run_histories::Model,
+    version: comparing_prompt_setting_versions::Model,
+) -> ComparingPromptRunHistoryModel {
+    ComparingPromptRunHistoryModel {
+        id: history.id,
+        run_id: history.run_id,
+        version_id: history.version_id,
+        setting_id: version.setting_id,
+        sample_index: history.sample_index,
+        response: history.response,
+        transcript: history.transcript,
+        cache_hit: history.cache_hit,
+        cancelled: history.cancelled,
     }
 }
 
@@ -72,10 +337,16 @@ impl ComparingPromptRunRepositoryImpl {
 
 #[cfg(test)]
 mod tests {
+    use sea_orm::sea_query::Expr;
+
     use crate::common::thelper::db::setup_db;
-    use crate::domain::comparing_prompt::ProviderType;
-    use crate::infra::repository::entities::prelude::{ComparingPromptManager, PromptManager};
-    use crate::infra::repository::entities::{comparing_prompt_manager, prompt_manager};
+    use crate::domain::comparing_prompt::{ProviderType, SweepValues};
+    use crate::infra::repository::entities::prelude::{
+        ComparingPromptManager, ComparingPromptSettings, PromptManager,
+    };
+    use crate::infra::repository::entities::{
+        comparing_prompt_manager, comparing_prompt_settings, prompt_manager,
+    };
 
     use super::*;
 
@@ -126,6 +397,18 @@ mod tests {
                 temperature: 0.0,
                 max_tokens: None,
                 response_format: None,
+                repetitions: 3,
+                top_p: Some(0.9),
+                sweep_id: None,
+                sampling: SamplingParameters {
+                    frequency_penalty: Some(0.5),
+                    presence_penalty: None,
+                    stop: Some(vec!["END".to_string()]),
+                    seed: Some(42),
+                    logit_bias: Some([("50256".to_string(), -100)].into()),
+                    user: Some("test_user".to_string()),
+                },
+                credential_profile_id: None,
             })
             .await;
 
@@ -148,5 +431,387 @@ mod tests {
         assert_eq!(new_item.model, "test_model");
         assert_eq!(new_item.temperature, 0.0);
         assert_eq!(new_item.max_token, None);
+        assert_eq!(new_item.repetitions, 3);
+        assert_eq!(new_item.top_p, Some(0.9));
+        assert_eq!(new_item.sweep_id, None);
+        assert_eq!(new_item.frequency_penalty, Some(0.5));
+        assert_eq!(new_item.presence_penalty, None);
+        assert_eq!(new_item.stop, Some(r#"["END"]"#.to_string()));
+        assert_eq!(new_item.seed, Some(42));
+        assert_eq!(new_item.logit_bias, Some(r#"{"50256":-100}"#.to_string()));
+        assert_eq!(new_item.user, Some("test_user".to_string()));
+
+        // 読み込み時にJSONから復元される
+        let run = repository
+            .find_comparing_prompt_run_by_id(new_id)
+            .await
+            .unwrap();
+        assert_eq!(run.sampling.stop, Some(vec!["END".to_string()]));
+        assert_eq!(run.sampling.logit_bias.unwrap().get("50256"), Some(&-100));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_comparing_prompt_sweep() {
+        let db = setup_db("test_create_and_find_comparing_prompt_sweep").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let definition = SweepDefinition {
+            temperature: SweepValues::List {
+                values: vec![0.0, 1.0],
+            },
+            top_p: Some(SweepValues::Range {
+                start: 0.5,
+                end: 1.0,
+                step: 0.5,
+            }),
+            max_tokens: None,
+        };
+        let run = |temperature: f64, top_p: f64| ComparingPromptSettingRunModel {
+            id: 0,
+            manager_id,
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            temperature,
+            max_tokens: None,
+            response_format: None,
+            repetitions: 1,
+            top_p: Some(top_p),
+            sweep_id: None,
+            sampling: SamplingParameters::default(),
+            credential_profile_id: None,
+        };
+        let runs = vec![run(0.0, 0.5), run(0.0, 1.0), run(1.0, 0.5), run(1.0, 1.0)];
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository
+            .create_comparing_prompt_sweep(manager_id, &definition, runs)
+            .await;
+
+        // assert
+        assert!(result.is_ok());
+        let (sweep_id, run_ids) = result.unwrap();
+        assert_eq!(run_ids.len(), 4);
+
+        let sweep = repository
+            .find_comparing_prompt_sweep_by_id(sweep_id)
+            .await
+            .unwrap();
+        assert_eq!(sweep.manager_id, manager_id);
+        assert_eq!(sweep.definition, definition);
+
+        let runs = repository
+            .find_comparing_prompt_runs_by_sweep_id(sweep_id)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 4);
+        assert_eq!(runs.iter().map(|run| run.id).collect::<Vec<i32>>(), run_ids);
+        assert!(runs.iter().all(|run| run.sweep_id == Some(sweep_id)));
+        assert_eq!(runs[1].temperature, 0.0);
+        assert_eq!(runs[1].top_p, Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_find_comparing_prompt_sweep_by_id_not_found_error() {
+        let db = setup_db("test_find_comparing_prompt_sweep_by_id_not_found_error").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 存在しないIDで呼び出し
+        let result = repository.find_comparing_prompt_sweep_by_id(9999).await;
+        assert!(result.is_err());
+    }
+
+    async fn seed_comparing_prompt_version(db: Arc<DatabaseConnection>, manager_id: i32) -> i32 {
+        let setting = comparing_prompt_settings::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            current_version: ActiveValue::Set(1),
+            deleted_at: ActiveValue::Set(None),
+        };
+        let setting_id = ComparingPromptSettings::insert(setting)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_setting")
+            .last_insert_id;
+        let version = comparing_prompt_setting_versions::ActiveModel {
+            id: Default::default(),
+            setting_id: ActiveValue::Set(setting_id),
+            version: ActiveValue::Set(1),
+            system_prompt: ActiveValue::Set("test_system_prompt".to_string()),
+            tools: ActiveValue::Set(None),
+            tool_choice: ActiveValue::Set(None),
+            tool_scripts: ActiveValue::Set(None),
+        };
+        ComparingPromptSettingVersions::insert(version)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_setting_version")
+            .last_insert_id
+    }
+
+    async fn seed_comparing_prompt_run(db: Arc<DatabaseConnection>, manager_id: i32) -> i32 {
+        let run = comparing_prompt_runs::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            provider_type: ActiveValue::Set(ProviderType::OpenAI.to_string()),
+            user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+            model: ActiveValue::Set("test_model".to_string()),
+            temperature: ActiveValue::Set(0.0),
+            max_token: ActiveValue::Set(None),
+            repetitions: ActiveValue::Set(1),
+            top_p: ActiveValue::Set(None),
+            sweep_id: ActiveValue::Set(None),
+            frequency_penalty: ActiveValue::Set(None),
+            presence_penalty: ActiveValue::Set(None),
+            stop: ActiveValue::Set(None),
+            seed: ActiveValue::Set(None),
+            logit_bias: ActiveValue::Set(None),
+            user: ActiveValue::Set(None),
+            credential_profile_id: ActiveValue::Set(None),
+        };
+        ComparingPromptRuns::insert(run)
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to insert comparing_prompt_run")
+            .last_insert_id
+    }
+
+    fn history_target(run_id: i32, version_id: i32) -> RunHistoryTarget {
+        RunHistoryTarget {
+            run_id,
+            version_id,
+            job_item_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_comparing_prompt_run_history() {
+        let db = setup_db("test_create_and_find_comparing_prompt_run_history").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let version_id = seed_comparing_prompt_version(Arc::clone(&db), manager_id).await;
+        let run_id = seed_comparing_prompt_run(Arc::clone(&db), manager_id).await;
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository
+            .create_comparing_prompt_run_history(
+                &history_target(run_id, version_id),
+                2,
+                "test_response",
+                Some(r#"{"messages":[]}"#),
+                true,
+            )
+            .await;
+
+        // assert
+        assert!(result.is_ok());
+        let history = repository
+            .find_comparing_prompt_run_history_by_id(result.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(history.run_id, run_id);
+        assert_eq!(history.version_id, version_id);
+        assert_eq!(history.setting_id, 1);
+        assert_eq!(history.sample_index, 2);
+        assert_eq!(history.response, "test_response");
+        assert_eq!(history.transcript, Some(r#"{"messages":[]}"#.to_string()));
+        assert!(history.cache_hit);
+
+        let histories = repository
+            .find_comparing_prompt_run_histories_by_run_id(run_id)
+            .await
+            .unwrap();
+        assert_eq!(histories.len(), 1);
+        assert_eq!(histories[0].id, history.id);
+        assert!(!histories[0].cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_create_cancelled_comparing_prompt_run_history() {
+        let db = setup_db("test_create_cancelled_comparing_prompt_run_history").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let version_id = seed_comparing_prompt_version(Arc::clone(&db), manager_id).await;
+        let run_id = seed_comparing_prompt_run(Arc::clone(&db), manager_id).await;
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository
+            .create_cancelled_comparing_prompt_run_history(
+                &history_target(run_id, version_id),
+                1,
+                None,
+            )
+            .await;
+
+        // assert
+        let history = repository
+            .find_comparing_prompt_run_history_by_id(result.unwrap())
+            .await
+            .unwrap();
+        assert!(history.cancelled);
+        assert!(!history.cache_hit);
+        assert_eq!(history.sample_index, 1);
+        assert_eq!(history.response, "");
+    }
+
+    #[tokio::test]
+    async fn test_delete_comparing_prompt_run_histories_by_job_item_id() {
+        let db = setup_db("test_delete_comparing_prompt_run_histories_by_job_item_id").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        // 項目1から2件（うち1件はベースライン）、項目2とジョブ以外から1件ずつ登録する
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let version_id = seed_comparing_prompt_version(Arc::clone(&db), manager_id).await;
+        let run_id = seed_comparing_prompt_run(Arc::clone(&db), manager_id).await;
+        let target = |job_item_id: Option<i32>| RunHistoryTarget {
+            run_id,
+            version_id,
+            job_item_id,
+        };
+        let mut history_ids = Vec::new();
+        for job_item_id in [Some(1), Some(1), Some(2), None] {
+            history_ids.push(
+                repository
+                    .create_comparing_prompt_run_history(
+                        &target(job_item_id),
+                        0,
+                        "test_response",
+                        None,
+                        false,
+                    )
+                    .await
+                    .unwrap(),
+            );
+        }
+        ComparingPromptBaselines::insert(comparing_prompt_baselines::ActiveModel {
+            id: Default::default(),
+            setting_id: ActiveValue::Set(1),
+            history_id: ActiveValue::Set(history_ids[1]),
+            user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+            response: ActiveValue::Set("test_response".to_string()),
+            drift_measure: ActiveValue::Set("Exact".to_string()),
+            threshold: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(chrono::Utc::now().to_string()),
+        })
+        .exec(db.as_ref())
+        .await
+        .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository
+            .delete_comparing_prompt_run_histories_by_job_item_id(1)
+            .await;
+
+        // assert
+        assert_eq!(result.unwrap(), 1);
+        let histories = repository
+            .find_comparing_prompt_run_histories_by_run_id(run_id)
+            .await
+            .unwrap();
+        let ids: Vec<i32> = histories.iter().map(|history| history.id).collect();
+        assert_eq!(ids, history_ids[1..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_find_comparing_prompt_run_history_by_id_not_found_error() {
+        let db = setup_db("test_find_comparing_prompt_run_history_by_id_not_found_error").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 存在しないIDで呼び出し
+        let result = repository
+            .find_comparing_prompt_run_history_by_id(9999)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_comparing_prompt_runs_of_deleted_settings() {
+        let db = setup_db("test_find_comparing_prompt_runs_of_deleted_settings").await;
+        let repository = ComparingPromptRunRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        // 1つの実行で2つの設定を比較する
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let deleted_version_id = seed_comparing_prompt_version(Arc::clone(&db), manager_id).await;
+        let version_id = seed_comparing_prompt_version(Arc::clone(&db), manager_id).await;
+        let run_id = seed_comparing_prompt_run(Arc::clone(&db), manager_id).await;
+        let deleted_history_id = repository
+            .create_comparing_prompt_run_history(
+                &history_target(run_id, deleted_version_id),
+                0,
+                "a",
+                None,
+                false,
+            )
+            .await
+            .unwrap();
+        let history_id = repository
+            .create_comparing_prompt_run_history(
+                &history_target(run_id, version_id),
+                0,
+                "b",
+                None,
+                false,
+            )
+            .await
+            .unwrap();
+        ComparingPromptSettings::update_many()
+            .col_expr(
+                comparing_prompt_settings::Column::DeletedAt,
+                Expr::value(chrono::Utc::now().to_string()),
+            )
+            .filter(comparing_prompt_settings::Column::Id.eq(1))
+            .exec(db.as_ref())
+            .await
+            .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        let histories = repository
+            .find_comparing_prompt_run_histories_by_run_id(run_id)
+            .await
+            .unwrap();
+
+        // assert
+        // 削除した設定の履歴のみ取得されない（実行は他の設定と共有するので取得される）
+        assert_eq!(histories.len(), 1);
+        assert_eq!(histories[0].id, history_id);
+        let result = repository
+            .find_comparing_prompt_run_history_by_id(deleted_history_id)
+            .await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+        assert!(repository
+            .find_comparing_prompt_run_by_id(run_id)
+            .await
+            .is_ok());
+
+        // マネージャーを削除すると、実行と全ての履歴が取得されない
+        PromptManager::update_many()
+            .col_expr(
+                prompt_manager::Column::DeletedAt,
+                Expr::value(chrono::Utc::now().to_string()),
+            )
+            .filter(prompt_manager::Column::Id.eq(manager_id))
+            .exec(db.as_ref())
+            .await
+            .unwrap();
+        let result = repository.find_comparing_prompt_run_by_id(run_id).await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+        let histories = repository
+            .find_comparing_prompt_run_histories_by_run_id(run_id)
+            .await
+            .unwrap();
+        assert!(histories.is_empty());
     }
 }
@@ -1,22 +1,27 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ActiveValue, ColumnTrait, Condition, DatabaseConnection, EntityTrait, LoaderTrait, ModelTrait,
-    QueryFilter, QuerySelect, RelationTrait, TransactionTrait,
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, EntityTrait,
+    LoaderTrait, ModelTrait, QueryFilter, QuerySelect, RelationTrait, TransactionTrait,
 };
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::common::errors::ApplicationError;
+use crate::domain::chat::{ToolChoice, ToolDefinition};
 use crate::domain::comparing_prompt::{
     ComparingPromptSettingModel, ComparingPromptSettingRepository,
-    ComparingPromptSettingVersionModel,
+    ComparingPromptSettingVersionModel, ToolScript,
 };
 use crate::infra::repository::entities::prelude::{
-    ComparingPromptSettingVersions, ComparingPromptSettings,
+    ComparingPromptSettingVersions, ComparingPromptSettings, PromptManager,
 };
 use crate::infra::repository::entities::{
     comparing_prompt_setting_versions, comparing_prompt_settings,
 };
+use crate::infra::repository::visibility::{active_manager_ids, active_setting_ids};
 
 #[derive(Clone, Debug)]
 pub struct ComparingPromptSettingRepositoryImpl {
@@ -30,6 +35,7 @@ impl ComparingPromptSettingRepository for ComparingPromptSettingRepositoryImpl {
         id: i32,
     ) -> Result<ComparingPromptSettingModel, ApplicationError> {
         let res = ComparingPromptSettings::find_by_id(id)
+            .filter(comparing_prompt_settings::Column::Id.in_subquery(active_setting_ids()))
             .one(self.db.as_ref())
             .await
             .map_err(ApplicationError::DBError)?;
@@ -55,12 +61,7 @@ impl ComparingPromptSettingRepository for ComparingPromptSettingRepositoryImpl {
             id: res.id,
             manager_id: res.manager_id,
             current_version: res.current_version,
-            versions: vec![ComparingPromptSettingVersionModel {
-                id: version.id,
-                setting_id: version.setting_id,
-                version: version.version,
-                system_prompt: version.system_prompt,
-            }],
+            versions: vec![to_version_model(version)?],
         })
     }
 
@@ -73,32 +74,31 @@ impl ComparingPromptSettingRepository for ComparingPromptSettingRepositoryImpl {
             .filter(
                 Condition::all()
                     .add(comparing_prompt_settings::Column::ManagerId.eq(manager_id))
-                    .add(comparing_prompt_settings::Column::DeletedAt.is_null()),
+                    .add(comparing_prompt_settings::Column::DeletedAt.is_null())
+                    .add(
+                        comparing_prompt_settings::Column::ManagerId
+                            .in_subquery(active_manager_ids()),
+                    ),
             )
             .find_with_related(comparing_prompt_setting_versions::Entity)
             .all(self.db.as_ref())
             .await
             .map_err(ApplicationError::DBError)?;
 
-        Ok({
-            res.into_iter()
-                .map(|prompt_manager| ComparingPromptSettingModel {
+        res.into_iter()
+            .map(|prompt_manager| {
+                Ok(ComparingPromptSettingModel {
                     id: prompt_manager.0.id,
                     manager_id: prompt_manager.0.manager_id,
                     current_version: prompt_manager.0.current_version,
                     versions: prompt_manager
                         .1
                         .into_iter()
-                        .map(|version| ComparingPromptSettingVersionModel {
-                            id: version.id,
-                            setting_id: version.setting_id,
-                            version: version.version,
-                            system_prompt: version.system_prompt,
-                        })
-                        .collect(),
+                        .map(to_version_model)
+                        .collect::<Result<Vec<_>, _>>()?,
                 })
-                .collect()
-        })
+            })
+            .collect()
     }
 
     async fn create_comparing_prompt_setting(
@@ -122,6 +122,9 @@ impl ComparingPromptSettingRepository for ComparingPromptSettingRepositoryImpl {
             setting_id: ActiveValue::Set(res.last_insert_id),
             version: ActiveValue::Set(1),
             system_prompt: ActiveValue::Set("".to_string()),
+            tools: ActiveValue::Set(None),
+            tool_choice: ActiveValue::Set(None),
+            tool_scripts: ActiveValue::Set(None),
         };
         let _ = ComparingPromptSettingVersions::insert(version)
             .exec(self.db.as_ref())
@@ -129,6 +132,162 @@ impl ComparingPromptSettingRepository for ComparingPromptSettingRepositoryImpl {
             .map_err(ApplicationError::DBError)?;
         Ok(res.last_insert_id)
     }
+
+    async fn find_comparing_prompt_setting_version_by_id(
+        &self,
+        id: i32,
+    ) -> Result<ComparingPromptSettingVersionModel, ApplicationError> {
+        let version = ComparingPromptSettingVersions::find_by_id(id)
+            .filter(
+                comparing_prompt_setting_versions::Column::SettingId
+                    .in_subquery(active_setting_ids()),
+            )
+            .one(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        let version = version.ok_or(ApplicationError::EmptyResult)?;
+        to_version_model(version)
+    }
+
+    async fn update_comparing_prompt_setting_version_tools(
+        &self,
+        id: i32,
+        tools: &[ToolDefinition],
+        tool_choice: Option<&ToolChoice>,
+        tool_scripts: &[ToolScript],
+    ) -> Result<(), ApplicationError> {
+        let tools = to_json_list_column(tools)?;
+        let tool_scripts = to_json_list_column(tool_scripts)?;
+        let tool_choice = tool_choice
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+
+        let res = ComparingPromptSettingVersions::update_many()
+            .col_expr(
+                comparing_prompt_setting_versions::Column::Tools,
+                Expr::value(tools),
+            )
+            .col_expr(
+                comparing_prompt_setting_versions::Column::ToolChoice,
+                Expr::value(tool_choice),
+            )
+            .col_expr(
+                comparing_prompt_setting_versions::Column::ToolScripts,
+                Expr::value(tool_scripts),
+            )
+            .filter(comparing_prompt_setting_versions::Column::Id.eq(id))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        if res.rows_affected == 0 {
+            return Err(ApplicationError::EmptyResult);
+        }
+        Ok(())
+    }
+
+    async fn logical_delete_comparing_prompt_setting(
+        &self,
+        id: i32,
+    ) -> Result<(), ApplicationError> {
+        // 削除済みの設定と、マネージャーが削除されている設定は存在しないものとして扱う
+        let res = ComparingPromptSettings::update_many()
+            .col_expr(
+                comparing_prompt_settings::Column::DeletedAt,
+                Expr::value(chrono::Utc::now().to_string()),
+            )
+            .filter(comparing_prompt_settings::Column::Id.eq(id))
+            .filter(comparing_prompt_settings::Column::Id.in_subquery(active_setting_ids()))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        if res.rows_affected == 0 {
+            return Err(ApplicationError::EmptyResult);
+        }
+        Ok(())
+    }
+
+    async fn restore_comparing_prompt_setting(&self, id: i32) -> Result<(), ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        let setting = ComparingPromptSettings::find_by_id(id)
+            .one(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?
+            .ok_or(ApplicationError::EmptyResult)?;
+        if setting.deleted_at.is_none() {
+            return Err(ApplicationError::ValidationError(format!(
+                "comparing prompt setting is not deleted: {}",
+                id
+            )));
+        }
+        // マネージャーが削除されている場合は、先にマネージャーを元に戻す必要がある
+        let manager = PromptManager::find_by_id(setting.manager_id)
+            .one(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        let manager_deleted = match manager {
+            Some(manager) => manager.deleted_at.is_some(),
+            None => true,
+        };
+        if manager_deleted {
+            return Err(ApplicationError::ValidationError(format!(
+                "prompt manager of the comparing prompt setting is deleted: {}",
+                id
+            )));
+        }
+        let mut setting: comparing_prompt_settings::ActiveModel = setting.into();
+        setting.deleted_at = ActiveValue::Set(None);
+        setting
+            .update(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(())
+    }
+}
+
+/// 配列をJSON文字列に変換する（空の場合はNULLで保持する）
+fn to_json_list_column<T: Serialize>(values: &[T]) -> Result<Option<String>, ApplicationError> {
+    if values.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(values)
+        .map(Some)
+        .map_err(|e| ApplicationError::ParseError(e.to_string()))
+}
+
+/// JSON文字列を配列に変換する（NULLの場合は空とする）
+fn from_json_list_column<T: DeserializeOwned>(
+    value: Option<String>,
+) -> Result<Vec<T>, ApplicationError> {
+    match value {
+        Some(value) => {
+            serde_json::from_str(&value).map_err(|e| ApplicationError::ParseError(e.to_string()))
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+fn to_version_model(
+    version: comparing_prompt_setting_versions::Model,
+) -> Result<ComparingPromptSettingVersionModel, ApplicationError> {
+    // ツール定義、選択方針、模擬実行結果はJSON文字列で保持している
+    let tools = from_json_list_column(version.tools)?;
+    let tool_scripts = from_json_list_column(version.tool_scripts)?;
+    let tool_choice = version
+        .tool_choice
+        .map(|tool_choice| serde_json::from_str(&tool_choice))
+        .transpose()
+        .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+    Ok(ComparingPromptSettingVersionModel {
+        id: version.id,
+        setting_id: version.setting_id,
+        version: version.version,
+        system_prompt: version.system_prompt,
+        tools,
+        tool_choice,
+        tool_scripts,
+    })
 }
 
 impl ComparingPromptSettingRepositoryImpl {
@@ -140,7 +299,8 @@ impl ComparingPromptSettingRepositoryImpl {
 #[cfg(test)]
 mod tests {
     use crate::common::thelper::db::setup_db;
-    use crate::infra::repository::entities::prelude::{ComparingPromptManager, PromptManager};
+    use crate::domain::comparing_prompt::ToolScriptCase;
+    use crate::infra::repository::entities::prelude::ComparingPromptManager;
     use crate::infra::repository::entities::{comparing_prompt_manager, prompt_manager};
 
     use super::*;
@@ -199,6 +359,9 @@ mod tests {
             setting_id: ActiveValue::Set(id),
             version: ActiveValue::Set(1),
             system_prompt: ActiveValue::Set("".to_string()),
+            tools: ActiveValue::Set(None),
+            tool_choice: ActiveValue::Set(None),
+            tool_scripts: ActiveValue::Set(None),
         };
         let _ = ComparingPromptSettingVersions::insert(comparing_prompt_setting_version)
             .exec(db.as_ref())
@@ -245,6 +408,9 @@ mod tests {
             setting_id: ActiveValue::Set(id),
             version: ActiveValue::Set(1),
             system_prompt: ActiveValue::Set("".to_string()),
+            tools: ActiveValue::Set(None),
+            tool_choice: ActiveValue::Set(None),
+            tool_scripts: ActiveValue::Set(None),
         };
         let _ = ComparingPromptSettingVersions::insert(comparing_prompt_setting_version)
             .exec(db.as_ref())
@@ -301,4 +467,212 @@ mod tests {
         assert_eq!(version.setting_id, new_id);
         assert_eq!(version.version, 1);
     }
+
+    #[tokio::test]
+    async fn test_update_and_find_comparing_prompt_setting_version_tools() {
+        let db = setup_db("test_update_and_find_comparing_prompt_setting_version_tools").await;
+        let repository = ComparingPromptSettingRepositoryImpl::new(db.clone());
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let setting_id = repository
+            .create_comparing_prompt_setting(manager_id)
+            .await
+            .unwrap();
+        let version_id = repository
+            .find_comparing_prompt_setting_by_id(setting_id)
+            .await
+            .unwrap()
+            .versions[0]
+            .id;
+        let tools = vec![ToolDefinition {
+            name: "get_weather".to_string(),
+            description: Some("現在の天気を取得する".to_string()),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": { "city": { "type": "string" } },
+                "required": ["city"]
+            }),
+        }];
+        let tool_choice = ToolChoice::Function {
+            name: "get_weather".to_string(),
+        };
+        let tool_scripts = vec![ToolScript {
+            name: "get_weather".to_string(),
+            cases: vec![ToolScriptCase {
+                arguments: serde_json::json!({ "city": "Tokyo" }),
+                result: serde_json::json!({ "weather": "sunny" }),
+            }],
+            default: Some(serde_json::json!({ "weather": "unknown" })),
+        }];
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository
+            .update_comparing_prompt_setting_version_tools(
+                version_id,
+                &tools,
+                Some(&tool_choice),
+                &tool_scripts,
+            )
+            .await;
+
+        // assert
+        assert!(result.is_ok());
+        let version = repository
+            .find_comparing_prompt_setting_version_by_id(version_id)
+            .await
+            .unwrap();
+        assert_eq!(version.setting_id, setting_id);
+        assert_eq!(version.tools, tools);
+        assert_eq!(version.tool_choice, Some(tool_choice));
+        assert_eq!(version.tool_scripts, tool_scripts);
+
+        // ツールを空にするとNULLで保持される
+        repository
+            .update_comparing_prompt_setting_version_tools(version_id, &[], None, &[])
+            .await
+            .unwrap();
+        let version = ComparingPromptSettingVersions::find_by_id(version_id)
+            .one(db.as_ref())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.tools, None);
+        assert_eq!(version.tool_choice, None);
+        assert_eq!(version.tool_scripts, None);
+    }
+
+    #[tokio::test]
+    async fn test_update_comparing_prompt_setting_version_tools_not_found_error() {
+        let db =
+            setup_db("test_update_comparing_prompt_setting_version_tools_not_found_error").await;
+        let repository = ComparingPromptSettingRepositoryImpl::new(db.clone());
+
+        // 存在しないIDで呼び出し
+        let result = repository
+            .update_comparing_prompt_setting_version_tools(9999, &[], None, &[])
+            .await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+    }
+
+    /// マネージャーの論理削除を設定または解除する
+    async fn set_prompt_manager_deleted(db: Arc<DatabaseConnection>, id: i32, deleted: bool) {
+        let deleted_at = deleted.then(|| chrono::Utc::now().to_string());
+        PromptManager::update_many()
+            .col_expr(prompt_manager::Column::DeletedAt, Expr::value(deleted_at))
+            .filter(prompt_manager::Column::Id.eq(id))
+            .exec(db.as_ref())
+            .await
+            .expect("Failed to update prompt manager");
+    }
+
+    #[tokio::test]
+    async fn test_logical_delete_and_restore_comparing_prompt_setting() {
+        let db = setup_db("test_logical_delete_and_restore_comparing_prompt_setting").await;
+        let repository = ComparingPromptSettingRepositoryImpl::new(db.clone());
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let setting_id = repository
+            .create_comparing_prompt_setting(manager_id)
+            .await
+            .unwrap();
+        let version_id = repository
+            .find_comparing_prompt_setting_by_id(setting_id)
+            .await
+            .unwrap()
+            .versions[0]
+            .id;
+
+        // テスト対象のメソッドを呼び出し
+        repository
+            .logical_delete_comparing_prompt_setting(setting_id)
+            .await
+            .unwrap();
+
+        // assert
+        // 削除した設定とバージョンは取得されない
+        let result = repository
+            .find_comparing_prompt_setting_by_id(setting_id)
+            .await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+        let result = repository
+            .find_comparing_prompt_setting_version_by_id(version_id)
+            .await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+        let settings = repository
+            .find_all_comparing_prompt_settings_by_manager_id(manager_id)
+            .await
+            .unwrap();
+        assert!(settings.is_empty());
+        let result = repository
+            .logical_delete_comparing_prompt_setting(setting_id)
+            .await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+
+        // マネージャーが削除されている間は元に戻せない
+        set_prompt_manager_deleted(Arc::clone(&db), manager_id, true).await;
+        let result = repository
+            .restore_comparing_prompt_setting(setting_id)
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+
+        set_prompt_manager_deleted(Arc::clone(&db), manager_id, false).await;
+        repository
+            .restore_comparing_prompt_setting(setting_id)
+            .await
+            .unwrap();
+        let setting = repository
+            .find_comparing_prompt_setting_by_id(setting_id)
+            .await
+            .unwrap();
+        assert_eq!(setting.id, setting_id);
+        let result = repository
+            .restore_comparing_prompt_setting(setting_id)
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_find_comparing_prompt_settings_of_deleted_prompt_manager() {
+        let db = setup_db("test_find_comparing_prompt_settings_of_deleted_prompt_manager").await;
+        let repository = ComparingPromptSettingRepositoryImpl::new(db.clone());
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(Arc::clone(&db)).await;
+        let _ = seed_comparing_prompt_manager(Arc::clone(&db), manager_id).await;
+        let setting_id = repository
+            .create_comparing_prompt_setting(manager_id)
+            .await
+            .unwrap();
+        set_prompt_manager_deleted(Arc::clone(&db), manager_id, true).await;
+
+        // テスト対象のメソッドを呼び出し
+        let settings = repository
+            .find_all_comparing_prompt_settings_by_manager_id(manager_id)
+            .await
+            .unwrap();
+        let result = repository
+            .find_comparing_prompt_setting_by_id(setting_id)
+            .await;
+
+        // assert
+        // マネージャーが削除されている場合は、設定も削除されたものとして扱う
+        assert!(settings.is_empty());
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+        let result = repository
+            .logical_delete_comparing_prompt_setting(setting_id)
+            .await;
+        assert_eq!(result.unwrap_err(), ApplicationError::EmptyResult);
+
+        // マネージャーを元に戻すと設定も取得される
+        set_prompt_manager_deleted(Arc::clone(&db), manager_id, false).await;
+        let settings = repository
+            .find_all_comparing_prompt_settings_by_manager_id(manager_id)
+            .await
+            .unwrap();
+        assert_eq!(settings.len(), 1);
+    }
 }
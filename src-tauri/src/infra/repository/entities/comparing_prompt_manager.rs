@@ -15,6 +15,8 @@ pub enum Relation {
     ComparingPromptRuns,
     #[sea_orm(has_many = "super::comparing_prompt_settings::Entity")]
     ComparingPromptSettings,
+    #[sea_orm(has_many = "super::comparing_prompt_sweeps::Entity")]
+    ComparingPromptSweeps,
     #[sea_orm(
         belongs_to = "super::prompt_manager::Entity",
         from = "Column::ManagerId",
@@ -37,6 +39,12 @@ impl Related<super::comparing_prompt_settings::Entity> for Entity {
     }
 }
 
+impl Related<super::comparing_prompt_sweeps::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComparingPromptSweeps.def()
+    }
+}
+
 impl Related<super::prompt_manager::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PromptManager.def()
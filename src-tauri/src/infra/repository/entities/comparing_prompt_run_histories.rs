@@ -10,10 +10,17 @@ pub struct Model {
     pub run_id: i32,
     pub version_id: i32,
     pub response: String,
+    pub sample_index: i32,
+    pub transcript: Option<String>,
+    pub cache_hit: bool,
+    pub cancelled: bool,
+    pub job_item_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::comparing_prompt_baselines::Entity")]
+    ComparingPromptBaselines,
     #[sea_orm(
         belongs_to = "super::comparing_prompt_runs::Entity",
         from = "Column::RunId",
@@ -32,6 +39,12 @@ pub enum Relation {
     ComparingPromptSettingVersions,
 }
 
+impl Related<super::comparing_prompt_baselines::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComparingPromptBaselines.def()
+    }
+}
+
 impl Related<super::comparing_prompt_runs::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ComparingPromptRuns.def()
@@ -14,6 +14,19 @@ pub struct Model {
     #[sea_orm(column_type = "Double")]
     pub temperature: f64,
     pub max_token: Option<i32>,
+    pub repetitions: i32,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub top_p: Option<f64>,
+    pub sweep_id: Option<i32>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub frequency_penalty: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub presence_penalty: Option<f64>,
+    pub stop: Option<String>,
+    pub seed: Option<i64>,
+    pub logit_bias: Option<String>,
+    pub user: Option<String>,
+    pub credential_profile_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,6 +41,14 @@ pub enum Relation {
     ComparingPromptManager,
     #[sea_orm(has_many = "super::comparing_prompt_run_histories::Entity")]
     ComparingPromptRunHistories,
+    #[sea_orm(
+        belongs_to = "super::comparing_prompt_sweeps::Entity",
+        from = "Column::SweepId",
+        to = "super::comparing_prompt_sweeps::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    ComparingPromptSweeps,
 }
 
 impl Related<super::comparing_prompt_manager::Entity> for Entity {
@@ -42,4 +63,10 @@ impl Related<super::comparing_prompt_run_histories::Entity> for Entity {
     }
 }
 
+impl Related<super::comparing_prompt_sweeps::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComparingPromptSweeps.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -10,6 +10,9 @@ pub struct Model {
     pub version: i32,
     pub setting_id: i32,
     pub system_prompt: String,
+    pub tools: Option<String>,
+    pub tool_choice: Option<String>,
+    pub tool_scripts: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -14,6 +14,8 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::comparing_prompt_baselines::Entity")]
+    ComparingPromptBaselines,
     #[sea_orm(
         belongs_to = "super::comparing_prompt_manager::Entity",
         from = "Column::ManagerId",
@@ -26,6 +28,12 @@ pub enum Relation {
     ComparingPromptSettingVersions,
 }
 
+impl Related<super::comparing_prompt_baselines::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComparingPromptBaselines.def()
+    }
+}
+
 impl Related<super::comparing_prompt_manager::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ComparingPromptManager.def()
@@ -2,13 +2,22 @@
 
 pub mod prelude;
 
+pub mod app_settings;
+pub mod comparing_prompt_baselines;
 pub mod comparing_prompt_chat_setting_details;
 pub mod comparing_prompt_manager;
 pub mod comparing_prompt_run_histories;
 pub mod comparing_prompt_runs;
 pub mod comparing_prompt_setting_versions;
 pub mod comparing_prompt_settings;
+pub mod comparing_prompt_sweeps;
 pub mod comparing_prompt_vision_setting_details;
+pub mod credential_profiles;
+pub mod job_items;
+pub mod jobs;
+pub mod model_catalog;
 pub mod prompt_manager;
+pub mod prompt_manager_credential_profiles;
 pub mod prompt_manager_tag;
+pub mod response_cache;
 pub mod tag;
@@ -1,12 +1,21 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.6
 
+pub use super::app_settings::Entity as AppSettings;
+pub use super::comparing_prompt_baselines::Entity as ComparingPromptBaselines;
 pub use super::comparing_prompt_chat_setting_details::Entity as ComparingPromptChatSettingDetails;
 pub use super::comparing_prompt_manager::Entity as ComparingPromptManager;
 pub use super::comparing_prompt_run_histories::Entity as ComparingPromptRunHistories;
 pub use super::comparing_prompt_runs::Entity as ComparingPromptRuns;
 pub use super::comparing_prompt_setting_versions::Entity as ComparingPromptSettingVersions;
 pub use super::comparing_prompt_settings::Entity as ComparingPromptSettings;
+pub use super::comparing_prompt_sweeps::Entity as ComparingPromptSweeps;
 pub use super::comparing_prompt_vision_setting_details::Entity as ComparingPromptVisionSettingDetails;
+pub use super::credential_profiles::Entity as CredentialProfiles;
+pub use super::job_items::Entity as JobItems;
+pub use super::jobs::Entity as Jobs;
+pub use super::model_catalog::Entity as ModelCatalog;
 pub use super::prompt_manager::Entity as PromptManager;
+pub use super::prompt_manager_credential_profiles::Entity as PromptManagerCredentialProfiles;
 pub use super::prompt_manager_tag::Entity as PromptManagerTag;
+pub use super::response_cache::Entity as ResponseCache;
 pub use super::tag::Entity as Tag;
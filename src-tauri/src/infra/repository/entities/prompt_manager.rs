@@ -17,6 +17,8 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::comparing_prompt_manager::Entity")]
     ComparingPromptManager,
+    #[sea_orm(has_many = "super::prompt_manager_credential_profiles::Entity")]
+    PromptManagerCredentialProfiles,
     #[sea_orm(has_many = "super::prompt_manager_tag::Entity")]
     PromptManagerTag,
 }
@@ -27,6 +29,12 @@ impl Related<super::comparing_prompt_manager::Entity> for Entity {
     }
 }
 
+impl Related<super::prompt_manager_credential_profiles::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PromptManagerCredentialProfiles.def()
+    }
+}
+
 impl Related<super::prompt_manager_tag::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PromptManagerTag.def()
@@ -0,0 +1,372 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::job::{
+    JobItemModel, JobItemStatus, JobModel, JobRepository, JobStatus, JobType,
+};
+use crate::infra::repository::entities::prelude::{JobItems, Jobs};
+use crate::infra::repository::entities::{job_items, jobs};
+
+#[derive(Clone, Debug)]
+pub struct JobRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl JobRepository for JobRepositoryImpl {
+    async fn create_job(
+        &self,
+        job_type: &JobType,
+        payload: &str,
+        items: &[String],
+    ) -> Result<i32, ApplicationError> {
+        let now = chrono::Utc::now().to_string();
+        let txn = self.db.begin().await?;
+        let job = jobs::ActiveModel {
+            id: Default::default(),
+            job_type: ActiveValue::Set(job_type.to_string()),
+            payload: ActiveValue::Set(payload.to_string()),
+            status: ActiveValue::Set(JobStatus::Queued.to_string()),
+            total_items: ActiveValue::Set(items.len() as i32),
+            completed_items: ActiveValue::Set(0),
+            failed_items: ActiveValue::Set(0),
+            error: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now.clone()),
+            updated_at: ActiveValue::Set(now),
+        };
+        let job_id = Jobs::insert(job).exec(&txn).await?.last_insert_id;
+        for (item_index, item) in items.iter().enumerate() {
+            let item = job_items::ActiveModel {
+                id: Default::default(),
+                job_id: ActiveValue::Set(job_id),
+                item_index: ActiveValue::Set(item_index as i32),
+                payload: ActiveValue::Set(item.clone()),
+                status: ActiveValue::Set(JobItemStatus::Pending.to_string()),
+                result: ActiveValue::Set(None),
+                error: ActiveValue::Set(None),
+            };
+            JobItems::insert(item).exec(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(job_id)
+    }
+
+    async fn find_job_by_id(&self, id: i32) -> Result<JobModel, ApplicationError> {
+        let res = Jobs::find_by_id(id)
+            .one(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        to_job_model(res.ok_or(ApplicationError::EmptyResult)?)
+    }
+
+    async fn find_jobs(
+        &self,
+        status: Option<&JobStatus>,
+    ) -> Result<Vec<JobModel>, ApplicationError> {
+        let mut query = Jobs::find();
+        if let Some(status) = status {
+            query = query.filter(jobs::Column::Status.eq(status.to_string()));
+        }
+        let res = query
+            .order_by_desc(jobs::Column::Id)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.into_iter().map(to_job_model).collect()
+    }
+
+    async fn find_job_items_by_job_id(
+        &self,
+        job_id: i32,
+    ) -> Result<Vec<JobItemModel>, ApplicationError> {
+        let res = JobItems::find()
+            .filter(job_items::Column::JobId.eq(job_id))
+            .order_by_asc(job_items::Column::ItemIndex)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.into_iter().map(to_job_item_model).collect()
+    }
+
+    async fn claim_next_job(&self) -> Result<Option<JobModel>, ApplicationError> {
+        loop {
+            let job = Jobs::find()
+                .filter(jobs::Column::Status.eq(JobStatus::Queued.to_string()))
+                .order_by_asc(jobs::Column::Id)
+                .one(self.db.as_ref())
+                .await
+                .map_err(ApplicationError::DBError)?;
+            let job = match job {
+                Some(job) => job,
+                None => return Ok(None),
+            };
+            // 他のワーカーが先に取得した場合は更新されないので次のジョブを探す
+            let res = Jobs::update_many()
+                .col_expr(
+                    jobs::Column::Status,
+                    Expr::value(JobStatus::Running.to_string()),
+                )
+                .col_expr(
+                    jobs::Column::UpdatedAt,
+                    Expr::value(chrono::Utc::now().to_string()),
+                )
+                .filter(jobs::Column::Id.eq(job.id))
+                .filter(jobs::Column::Status.eq(JobStatus::Queued.to_string()))
+                .exec(self.db.as_ref())
+                .await?;
+            if res.rows_affected == 1 {
+                return self.find_job_by_id(job.id).await.map(Some);
+            }
+        }
+    }
+
+    async fn start_job_item(&self, item_id: i32) -> Result<(), ApplicationError> {
+        JobItems::update_many()
+            .col_expr(
+                job_items::Column::Status,
+                Expr::value(JobItemStatus::Running.to_string()),
+            )
+            .filter(job_items::Column::Id.eq(item_id))
+            .exec(self.db.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    async fn finish_job_item(
+        &self,
+        item_id: i32,
+        result: Result<String, String>,
+    ) -> Result<(), ApplicationError> {
+        let item = JobItems::find_by_id(item_id)
+            .one(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?
+            .ok_or(ApplicationError::EmptyResult)?;
+        let (status, result, error, counter) = match result {
+            Ok(result) => (
+                JobItemStatus::Completed,
+                Some(result),
+                None,
+                jobs::Column::CompletedItems,
+            ),
+            Err(error) => (
+                JobItemStatus::Failed,
+                None,
+                Some(error),
+                jobs::Column::FailedItems,
+            ),
+        };
+
+        // 項目の結果と進捗は同時に更新する
+        let txn = self.db.begin().await?;
+        JobItems::update(job_items::ActiveModel {
+            id: ActiveValue::Unchanged(item.id),
+            status: ActiveValue::Set(status.to_string()),
+            result: ActiveValue::Set(result),
+            error: ActiveValue::Set(error),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+        Jobs::update_many()
+            .col_expr(counter, Expr::col(counter).add(1))
+            .col_expr(
+                jobs::Column::UpdatedAt,
+                Expr::value(chrono::Utc::now().to_string()),
+            )
+            .filter(jobs::Column::Id.eq(item.job_id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn finish_job(
+        &self,
+        id: i32,
+        status: &JobStatus,
+        error: Option<&str>,
+    ) -> Result<(), ApplicationError> {
+        Jobs::update(jobs::ActiveModel {
+            id: ActiveValue::Unchanged(id),
+            status: ActiveValue::Set(status.to_string()),
+            error: ActiveValue::Set(error.map(|error| error.to_string())),
+            updated_at: ActiveValue::Set(chrono::Utc::now().to_string()),
+            ..Default::default()
+        })
+        .exec(self.db.as_ref())
+        .await?;
+        Ok(())
+    }
+
+    async fn resume_interrupted_jobs(&self) -> Result<u64, ApplicationError> {
+        let txn = self.db.begin().await?;
+        JobItems::update_many()
+            .col_expr(
+                job_items::Column::Status,
+                Expr::value(JobItemStatus::Pending.to_string()),
+            )
+            .filter(job_items::Column::Status.eq(JobItemStatus::Running.to_string()))
+            .exec(&txn)
+            .await?;
+        let res = Jobs::update_many()
+            .col_expr(
+                jobs::Column::Status,
+                Expr::value(JobStatus::Queued.to_string()),
+            )
+            .col_expr(
+                jobs::Column::UpdatedAt,
+                Expr::value(chrono::Utc::now().to_string()),
+            )
+            .filter(jobs::Column::Status.eq(JobStatus::Running.to_string()))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(res.rows_affected)
+    }
+}
+
+fn to_job_model(model: jobs::Model) -> Result<JobModel, ApplicationError> {
+    Ok(JobModel {
+        id: model.id,
+        job_type: JobType::from_str(&model.job_type)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+        payload: model.payload,
+        status: JobStatus::from_str(&model.status)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+        total_items: model.total_items,
+        completed_items: model.completed_items,
+        failed_items: model.failed_items,
+        error: model.error,
+        created_at: model.created_at,
+        updated_at: model.updated_at,
+    })
+}
+
+fn to_job_item_model(model: job_items::Model) -> Result<JobItemModel, ApplicationError> {
+    Ok(JobItemModel {
+        id: model.id,
+        job_id: model.job_id,
+        item_index: model.item_index,
+        payload: model.payload,
+        status: JobItemStatus::from_str(&model.status)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+        result: model.result,
+        error: model.error,
+    })
+}
+
+impl JobRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        JobRepositoryImpl { db }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::thelper::db::setup_db;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_claim_and_finish_job() {
+        let db = setup_db("test_claim_and_finish_job").await;
+        let repository = JobRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let items = vec!["{\"index\":0}".to_string(), "{\"index\":1}".to_string()];
+        let job_id = repository
+            .create_job(&JobType::ComparingPromptRun, "{}", &items)
+            .await
+            .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        let job = repository.claim_next_job().await.unwrap().unwrap();
+        let job_items = repository.find_job_items_by_job_id(job_id).await.unwrap();
+        repository.start_job_item(job_items[0].id).await.unwrap();
+        repository
+            .finish_job_item(job_items[0].id, Ok("{}".to_string()))
+            .await
+            .unwrap();
+        repository.start_job_item(job_items[1].id).await.unwrap();
+        repository
+            .finish_job_item(job_items[1].id, Err("failed".to_string()))
+            .await
+            .unwrap();
+        repository
+            .finish_job(job_id, &JobStatus::Failed, Some("1 item failed"))
+            .await
+            .unwrap();
+
+        // assert
+        assert_eq!(job.id, job_id);
+        assert_eq!(job.status, JobStatus::Running);
+        assert_eq!(job.total_items, 2);
+        // 取得済みのジョブは他のワーカーに取得されない
+        assert!(repository.claim_next_job().await.unwrap().is_none());
+
+        let job = repository.find_job_by_id(job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.completed_items, 1);
+        assert_eq!(job.failed_items, 1);
+        assert_eq!(job.error.as_deref(), Some("1 item failed"));
+
+        let job_items = repository.find_job_items_by_job_id(job_id).await.unwrap();
+        assert_eq!(job_items[0].status, JobItemStatus::Completed);
+        assert_eq!(job_items[0].result.as_deref(), Some("{}"));
+        assert_eq!(job_items[1].status, JobItemStatus::Failed);
+        assert_eq!(job_items[1].error.as_deref(), Some("failed"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_interrupted_jobs() {
+        let db = setup_db("test_resume_interrupted_jobs").await;
+        let repository = JobRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        // 1件目の項目が完了し、2件目の実行中にアプリが終了した状態
+        let items = vec!["{}".to_string(), "{}".to_string(), "{}".to_string()];
+        let job_id = repository
+            .create_job(&JobType::ComparingPromptSweep, "{}", &items)
+            .await
+            .unwrap();
+        repository.claim_next_job().await.unwrap().unwrap();
+        let job_items = repository.find_job_items_by_job_id(job_id).await.unwrap();
+        repository
+            .finish_job_item(job_items[0].id, Ok("{}".to_string()))
+            .await
+            .unwrap();
+        repository.start_job_item(job_items[1].id).await.unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        let resumed = repository.resume_interrupted_jobs().await.unwrap();
+
+        // assert
+        assert_eq!(resumed, 1);
+        let job = repository
+            .find_jobs(Some(&JobStatus::Queued))
+            .await
+            .unwrap();
+        assert_eq!(job.len(), 1);
+        assert_eq!(job[0].completed_items, 1);
+        // 完了した項目はそのまま残り、実行中だった項目は再実行される
+        let job_items = repository.find_job_items_by_job_id(job_id).await.unwrap();
+        let statuses: Vec<JobItemStatus> = job_items.iter().map(|item| item.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                JobItemStatus::Completed,
+                JobItemStatus::Pending,
+                JobItemStatus::Pending
+            ]
+        );
+    }
+}
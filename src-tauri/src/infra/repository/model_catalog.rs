@@ -0,0 +1,163 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::ProviderType;
+use crate::domain::model_catalog::{ModelCapabilities, ModelCatalogModel, ModelCatalogRepository};
+use crate::infra::repository::entities::model_catalog;
+use crate::infra::repository::entities::prelude::ModelCatalog;
+
+#[derive(Clone, Debug)]
+pub struct ModelCatalogRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl ModelCatalogRepository for ModelCatalogRepositoryImpl {
+    async fn find_model_catalog(
+        &self,
+        provider_type: Option<&ProviderType>,
+    ) -> Result<Vec<ModelCatalogModel>, ApplicationError> {
+        let mut query = ModelCatalog::find();
+        if let Some(provider_type) = provider_type {
+            query = query.filter(model_catalog::Column::ProviderType.eq(provider_type.to_string()));
+        }
+        let res = query
+            .order_by_asc(model_catalog::Column::ProviderType)
+            .order_by_asc(model_catalog::Column::Model)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.into_iter().map(to_model_catalog_model).collect()
+    }
+
+    async fn replace_model_catalog(
+        &self,
+        provider_type: &ProviderType,
+        models: Vec<ModelCatalogModel>,
+    ) -> Result<(), ApplicationError> {
+        let synced_at = chrono::Utc::now().to_string();
+        let txn = self.db.begin().await?;
+        ModelCatalog::delete_many()
+            .filter(model_catalog::Column::ProviderType.eq(provider_type.to_string()))
+            .exec(&txn)
+            .await?;
+        for model in models {
+            let model = model_catalog::ActiveModel {
+                id: Default::default(),
+                provider_type: ActiveValue::Set(provider_type.to_string()),
+                model: ActiveValue::Set(model.model),
+                known: ActiveValue::Set(model.known),
+                vision: ActiveValue::Set(model.capabilities.vision),
+                json_mode: ActiveValue::Set(model.capabilities.json_mode),
+                tools: ActiveValue::Set(model.capabilities.tools),
+                context_window: ActiveValue::Set(model.capabilities.context_window),
+                input_price: ActiveValue::Set(model.capabilities.input_price),
+                output_price: ActiveValue::Set(model.capabilities.output_price),
+                synced_at: ActiveValue::Set(synced_at.clone()),
+            };
+            ModelCatalog::insert(model).exec(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+fn to_model_catalog_model(
+    model: model_catalog::Model,
+) -> Result<ModelCatalogModel, ApplicationError> {
+    Ok(ModelCatalogModel {
+        id: model.id,
+        provider_type: ProviderType::from_str(&model.provider_type)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+        model: model.model,
+        known: model.known,
+        capabilities: ModelCapabilities {
+            vision: model.vision,
+            json_mode: model.json_mode,
+            tools: model.tools,
+            context_window: model.context_window,
+            input_price: model.input_price,
+            output_price: model.output_price,
+        },
+    })
+}
+
+impl ModelCatalogRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        ModelCatalogRepositoryImpl { db }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::thelper::db::setup_db;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replace_model_catalog() {
+        let db = setup_db("test_replace_model_catalog").await;
+        let repository = ModelCatalogRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        repository
+            .replace_model_catalog(
+                &ProviderType::OpenAI,
+                vec![ModelCatalogModel::from_listed(
+                    ProviderType::OpenAI,
+                    "gpt-4".to_string(),
+                )],
+            )
+            .await
+            .unwrap();
+        repository
+            .replace_model_catalog(
+                &ProviderType::Gemini,
+                vec![ModelCatalogModel::from_listed(
+                    ProviderType::Gemini,
+                    "gemini-pro".to_string(),
+                )],
+            )
+            .await
+            .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        repository
+            .replace_model_catalog(
+                &ProviderType::OpenAI,
+                vec![
+                    ModelCatalogModel::from_listed(ProviderType::OpenAI, "gpt-4o".to_string()),
+                    ModelCatalogModel::from_listed(
+                        ProviderType::OpenAI,
+                        "text-embedding-3-small".to_string(),
+                    ),
+                ],
+            )
+            .await
+            .unwrap();
+
+        // assert
+        // 同じプロバイダーのモデルのみ入れ替わる
+        let openai_models = repository
+            .find_model_catalog(Some(&ProviderType::OpenAI))
+            .await
+            .unwrap();
+        let names: Vec<&str> = openai_models.iter().map(|m| m.model.as_str()).collect();
+        assert_eq!(names, vec!["gpt-4o", "text-embedding-3-small"]);
+        assert!(openai_models[0].known);
+        assert!(openai_models[0].capabilities.vision);
+        assert_eq!(openai_models[0].capabilities.context_window, Some(128_000));
+        assert!(!openai_models[1].known);
+        assert_eq!(openai_models[1].capabilities, ModelCapabilities::default());
+
+        let all_models = repository.find_model_catalog(None).await.unwrap();
+        assert_eq!(all_models.len(), 3);
+    }
+}
@@ -2,20 +2,34 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use sea_orm::sea_query::{Expr, Func, LikeExpr, Query, SelectStatement, SimpleExpr};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait,
-    QueryFilter, TransactionTrait,
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, EntityTrait,
+    FromQueryResult, ModelTrait, Order, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
 };
 
 use crate::common::errors::ApplicationError;
 use crate::domain::prompt_manager::{
-    APIType, ActionType, PromptManagerModel, PromptManagerRepository,
+    APIType, ActionType, PromptManagerCursor, PromptManagerModel, PromptManagerPage,
+    PromptManagerQuery, PromptManagerRepository, PromptManagerSort, TagMatch,
+};
+use crate::infra::repository::entities::prelude::{
+    ComparingPromptRuns, PromptManager, PromptManagerTag, Tag,
 };
-use crate::infra::repository::entities::prelude::{PromptManager, PromptManagerTag, Tag};
 use crate::infra::repository::entities::{
-    comparing_prompt_manager, prompt_manager, prompt_manager_tag, tag,
+    comparing_prompt_manager, comparing_prompt_runs, prompt_manager, prompt_manager_tag, tag,
 };
 
+/// 検索結果の行（並び順に使う最後の実行のIDを含む）
+#[derive(Debug, FromQueryResult)]
+struct PromptManagerRow {
+    id: i32,
+    title: String,
+    action_type: Option<String>,
+    api_type: Option<String>,
+    last_run_id: i32,
+}
+
 #[derive(Clone, Debug)]
 pub struct PromptManagerRepositoryImpl {
     db: Arc<DatabaseConnection>,
@@ -51,24 +65,142 @@ impl PromptManagerRepository for PromptManagerRepositoryImpl {
         })
     }
 
-    async fn find_all_prompt_managers(&self) -> Result<Vec<PromptManagerModel>, ApplicationError> {
-        let prompt_managers: Vec<(prompt_manager::Model, Vec<tag::Model>)> = PromptManager::find()
-            .filter(prompt_manager::Column::DeletedAt.is_null())
-            .find_with_related(Tag)
+    async fn find_prompt_managers(
+        &self,
+        query: &PromptManagerQuery,
+    ) -> Result<PromptManagerPage, ApplicationError> {
+        let last_run_id = last_run_id_expr();
+        let mut select = PromptManager::find()
+            .select_only()
+            .columns([
+                prompt_manager::Column::Id,
+                prompt_manager::Column::Title,
+                prompt_manager::Column::ActionType,
+                prompt_manager::Column::ApiType,
+            ])
+            .column_as(last_run_id.clone(), "last_run_id")
+            .filter(prompt_manager::Column::DeletedAt.is_null());
+
+        if let Some(title) = &query.title {
+            select = select.filter(
+                Expr::col((PromptManager, prompt_manager::Column::Title))
+                    .like(LikeExpr::new(format!("%{}%", escape_like(title))).escape('\\')),
+            );
+        }
+        if let Some(action_type) = &query.action_type {
+            select = select.filter(prompt_manager::Column::ActionType.eq(action_type.to_string()));
+        }
+        if let Some(api_type) = &query.api_type {
+            select = select.filter(prompt_manager::Column::ApiType.eq(api_type.to_string()));
+        }
+        if !query.tags.is_empty() {
+            select = match query.tag_match {
+                TagMatch::Any => select.filter(
+                    prompt_manager::Column::Id.in_subquery(tagged_manager_ids(&query.tags)),
+                ),
+                // タグごとに絞り込み、全てのタグを含むマネージャーのみ残す
+                TagMatch::All => query.tags.iter().fold(select, |select, tag| {
+                    select.filter(
+                        prompt_manager::Column::Id
+                            .in_subquery(tagged_manager_ids(std::slice::from_ref(tag))),
+                    )
+                }),
+            };
+        }
+
+        // 前のページの最後のマネージャーより後ろのみ取得する
+        if let Some(cursor) = &query.cursor {
+            if cursor.sort() != query.sort {
+                return Err(ApplicationError::ValidationError(format!(
+                    "cursor is for sort {}",
+                    cursor.sort()
+                )));
+            }
+            select = select.filter(match cursor {
+                PromptManagerCursor::Title { title, id } => Condition::any()
+                    .add(prompt_manager::Column::Title.gt(title.as_str()))
+                    .add(
+                        Condition::all()
+                            .add(prompt_manager::Column::Title.eq(title.as_str()))
+                            .add(prompt_manager::Column::Id.gt(*id)),
+                    ),
+                PromptManagerCursor::Created { id } => {
+                    Condition::all().add(prompt_manager::Column::Id.lt(*id))
+                }
+                PromptManagerCursor::LastRun {
+                    last_run_id: run_id,
+                    id,
+                } => Condition::any()
+                    .add(Expr::expr(last_run_id.clone()).lt(*run_id))
+                    .add(
+                        Condition::all()
+                            .add(Expr::expr(last_run_id.clone()).eq(*run_id))
+                            .add(prompt_manager::Column::Id.lt(*id)),
+                    ),
+            });
+        }
+
+        select = match query.sort {
+            PromptManagerSort::Title => select
+                .order_by_asc(prompt_manager::Column::Title)
+                .order_by_asc(prompt_manager::Column::Id),
+            PromptManagerSort::Created => select.order_by_desc(prompt_manager::Column::Id),
+            PromptManagerSort::LastRun => select
+                .order_by(last_run_id, Order::Desc)
+                .order_by_desc(prompt_manager::Column::Id),
+        };
+
+        // 次のページがあるか確認するため1件多く取得する
+        let mut rows: Vec<PromptManagerRow> = select
+            .limit(query.limit + 1)
+            .into_model::<PromptManagerRow>()
             .all(self.db.as_ref())
             .await?;
+        let next_cursor = if rows.len() as u64 > query.limit {
+            rows.truncate(query.limit as usize);
+            rows.last().map(|row| match query.sort {
+                PromptManagerSort::Title => PromptManagerCursor::Title {
+                    title: row.title.clone(),
+                    id: row.id,
+                },
+                PromptManagerSort::Created => PromptManagerCursor::Created { id: row.id },
+                PromptManagerSort::LastRun => PromptManagerCursor::LastRun {
+                    last_run_id: row.last_run_id,
+                    id: row.id,
+                },
+            })
+        } else {
+            None
+        };
 
-        Ok({
-            prompt_managers
-                .into_iter()
-                .map(|s| PromptManagerModel {
-                    id: s.0.id,
-                    title: s.0.title,
-                    action_type: s.0.action_type.map(|a| a.parse().unwrap()),
-                    api_type: s.0.api_type.map(|a| a.parse().unwrap()),
-                    tags: s.1.into_iter().map(|t| t.value).collect(),
-                })
-                .collect()
+        // ページのマネージャーのタグのみ取得する
+        let tags: Vec<(prompt_manager_tag::Model, Option<tag::Model>)> = PromptManagerTag::find()
+            .filter(
+                prompt_manager_tag::Column::PromptManagerId
+                    .is_in(rows.iter().map(|row| row.id).collect::<Vec<i32>>()),
+            )
+            .order_by_asc(prompt_manager_tag::Column::Id)
+            .find_also_related(Tag)
+            .all(self.db.as_ref())
+            .await?;
+
+        let managers = rows
+            .into_iter()
+            .map(|row| PromptManagerModel {
+                id: row.id,
+                title: row.title,
+                action_type: row.action_type.map(|a| a.parse().unwrap()),
+                api_type: row.api_type.map(|a| a.parse().unwrap()),
+                tags: tags
+                    .iter()
+                    .filter(|(manager_tag, _)| manager_tag.prompt_manager_id == row.id)
+                    .filter_map(|(_, tag)| tag.as_ref().map(|t| t.value.clone()))
+                    .collect(),
+            })
+            .collect();
+        Ok(PromptManagerPage {
+            managers,
+            next_cursor,
         })
     }
 
@@ -243,17 +375,66 @@ impl PromptManagerRepositoryImpl {
     }
 }
 
+/// マネージャーの最後の実行のID（実行していない場合は0）
+fn last_run_id_expr() -> SimpleExpr {
+    let last_run = Query::select()
+        .expr(Expr::col((ComparingPromptRuns, comparing_prompt_runs::Column::Id)).max())
+        .from(ComparingPromptRuns)
+        .and_where(
+            Expr::col((
+                ComparingPromptRuns,
+                comparing_prompt_runs::Column::ManagerId,
+            ))
+            .equals((PromptManager, prompt_manager::Column::Id)),
+        )
+        .to_owned();
+    Func::coalesce([
+        SimpleExpr::SubQuery(None, Box::new(last_run.into_sub_query_statement())),
+        Expr::val(0).into(),
+    ])
+    .into()
+}
+
+/// いずれかのタグが付いているマネージャーのID
+fn tagged_manager_ids(tags: &[String]) -> SelectStatement {
+    Query::select()
+        .column((
+            PromptManagerTag,
+            prompt_manager_tag::Column::PromptManagerId,
+        ))
+        .from(PromptManagerTag)
+        .inner_join(
+            Tag,
+            Expr::col((Tag, tag::Column::Id))
+                .equals((PromptManagerTag, prompt_manager_tag::Column::TagId)),
+        )
+        .and_where(Expr::col((Tag, tag::Column::Value)).is_in(tags.to_vec()))
+        .to_owned()
+}
+
+/// LIKEの特殊文字をエスケープする
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 #[cfg(test)]
 mod tests {
     use sea_orm::{ActiveValue, EntityTrait};
 
+    use crate::common::errors::ApplicationError;
     use crate::common::thelper::db::setup_db;
-    use crate::domain::prompt_manager::{APIType, ActionType, PromptManagerRepository};
+    use crate::domain::prompt_manager::{
+        APIType, ActionType, PromptManagerCursor, PromptManagerQuery, PromptManagerRepository,
+        PromptManagerSort, TagMatch,
+    };
     use crate::infra::repository::entities::prelude::{
-        ComparingPromptManager, PromptManager, PromptManagerTag, Tag,
+        ComparingPromptManager, ComparingPromptRuns, PromptManager, PromptManagerTag, Tag,
     };
     use crate::infra::repository::entities::{
-        comparing_prompt_manager, prompt_manager, prompt_manager_tag, tag,
+        comparing_prompt_manager, comparing_prompt_runs, prompt_manager, prompt_manager_tag, tag,
     };
     use crate::infra::repository::prompt_manager::PromptManagerRepositoryImpl;
 
@@ -316,52 +497,194 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_find_all_prompt_managers() {
-        let db = setup_db("test_find_all_prompt_managers").await;
+    async fn test_find_prompt_managers() {
+        let db = setup_db("test_find_prompt_managers").await;
         let repo = PromptManagerRepositoryImpl::new(db.clone());
 
-        let prompt_manager = prompt_manager::ActiveModel {
-            id: Default::default(),
-            title: ActiveValue::Set("test_title".to_string()),
-            action_type: ActiveValue::Set(Option::from(ActionType::ComparingPrompt.to_string())),
-            api_type: ActiveValue::Set(None),
-            deleted_at: ActiveValue::Set(None),
-        };
-        let inserted_prompt_manager = PromptManager::insert(prompt_manager)
-            .exec(db.as_ref())
+        // 事前データ
+        let managers = [
+            (
+                "alpha report",
+                ActionType::ComparingPrompt,
+                APIType::Chat,
+                vec!["a", "b"],
+            ),
+            (
+                "Beta_notes",
+                ActionType::ComparingPrompt,
+                APIType::Vision,
+                vec!["b"],
+            ),
+            (
+                "gamma report",
+                ActionType::ComparingModel,
+                APIType::Chat,
+                vec!["c"],
+            ),
+            (
+                "deleted report",
+                ActionType::ComparingPrompt,
+                APIType::Chat,
+                vec!["a"],
+            ),
+        ];
+        for (title, action_type, api_type, tags) in managers {
+            let id = repo.create_prompt_manager(title).await.unwrap();
+            repo.update_prompt_manager(
+                id,
+                title,
+                Some(action_type),
+                Some(api_type),
+                tags.into_iter().map(|t| t.to_string()).collect(),
+            )
             .await
-            .expect("Failed to insert prompt manager");
-        let prompt_manager_id = inserted_prompt_manager.last_insert_id;
+            .unwrap();
+        }
+        repo.logical_delete_prompt_manager(4).await.unwrap();
+        // 2, 1の順に実行する
+        for manager_id in [2, 1] {
+            let run = comparing_prompt_runs::ActiveModel {
+                id: Default::default(),
+                manager_id: ActiveValue::Set(manager_id),
+                provider_type: ActiveValue::Set("OpenAI".to_string()),
+                user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+                model: ActiveValue::Set("test_model".to_string()),
+                temperature: ActiveValue::Set(0.0),
+                max_token: ActiveValue::Set(None),
+                repetitions: ActiveValue::Set(1),
+                top_p: ActiveValue::Set(None),
+                sweep_id: ActiveValue::Set(None),
+                frequency_penalty: ActiveValue::Set(None),
+                presence_penalty: ActiveValue::Set(None),
+                stop: ActiveValue::Set(None),
+                seed: ActiveValue::Set(None),
+                logit_bias: ActiveValue::Set(None),
+                user: ActiveValue::Set(None),
+                credential_profile_id: ActiveValue::Set(None),
+            };
+            ComparingPromptRuns::insert(run)
+                .exec(db.as_ref())
+                .await
+                .expect("Failed to insert comparing_prompt_run");
+        }
 
-        let tag = tag::ActiveModel {
-            id: Default::default(),
-            value: ActiveValue::Set("test_tag".to_string()),
+        let default_query = PromptManagerQuery {
+            title: None,
+            tags: Vec::new(),
+            tag_match: TagMatch::Any,
+            action_type: None,
+            api_type: None,
+            sort: PromptManagerSort::Created,
+            cursor: None,
+            limit: 50,
         };
-        let inserted_tag = Tag::insert(tag)
-            .exec(db.as_ref())
-            .await
-            .expect("Failed to insert tag");
-        let tag_id = inserted_tag.last_insert_id;
-        let prompt_manager_tag = prompt_manager_tag::ActiveModel {
-            id: Default::default(),
-            prompt_manager_id: ActiveValue::Set(prompt_manager_id),
-            tag_id: ActiveValue::Set(tag_id),
+        let find = |query: PromptManagerQuery| {
+            let repo = &repo;
+            async move {
+                let page = repo.find_prompt_managers(&query).await.unwrap();
+                let ids: Vec<i32> = page.managers.iter().map(|m| m.id).collect();
+                (ids, page)
+            }
         };
-        PromptManagerTag::insert(prompt_manager_tag)
-            .exec(db.as_ref())
-            .await
-            .expect("Failed to insert into prompt_manager_tag");
 
-        let result = repo.find_all_prompt_managers().await;
-        assert!(result.is_ok());
-        let managers = result.unwrap();
+        // テスト対象のメソッドを呼び出し
+        // 削除されたマネージャーは含まず、作成日時の新しい順に返す
+        let (ids, page) = find(default_query.clone()).await;
+        assert_eq!(ids, vec![3, 2, 1]);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.managers[2].title, "alpha report");
+        assert_eq!(
+            page.managers[2].action_type,
+            Some(ActionType::ComparingPrompt)
+        );
+        assert_eq!(page.managers[2].api_type, Some(APIType::Chat));
+        assert_eq!(
+            page.managers[2].tags,
+            vec!["a".to_string(), "b".to_string()]
+        );
 
-        // assert
-        assert_eq!(managers.len(), 1);
-        assert_eq!(managers[0].title, "test_title");
-        assert_eq!(managers[0].action_type, Some(ActionType::ComparingPrompt));
-        assert_eq!(managers[0].api_type, None);
-        assert_eq!(managers[0].tags[0], "test_tag");
+        // タイトルの部分一致（LIKEの特殊文字はそのまま検索する）
+        let query = |title: &str| PromptManagerQuery {
+            title: Some(title.to_string()),
+            ..default_query.clone()
+        };
+        assert_eq!(find(query("report")).await.0, vec![3, 1]);
+        assert_eq!(find(query("_")).await.0, vec![2]);
+        assert_eq!(find(query("%")).await.0, Vec::<i32>::new());
+
+        // タグ
+        let query = |tags: &[&str], tag_match: TagMatch| PromptManagerQuery {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            tag_match,
+            ..default_query.clone()
+        };
+        assert_eq!(find(query(&["a", "c"], TagMatch::Any)).await.0, vec![3, 1]);
+        assert_eq!(find(query(&["a", "b"], TagMatch::All)).await.0, vec![1]);
+        assert_eq!(
+            find(query(&["b", "c"], TagMatch::All)).await.0,
+            Vec::<i32>::new()
+        );
+
+        // action_typeとapi_type
+        let (ids, _) = find(PromptManagerQuery {
+            action_type: Some(ActionType::ComparingPrompt),
+            ..default_query.clone()
+        })
+        .await;
+        assert_eq!(ids, vec![2, 1]);
+        let (ids, _) = find(PromptManagerQuery {
+            api_type: Some(APIType::Vision),
+            ..default_query.clone()
+        })
+        .await;
+        assert_eq!(ids, vec![2]);
+
+        // 並び順とページ
+        let pages = |sort: PromptManagerSort, limit: u64| {
+            let find = &find;
+            let default_query = &default_query;
+            async move {
+                let mut pages: Vec<Vec<i32>> = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let (ids, page) = find(PromptManagerQuery {
+                        sort,
+                        cursor,
+                        limit,
+                        ..default_query.clone()
+                    })
+                    .await;
+                    pages.push(ids);
+                    match page.next_cursor {
+                        Some(next_cursor) => cursor = Some(next_cursor),
+                        None => return pages,
+                    }
+                }
+            }
+        };
+        assert_eq!(
+            pages(PromptManagerSort::Title, 1).await,
+            vec![vec![2], vec![1], vec![3]]
+        );
+        assert_eq!(
+            pages(PromptManagerSort::Created, 2).await,
+            vec![vec![3, 2], vec![1]]
+        );
+        // 実行していないマネージャーは最後
+        assert_eq!(
+            pages(PromptManagerSort::LastRun, 2).await,
+            vec![vec![1, 2], vec![3]]
+        );
+
+        // 並び順と異なるカーソル
+        let result = repo
+            .find_prompt_managers(&PromptManagerQuery {
+                sort: PromptManagerSort::Title,
+                cursor: Some(PromptManagerCursor::Created { id: 3 }),
+                ..default_query.clone()
+            })
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
     }
 
     #[tokio::test]
@@ -0,0 +1,216 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::ProviderType;
+use crate::domain::response_cache::{ResponseCacheModel, ResponseCacheRepository};
+use crate::infra::repository::entities::prelude::ResponseCache;
+use crate::infra::repository::entities::response_cache;
+
+#[derive(Clone, Debug)]
+pub struct ResponseCacheRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl ResponseCacheRepository for ResponseCacheRepositoryImpl {
+    async fn find_response_cache(
+        &self,
+        cache_key: &str,
+    ) -> Result<Option<ResponseCacheModel>, ApplicationError> {
+        let res = ResponseCache::find()
+            .filter(response_cache::Column::CacheKey.eq(cache_key))
+            .filter(response_cache::Column::ExpiresAt.gt(chrono::Utc::now().timestamp()))
+            .one(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        res.map(to_response_cache_model).transpose()
+    }
+
+    async fn save_response_cache(
+        &self,
+        cache_key: &str,
+        provider_type: &ProviderType,
+        model: &str,
+        responses: &[String],
+        ttl_seconds: u32,
+    ) -> Result<(), ApplicationError> {
+        let now = chrono::Utc::now();
+        let responses = serde_json::to_string(responses)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+        let cache = response_cache::ActiveModel {
+            id: Default::default(),
+            cache_key: ActiveValue::Set(cache_key.to_string()),
+            provider_type: ActiveValue::Set(provider_type.to_string()),
+            model: ActiveValue::Set(model.to_string()),
+            responses: ActiveValue::Set(responses),
+            created_at: ActiveValue::Set(now.to_string()),
+            expires_at: ActiveValue::Set(now.timestamp() + ttl_seconds as i64),
+        };
+        ResponseCache::insert(cache)
+            .on_conflict(
+                OnConflict::column(response_cache::Column::CacheKey)
+                    .update_columns([
+                        response_cache::Column::Responses,
+                        response_cache::Column::CreatedAt,
+                        response_cache::Column::ExpiresAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(self.db.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_response_caches(
+        &self,
+        provider_type: Option<&ProviderType>,
+        model: Option<&str>,
+        expired_only: bool,
+    ) -> Result<u64, ApplicationError> {
+        let mut query = ResponseCache::delete_many();
+        if let Some(provider_type) = provider_type {
+            query =
+                query.filter(response_cache::Column::ProviderType.eq(provider_type.to_string()));
+        }
+        if let Some(model) = model {
+            query = query.filter(response_cache::Column::Model.eq(model));
+        }
+        if expired_only {
+            query =
+                query.filter(response_cache::Column::ExpiresAt.lte(chrono::Utc::now().timestamp()));
+        }
+        let res = query.exec(self.db.as_ref()).await?;
+        Ok(res.rows_affected)
+    }
+}
+
+fn to_response_cache_model(
+    model: response_cache::Model,
+) -> Result<ResponseCacheModel, ApplicationError> {
+    Ok(ResponseCacheModel {
+        id: model.id,
+        cache_key: model.cache_key,
+        provider_type: ProviderType::from_str(&model.provider_type)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+        model: model.model,
+        responses: serde_json::from_str(&model.responses)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+    })
+}
+
+impl ResponseCacheRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        ResponseCacheRepositoryImpl { db }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::thelper::db::setup_db;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_response_cache() {
+        let db = setup_db("test_save_response_cache").await;
+        let repository = ResponseCacheRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        repository
+            .save_response_cache(
+                "key1",
+                &ProviderType::OpenAI,
+                "gpt-4",
+                &["old answer".to_string()],
+                3600,
+            )
+            .await
+            .unwrap();
+
+        // テスト対象のメソッドを呼び出し
+        repository
+            .save_response_cache(
+                "key1",
+                &ProviderType::OpenAI,
+                "gpt-4",
+                &["answer1".to_string(), "answer2".to_string()],
+                3600,
+            )
+            .await
+            .unwrap();
+
+        // assert
+        // 同じキーは上書きされる
+        let cache = repository
+            .find_response_cache("key1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cache.provider_type, ProviderType::OpenAI);
+        assert_eq!(cache.model, "gpt-4");
+        assert_eq!(cache.responses, vec!["answer1", "answer2"]);
+        assert!(repository
+            .find_response_cache("key2")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_response_caches() {
+        let db = setup_db("test_delete_response_caches").await;
+        let repository = ResponseCacheRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let responses = ["answer".to_string()];
+        repository
+            .save_response_cache("expired", &ProviderType::OpenAI, "gpt-4", &responses, 0)
+            .await
+            .unwrap();
+        repository
+            .save_response_cache("openai", &ProviderType::OpenAI, "gpt-4", &responses, 3600)
+            .await
+            .unwrap();
+        repository
+            .save_response_cache(
+                "gemini",
+                &ProviderType::Gemini,
+                "gemini-pro",
+                &responses,
+                3600,
+            )
+            .await
+            .unwrap();
+
+        // 期限切れのキャッシュは取得できない
+        assert!(repository
+            .find_response_cache("expired")
+            .await
+            .unwrap()
+            .is_none());
+
+        // テスト対象のメソッドを呼び出し
+        let expired = repository
+            .delete_response_caches(None, None, true)
+            .await
+            .unwrap();
+        let openai = repository
+            .delete_response_caches(Some(&ProviderType::OpenAI), None, false)
+            .await
+            .unwrap();
+
+        // assert
+        assert_eq!(expired, 1);
+        assert_eq!(openai, 1);
+        assert!(repository
+            .find_response_cache("gemini")
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,412 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement, Value};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::search::{SearchHitKind, SearchHitModel, SearchRepository, SnippetPart};
+
+/// 抜粋で検索語に一致した部分の前後に付ける印（本文に含まれない制御文字を使う）
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_END: char = '\u{3}';
+/// 抜粋に含める最大のトークン数
+const SNIPPET_TOKENS: i32 = 16;
+
+/// 検索結果の行
+#[derive(Debug, FromQueryResult)]
+struct SearchHitRow {
+    kind: String,
+    manager_id: i32,
+    manager_title: String,
+    setting_id: Option<i32>,
+    version_id: Option<i32>,
+    version: Option<i32>,
+    run_id: Option<i32>,
+    snippet: String,
+    score: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl SearchRepository for SearchRepositoryImpl {
+    async fn search(
+        &self,
+        query: &str,
+        kinds: &[SearchHitKind],
+        limit: u64,
+    ) -> Result<Vec<SearchHitModel>, ApplicationError> {
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        // 種類ごとの索引の検索をまとめて、関連度の順に並べる
+        let sql = format!(
+            "SELECT * FROM ({}) ORDER BY score ASC LIMIT ?",
+            kinds
+                .iter()
+                .map(|kind| search_sql(*kind))
+                .collect::<Vec<_>>()
+                .join(" UNION ALL ")
+        );
+        let mut values: Vec<Value> = kinds.iter().map(|_| query.into()).collect();
+        values.push((limit as i64).into());
+        let rows = SearchHitRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            sql,
+            values,
+        ))
+        .all(self.db.as_ref())
+        .await
+        .map_err(ApplicationError::DBError)?;
+        rows.into_iter().map(to_search_hit_model).collect()
+    }
+}
+
+impl SearchRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        SearchRepositoryImpl { db }
+    }
+}
+
+/// 種類ごとに、索引を検索して項目を持つマネージャー、バージョン、実行と結合するSQL
+/// 索引はmigrationのm000014_add_search_indexで作成している
+fn search_sql(kind: SearchHitKind) -> String {
+    let (index, [setting_id, version_id, version, run_id], joins, conditions) = match kind {
+        SearchHitKind::Manager => (
+            "search_prompt_manager",
+            ["NULL", "NULL", "NULL", "NULL"],
+            "JOIN prompt_manager m ON m.id = search_prompt_manager.rowid",
+            "",
+        ),
+        SearchHitKind::Version => (
+            "search_setting_versions",
+            ["s.id", "v.id", "v.version", "NULL"],
+            "JOIN comparing_prompt_setting_versions v ON v.id = search_setting_versions.rowid \
+             JOIN comparing_prompt_settings s ON s.id = v.setting_id \
+             JOIN prompt_manager m ON m.id = s.manager_id",
+            "AND s.deleted_at IS NULL",
+        ),
+        SearchHitKind::Run => (
+            "search_runs",
+            ["NULL", "NULL", "NULL", "r.id"],
+            "JOIN comparing_prompt_runs r ON r.id = search_runs.rowid \
+             JOIN prompt_manager m ON m.id = r.manager_id",
+            "",
+        ),
+        SearchHitKind::Response => (
+            "search_run_histories",
+            ["s.id", "v.id", "v.version", "r.id"],
+            "JOIN comparing_prompt_run_histories h ON h.id = search_run_histories.rowid \
+             JOIN comparing_prompt_runs r ON r.id = h.run_id \
+             JOIN comparing_prompt_setting_versions v ON v.id = h.version_id \
+             JOIN comparing_prompt_settings s ON s.id = v.setting_id \
+             JOIN prompt_manager m ON m.id = r.manager_id",
+            "AND s.deleted_at IS NULL",
+        ),
+    };
+    format!(
+        "SELECT '{kind}' AS kind, m.id AS manager_id, m.title AS manager_title, \
+         {setting_id} AS setting_id, {version_id} AS version_id, {version} AS version, \
+         {run_id} AS run_id, \
+         snippet({index}, 0, char({start}), char({end}), '…', {tokens}) AS snippet, \
+         bm25({index}) AS score \
+         FROM {index} {joins} \
+         WHERE {index} MATCH ? AND m.deleted_at IS NULL {conditions}",
+        start = HIGHLIGHT_START as u32,
+        end = HIGHLIGHT_END as u32,
+        tokens = SNIPPET_TOKENS,
+    )
+}
+
+fn to_search_hit_model(row: SearchHitRow) -> Result<SearchHitModel, ApplicationError> {
+    Ok(SearchHitModel {
+        kind: SearchHitKind::from_str(&row.kind)
+            .map_err(|e| ApplicationError::ParseError(format!("{}: {}", row.kind, e)))?,
+        manager_id: row.manager_id,
+        manager_title: row.manager_title,
+        setting_id: row.setting_id,
+        version_id: row.version_id,
+        version: row.version,
+        run_id: row.run_id,
+        snippet: parse_snippet(&row.snippet),
+        score: row.score,
+    })
+}
+
+/// 印の付いた抜粋を、検索語に一致した部分とそれ以外に分ける
+/// 画面ではHTMLとして解釈せずに強調表示できるようにする
+fn parse_snippet(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut highlighted = false;
+    for c in snippet.chars() {
+        let next = match c {
+            HIGHLIGHT_START => true,
+            HIGHLIGHT_END => false,
+            _ => {
+                text.push(c);
+                continue;
+            }
+        };
+        if !text.is_empty() {
+            parts.push(SnippetPart {
+                text: std::mem::take(&mut text),
+                highlighted,
+            });
+        }
+        highlighted = next;
+    }
+    if !text.is_empty() {
+        parts.push(SnippetPart { text, highlighted });
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait};
+
+    use crate::common::thelper::db::setup_db;
+    use crate::infra::repository::entities::prelude::{
+        ComparingPromptManager, ComparingPromptRunHistories, ComparingPromptRuns,
+        ComparingPromptSettingVersions, ComparingPromptSettings, PromptManager,
+    };
+    use crate::infra::repository::entities::{
+        comparing_prompt_manager, comparing_prompt_run_histories, comparing_prompt_runs,
+        comparing_prompt_setting_versions, comparing_prompt_settings, prompt_manager,
+    };
+
+    use super::*;
+
+    const ALL_KINDS: [SearchHitKind; 4] = [
+        SearchHitKind::Manager,
+        SearchHitKind::Version,
+        SearchHitKind::Run,
+        SearchHitKind::Response,
+    ];
+
+    /// マネージャー、設定、バージョン、実行、履歴を1件ずつ作成し、マネージャー、バージョン、履歴のIDを返す
+    async fn seed(db: &DatabaseConnection, title: &str) -> (i32, i32, i32) {
+        let manager_id = PromptManager::insert(prompt_manager::ActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set(title.to_string()),
+            action_type: ActiveValue::Set(None),
+            api_type: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(None),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        ComparingPromptManager::insert(comparing_prompt_manager::ActiveModel {
+            manager_id: ActiveValue::Set(manager_id),
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        let setting_id = ComparingPromptSettings::insert(comparing_prompt_settings::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            current_version: ActiveValue::Set(1),
+            deleted_at: ActiveValue::Set(None),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        let version_id = ComparingPromptSettingVersions::insert(
+            comparing_prompt_setting_versions::ActiveModel {
+                id: Default::default(),
+                version: ActiveValue::Set(1),
+                setting_id: ActiveValue::Set(setting_id),
+                system_prompt: ActiveValue::Set("あなたは翻訳のアシスタントです".to_string()),
+                tools: ActiveValue::Set(None),
+                tool_choice: ActiveValue::Set(None),
+                tool_scripts: ActiveValue::Set(None),
+            },
+        )
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        let run_id = ComparingPromptRuns::insert(comparing_prompt_runs::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            provider_type: ActiveValue::Set("OpenAI".to_string()),
+            model: ActiveValue::Set("gpt-4".to_string()),
+            user_prompt: ActiveValue::Set("summarize the weather report".to_string()),
+            temperature: ActiveValue::Set(0.0),
+            max_token: ActiveValue::Set(None),
+            repetitions: ActiveValue::Set(1),
+            top_p: ActiveValue::Set(None),
+            sweep_id: ActiveValue::Set(None),
+            frequency_penalty: ActiveValue::Set(None),
+            presence_penalty: ActiveValue::Set(None),
+            stop: ActiveValue::Set(None),
+            seed: ActiveValue::Set(None),
+            logit_bias: ActiveValue::Set(None),
+            user: ActiveValue::Set(None),
+            credential_profile_id: ActiveValue::Set(None),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        let history_id =
+            ComparingPromptRunHistories::insert(comparing_prompt_run_histories::ActiveModel {
+                id: Default::default(),
+                run_id: ActiveValue::Set(run_id),
+                version_id: ActiveValue::Set(version_id),
+                response: ActiveValue::Set("tomorrow will be sunny with weather".to_string()),
+                sample_index: ActiveValue::Set(0),
+                transcript: ActiveValue::Set(None),
+                cache_hit: ActiveValue::Set(false),
+                cancelled: ActiveValue::Set(false),
+                job_item_id: ActiveValue::Set(None),
+            })
+            .exec(db)
+            .await
+            .unwrap()
+            .last_insert_id;
+        (manager_id, version_id, history_id)
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let db = setup_db("test_search").await;
+        let repository = SearchRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let (manager_id, version_id, _) = seed(db.as_ref(), "weather bot").await;
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository
+            .search("\"weather\"", &ALL_KINDS, 10)
+            .await
+            .unwrap();
+
+        // assert
+        // タイトル、ユーザープロンプト、回答が一致する
+        let mut kinds: Vec<SearchHitKind> = result.iter().map(|hit| hit.kind).collect();
+        kinds.sort_by_key(|kind| kind.to_string());
+        assert_eq!(
+            kinds,
+            vec![
+                SearchHitKind::Manager,
+                SearchHitKind::Response,
+                SearchHitKind::Run
+            ]
+        );
+        assert!(result.iter().all(|hit| hit.manager_id == manager_id));
+        let response = result
+            .iter()
+            .find(|hit| hit.kind == SearchHitKind::Response)
+            .unwrap();
+        assert_eq!(response.version_id, Some(version_id));
+        assert_eq!(response.version, Some(1));
+        assert!(response.run_id.is_some());
+        assert!(response.snippet.contains(&SnippetPart {
+            text: "weather".to_string(),
+            highlighted: true,
+        }));
+
+        // 日本語のシステムプロンプトも部分一致で検索できる
+        let result = repository
+            .search("\"翻訳\"", &[SearchHitKind::Version], 10)
+            .await;
+        assert!(result.unwrap().is_empty()); // trigramは3文字以上で一致する
+        let result = repository
+            .search("\"翻訳の\"", &[SearchHitKind::Version], 10)
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].version_id, Some(version_id));
+
+        // 件数の上限
+        let result = repository
+            .search("\"weather\"", &ALL_KINDS, 2)
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_index_sync() {
+        let db = setup_db("test_search_index_sync").await;
+        let repository = SearchRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let (manager_id, _, history_id) = seed(db.as_ref(), "weather bot").await;
+        let (other_manager_id, _, _) = seed(db.as_ref(), "deleted manager").await;
+        let kinds = [SearchHitKind::Manager, SearchHitKind::Response];
+
+        // タイトルを更新すると索引も更新される
+        let manager = PromptManager::find_by_id(manager_id)
+            .one(db.as_ref())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut manager: prompt_manager::ActiveModel = manager.into();
+        manager.title = ActiveValue::Set("forecast bot".to_string());
+        manager.update(db.as_ref()).await.unwrap();
+        let result = repository
+            .search("\"weather bot\"", &kinds, 10)
+            .await
+            .unwrap();
+        assert!(result.is_empty());
+        let result = repository.search("\"forecast\"", &kinds, 10).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].manager_title, "forecast bot");
+
+        // 履歴を削除すると索引からも削除される
+        ComparingPromptRunHistories::delete_by_id(history_id)
+            .exec(db.as_ref())
+            .await
+            .unwrap();
+        let result = repository.search("\"sunny\"", &kinds, 10).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].manager_id, other_manager_id);
+
+        // 論理削除されたマネージャーは含まない
+        let manager = PromptManager::find_by_id(other_manager_id)
+            .one(db.as_ref())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut manager: prompt_manager::ActiveModel = manager.into();
+        manager.deleted_at = ActiveValue::Set(Some(chrono::Utc::now().to_string()));
+        manager.update(db.as_ref()).await.unwrap();
+        let result = repository.search("\"sunny\"", &kinds, 10).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_parse_snippet() {
+        let result = parse_snippet("…the \u{2}weather\u{3} is \u{2}sunny\u{3}");
+        assert_eq!(
+            result,
+            vec![
+                SnippetPart {
+                    text: "…the ".to_string(),
+                    highlighted: false,
+                },
+                SnippetPart {
+                    text: "weather".to_string(),
+                    highlighted: true,
+                },
+                SnippetPart {
+                    text: " is ".to_string(),
+                    highlighted: false,
+                },
+                SnippetPart {
+                    text: "sunny".to_string(),
+                    highlighted: true,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::sea_query::{Expr, Query, SimpleExpr};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::tag::{TagModel, TagRepository};
+use crate::infra::repository::entities::prelude::{PromptManager, PromptManagerTag, Tag};
+use crate::infra::repository::entities::{prompt_manager, prompt_manager_tag, tag};
+
+/// 利用数を含むタグの行
+#[derive(Debug, FromQueryResult)]
+struct TagRow {
+    id: i32,
+    value: String,
+    usage_count: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct TagRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl TagRepository for TagRepositoryImpl {
+    async fn find_tags(&self) -> Result<Vec<TagModel>, ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        let rows = Tag::find()
+            .select_only()
+            .columns([tag::Column::Id, tag::Column::Value])
+            .column_as(usage_count_expr(), "usage_count")
+            .order_by_asc(tag::Column::Value)
+            .into_model::<TagRow>()
+            .all(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| TagModel {
+                id: row.id,
+                value: row.value,
+                usage_count: row.usage_count,
+            })
+            .collect())
+    }
+
+    async fn rename_tag(&self, id: i32, value: &str) -> Result<(), ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        let tag = find_tag(&txn, id).await?;
+        let duplicated = Tag::find()
+            .filter(tag::Column::Value.eq(value))
+            .filter(tag::Column::Id.ne(id))
+            .one(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        if duplicated.is_some() {
+            return Err(ApplicationError::ValidationError(format!(
+                "tag already exists: {}",
+                value
+            )));
+        }
+        let mut tag: tag::ActiveModel = tag.into();
+        tag.value = ActiveValue::Set(value.to_string());
+        tag.update(&txn).await.map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(())
+    }
+
+    async fn merge_tags(&self, source_ids: &[i32], target_id: i32) -> Result<(), ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        find_tag(&txn, target_id).await?;
+        for source_id in source_ids {
+            find_tag(&txn, *source_id).await?;
+        }
+
+        // 統合先のタグが既に付いているマネージャーには、同じタグを重ねて付けない
+        let mut tagged_manager_ids: HashSet<i32> = PromptManagerTag::find()
+            .filter(prompt_manager_tag::Column::TagId.eq(target_id))
+            .all(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?
+            .into_iter()
+            .map(|manager_tag| manager_tag.prompt_manager_id)
+            .collect();
+        let source_manager_tags = PromptManagerTag::find()
+            .filter(prompt_manager_tag::Column::TagId.is_in(source_ids.to_vec()))
+            .order_by_asc(prompt_manager_tag::Column::Id)
+            .all(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        for manager_tag in source_manager_tags {
+            if tagged_manager_ids.insert(manager_tag.prompt_manager_id) {
+                let mut manager_tag: prompt_manager_tag::ActiveModel = manager_tag.into();
+                manager_tag.tag_id = ActiveValue::Set(target_id);
+                manager_tag
+                    .update(&txn)
+                    .await
+                    .map_err(ApplicationError::DBError)?;
+            } else {
+                PromptManagerTag::delete_by_id(manager_tag.id)
+                    .exec(&txn)
+                    .await
+                    .map_err(ApplicationError::DBError)?;
+            }
+        }
+
+        Tag::delete_many()
+            .filter(tag::Column::Id.is_in(source_ids.to_vec()))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(())
+    }
+
+    async fn delete_tag(&self, id: i32) -> Result<(), ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        find_tag(&txn, id).await?;
+        PromptManagerTag::delete_many()
+            .filter(prompt_manager_tag::Column::TagId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        Tag::delete_by_id(id)
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(())
+    }
+
+    async fn delete_unused_tags(&self) -> Result<u64, ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        let res = Tag::delete_many()
+            .filter(
+                tag::Column::Id.not_in_subquery(
+                    Query::select()
+                        .column(prompt_manager_tag::Column::TagId)
+                        .from(PromptManagerTag)
+                        .to_owned(),
+                ),
+            )
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(res.rows_affected)
+    }
+}
+
+impl TagRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        TagRepositoryImpl { db }
+    }
+}
+
+async fn find_tag(txn: &DatabaseTransaction, id: i32) -> Result<tag::Model, ApplicationError> {
+    Tag::find_by_id(id)
+        .one(txn)
+        .await
+        .map_err(ApplicationError::DBError)?
+        .ok_or(ApplicationError::EmptyResult)
+}
+
+/// タグが付いている削除されていないマネージャーの数
+fn usage_count_expr() -> SimpleExpr {
+    let usage_count = Query::select()
+        .expr(Expr::col((PromptManagerTag, prompt_manager_tag::Column::Id)).count())
+        .from(PromptManagerTag)
+        .inner_join(
+            PromptManager,
+            Expr::col((PromptManager, prompt_manager::Column::Id)).equals((
+                PromptManagerTag,
+                prompt_manager_tag::Column::PromptManagerId,
+            )),
+        )
+        .and_where(
+            Expr::col((PromptManagerTag, prompt_manager_tag::Column::TagId))
+                .equals((Tag, tag::Column::Id)),
+        )
+        .and_where(Expr::col((PromptManager, prompt_manager::Column::DeletedAt)).is_null())
+        .to_owned();
+    SimpleExpr::SubQuery(None, Box::new(usage_count.into_sub_query_statement()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::thelper::db::setup_db;
+
+    use super::*;
+
+    async fn seed_prompt_manager(db: &DatabaseConnection, deleted: bool) -> i32 {
+        let prompt_manager = prompt_manager::ActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set("test_title".to_string()),
+            action_type: ActiveValue::Set(None),
+            api_type: ActiveValue::Set(None),
+            deleted_at: ActiveValue::Set(deleted.then(|| chrono::Utc::now().to_string())),
+        };
+        PromptManager::insert(prompt_manager)
+            .exec(db)
+            .await
+            .expect("Failed to insert prompt manager")
+            .last_insert_id
+    }
+
+    async fn seed_tag(db: &DatabaseConnection, value: &str, manager_ids: &[i32]) -> i32 {
+        let tag = tag::ActiveModel {
+            id: Default::default(),
+            value: ActiveValue::Set(value.to_string()),
+        };
+        let tag_id = Tag::insert(tag)
+            .exec(db)
+            .await
+            .expect("Failed to insert tag")
+            .last_insert_id;
+        for manager_id in manager_ids {
+            let prompt_manager_tag = prompt_manager_tag::ActiveModel {
+                id: Default::default(),
+                prompt_manager_id: ActiveValue::Set(*manager_id),
+                tag_id: ActiveValue::Set(tag_id),
+            };
+            PromptManagerTag::insert(prompt_manager_tag)
+                .exec(db)
+                .await
+                .expect("Failed to insert into prompt_manager_tag");
+        }
+        tag_id
+    }
+
+    /// マネージャーごとのタグの値
+    async fn manager_tags(db: &DatabaseConnection, manager_id: i32) -> Vec<String> {
+        let mut tags: Vec<String> = PromptManagerTag::find()
+            .filter(prompt_manager_tag::Column::PromptManagerId.eq(manager_id))
+            .find_also_related(Tag)
+            .all(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|(_, tag)| tag.map(|t| t.value))
+            .collect();
+        tags.sort();
+        tags
+    }
+
+    #[tokio::test]
+    async fn test_find_tags() {
+        let db = setup_db("test_find_tags").await;
+        let repository = TagRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(db.as_ref(), false).await;
+        let deleted_manager_id = seed_prompt_manager(db.as_ref(), true).await;
+        seed_tag(db.as_ref(), "rust", &[manager_id, deleted_manager_id]).await;
+        seed_tag(db.as_ref(), "python", &[]).await;
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository.find_tags().await.unwrap();
+
+        // assert
+        // 論理削除されたマネージャーは数えない
+        let result: Vec<(String, i32)> = result
+            .into_iter()
+            .map(|tag| (tag.value, tag.usage_count))
+            .collect();
+        assert_eq!(
+            result,
+            vec![("python".to_string(), 0), ("rust".to_string(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag() {
+        let db = setup_db("test_rename_tag").await;
+        let repository = TagRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(db.as_ref(), false).await;
+        let tag_id = seed_tag(db.as_ref(), "pyhton", &[manager_id]).await;
+        seed_tag(db.as_ref(), "rust", &[]).await;
+
+        // テスト対象のメソッドを呼び出し
+        repository.rename_tag(tag_id, "python").await.unwrap();
+
+        // assert
+        assert_eq!(manager_tags(db.as_ref(), manager_id).await, vec!["python"]);
+        // 既にある値と、存在しないタグ
+        let result = repository.rename_tag(tag_id, "rust").await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let result = repository.rename_tag(tag_id + 100, "go").await;
+        assert!(matches!(result, Err(ApplicationError::EmptyResult)));
+    }
+
+    #[tokio::test]
+    async fn test_merge_tags() {
+        let db = setup_db("test_merge_tags").await;
+        let repository = TagRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id1 = seed_prompt_manager(db.as_ref(), false).await;
+        let manager_id2 = seed_prompt_manager(db.as_ref(), false).await;
+        let manager_id3 = seed_prompt_manager(db.as_ref(), false).await;
+        let target_id = seed_tag(db.as_ref(), "python", &[manager_id1]).await;
+        let source_id1 = seed_tag(db.as_ref(), "pyhton", &[manager_id1, manager_id2]).await;
+        let source_id2 = seed_tag(db.as_ref(), "py", &[manager_id2, manager_id3]).await;
+
+        // テスト対象のメソッドを呼び出し
+        repository
+            .merge_tags(&[source_id1, source_id2], target_id)
+            .await
+            .unwrap();
+
+        // assert
+        // 統合先のタグは1つのマネージャーに1つだけ付く
+        for manager_id in [manager_id1, manager_id2, manager_id3] {
+            assert_eq!(manager_tags(db.as_ref(), manager_id).await, vec!["python"]);
+        }
+        let tags = repository.find_tags().await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].usage_count, 3);
+
+        // 存在しないタグが含まれる場合は何も変更しない
+        let source_id = seed_tag(db.as_ref(), "pytohn", &[manager_id1]).await;
+        let result = repository
+            .merge_tags(&[source_id, source_id + 100], target_id)
+            .await;
+        assert!(matches!(result, Err(ApplicationError::EmptyResult)));
+        assert_eq!(
+            manager_tags(db.as_ref(), manager_id1).await,
+            vec!["python", "pytohn"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_tag() {
+        let db = setup_db("test_delete_tag").await;
+        let repository = TagRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed_prompt_manager(db.as_ref(), false).await;
+        let deleted_manager_id = seed_prompt_manager(db.as_ref(), true).await;
+        let tag_id = seed_tag(db.as_ref(), "rust", &[manager_id]).await;
+        seed_tag(db.as_ref(), "python", &[manager_id]).await;
+        seed_tag(db.as_ref(), "archived", &[deleted_manager_id]).await;
+        seed_tag(db.as_ref(), "unused1", &[]).await;
+        seed_tag(db.as_ref(), "unused2", &[]).await;
+
+        // テスト対象のメソッドを呼び出し
+        repository.delete_tag(tag_id).await.unwrap();
+        let deleted = repository.delete_unused_tags().await.unwrap();
+
+        // assert
+        assert_eq!(manager_tags(db.as_ref(), manager_id).await, vec!["python"]);
+        // 論理削除されたマネージャーのタグは残す
+        assert_eq!(deleted, 2);
+        let tags: Vec<String> = repository
+            .find_tags()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.value)
+            .collect();
+        assert_eq!(tags, vec!["archived", "python"]);
+    }
+}
@@ -0,0 +1,471 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::sea_query::{Expr, Query, SelectStatement};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::trash::{DeletedPromptManagerModel, TrashRepository};
+use crate::infra::repository::entities::prelude::{
+    ComparingPromptBaselines, ComparingPromptChatSettingDetails, ComparingPromptManager,
+    ComparingPromptRunHistories, ComparingPromptRuns, ComparingPromptSettingVersions,
+    ComparingPromptSettings, ComparingPromptSweeps, ComparingPromptVisionSettingDetails,
+    PromptManager, PromptManagerCredentialProfiles, PromptManagerTag,
+};
+use crate::infra::repository::entities::{
+    comparing_prompt_baselines, comparing_prompt_chat_setting_details, comparing_prompt_manager,
+    comparing_prompt_run_histories, comparing_prompt_runs, comparing_prompt_setting_versions,
+    comparing_prompt_settings, comparing_prompt_sweeps, comparing_prompt_vision_setting_details,
+    prompt_manager, prompt_manager_credential_profiles, prompt_manager_tag,
+};
+
+#[derive(Clone, Debug)]
+pub struct TrashRepositoryImpl {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl TrashRepository for TrashRepositoryImpl {
+    async fn find_deleted_prompt_managers(
+        &self,
+    ) -> Result<Vec<DeletedPromptManagerModel>, ApplicationError> {
+        let managers = PromptManager::find()
+            .filter(prompt_manager::Column::DeletedAt.is_not_null())
+            .order_by_desc(prompt_manager::Column::DeletedAt)
+            .order_by_desc(prompt_manager::Column::Id)
+            .all(self.db.as_ref())
+            .await
+            .map_err(ApplicationError::DBError)?;
+        Ok(managers
+            .into_iter()
+            .map(|manager| DeletedPromptManagerModel {
+                id: manager.id,
+                title: manager.title,
+                deleted_at: manager.deleted_at.unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    async fn restore_prompt_manager(&self, id: i32) -> Result<(), ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        let manager = find_deleted_prompt_manager(&txn, id).await?;
+        let mut manager: prompt_manager::ActiveModel = manager.into();
+        manager.deleted_at = ActiveValue::Set(None);
+        manager
+            .update(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(())
+    }
+
+    async fn purge_prompt_manager(&self, id: i32) -> Result<(), ApplicationError> {
+        let txn = self.db.begin().await.map_err(ApplicationError::DBError)?;
+        find_deleted_prompt_manager(&txn, id).await?;
+
+        // 外部キーで参照される側が後になるように削除する
+        // 設定、バージョン、実行のIDはサブクエリで取得するので、それぞれ参照する行より後に削除する
+        ComparingPromptBaselines::delete_many()
+            .filter(comparing_prompt_baselines::Column::SettingId.in_subquery(setting_ids(id)))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptRunHistories::delete_many()
+            .filter(
+                Condition::any()
+                    .add(comparing_prompt_run_histories::Column::RunId.in_subquery(run_ids(id)))
+                    .add(
+                        comparing_prompt_run_histories::Column::VersionId
+                            .in_subquery(version_ids(id)),
+                    ),
+            )
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptRuns::delete_many()
+            .filter(comparing_prompt_runs::Column::ManagerId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptSweeps::delete_many()
+            .filter(comparing_prompt_sweeps::Column::ManagerId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptChatSettingDetails::delete_many()
+            .filter(
+                comparing_prompt_chat_setting_details::Column::VersionId
+                    .in_subquery(version_ids(id)),
+            )
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptVisionSettingDetails::delete_many()
+            .filter(
+                comparing_prompt_vision_setting_details::Column::VersionId
+                    .in_subquery(version_ids(id)),
+            )
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptSettingVersions::delete_many()
+            .filter(
+                comparing_prompt_setting_versions::Column::SettingId.in_subquery(setting_ids(id)),
+            )
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptSettings::delete_many()
+            .filter(comparing_prompt_settings::Column::ManagerId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        ComparingPromptManager::delete_many()
+            .filter(comparing_prompt_manager::Column::ManagerId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        PromptManagerTag::delete_many()
+            .filter(prompt_manager_tag::Column::PromptManagerId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        PromptManagerCredentialProfiles::delete_many()
+            .filter(prompt_manager_credential_profiles::Column::ManagerId.eq(id))
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+        PromptManager::delete_by_id(id)
+            .exec(&txn)
+            .await
+            .map_err(ApplicationError::DBError)?;
+
+        txn.commit().await.map_err(ApplicationError::DBError)?;
+        Ok(())
+    }
+}
+
+impl TrashRepositoryImpl {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        TrashRepositoryImpl { db }
+    }
+}
+
+async fn find_deleted_prompt_manager(
+    txn: &DatabaseTransaction,
+    id: i32,
+) -> Result<prompt_manager::Model, ApplicationError> {
+    let manager = PromptManager::find_by_id(id)
+        .one(txn)
+        .await
+        .map_err(ApplicationError::DBError)?
+        .ok_or(ApplicationError::EmptyResult)?;
+    if manager.deleted_at.is_none() {
+        return Err(ApplicationError::ValidationError(format!(
+            "prompt manager is not deleted: {}",
+            id
+        )));
+    }
+    Ok(manager)
+}
+
+/// マネージャーの設定のID
+fn setting_ids(manager_id: i32) -> SelectStatement {
+    Query::select()
+        .column(comparing_prompt_settings::Column::Id)
+        .from(ComparingPromptSettings)
+        .and_where(Expr::col(comparing_prompt_settings::Column::ManagerId).eq(manager_id))
+        .to_owned()
+}
+
+/// マネージャーの設定のバージョンのID
+fn version_ids(manager_id: i32) -> SelectStatement {
+    Query::select()
+        .column(comparing_prompt_setting_versions::Column::Id)
+        .from(ComparingPromptSettingVersions)
+        .and_where(
+            Expr::col(comparing_prompt_setting_versions::Column::SettingId)
+                .in_subquery(setting_ids(manager_id)),
+        )
+        .to_owned()
+}
+
+/// マネージャーの実行のID
+fn run_ids(manager_id: i32) -> SelectStatement {
+    Query::select()
+        .column(comparing_prompt_runs::Column::Id)
+        .from(ComparingPromptRuns)
+        .and_where(Expr::col(comparing_prompt_runs::Column::ManagerId).eq(manager_id))
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::PaginatorTrait;
+
+    use crate::common::thelper::db::setup_db;
+    use crate::infra::repository::entities::prelude::{CredentialProfiles, Tag};
+    use crate::infra::repository::entities::{credential_profiles, tag};
+
+    use super::*;
+
+    /// マネージャーと、マネージャーに紐づくデータを1件ずつ作成する
+    async fn seed(db: &DatabaseConnection, deleted: bool) -> i32 {
+        let now = chrono::Utc::now().to_string();
+        let manager_id = PromptManager::insert(prompt_manager::ActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set("test_title".to_string()),
+            action_type: ActiveValue::Set(Some("ComparingPrompt".to_string())),
+            api_type: ActiveValue::Set(Some("Chat".to_string())),
+            deleted_at: ActiveValue::Set(deleted.then(|| now.clone())),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        ComparingPromptManager::insert(comparing_prompt_manager::ActiveModel {
+            manager_id: ActiveValue::Set(manager_id),
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        let setting_id = ComparingPromptSettings::insert(comparing_prompt_settings::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            current_version: ActiveValue::Set(1),
+            deleted_at: ActiveValue::Set(None),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        let version_id = ComparingPromptSettingVersions::insert(
+            comparing_prompt_setting_versions::ActiveModel {
+                id: Default::default(),
+                version: ActiveValue::Set(1),
+                setting_id: ActiveValue::Set(setting_id),
+                system_prompt: ActiveValue::Set("test_system_prompt".to_string()),
+                tools: ActiveValue::Set(None),
+                tool_choice: ActiveValue::Set(None),
+                tool_scripts: ActiveValue::Set(None),
+            },
+        )
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        ComparingPromptChatSettingDetails::insert(
+            comparing_prompt_chat_setting_details::ActiveModel {
+                id: Default::default(),
+                version_id: ActiveValue::Set(version_id),
+                response_format: ActiveValue::Set("text".to_string()),
+            },
+        )
+        .exec(db)
+        .await
+        .unwrap();
+        let sweep_id = ComparingPromptSweeps::insert(comparing_prompt_sweeps::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            definition: ActiveValue::Set("{}".to_string()),
+            created_at: ActiveValue::Set(now.clone()),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        let run_id = ComparingPromptRuns::insert(comparing_prompt_runs::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            provider_type: ActiveValue::Set("OpenAI".to_string()),
+            model: ActiveValue::Set("gpt-4".to_string()),
+            user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+            temperature: ActiveValue::Set(0.0),
+            max_token: ActiveValue::Set(None),
+            repetitions: ActiveValue::Set(1),
+            top_p: ActiveValue::Set(None),
+            sweep_id: ActiveValue::Set(Some(sweep_id)),
+            frequency_penalty: ActiveValue::Set(None),
+            presence_penalty: ActiveValue::Set(None),
+            stop: ActiveValue::Set(None),
+            seed: ActiveValue::Set(None),
+            logit_bias: ActiveValue::Set(None),
+            user: ActiveValue::Set(None),
+            credential_profile_id: ActiveValue::Set(None),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        let history_id =
+            ComparingPromptRunHistories::insert(comparing_prompt_run_histories::ActiveModel {
+                id: Default::default(),
+                run_id: ActiveValue::Set(run_id),
+                version_id: ActiveValue::Set(version_id),
+                response: ActiveValue::Set("test_response".to_string()),
+                sample_index: ActiveValue::Set(0),
+                transcript: ActiveValue::Set(None),
+                cache_hit: ActiveValue::Set(false),
+                cancelled: ActiveValue::Set(false),
+                job_item_id: ActiveValue::Set(None),
+            })
+            .exec(db)
+            .await
+            .unwrap()
+            .last_insert_id;
+        ComparingPromptBaselines::insert(comparing_prompt_baselines::ActiveModel {
+            id: Default::default(),
+            setting_id: ActiveValue::Set(setting_id),
+            history_id: ActiveValue::Set(history_id),
+            user_prompt: ActiveValue::Set("test_user_prompt".to_string()),
+            response: ActiveValue::Set("test_response".to_string()),
+            drift_measure: ActiveValue::Set("cosine".to_string()),
+            threshold: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now.clone()),
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        let tag_id = Tag::insert(tag::ActiveModel {
+            id: Default::default(),
+            value: ActiveValue::Set(format!("tag{}", manager_id)),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        PromptManagerTag::insert(prompt_manager_tag::ActiveModel {
+            id: Default::default(),
+            prompt_manager_id: ActiveValue::Set(manager_id),
+            tag_id: ActiveValue::Set(tag_id),
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        let credential_profile_id = CredentialProfiles::insert(credential_profiles::ActiveModel {
+            id: Default::default(),
+            provider_type: ActiveValue::Set("OpenAI".to_string()),
+            label: ActiveValue::Set("test_label".to_string()),
+            api_key: ActiveValue::Set("test_api_key".to_string()),
+            organization_id: ActiveValue::Set(None),
+            project_id: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now.clone()),
+            updated_at: ActiveValue::Set(now),
+            deleted_at: ActiveValue::Set(None),
+        })
+        .exec(db)
+        .await
+        .unwrap()
+        .last_insert_id;
+        PromptManagerCredentialProfiles::insert(prompt_manager_credential_profiles::ActiveModel {
+            id: Default::default(),
+            manager_id: ActiveValue::Set(manager_id),
+            provider_type: ActiveValue::Set("OpenAI".to_string()),
+            credential_profile_id: ActiveValue::Set(credential_profile_id),
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        manager_id
+    }
+
+    /// マネージャーに紐づくテーブルごとの行数
+    async fn count_rows(db: &DatabaseConnection) -> Vec<u64> {
+        vec![
+            PromptManager::find().count(db).await.unwrap(),
+            ComparingPromptManager::find().count(db).await.unwrap(),
+            ComparingPromptSettings::find().count(db).await.unwrap(),
+            ComparingPromptSettingVersions::find()
+                .count(db)
+                .await
+                .unwrap(),
+            ComparingPromptChatSettingDetails::find()
+                .count(db)
+                .await
+                .unwrap(),
+            ComparingPromptSweeps::find().count(db).await.unwrap(),
+            ComparingPromptRuns::find().count(db).await.unwrap(),
+            ComparingPromptRunHistories::find().count(db).await.unwrap(),
+            ComparingPromptBaselines::find().count(db).await.unwrap(),
+            PromptManagerTag::find().count(db).await.unwrap(),
+            PromptManagerCredentialProfiles::find()
+                .count(db)
+                .await
+                .unwrap(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_find_deleted_prompt_managers() {
+        let db = setup_db("test_find_deleted_prompt_managers").await;
+        let repository = TrashRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        seed(db.as_ref(), false).await;
+        let deleted_id = seed(db.as_ref(), true).await;
+
+        // テスト対象のメソッドを呼び出し
+        let result = repository.find_deleted_prompt_managers().await.unwrap();
+
+        // assert
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, deleted_id);
+        assert!(!result[0].deleted_at.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_prompt_manager() {
+        let db = setup_db("test_restore_prompt_manager").await;
+        let repository = TrashRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed(db.as_ref(), false).await;
+        let deleted_id = seed(db.as_ref(), true).await;
+
+        // テスト対象のメソッドを呼び出し
+        repository.restore_prompt_manager(deleted_id).await.unwrap();
+
+        // assert
+        let manager = PromptManager::find_by_id(deleted_id)
+            .one(db.as_ref())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(manager.deleted_at.is_none());
+        // 削除されていないマネージャーと、存在しないマネージャー
+        let result = repository.restore_prompt_manager(manager_id).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let result = repository.restore_prompt_manager(deleted_id + 100).await;
+        assert!(matches!(result, Err(ApplicationError::EmptyResult)));
+    }
+
+    #[tokio::test]
+    async fn test_purge_prompt_manager() {
+        let db = setup_db("test_purge_prompt_manager").await;
+        let repository = TrashRepositoryImpl::new(Arc::clone(&db));
+
+        // 事前データ
+        let manager_id = seed(db.as_ref(), false).await;
+        let deleted_id = seed(db.as_ref(), true).await;
+        assert_eq!(count_rows(db.as_ref()).await, vec![2; 11]);
+
+        // テスト対象のメソッドを呼び出し
+        repository.purge_prompt_manager(deleted_id).await.unwrap();
+
+        // assert
+        // 削除したマネージャーのデータのみ削除される
+        assert_eq!(count_rows(db.as_ref()).await, vec![1; 11]);
+        let manager = PromptManager::find_by_id(manager_id)
+            .one(db.as_ref())
+            .await
+            .unwrap();
+        assert!(manager.is_some());
+        // 削除されていないマネージャーは物理削除できない
+        let result = repository.purge_prompt_manager(manager_id).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        assert_eq!(count_rows(db.as_ref()).await, vec![1; 11]);
+    }
+}
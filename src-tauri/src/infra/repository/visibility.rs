@@ -0,0 +1,46 @@
+// 論理削除の伝播
+// マネージャーを削除すると、設定・実行・履歴・ベースラインも全て削除されたものとみなす
+// 設定を削除すると、設定のバージョン・履歴・ベースラインも削除されたものとみなす（実行は他の設定と共有するので残す）
+// 子のdeleted_atは更新しないので、マネージャーを元に戻すと個別に削除していない設定も元に戻る
+use sea_orm::sea_query::{Expr, Query, SelectStatement};
+
+use crate::infra::repository::entities::prelude::{
+    ComparingPromptSettingVersions, ComparingPromptSettings, PromptManager,
+};
+use crate::infra::repository::entities::{
+    comparing_prompt_setting_versions, comparing_prompt_settings, prompt_manager,
+};
+
+/// 削除されていないマネージャーのID
+pub fn active_manager_ids() -> SelectStatement {
+    Query::select()
+        .column(prompt_manager::Column::Id)
+        .from(PromptManager)
+        .and_where(Expr::col(prompt_manager::Column::DeletedAt).is_null())
+        .to_owned()
+}
+
+/// 削除されていない設定のID（マネージャーが削除されている場合を除く）
+pub fn active_setting_ids() -> SelectStatement {
+    Query::select()
+        .column(comparing_prompt_settings::Column::Id)
+        .from(ComparingPromptSettings)
+        .and_where(Expr::col(comparing_prompt_settings::Column::DeletedAt).is_null())
+        .and_where(
+            Expr::col(comparing_prompt_settings::Column::ManagerId)
+                .in_subquery(active_manager_ids()),
+        )
+        .to_owned()
+}
+
+/// 削除されていない設定のバージョンのID
+pub fn active_version_ids() -> SelectStatement {
+    Query::select()
+        .column(comparing_prompt_setting_versions::Column::Id)
+        .from(ComparingPromptSettingVersions)
+        .and_where(
+            Expr::col(comparing_prompt_setting_versions::Column::SettingId)
+                .in_subquery(active_setting_ids()),
+        )
+        .to_owned()
+}
@@ -0,0 +1,117 @@
+use tiktoken_rs::{cl100k_base_singleton, o200k_base_singleton};
+
+use crate::domain::comparing_prompt::ProviderType;
+use crate::domain::model_catalog::builtin_capabilities;
+use crate::domain::tokenizer::{ModelTokenLimit, TokenEncoding, Tokenizer};
+
+/// モデル名の接頭辞ごとのエンコーディング（最も長く一致した接頭辞を使う）
+const MODEL_ENCODINGS: &[(&str, TokenEncoding)] = &[
+    ("gpt-4o", TokenEncoding::O200kBase),
+    ("o1", TokenEncoding::O200kBase),
+    ("o3", TokenEncoding::O200kBase),
+    ("gpt-4", TokenEncoding::Cl100kBase),
+    ("gpt-3.5-turbo", TokenEncoding::Cl100kBase),
+];
+
+/// tiktokenを使ってローカルでトークン数を数える
+#[derive(Clone, Debug, Default)]
+pub struct TiktokenTokenizer {}
+
+impl Tokenizer for TiktokenTokenizer {
+    fn model_limit(&self, model: &str) -> Option<ModelTokenLimit> {
+        let encoding = MODEL_ENCODINGS
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, encoding)| *encoding)?;
+        // コンテキストウィンドウはモデルの対応機能のマニフェストから取得する
+        let context_window = builtin_capabilities(&ProviderType::OpenAI, model)?.context_window?;
+        Some(ModelTokenLimit {
+            encoding,
+            context_window: context_window as usize,
+        })
+    }
+
+    fn count_tokens(&self, encoding: TokenEncoding, text: &str) -> usize {
+        let bpe = match encoding {
+            TokenEncoding::Cl100kBase => cl100k_base_singleton(),
+            TokenEncoding::O200kBase => o200k_base_singleton(),
+        };
+        let bpe = bpe.lock();
+        bpe.encode_ordinary(text).len()
+    }
+}
+
+impl TiktokenTokenizer {
+    pub fn new() -> Self {
+        TiktokenTokenizer {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_limit() {
+        let tokenizer = TiktokenTokenizer::new();
+        // 日付付きのモデル名は最も長く一致した接頭辞で判定する
+        let cases = vec![
+            ("gpt-4o-2024-05-13", TokenEncoding::O200kBase, 128_000),
+            ("gpt-4-1106-preview", TokenEncoding::Cl100kBase, 128_000),
+            ("gpt-4-0613", TokenEncoding::Cl100kBase, 8_192),
+            ("gpt-3.5-turbo-0613", TokenEncoding::Cl100kBase, 4_096),
+            ("gpt-3.5-turbo-0125", TokenEncoding::Cl100kBase, 16_385),
+        ];
+        for (model, encoding, context_window) in cases {
+            assert_eq!(
+                tokenizer.model_limit(model),
+                Some(ModelTokenLimit {
+                    encoding,
+                    context_window,
+                }),
+                "{}",
+                model
+            );
+        }
+        assert_eq!(tokenizer.model_limit("gemini-pro"), None);
+    }
+
+    #[test]
+    fn test_count_tokens() {
+        let tokenizer = TiktokenTokenizer::new();
+        assert_eq!(
+            tokenizer.count_tokens(TokenEncoding::Cl100kBase, "hello world"),
+            2
+        );
+        assert_eq!(
+            tokenizer.count_tokens(TokenEncoding::O200kBase, "hello world"),
+            2
+        );
+        assert_eq!(tokenizer.count_tokens(TokenEncoding::Cl100kBase, ""), 0);
+
+        // 制御トークンを含めてチャットのトークン数を数える
+        // system(3 + 1 + 2) + user(3 + 1 + 2) + reply(3)
+        assert_eq!(
+            tokenizer.count_chat_tokens(TokenEncoding::Cl100kBase, "hello world", "hello world"),
+            15
+        );
+    }
+
+    #[test]
+    fn test_context_window_usage() {
+        let tokenizer = TiktokenTokenizer::new();
+        let usage = tokenizer
+            .context_window_usage("gpt-4", "hello world", "hello world", Some(8_177))
+            .unwrap();
+        assert_eq!(usage.prompt_tokens, 15);
+        assert!(usage.fits());
+        let usage = tokenizer
+            .context_window_usage("gpt-4", "hello world", "hello world", Some(8_178))
+            .unwrap();
+        assert!(!usage.fits());
+        assert!(tokenizer
+            .context_window_usage("gemini-pro", "", "", None)
+            .is_none());
+    }
+}
@@ -14,6 +14,9 @@ mod infra;
 mod migration;
 mod usecase;
 
+/// バックグラウンドでジョブを処理するワーカー数
+const JOB_WORKERS: usize = 2;
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -37,8 +40,54 @@ async fn main() {
     }
 
     // infra層の初期化
-    let openai_client = Arc::new(infra::core::openai::OpenAIClient::new());
-    let chat = Arc::new(infra::chat::OpenAIChat::new(Arc::clone(&openai_client)));
+    // プロキシ・証明書・タイムアウトなどの通信の設定は全てのプロバイダーで共有する
+    // 環境変数の通信の設定は既定値で、設定画面で上書きできる
+    let http_client = Arc::new(
+        infra::core::http::HttpClientConfig::from_env()
+            .and_then(infra::core::http::SharedHttpClient::new)
+            .expect("Invalid http client settings"),
+    );
+    // 環境変数のレート制限は既定値で、設定画面でプロバイダーごとに上書きできる
+    let rate_limit_config =
+        infra::core::rate_limit::RateLimitConfig::from_env().expect("Invalid rate limit settings");
+    // APP_CASSETTE_MODEが指定されている場合は、通信を記録または記録から再生する
+    // 1つのカセットにプロバイダーを区別して記録する
+    let cassette_mode =
+        infra::core::cassette::CassetteMode::from_env().expect("Cannot load cassette");
+    let openai_client = Arc::new(infra::core::cassette::CassetteAIClient::new(
+        &cassette_mode,
+        domain::comparing_prompt::ProviderType::OpenAI,
+        infra::core::openai::OpenAIClient::new(rate_limit_config.clone(), Arc::clone(&http_client)),
+    ));
+    let azure_openai_client = Arc::new(infra::core::cassette::CassetteAIClient::new(
+        &cassette_mode,
+        domain::comparing_prompt::ProviderType::AzureOpenAI,
+        infra::core::azure_openai::AzureOpenAIClient::new(
+            rate_limit_config,
+            Arc::clone(&http_client),
+        ),
+    ));
+    // APP_MOCK_CHATが指定されている場合は、通信せずにモックプロバイダーの回答を返す（開発モードのみ）
+    let chat = Arc::new(
+        infra::mock_chat::MockableChat::from_env(infra::chat::OpenAIChat::new(
+            Arc::clone(&openai_client),
+            Arc::clone(&azure_openai_client),
+        ))
+        .expect("Invalid mock chat settings"),
+    );
+    let embedding = Arc::new(infra::embedding::OpenAIEmbedding::new(Arc::clone(
+        &openai_client,
+    )));
+    let tokenizer = Arc::new(infra::tokenizer::TiktokenTokenizer::new());
+    let event_emitter = Arc::new(infra::event::TauriEventEmitter::new());
+    let model_list = Arc::new(infra::model_list::ModelListImpl::new(
+        Arc::clone(&openai_client),
+        Arc::clone(&azure_openai_client),
+    ));
+    // APP_SECRET_PASSPHRASEが指定されている場合は鍵ファイルの代わりにパスフレーズから鍵を導出する
+    let secret_cipher = Arc::new(
+        infra::core::secret::LocalSecretCipher::from_env().expect("Cannot load secret key"),
+    );
     let prompt_manager_repository = Arc::new(
         infra::repository::prompt_manager::PromptManagerRepositoryImpl::new(Arc::clone(&db)),
     );
@@ -52,19 +101,120 @@ async fn main() {
             &db,
         )),
     );
+    let comparing_prompt_baseline_repository = Arc::new(
+        infra::repository::comparing_prompt_baseline::ComparingPromptBaselineRepositoryImpl::new(
+            Arc::clone(&db),
+        ),
+    );
+    let model_catalog_repository = Arc::new(
+        infra::repository::model_catalog::ModelCatalogRepositoryImpl::new(Arc::clone(&db)),
+    );
+    let response_cache_repository = Arc::new(
+        infra::repository::response_cache::ResponseCacheRepositoryImpl::new(Arc::clone(&db)),
+    );
+    let job_repository = Arc::new(infra::repository::job::JobRepositoryImpl::new(Arc::clone(
+        &db,
+    )));
+    let app_setting_repository =
+        Arc::new(infra::repository::app_setting::AppSettingRepositoryImpl::new(Arc::clone(&db)));
+    let tag_repository = Arc::new(infra::repository::tag::TagRepositoryImpl::new(Arc::clone(
+        &db,
+    )));
+    let trash_repository = Arc::new(infra::repository::trash::TrashRepositoryImpl::new(
+        Arc::clone(&db),
+    ));
+    let search_repository = Arc::new(infra::repository::search::SearchRepositoryImpl::new(
+        Arc::clone(&db),
+    ));
     // usecase層の初期化
+    // 設定画面で保存されたAPIキーを環境変数より優先する
+    let app_setting_usecase = usecase::app_setting::AppSettingUsecase::new(
+        Arc::clone(&app_setting_repository),
+        Arc::new(infra::core::openai::ApiKeyUpdaters(vec![
+            Arc::clone(&openai_client) as Arc<dyn domain::app_setting::ApiKeyUpdater>,
+            Arc::clone(&azure_openai_client) as Arc<dyn domain::app_setting::ApiKeyUpdater>,
+            Arc::clone(&http_client) as Arc<dyn domain::app_setting::ApiKeyUpdater>,
+        ])),
+        Arc::clone(&secret_cipher),
+    );
+    app_setting_usecase
+        .reencrypt_secrets()
+        .await
+        .expect("Cannot encrypt secrets");
+    app_setting_usecase
+        .apply_api_keys()
+        .await
+        .expect("Cannot apply api keys");
+    let regression_usecase = usecase::regression::RegressionUsecase::new(
+        Arc::clone(&embedding),
+        Arc::clone(&comparing_prompt_run_repository),
+        Arc::clone(&comparing_prompt_baseline_repository),
+    );
     let chat_usecase = usecase::comparing_prompt::ChatUsecase::new(
         Arc::clone(&chat),
         Arc::clone(&comparing_prompt_setting_repository),
         Arc::clone(&comparing_prompt_run_repository),
+        Arc::clone(&tokenizer),
+        Arc::clone(&model_catalog_repository),
+        Arc::clone(&response_cache_repository),
+        // 実行に使う認証情報のプロファイルは設定のユースケースで復号する
+        Arc::new(app_setting_usecase.clone()),
+        // 実行の回答はベースラインと比較するユースケースでドリフトを判定する
+        Arc::new(regression_usecase.clone()),
     );
     let prompt_manager_usecase =
         usecase::prompt_manager::PromptManagerUsecase::new(Arc::clone(&prompt_manager_repository));
+    let token_count_usecase = usecase::token_count::TokenCountUsecase::new(Arc::clone(&tokenizer));
+    let model_catalog_usecase = usecase::model_catalog::ModelCatalogUsecase::new(
+        Arc::clone(&model_list),
+        Arc::clone(&model_catalog_repository),
+    );
+    let tag_usecase = usecase::tag::TagUsecase::new(Arc::clone(&tag_repository));
+    let trash_usecase = usecase::trash::TrashUsecase::new(
+        Arc::clone(&trash_repository),
+        Arc::new(app_setting_usecase.clone()),
+    );
+    // 保持する日数を過ぎた削除済みのマネージャーを物理削除する
+    trash_usecase
+        .purge_expired_prompt_managers()
+        .await
+        .expect("Cannot purge expired prompt managers");
+    let search_usecase = usecase::search::SearchUsecase::new(Arc::clone(&search_repository));
+    // ジョブの実行はcontrollerと同じChatUsecaseを使い、実行のキャンセルを共有する
+    let job_executor = Arc::new(usecase::job::ComparingPromptJobExecutor::new(
+        Arc::clone(&comparing_prompt_setting_repository),
+        Arc::clone(&comparing_prompt_run_repository),
+        Arc::new(chat_usecase.clone()),
+    ));
+    let job_usecase = usecase::job::JobUsecase::new(
+        Arc::clone(&job_repository),
+        Arc::clone(&job_executor),
+        Arc::clone(&event_emitter),
+    );
+    // 前回の起動時に中断されたジョブを再開する
+    job_usecase
+        .resume_interrupted_jobs()
+        .await
+        .expect("Cannot resume jobs");
+    job_usecase.start_workers(JOB_WORKERS);
     // controller層の初期化
     controller::comparing_prompt::Controller::init(chat_usecase);
     controller::prompt_manager::Controller::init(prompt_manager_usecase);
+    controller::regression::Controller::init(regression_usecase);
+    controller::token_count::Controller::init(token_count_usecase);
+    controller::model_catalog::Controller::init(model_catalog_usecase);
+    controller::job::Controller::init(job_usecase);
+    controller::app_setting::Controller::init(app_setting_usecase);
+    controller::search::Controller::init(search_usecase);
+    controller::tag::Controller::init(tag_usecase);
+    controller::trash::Controller::init(trash_usecase);
 
     tauri::Builder::default()
+        .setup(move |app| {
+            // 起動後にジョブの進捗をフロントエンドに通知できるようにする
+            event_emitter.set_app_handle(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             controller::prompt_manager::create_prompt_manager,
             controller::prompt_manager::update_prompt_manager,
@@ -73,8 +223,47 @@ async fn main() {
             controller::prompt_manager::logical_delete_prompt_manager,
             controller::comparing_prompt::add_comparing_prompt_setting,
             controller::comparing_prompt::get_all_comparing_prompt_settings,
+            controller::comparing_prompt::delete_comparing_prompt_setting,
+            controller::comparing_prompt::restore_comparing_prompt_setting,
             controller::comparing_prompt::save_comparing_prompt_run,
             controller::comparing_prompt::run_comparing_prompt,
+            controller::comparing_prompt::get_comparing_prompt_consistency_metrics,
+            controller::comparing_prompt::save_comparing_prompt_sweep,
+            controller::comparing_prompt::get_comparing_prompt_sweep_matrix,
+            controller::comparing_prompt::save_comparing_prompt_version_tools,
+            controller::comparing_prompt::run_comparing_prompt_agent,
+            controller::comparing_prompt::preflight_comparing_prompt_run,
+            controller::comparing_prompt::clear_comparing_prompt_response_cache,
+            controller::comparing_prompt::cancel_comparing_prompt_run,
+            controller::regression::approve_baseline,
+            controller::regression::get_baselines,
+            controller::regression::delete_baseline,
+            controller::regression::get_regression_report,
+            controller::token_count::count_tokens,
+            controller::model_catalog::sync_models,
+            controller::model_catalog::get_models,
+            controller::job::enqueue_comparing_prompt_run_job,
+            controller::job::enqueue_comparing_prompt_sweep_job,
+            controller::job::get_job,
+            controller::job::get_jobs,
+            controller::app_setting::get_app_settings,
+            controller::app_setting::save_app_settings,
+            controller::app_setting::rotate_secret_key,
+            controller::app_setting::get_credential_profiles,
+            controller::app_setting::save_credential_profile,
+            controller::app_setting::delete_credential_profile,
+            controller::app_setting::get_manager_credential_profiles,
+            controller::app_setting::save_manager_credential_profile,
+            controller::app_setting::get_credential_profile_usage,
+            controller::search::search,
+            controller::tag::get_tags,
+            controller::tag::rename_tag,
+            controller::tag::merge_tags,
+            controller::tag::delete_tag,
+            controller::tag::delete_unused_tags,
+            controller::trash::get_deleted_prompt_managers,
+            controller::trash::restore_prompt_manager,
+            controller::trash::purge_prompt_manager,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
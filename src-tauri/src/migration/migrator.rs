@@ -1,6 +1,20 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m000001_init;
+mod m000002_add_comparing_prompt_baselines;
+mod m000003_add_comparing_prompt_samples;
+mod m000004_add_comparing_prompt_sweeps;
+mod m000005_add_comparing_prompt_sampling_parameters;
+mod m000006_add_comparing_prompt_version_tools;
+mod m000007_add_comparing_prompt_tool_scripts;
+mod m000008_add_model_catalog;
+mod m000009_add_response_cache;
+mod m000010_add_comparing_prompt_run_history_cancelled;
+mod m000011_add_jobs;
+mod m000012_add_app_settings;
+mod m000013_add_credential_profiles;
+mod m000014_add_search_index;
+mod m000015_add_comparing_prompt_run_history_job_item;
 
 pub struct Migrator;
 
@@ -10,6 +24,20 @@ impl MigratorTrait for Migrator {
         vec![
             // migrationファイルを追加したらここにも追加する
             Box::new(m000001_init::Migration),
+            Box::new(m000002_add_comparing_prompt_baselines::Migration),
+            Box::new(m000003_add_comparing_prompt_samples::Migration),
+            Box::new(m000004_add_comparing_prompt_sweeps::Migration),
+            Box::new(m000005_add_comparing_prompt_sampling_parameters::Migration),
+            Box::new(m000006_add_comparing_prompt_version_tools::Migration),
+            Box::new(m000007_add_comparing_prompt_tool_scripts::Migration),
+            Box::new(m000008_add_model_catalog::Migration),
+            Box::new(m000009_add_response_cache::Migration),
+            Box::new(m000010_add_comparing_prompt_run_history_cancelled::Migration),
+            Box::new(m000011_add_jobs::Migration),
+            Box::new(m000012_add_app_settings::Migration),
+            Box::new(m000013_add_credential_profiles::Migration),
+            Box::new(m000014_add_search_index::Migration),
+            Box::new(m000015_add_comparing_prompt_run_history_job_item::Migration),
         ]
     }
 }
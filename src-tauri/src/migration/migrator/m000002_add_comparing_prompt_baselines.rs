@@ -0,0 +1,128 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // プロンプト比較ベースラインテーブル
+        manager
+            .create_table(
+                Table::create()
+                    .table(ComparingPromptBaselines::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::SettingId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparing_prompt_baselines-comparing_prompt_settings-id")
+                            .from(
+                                ComparingPromptBaselines::Table,
+                                ComparingPromptBaselines::SettingId,
+                            )
+                            .to(ComparingPromptSettings::Table, ComparingPromptSettings::Id),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::HistoryId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparing_prompt_baselines-comparing_prompt_run_histories-id")
+                            .from(
+                                ComparingPromptBaselines::Table,
+                                ComparingPromptBaselines::HistoryId,
+                            )
+                            .to(
+                                ComparingPromptRunHistories::Table,
+                                ComparingPromptRunHistories::Id,
+                            ),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::UserPrompt)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::Response)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::DriftMeasure)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ComparingPromptBaselines::Threshold).double())
+                    .col(
+                        ColumnDef::new(ComparingPromptBaselines::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 設定と入力の組み合わせにつきベースラインは1件
+        manager
+            .create_index(
+                Index::create()
+                    .name("unique-idx-comparing_prompt_baselines-setting_id-user_prompt")
+                    .table(ComparingPromptBaselines::Table)
+                    .if_not_exists()
+                    .col(ComparingPromptBaselines::SettingId)
+                    .col(ComparingPromptBaselines::UserPrompt)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ComparingPromptBaselines::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptSettings {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRunHistories {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptBaselines {
+    Table,
+    Id,
+    SettingId,
+    HistoryId,
+    UserPrompt,
+    Response,
+    DriftMeasure,
+    Threshold,
+    CreatedAt,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 実行ごとの繰り返し回数（SQLiteは1回のALTERで1カラムしか追加できない）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .add_column(
+                        ColumnDef::new(ComparingPromptRuns::Repetitions)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 同じ実行・バージョン内でのサンプル番号（0始まり）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .add_column(
+                        ColumnDef::new(ComparingPromptRunHistories::SampleIndex)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .drop_column(ComparingPromptRunHistories::SampleIndex)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .drop_column(ComparingPromptRuns::Repetitions)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRuns {
+    Table,
+    Repetitions,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRunHistories {
+    Table,
+    SampleIndex,
+}
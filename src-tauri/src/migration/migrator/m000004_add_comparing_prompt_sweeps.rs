@@ -0,0 +1,124 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // パラメータスイープ定義テーブル
+        manager
+            .create_table(
+                Table::create()
+                    .table(ComparingPromptSweeps::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ComparingPromptSweeps::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptSweeps::ManagerId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparing_prompt_sweeps-comparing_prompt_manager-manager_id")
+                            .from(
+                                ComparingPromptSweeps::Table,
+                                ComparingPromptSweeps::ManagerId,
+                            )
+                            .to(
+                                ComparingPromptManager::Table,
+                                ComparingPromptManager::ManagerId,
+                            ),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptSweeps::Definition)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ComparingPromptSweeps::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .add_column(ColumnDef::new(ComparingPromptRuns::TopP).double())
+                    .to_owned(),
+            )
+            .await?;
+
+        // スイープから展開された実行の場合のみ設定する
+        // SQLiteはALTER TABLEで外部キーを追加できないため、カラムのみ追加する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .add_column(ColumnDef::new(ComparingPromptRuns::SweepId).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .drop_column(ComparingPromptRuns::SweepId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .drop_column(ComparingPromptRuns::TopP)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ComparingPromptSweeps::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptManager {
+    Table,
+    ManagerId,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRuns {
+    Table,
+    TopP,
+    SweepId,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptSweeps {
+    Table,
+    Id,
+    ManagerId,
+    Definition,
+    CreatedAt,
+}
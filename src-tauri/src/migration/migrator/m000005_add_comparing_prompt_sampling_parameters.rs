@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLiteは1回のALTERで1カラムしか追加できないので、カラムごとに追加する
+        // stopとlogit_biasはJSON文字列で保持する
+        let columns = vec![
+            ColumnDef::new(ComparingPromptRuns::FrequencyPenalty)
+                .double()
+                .to_owned(),
+            ColumnDef::new(ComparingPromptRuns::PresencePenalty)
+                .double()
+                .to_owned(),
+            ColumnDef::new(ComparingPromptRuns::Stop).text().to_owned(),
+            ColumnDef::new(ComparingPromptRuns::Seed)
+                .big_integer()
+                .to_owned(),
+            ColumnDef::new(ComparingPromptRuns::LogitBias)
+                .text()
+                .to_owned(),
+            ColumnDef::new(ComparingPromptRuns::User)
+                .string()
+                .to_owned(),
+        ];
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ComparingPromptRuns::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = vec![
+            ComparingPromptRuns::User,
+            ComparingPromptRuns::LogitBias,
+            ComparingPromptRuns::Seed,
+            ComparingPromptRuns::Stop,
+            ComparingPromptRuns::PresencePenalty,
+            ComparingPromptRuns::FrequencyPenalty,
+        ];
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ComparingPromptRuns::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRuns {
+    Table,
+    FrequencyPenalty,
+    PresencePenalty,
+    Stop,
+    Seed,
+    LogitBias,
+    User,
+}
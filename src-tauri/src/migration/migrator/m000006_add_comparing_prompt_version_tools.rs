@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ツール定義の配列（JSON文字列）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptSettingVersions::Table)
+                    .add_column(ColumnDef::new(ComparingPromptSettingVersions::Tools).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        // ツールの選択方針（JSON文字列）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptSettingVersions::Table)
+                    .add_column(ColumnDef::new(ComparingPromptSettingVersions::ToolChoice).text())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptSettingVersions::Table)
+                    .drop_column(ComparingPromptSettingVersions::ToolChoice)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptSettingVersions::Table)
+                    .drop_column(ComparingPromptSettingVersions::Tools)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptSettingVersions {
+    Table,
+    Tools,
+    ToolChoice,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ツールの模擬実行結果の定義（JSON文字列）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptSettingVersions::Table)
+                    .add_column(ColumnDef::new(ComparingPromptSettingVersions::ToolScripts).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        // ツール実行を含む会話の全体（JSON文字列）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .add_column(ColumnDef::new(ComparingPromptRunHistories::Transcript).text())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .drop_column(ComparingPromptRunHistories::Transcript)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptSettingVersions::Table)
+                    .drop_column(ComparingPromptSettingVersions::ToolScripts)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptSettingVersions {
+    Table,
+    ToolScripts,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRunHistories {
+    Table,
+    Transcript,
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // プロバイダーのモデル一覧と対応機能のテーブル
+        manager
+            .create_table(
+                Table::create()
+                    .table(ModelCatalog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ModelCatalog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ModelCatalog::ProviderType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ModelCatalog::Model).string().not_null())
+                    // 組み込みのマニフェストに無いモデルは対応機能が不明なのでfalseにする
+                    .col(
+                        ColumnDef::new(ModelCatalog::Known)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ModelCatalog::Vision)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ModelCatalog::JsonMode)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ModelCatalog::Tools)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(ModelCatalog::ContextWindow).integer())
+                    .col(ColumnDef::new(ModelCatalog::InputPrice).double())
+                    .col(ColumnDef::new(ModelCatalog::OutputPrice).double())
+                    .col(
+                        ColumnDef::new(ModelCatalog::SyncedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("unique-idx-model_catalog-provider_type-model")
+                    .table(ModelCatalog::Table)
+                    .if_not_exists()
+                    .col(ModelCatalog::ProviderType)
+                    .col(ModelCatalog::Model)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ModelCatalog::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModelCatalog {
+    Table,
+    Id,
+    ProviderType,
+    Model,
+    Known,
+    Vision,
+    JsonMode,
+    Tools,
+    ContextWindow,
+    InputPrice,
+    OutputPrice,
+    SyncedAt,
+}
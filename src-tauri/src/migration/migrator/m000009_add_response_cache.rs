@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 同一リクエストの回答のキャッシュテーブル
+        manager
+            .create_table(
+                Table::create()
+                    .table(ResponseCache::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ResponseCache::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ResponseCache::CacheKey)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ResponseCache::ProviderType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ResponseCache::Model).string().not_null())
+                    .col(ColumnDef::new(ResponseCache::Responses).text().not_null())
+                    .col(
+                        ColumnDef::new(ResponseCache::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    // 期限の比較を簡単にするためUNIX時間（秒）で保持する
+                    .col(
+                        ColumnDef::new(ResponseCache::ExpiresAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .add_column(
+                        ColumnDef::new(ComparingPromptRunHistories::CacheHit)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .drop_column(ComparingPromptRunHistories::CacheHit)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ResponseCache::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRunHistories {
+    Table,
+    CacheHit,
+}
+
+#[derive(DeriveIden)]
+enum ResponseCache {
+    Table,
+    Id,
+    CacheKey,
+    ProviderType,
+    Model,
+    Responses,
+    CreatedAt,
+    ExpiresAt,
+}
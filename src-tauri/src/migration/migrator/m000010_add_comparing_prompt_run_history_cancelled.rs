@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // キャンセルされた実行の履歴（回答は空）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .add_column(
+                        ColumnDef::new(ComparingPromptRunHistories::Cancelled)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .drop_column(ComparingPromptRunHistories::Cancelled)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRunHistories {
+    Table,
+    Cancelled,
+}
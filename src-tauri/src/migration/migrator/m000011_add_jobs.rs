@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // バックグラウンドで実行するジョブのテーブル
+        manager
+            .create_table(
+                Table::create()
+                    .table(Jobs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Jobs::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Jobs::JobType).string().not_null())
+                    .col(ColumnDef::new(Jobs::Payload).text().not_null())
+                    .col(ColumnDef::new(Jobs::Status).string().not_null())
+                    .col(ColumnDef::new(Jobs::TotalItems).integer().not_null())
+                    .col(
+                        ColumnDef::new(Jobs::CompletedItems)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::FailedItems)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Jobs::Error).text())
+                    .col(ColumnDef::new(Jobs::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(Jobs::UpdatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // ジョブを構成する処理の単位（再開時は完了していないものだけ実行する）
+        manager
+            .create_table(
+                Table::create()
+                    .table(JobItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(JobItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(JobItems::JobId).integer().not_null())
+                    .col(ColumnDef::new(JobItems::ItemIndex).integer().not_null())
+                    .col(ColumnDef::new(JobItems::Payload).text().not_null())
+                    .col(ColumnDef::new(JobItems::Status).string().not_null())
+                    .col(ColumnDef::new(JobItems::Result).text())
+                    .col(ColumnDef::new(JobItems::Error).text())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-job_items-jobs-job_id")
+                            .from(JobItems::Table, JobItems::JobId)
+                            .to(Jobs::Table, Jobs::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JobItems::Table).if_exists().to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Jobs::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Id,
+    JobType,
+    Payload,
+    Status,
+    TotalItems,
+    CompletedItems,
+    FailedItems,
+    Error,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum JobItems {
+    Table,
+    Id,
+    JobId,
+    ItemIndex,
+    Payload,
+    Status,
+    Result,
+    Error,
+}
@@ -0,0 +1,186 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // プロバイダーごとの認証情報のプロファイル（APIキーは暗号化して保存する）
+        // 実行の集計でラベルを表示するため、削除は論理削除とする
+        manager
+            .create_table(
+                Table::create()
+                    .table(CredentialProfiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CredentialProfiles::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CredentialProfiles::ProviderType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CredentialProfiles::Label)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CredentialProfiles::ApiKey).text().not_null())
+                    .col(ColumnDef::new(CredentialProfiles::OrganizationId).string())
+                    .col(ColumnDef::new(CredentialProfiles::ProjectId).string())
+                    .col(
+                        ColumnDef::new(CredentialProfiles::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CredentialProfiles::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CredentialProfiles::DeletedAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // プロンプトマネージャーごとに使うプロファイル（プロバイダーごとに1件）
+        manager
+            .create_table(
+                Table::create()
+                    .table(PromptManagerCredentialProfiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PromptManagerCredentialProfiles::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PromptManagerCredentialProfiles::ManagerId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PromptManagerCredentialProfiles::ProviderType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PromptManagerCredentialProfiles::CredentialProfileId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-prompt_manager_credential_profiles-manager_id")
+                            .from(
+                                PromptManagerCredentialProfiles::Table,
+                                PromptManagerCredentialProfiles::ManagerId,
+                            )
+                            .to(PromptManager::Table, PromptManager::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-prompt_manager_credential_profiles-credential_profile_id")
+                            .from(
+                                PromptManagerCredentialProfiles::Table,
+                                PromptManagerCredentialProfiles::CredentialProfileId,
+                            )
+                            .to(CredentialProfiles::Table, CredentialProfiles::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-prompt_manager_credential_profiles-manager_id-provider_type")
+                    .table(PromptManagerCredentialProfiles::Table)
+                    .col(PromptManagerCredentialProfiles::ManagerId)
+                    .col(PromptManagerCredentialProfiles::ProviderType)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 実行に使ったプロファイル（未指定の場合は設定画面または環境変数のAPIキーを使った）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .add_column(ColumnDef::new(ComparingPromptRuns::CredentialProfileId).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRuns::Table)
+                    .drop_column(ComparingPromptRuns::CredentialProfileId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PromptManagerCredentialProfiles::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(CredentialProfiles::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CredentialProfiles {
+    Table,
+    Id,
+    ProviderType,
+    Label,
+    ApiKey,
+    OrganizationId,
+    ProjectId,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
+
+#[derive(DeriveIden)]
+enum PromptManagerCredentialProfiles {
+    Table,
+    Id,
+    ManagerId,
+    ProviderType,
+    CredentialProfileId,
+}
+
+#[derive(DeriveIden)]
+enum PromptManager {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRuns {
+    Table,
+    CredentialProfileId,
+}
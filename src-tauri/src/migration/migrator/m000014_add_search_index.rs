@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// 全文検索の索引（FTS5の外部コンテンツテーブル）と、索引元のテーブルとカラム
+/// 日本語は単語に区切れないので、3文字ずつに区切るtrigramで索引する
+const SEARCH_INDEXES: [(&str, &str, &str); 4] = [
+    ("search_prompt_manager", "prompt_manager", "title"),
+    (
+        "search_setting_versions",
+        "comparing_prompt_setting_versions",
+        "system_prompt",
+    ),
+    ("search_runs", "comparing_prompt_runs", "user_prompt"),
+    (
+        "search_run_histories",
+        "comparing_prompt_run_histories",
+        "response",
+    ),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (index, table, column) in SEARCH_INDEXES {
+            db.execute_unprepared(&format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS {index} USING fts5(\
+                 {column}, content='{table}', content_rowid='id', tokenize='trigram')"
+            ))
+            .await?;
+            // 書き込み時に索引を同期するトリガー
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER IF NOT EXISTS {index}_ai AFTER INSERT ON {table} BEGIN \
+                 INSERT INTO {index}(rowid, {column}) VALUES (new.id, new.{column}); \
+                 END"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER IF NOT EXISTS {index}_ad AFTER DELETE ON {table} BEGIN \
+                 INSERT INTO {index}({index}, rowid, {column}) \
+                 VALUES ('delete', old.id, old.{column}); \
+                 END"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER IF NOT EXISTS {index}_au AFTER UPDATE OF {column} ON {table} BEGIN \
+                 INSERT INTO {index}({index}, rowid, {column}) \
+                 VALUES ('delete', old.id, old.{column}); \
+                 INSERT INTO {index}(rowid, {column}) VALUES (new.id, new.{column}); \
+                 END"
+            ))
+            .await?;
+            // 既存のデータを索引する
+            db.execute_unprepared(&format!("INSERT INTO {index}({index}) VALUES ('rebuild')"))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (index, _, _) in SEARCH_INDEXES {
+            for trigger in ["ai", "ad", "au"] {
+                db.execute_unprepared(&format!("DROP TRIGGER IF EXISTS {index}_{trigger}"))
+                    .await?;
+            }
+            db.execute_unprepared(&format!("DROP TABLE IF EXISTS {index}"))
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ジョブの項目から実行した履歴の項目（中断された項目を再実行する際に途中までの履歴を削除する）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .add_column(ColumnDef::new(ComparingPromptRunHistories::JobItemId).integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-comparing_prompt_run_histories-job_item_id")
+                    .table(ComparingPromptRunHistories::Table)
+                    .if_not_exists()
+                    .col(ComparingPromptRunHistories::JobItemId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-comparing_prompt_run_histories-job_item_id")
+                    .table(ComparingPromptRunHistories::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComparingPromptRunHistories::Table)
+                    .drop_column(ComparingPromptRunHistories::JobItemId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComparingPromptRunHistories {
+    Table,
+    JobItemId,
+}
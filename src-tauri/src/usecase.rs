@@ -1,2 +1,11 @@
+pub mod app_setting;
 pub mod comparing_prompt;
+pub mod job;
+pub mod model_catalog;
+pub mod progress;
 pub mod prompt_manager;
+pub mod regression;
+pub mod search;
+pub mod tag;
+pub mod token_count;
+pub mod trash;
@@ -0,0 +1,2089 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::common::errors::ApplicationError;
+use crate::common::secret::SecretString;
+use crate::domain::app_setting::{
+    ApiKeyUpdater, AppSettingModel, AppSettingName, AppSettingRepository, AzureOpenAIDeployment,
+    AzureOpenAISettings, CredentialProfileModel, HttpClientSettings, ProviderCredential,
+    RateLimitSettings, SecretCipher, SecretRef, Theme,
+};
+use crate::domain::comparing_prompt::ProviderType;
+
+/// APIキーを表示する際に残す末尾の文字数
+const API_KEY_VISIBLE_CHARS: usize = 4;
+
+/// 削除したマネージャーを自動で物理削除するまでの日数の上限
+const MAX_TRASH_RETENTION_DAYS: u32 = 3650;
+
+/// 設定を保存できるプロバイダー
+const PROVIDER_TYPES: [ProviderType; 3] = [
+    ProviderType::OpenAI,
+    ProviderType::AzureOpenAI,
+    ProviderType::Gemini,
+];
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAppSettingsRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAppSettingsResponse {
+    pub api_keys: Vec<ApiKeyItem>,
+    pub default_provider: Option<ProviderType>,
+    pub default_model: Option<String>,
+    pub theme: Theme,
+    pub azure_openai: Option<AzureOpenAISettings>,
+    pub trash_retention_days: Option<u32>,
+    pub rate_limits: Vec<RateLimitItem>, // 保存されているプロバイダーのみ
+    pub http_client: Option<HttpClientSettings>,
+}
+
+/// APIキーそのものは返さず、設定済みかどうかと末尾のみを返す
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyItem {
+    pub provider_type: ProviderType,
+    pub configured: bool,
+    pub masked_key: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveAppSettingsRequest {
+    /// 変更するAPIキーのみ指定する（含まれないプロバイダーのAPIキーは変更しない）
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyInput>,
+    pub default_provider: Option<ProviderType>,
+    pub default_model: Option<String>,
+    pub theme: Theme,
+    /// 未指定の場合はAzure OpenAIの設定を削除する
+    #[serde(default)]
+    pub azure_openai: Option<AzureOpenAISettings>,
+    /// 削除したマネージャーを自動で物理削除するまでの日数（未指定の場合は自動で削除しない）
+    #[serde(default)]
+    pub trash_retention_days: Option<u32>,
+    /// 含まれないプロバイダーのレート制限は削除する（環境変数の既定値に戻す）
+    #[serde(default)]
+    pub rate_limits: Vec<RateLimitItem>,
+    /// 未指定の場合は通信の設定を削除する（環境変数の設定に戻す）
+    #[serde(default)]
+    pub http_client: Option<HttpClientSettings>,
+}
+
+/// プロバイダーごとのレート制限（未指定の項目は環境変数の既定値を使う）
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitItem {
+    pub provider_type: ProviderType,
+    #[serde(flatten)]
+    pub settings: RateLimitSettings,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyInput {
+    pub provider_type: ProviderType,
+    pub api_key: Option<SecretString>, // 未指定または空の場合は削除する
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateSecretKeyRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateSecretKeyResponse {
+    pub reencrypted: i32, // 新しい鍵で暗号化し直した設定の数
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCredentialProfilesRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCredentialProfilesResponse {
+    pub profiles: Vec<CredentialProfileItem>,
+}
+
+/// APIキーそのものは返さず、末尾のみを返す
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialProfileItem {
+    pub id: i32,
+    pub provider_type: ProviderType,
+    pub label: String,
+    pub masked_key: Option<String>, // 復号できない場合はNone
+    pub organization_id: Option<String>,
+    pub project_id: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveCredentialProfileRequest {
+    pub id: Option<i32>, // 未指定の場合は新規作成する
+    pub provider_type: ProviderType,
+    pub label: String,
+    pub api_key: Option<SecretString>, // 更新時に未指定または空の場合は変更しない
+    pub organization_id: Option<String>,
+    pub project_id: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveCredentialProfileResponse {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteCredentialProfileRequest {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetManagerCredentialProfilesRequest {
+    pub manager_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GetManagerCredentialProfilesResponse {
+    pub profiles: Vec<ManagerCredentialProfileItem>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagerCredentialProfileItem {
+    pub provider_type: ProviderType,
+    pub credential_profile_id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveManagerCredentialProfileRequest {
+    pub manager_id: i32,
+    pub provider_type: ProviderType,
+    pub credential_profile_id: Option<i32>, // 未指定の場合は選択を解除する
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCredentialProfileUsageRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCredentialProfileUsageResponse {
+    pub items: Vec<CredentialProfileUsageItem>,
+}
+
+/// プロファイルとモデルごとの実行数（利用料の按分に使う）
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialProfileUsageItem {
+    pub credential_profile_id: Option<i32>, // Noneは設定画面または環境変数のAPIキー
+    pub label: Option<String>,
+    pub provider_type: ProviderType,
+    pub model: String,
+    pub runs: i32,
+    pub responses: i32, // APIから返った回答の数（キャッシュとキャンセルを除く）
+    pub cached_responses: i32,
+}
+
+#[async_trait]
+pub trait AppSettings: Send + Sync {
+    async fn get_app_settings(
+        &self,
+        request: GetAppSettingsRequest,
+    ) -> Result<GetAppSettingsResponse, ApplicationError>;
+
+    async fn save_app_settings(
+        &self,
+        request: SaveAppSettingsRequest,
+    ) -> Result<(), ApplicationError>;
+
+    /// 秘密の設定を暗号化する鍵を新しい鍵に入れ替える
+    async fn rotate_secret_key(
+        &self,
+        request: RotateSecretKeyRequest,
+    ) -> Result<RotateSecretKeyResponse, ApplicationError>;
+
+    async fn get_credential_profiles(
+        &self,
+        request: GetCredentialProfilesRequest,
+    ) -> Result<GetCredentialProfilesResponse, ApplicationError>;
+
+    async fn save_credential_profile(
+        &self,
+        request: SaveCredentialProfileRequest,
+    ) -> Result<SaveCredentialProfileResponse, ApplicationError>;
+
+    async fn delete_credential_profile(
+        &self,
+        request: DeleteCredentialProfileRequest,
+    ) -> Result<(), ApplicationError>;
+
+    async fn get_manager_credential_profiles(
+        &self,
+        request: GetManagerCredentialProfilesRequest,
+    ) -> Result<GetManagerCredentialProfilesResponse, ApplicationError>;
+
+    async fn save_manager_credential_profile(
+        &self,
+        request: SaveManagerCredentialProfileRequest,
+    ) -> Result<(), ApplicationError>;
+
+    async fn get_credential_profile_usage(
+        &self,
+        request: GetCredentialProfileUsageRequest,
+    ) -> Result<GetCredentialProfileUsageResponse, ApplicationError>;
+}
+
+/// 実行に使う認証情報のプロファイルやプロバイダーの設定を解決するtrait（比較の実行から使う）
+#[async_trait]
+pub trait CredentialResolver: Send + Sync {
+    /// 実行に記録するプロファイルを決める（未指定の場合はプロンプトマネージャーで選択されたプロファイル）
+    async fn resolve_credential_profile_id(
+        &self,
+        manager_id: i32,
+        provider_type: &ProviderType,
+        credential_profile_id: Option<i32>,
+    ) -> Result<Option<i32>, ApplicationError>;
+
+    /// プロファイルのAPIキーを復号して返す
+    async fn find_credential(
+        &self,
+        credential_profile_id: i32,
+    ) -> Result<ProviderCredential, ApplicationError>;
+
+    /// トークン数の計算に使うモデル名を返す
+    /// Azure OpenAIではモデル名にデプロイメント名が入るので、設定されたデプロイメントのモデルに変換する
+    async fn resolve_token_model(
+        &self,
+        provider_type: &ProviderType,
+        model: &str,
+    ) -> Result<String, ApplicationError>;
+}
+
+/// 削除したマネージャーを保持する日数を取得するtrait（ゴミ箱の自動削除から使う）
+#[async_trait]
+pub trait TrashRetention: Send + Sync {
+    /// 自動で物理削除するまでの日数（Noneの場合は自動で削除しない）
+    async fn trash_retention_days(&self) -> Result<Option<u32>, ApplicationError>;
+}
+
+#[derive(Debug)]
+pub struct AppSettingUsecase<S, A, X>
+where
+    S: AppSettingRepository,
+    A: ApiKeyUpdater,
+    X: SecretCipher,
+{
+    app_setting_repository: Arc<S>,
+    api_key_updater: Arc<A>,
+    secret_cipher: Arc<X>,
+    // 保存と鍵のローテーションが同時に行われないようにする
+    secret_lock: Arc<Mutex<()>>,
+}
+
+/// controllerとChatUsecaseで同じロックを共有するため、フィールドのArcを複製する
+impl<S, A, X> Clone for AppSettingUsecase<S, A, X>
+where
+    S: AppSettingRepository,
+    A: ApiKeyUpdater,
+    X: SecretCipher,
+{
+    fn clone(&self) -> Self {
+        AppSettingUsecase {
+            app_setting_repository: Arc::clone(&self.app_setting_repository),
+            api_key_updater: Arc::clone(&self.api_key_updater),
+            secret_cipher: Arc::clone(&self.secret_cipher),
+            secret_lock: Arc::clone(&self.secret_lock),
+        }
+    }
+}
+
+#[async_trait]
+impl<S, A, X> AppSettings for AppSettingUsecase<S, A, X>
+where
+    S: AppSettingRepository,
+    A: ApiKeyUpdater,
+    X: SecretCipher,
+{
+    async fn get_app_settings(
+        &self,
+        _request: GetAppSettingsRequest,
+    ) -> Result<GetAppSettingsResponse, ApplicationError> {
+        let settings = self.app_setting_repository.find_app_settings().await?;
+        let api_keys = PROVIDER_TYPES
+            .iter()
+            .map(|provider_type| {
+                let name = AppSettingName::ApiKey(provider_type.clone());
+                let api_key = find_value(&settings, &name);
+                ApiKeyItem {
+                    provider_type: provider_type.clone(),
+                    configured: api_key.is_some(),
+                    // 復号できない場合も設定済みとして扱い、上書きできるようにする
+                    masked_key: api_key
+                        .and_then(|api_key| {
+                            self.decrypt_secret(&SecretRef::AppSetting(name.clone()), api_key)
+                        })
+                        .map(|api_key| mask_api_key(&api_key)),
+                }
+            })
+            .collect();
+        let default_provider = find_value(&settings, &AppSettingName::DefaultProvider)
+            .map(|provider_type| {
+                ProviderType::from_str(provider_type)
+                    .map_err(|e| ApplicationError::ParseError(e.to_string()))
+            })
+            .transpose()?;
+        let theme = find_value(&settings, &AppSettingName::Theme)
+            .map(|theme| {
+                Theme::from_str(theme).map_err(|e| ApplicationError::ParseError(e.to_string()))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(GetAppSettingsResponse {
+            api_keys,
+            default_provider,
+            default_model: find_value(&settings, &AppSettingName::DefaultModel)
+                .map(|model| model.to_string()),
+            theme,
+            azure_openai: find_azure_openai_settings(&settings)?,
+            trash_retention_days: find_trash_retention_days(&settings)?,
+            rate_limits: find_rate_limits(&settings)?,
+            http_client: find_http_client_settings(&settings)?,
+        })
+    }
+
+    async fn save_app_settings(
+        &self,
+        request: SaveAppSettingsRequest,
+    ) -> Result<(), ApplicationError> {
+        let api_keys: Vec<(ProviderType, Option<String>)> = request
+            .api_keys
+            .into_iter()
+            .map(|input| {
+                let api_key = input
+                    .api_key
+                    .map(|api_key| api_key.expose().trim().to_string())
+                    .filter(|api_key| !api_key.is_empty());
+                (input.provider_type, api_key)
+            })
+            .collect();
+        let default_model = request
+            .default_model
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty());
+        let azure_openai = request
+            .azure_openai
+            .map(validate_azure_openai_settings)
+            .transpose()?;
+        if let Some(days) = request.trash_retention_days {
+            if days == 0 || days > MAX_TRASH_RETENTION_DAYS {
+                return Err(ApplicationError::ValidationError(format!(
+                    "trash retention days must be between 1 and {}",
+                    MAX_TRASH_RETENTION_DAYS
+                )));
+            }
+        }
+        let azure_openai_value = azure_openai
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+        let rate_limits = validate_rate_limits(request.rate_limits)?;
+        let rate_limit_values = rate_limits
+            .iter()
+            .map(|(provider_type, settings)| {
+                let value = settings
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()
+                    .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+                Ok((AppSettingName::RateLimit(provider_type.clone()), value))
+            })
+            .collect::<Result<Vec<_>, ApplicationError>>()?;
+        let http_client = request
+            .http_client
+            .map(validate_http_client_settings)
+            .transpose()?
+            .flatten();
+        let http_client_value = http_client
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+
+        let _guard = self.secret_lock.lock().await;
+        let mut settings: Vec<(AppSettingName, Option<String>)> = api_keys
+            .iter()
+            .map(|(provider_type, api_key)| {
+                let api_key = api_key
+                    .as_deref()
+                    .map(|api_key| self.secret_cipher.encrypt(api_key))
+                    .transpose()?;
+                Ok((AppSettingName::ApiKey(provider_type.clone()), api_key))
+            })
+            .collect::<Result<_, ApplicationError>>()?;
+        settings.push((
+            AppSettingName::DefaultProvider,
+            request
+                .default_provider
+                .map(|provider_type| provider_type.to_string()),
+        ));
+        settings.push((AppSettingName::DefaultModel, default_model));
+        settings.push((AppSettingName::Theme, Some(request.theme.to_string())));
+        settings.push((AppSettingName::AzureOpenAI, azure_openai_value));
+        settings.push((
+            AppSettingName::TrashRetentionDays,
+            request.trash_retention_days.map(|days| days.to_string()),
+        ));
+        settings.extend(rate_limit_values);
+        settings.push((AppSettingName::HttpClient, http_client_value));
+
+        // HTTPクライアントを作れない設定（存在しない証明書など）は保存しないため、先に反映して確かめる
+        // 保存できなかった場合は保存済みの設定に戻す
+        let current_http_client =
+            find_http_client_settings(&self.app_setting_repository.find_app_settings().await?)?;
+        self.api_key_updater
+            .update_http_client(http_client.as_ref())?;
+        if let Err(e) = self
+            .app_setting_repository
+            .save_app_settings(&settings)
+            .await
+        {
+            if let Err(e) = self
+                .api_key_updater
+                .update_http_client(current_http_client.as_ref())
+            {
+                log::warn!("Cannot restore http client settings: {}", e);
+            }
+            return Err(e);
+        }
+
+        // 保存できた場合のみクライアントに反映する
+        for (provider_type, api_key) in &api_keys {
+            self.api_key_updater
+                .update_api_key(provider_type, api_key.as_deref());
+        }
+        self.api_key_updater
+            .update_azure_openai_settings(azure_openai.as_ref());
+        for (provider_type, settings) in &rate_limits {
+            self.api_key_updater
+                .update_rate_limit(provider_type, settings.as_ref());
+        }
+        Ok(())
+    }
+
+    async fn rotate_secret_key(
+        &self,
+        _request: RotateSecretKeyRequest,
+    ) -> Result<RotateSecretKeyResponse, ApplicationError> {
+        let _guard = self.secret_lock.lock().await;
+        // 復号できない設定があると新しい鍵で暗号化し直せないので、鍵を作る前に全て復号する
+        let secrets = self
+            .app_setting_repository
+            .find_secrets()
+            .await?
+            .into_iter()
+            .map(|(secret, value)| {
+                let value = self.secret_cipher.decrypt(&value)?;
+                Ok((secret, value))
+            })
+            .collect::<Result<Vec<_>, ApplicationError>>()?;
+
+        self.secret_cipher.begin_key_rotation()?;
+        let saved = self.save_secrets(&secrets).await;
+        if let Err(e) = saved {
+            self.secret_cipher.abort_key_rotation()?;
+            return Err(e);
+        }
+        self.secret_cipher.commit_key_rotation()?;
+        log::info!("rotated secret key: {} settings", secrets.len());
+        Ok(RotateSecretKeyResponse {
+            reencrypted: secrets.len() as i32,
+        })
+    }
+
+    async fn get_credential_profiles(
+        &self,
+        _request: GetCredentialProfilesRequest,
+    ) -> Result<GetCredentialProfilesResponse, ApplicationError> {
+        let profiles = self
+            .app_setting_repository
+            .find_credential_profiles()
+            .await?
+            .into_iter()
+            .filter(|profile| !profile.deleted)
+            .map(|profile| CredentialProfileItem {
+                id: profile.id,
+                masked_key: self
+                    .decrypt_secret(&SecretRef::CredentialProfile(profile.id), &profile.api_key)
+                    .map(|api_key| mask_api_key(&api_key)),
+                provider_type: profile.provider_type,
+                label: profile.label,
+                organization_id: profile.organization_id,
+                project_id: profile.project_id,
+            })
+            .collect();
+        Ok(GetCredentialProfilesResponse { profiles })
+    }
+
+    async fn save_credential_profile(
+        &self,
+        request: SaveCredentialProfileRequest,
+    ) -> Result<SaveCredentialProfileResponse, ApplicationError> {
+        let label = request.label.trim().to_string();
+        if label.is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "label must not be empty".to_string(),
+            ));
+        }
+        let api_key = request
+            .api_key
+            .map(|api_key| api_key.expose().trim().to_string())
+            .filter(|api_key| !api_key.is_empty());
+
+        let _guard = self.secret_lock.lock().await;
+        let api_key = api_key
+            .map(|api_key| self.secret_cipher.encrypt(&api_key))
+            .transpose()?;
+        let mut profile = CredentialProfileModel {
+            id: 0,
+            provider_type: request.provider_type,
+            label,
+            api_key: String::new(),
+            organization_id: non_empty(request.organization_id),
+            project_id: non_empty(request.project_id),
+            deleted: false,
+        };
+        let id = match request.id {
+            Some(id) => {
+                let current = self
+                    .find_active_credential_profile(id, Some(&profile.provider_type))
+                    .await?;
+                profile.id = id;
+                profile.api_key = api_key.unwrap_or(current.api_key);
+                self.app_setting_repository
+                    .update_credential_profile(profile)
+                    .await?;
+                id
+            }
+            None => {
+                profile.api_key = api_key.ok_or_else(|| {
+                    ApplicationError::ValidationError("api_key is required".to_string())
+                })?;
+                self.app_setting_repository
+                    .create_credential_profile(profile)
+                    .await?
+            }
+        };
+        Ok(SaveCredentialProfileResponse { id })
+    }
+
+    async fn delete_credential_profile(
+        &self,
+        request: DeleteCredentialProfileRequest,
+    ) -> Result<(), ApplicationError> {
+        self.find_active_credential_profile(request.id, None)
+            .await?;
+        self.app_setting_repository
+            .logical_delete_credential_profile(request.id)
+            .await
+    }
+
+    async fn get_manager_credential_profiles(
+        &self,
+        request: GetManagerCredentialProfilesRequest,
+    ) -> Result<GetManagerCredentialProfilesResponse, ApplicationError> {
+        let profiles = self
+            .app_setting_repository
+            .find_manager_credential_profiles(request.manager_id)
+            .await?
+            .into_iter()
+            .map(
+                |(provider_type, credential_profile_id)| ManagerCredentialProfileItem {
+                    provider_type,
+                    credential_profile_id,
+                },
+            )
+            .collect();
+        Ok(GetManagerCredentialProfilesResponse { profiles })
+    }
+
+    async fn save_manager_credential_profile(
+        &self,
+        request: SaveManagerCredentialProfileRequest,
+    ) -> Result<(), ApplicationError> {
+        if let Some(credential_profile_id) = request.credential_profile_id {
+            self.find_active_credential_profile(
+                credential_profile_id,
+                Some(&request.provider_type),
+            )
+            .await?;
+        }
+        self.app_setting_repository
+            .save_manager_credential_profile(
+                request.manager_id,
+                &request.provider_type,
+                request.credential_profile_id,
+            )
+            .await
+    }
+
+    async fn get_credential_profile_usage(
+        &self,
+        _request: GetCredentialProfileUsageRequest,
+    ) -> Result<GetCredentialProfileUsageResponse, ApplicationError> {
+        // 削除されたプロファイルもラベルを表示する
+        let profiles = self
+            .app_setting_repository
+            .find_credential_profiles()
+            .await?;
+        let items = self
+            .app_setting_repository
+            .find_credential_profile_usages()
+            .await?
+            .into_iter()
+            .map(|usage| CredentialProfileUsageItem {
+                label: usage.credential_profile_id.and_then(|id| {
+                    profiles
+                        .iter()
+                        .find(|profile| profile.id == id)
+                        .map(|profile| profile.label.clone())
+                }),
+                credential_profile_id: usage.credential_profile_id,
+                provider_type: usage.provider_type,
+                model: usage.model,
+                runs: usage.runs,
+                responses: usage.responses,
+                cached_responses: usage.cached_responses,
+            })
+            .collect();
+        Ok(GetCredentialProfileUsageResponse { items })
+    }
+}
+
+#[async_trait]
+impl<S, A, X> TrashRetention for AppSettingUsecase<S, A, X>
+where
+    S: AppSettingRepository,
+    A: ApiKeyUpdater,
+    X: SecretCipher,
+{
+    async fn trash_retention_days(&self) -> Result<Option<u32>, ApplicationError> {
+        let settings = self.app_setting_repository.find_app_settings().await?;
+        find_trash_retention_days(&settings)
+    }
+}
+
+#[async_trait]
+impl<S, A, X> CredentialResolver for AppSettingUsecase<S, A, X>
+where
+    S: AppSettingRepository,
+    A: ApiKeyUpdater,
+    X: SecretCipher,
+{
+    async fn resolve_credential_profile_id(
+        &self,
+        manager_id: i32,
+        provider_type: &ProviderType,
+        credential_profile_id: Option<i32>,
+    ) -> Result<Option<i32>, ApplicationError> {
+        if let Some(credential_profile_id) = credential_profile_id {
+            self.find_active_credential_profile(credential_profile_id, Some(provider_type))
+                .await?;
+            return Ok(Some(credential_profile_id));
+        }
+        let selected = self
+            .app_setting_repository
+            .find_manager_credential_profiles(manager_id)
+            .await?
+            .into_iter()
+            .find(|(selected_provider_type, _)| selected_provider_type == provider_type)
+            .map(|(_, credential_profile_id)| credential_profile_id);
+        Ok(selected)
+    }
+
+    async fn find_credential(
+        &self,
+        credential_profile_id: i32,
+    ) -> Result<ProviderCredential, ApplicationError> {
+        let profile = self
+            .find_active_credential_profile(credential_profile_id, None)
+            .await?;
+        let api_key = self.secret_cipher.decrypt(&profile.api_key)?;
+        Ok(ProviderCredential {
+            api_key: SecretString::new(api_key),
+            organization_id: profile.organization_id,
+            project_id: profile.project_id,
+        })
+    }
+
+    async fn resolve_token_model(
+        &self,
+        provider_type: &ProviderType,
+        model: &str,
+    ) -> Result<String, ApplicationError> {
+        if *provider_type != ProviderType::AzureOpenAI {
+            return Ok(model.to_string());
+        }
+        let settings = self.app_setting_repository.find_app_settings().await?;
+        let resolved = find_azure_openai_settings(&settings)?
+            .as_ref()
+            .and_then(|azure_openai| azure_openai.model_for(model))
+            .unwrap_or(model)
+            .to_string();
+        Ok(resolved)
+    }
+}
+
+impl<S, A, X> AppSettingUsecase<S, A, X>
+where
+    S: AppSettingRepository,
+    A: ApiKeyUpdater,
+    X: SecretCipher,
+{
+    pub fn new(
+        app_setting_repository: Arc<S>,
+        api_key_updater: Arc<A>,
+        secret_cipher: Arc<X>,
+    ) -> Self {
+        AppSettingUsecase {
+            app_setting_repository,
+            api_key_updater,
+            secret_cipher,
+            secret_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// 平文のまま保存されている秘密の設定や、中断されたローテーションの鍵で暗号化された設定を
+    /// 現在の鍵で暗号化し直す（起動時に呼び出す）
+    pub async fn reencrypt_secrets(&self) -> Result<i32, ApplicationError> {
+        let _guard = self.secret_lock.lock().await;
+        let secrets: Vec<(SecretRef, String)> = self
+            .app_setting_repository
+            .find_secrets()
+            .await?
+            .into_iter()
+            .filter(|(_, value)| self.secret_cipher.needs_reencryption(value))
+            .filter_map(|(secret, value)| {
+                let value = self.decrypt_secret(&secret, &value)?;
+                Some((secret, value))
+            })
+            .collect();
+        self.save_secrets(&secrets).await?;
+        self.secret_cipher.abort_key_rotation()?;
+        Ok(secrets.len() as i32)
+    }
+
+    /// 保存されているAPIキー、Azure OpenAI、レート制限と通信の設定をクライアントに反映する（起動時に呼び出す）
+    /// 保存されていないプロバイダーは環境変数のAPIキーとレート制限を使う
+    pub async fn apply_api_keys(&self) -> Result<(), ApplicationError> {
+        let settings = self.app_setting_repository.find_app_settings().await?;
+        for setting in &settings {
+            if let AppSettingName::ApiKey(provider_type) = &setting.name {
+                let secret = SecretRef::AppSetting(setting.name.clone());
+                if let Some(api_key) = self.decrypt_secret(&secret, &setting.value) {
+                    self.api_key_updater
+                        .update_api_key(provider_type, Some(&api_key));
+                }
+            }
+        }
+        self.api_key_updater
+            .update_azure_openai_settings(find_azure_openai_settings(&settings)?.as_ref());
+        for item in find_rate_limits(&settings)? {
+            self.api_key_updater
+                .update_rate_limit(&item.provider_type, Some(&item.settings));
+        }
+        // 証明書のファイルが削除された場合なども起動できるように、反映できない場合は環境変数の設定を使う
+        if let Err(e) = self
+            .api_key_updater
+            .update_http_client(find_http_client_settings(&settings)?.as_ref())
+        {
+            log::warn!("Cannot apply http client settings: {}", e);
+        }
+        Ok(())
+    }
+
+    /// 復号できない場合（鍵ファイルを失った場合など）はログに残してNoneを返す
+    fn decrypt_secret(&self, secret: &SecretRef, value: &str) -> Option<String> {
+        match self.secret_cipher.decrypt(value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                log::warn!("Cannot decrypt '{}': {}", secret, e);
+                None
+            }
+        }
+    }
+
+    /// 秘密の値を暗号化してまとめて保存する
+    async fn save_secrets(&self, secrets: &[(SecretRef, String)]) -> Result<(), ApplicationError> {
+        if secrets.is_empty() {
+            return Ok(());
+        }
+        let secrets = secrets
+            .iter()
+            .map(|(secret, value)| Ok((secret.clone(), self.secret_cipher.encrypt(value)?)))
+            .collect::<Result<Vec<_>, ApplicationError>>()?;
+        self.app_setting_repository.save_secrets(&secrets).await
+    }
+
+    /// 削除されていないプロファイルを取得する（プロバイダーが指定された場合は一致するか確認する）
+    async fn find_active_credential_profile(
+        &self,
+        id: i32,
+        provider_type: Option<&ProviderType>,
+    ) -> Result<CredentialProfileModel, ApplicationError> {
+        let profile = self
+            .app_setting_repository
+            .find_credential_profile_by_id(id)
+            .await?;
+        if profile.deleted {
+            return Err(ApplicationError::ValidationError(format!(
+                "credential profile {} is deleted",
+                id
+            )));
+        }
+        if let Some(provider_type) = provider_type {
+            if profile.provider_type != *provider_type {
+                return Err(ApplicationError::ValidationError(format!(
+                    "credential profile {} is for {}",
+                    id, profile.provider_type
+                )));
+            }
+        }
+        Ok(profile)
+    }
+}
+
+fn find_value<'a>(settings: &'a [AppSettingModel], name: &AppSettingName) -> Option<&'a str> {
+    settings
+        .iter()
+        .find(|setting| setting.name == *name)
+        .map(|setting| setting.value.as_str())
+}
+
+fn find_azure_openai_settings(
+    settings: &[AppSettingModel],
+) -> Result<Option<AzureOpenAISettings>, ApplicationError> {
+    find_value(settings, &AppSettingName::AzureOpenAI)
+        .map(|value| {
+            serde_json::from_str(value).map_err(|e| ApplicationError::ParseError(e.to_string()))
+        })
+        .transpose()
+}
+
+fn find_trash_retention_days(
+    settings: &[AppSettingModel],
+) -> Result<Option<u32>, ApplicationError> {
+    find_value(settings, &AppSettingName::TrashRetentionDays)
+        .map(|value| {
+            value
+                .parse::<u32>()
+                .map_err(|e| ApplicationError::ParseError(format!("{}: {}", value, e)))
+        })
+        .transpose()
+}
+
+fn find_rate_limits(settings: &[AppSettingModel]) -> Result<Vec<RateLimitItem>, ApplicationError> {
+    PROVIDER_TYPES
+        .iter()
+        .filter_map(|provider_type| {
+            find_value(settings, &AppSettingName::RateLimit(provider_type.clone())).map(|value| {
+                let settings = serde_json::from_str(value)
+                    .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+                Ok(RateLimitItem {
+                    provider_type: provider_type.clone(),
+                    settings,
+                })
+            })
+        })
+        .collect()
+}
+
+fn find_http_client_settings(
+    settings: &[AppSettingModel],
+) -> Result<Option<HttpClientSettings>, ApplicationError> {
+    find_value(settings, &AppSettingName::HttpClient)
+        .map(|value| {
+            serde_json::from_str(value).map_err(|e| ApplicationError::ParseError(e.to_string()))
+        })
+        .transpose()
+}
+
+/// 通信の設定の前後の空白を除く（全ての項目が未指定の場合はNone）
+/// プロキシのURLや証明書はHTTPクライアントを作る際に確認する
+fn validate_http_client_settings(
+    settings: HttpClientSettings,
+) -> Result<Option<HttpClientSettings>, ApplicationError> {
+    if settings.connect_timeout_secs == Some(0) || settings.request_timeout_secs == Some(0) {
+        return Err(ApplicationError::ValidationError(
+            "http timeouts must be greater than 0".to_string(),
+        ));
+    }
+    let mut headers = BTreeMap::new();
+    for (name, value) in settings.headers {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "http header name is required".to_string(),
+            ));
+        }
+        headers.insert(name, value.trim().to_string());
+    }
+    let settings = HttpClientSettings {
+        proxy: non_empty(settings.proxy),
+        no_proxy: non_empty(settings.no_proxy),
+        ca_certs: settings
+            .ca_certs
+            .into_iter()
+            .filter_map(|path| non_empty(Some(path)))
+            .collect(),
+        connect_timeout_secs: settings.connect_timeout_secs,
+        request_timeout_secs: settings.request_timeout_secs,
+        headers,
+    };
+    Ok(Some(settings).filter(|settings| !settings.is_empty()))
+}
+
+/// 全てのプロバイダーのレート制限を返す（含まれないプロバイダーと、全ての項目が未指定の場合はNone）
+fn validate_rate_limits(
+    items: Vec<RateLimitItem>,
+) -> Result<Vec<(ProviderType, Option<RateLimitSettings>)>, ApplicationError> {
+    for (index, item) in items.iter().enumerate() {
+        if items[..index]
+            .iter()
+            .any(|other| other.provider_type == item.provider_type)
+        {
+            return Err(ApplicationError::ValidationError(format!(
+                "duplicate rate limit: {}",
+                item.provider_type
+            )));
+        }
+        let settings = &item.settings;
+        if [
+            settings.max_concurrent,
+            settings.requests_per_minute,
+            settings.tokens_per_minute,
+        ]
+        .contains(&Some(0))
+        {
+            return Err(ApplicationError::ValidationError(format!(
+                "rate limits of {} must be greater than 0",
+                item.provider_type
+            )));
+        }
+    }
+    Ok(PROVIDER_TYPES
+        .iter()
+        .map(|provider_type| {
+            let settings = items
+                .iter()
+                .find(|item| item.provider_type == *provider_type)
+                .map(|item| item.settings.clone())
+                .filter(|settings| !settings.is_empty());
+            (provider_type.clone(), settings)
+        })
+        .collect())
+}
+
+/// Azure OpenAIの設定の前後の空白を除き、リクエストを送れる設定か確認する
+fn validate_azure_openai_settings(
+    settings: AzureOpenAISettings,
+) -> Result<AzureOpenAISettings, ApplicationError> {
+    let endpoint = settings.endpoint.trim().trim_end_matches('/').to_string();
+    if !endpoint.starts_with("https://") {
+        return Err(ApplicationError::ValidationError(
+            "Azure OpenAI endpoint must start with https://".to_string(),
+        ));
+    }
+    let api_version = settings.api_version.trim().to_string();
+    if api_version.is_empty() {
+        return Err(ApplicationError::ValidationError(
+            "Azure OpenAI api version is required".to_string(),
+        ));
+    }
+    let mut deployments: Vec<AzureOpenAIDeployment> = Vec::new();
+    for item in settings.deployments {
+        let deployment = item.deployment.trim().to_string();
+        let model = item.model.trim().to_string();
+        if deployment.is_empty() || model.is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "Azure OpenAI deployment and model are required".to_string(),
+            ));
+        }
+        if deployments.iter().any(|item| item.deployment == deployment) {
+            return Err(ApplicationError::ValidationError(format!(
+                "duplicate Azure OpenAI deployment: {}",
+                deployment
+            )));
+        }
+        deployments.push(AzureOpenAIDeployment { deployment, model });
+    }
+    if deployments.is_empty() {
+        return Err(ApplicationError::ValidationError(
+            "Azure OpenAI deployments are required".to_string(),
+        ));
+    }
+    Ok(AzureOpenAISettings {
+        endpoint,
+        api_version,
+        deployments,
+    })
+}
+
+/// 前後の空白を除き、空の場合はNoneにする
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// APIキーを末尾の数文字以外を伏せた文字列にする
+fn mask_api_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() <= API_KEY_VISIBLE_CHARS * 2 {
+        return "****".to_string();
+    }
+    let visible: String = chars[chars.len() - API_KEY_VISIBLE_CHARS..]
+        .iter()
+        .collect();
+    format!("****{}", visible)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::domain::app_setting::CredentialProfileUsageModel;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    #[derive(Default)]
+    struct MockAppSettingRepository {
+        settings: Mutex<Vec<AppSettingModel>>,
+        profiles: Mutex<Vec<CredentialProfileModel>>,
+        manager_profiles: Mutex<Vec<(i32, ProviderType, i32)>>,
+    }
+
+    /// 反映されたAPIキーを記録する
+    #[derive(Default)]
+    struct MockApiKeyUpdater {
+        updates: Mutex<Vec<(ProviderType, Option<String>)>>,
+        azure_openai_settings: Mutex<Vec<Option<AzureOpenAISettings>>>,
+        rate_limits: Mutex<Vec<(ProviderType, Option<RateLimitSettings>)>>,
+        http_client: Mutex<Vec<Option<HttpClientSettings>>>,
+    }
+
+    #[async_trait]
+    impl AppSettingRepository for MockAppSettingRepository {
+        async fn find_app_settings(&self) -> Result<Vec<AppSettingModel>, ApplicationError> {
+            Ok(self.settings.lock().unwrap().clone())
+        }
+
+        async fn save_app_settings(
+            &self,
+            settings: &[(AppSettingName, Option<String>)],
+        ) -> Result<(), ApplicationError> {
+            let mut saved = self.settings.lock().unwrap();
+            for (name, value) in settings {
+                saved.retain(|setting| setting.name != *name);
+                if let Some(value) = value {
+                    saved.push(AppSettingModel {
+                        name: name.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+            Ok(())
+        }
+
+        async fn find_secrets(&self) -> Result<Vec<(SecretRef, String)>, ApplicationError> {
+            let settings = self.settings.lock().unwrap();
+            let profiles = self.profiles.lock().unwrap();
+            Ok(settings
+                .iter()
+                .filter(|setting| setting.name.is_secret())
+                .map(|setting| {
+                    (
+                        SecretRef::AppSetting(setting.name.clone()),
+                        setting.value.clone(),
+                    )
+                })
+                .chain(
+                    profiles
+                        .iter()
+                        .filter(|profile| !profile.deleted)
+                        .map(|profile| {
+                            (
+                                SecretRef::CredentialProfile(profile.id),
+                                profile.api_key.clone(),
+                            )
+                        }),
+                )
+                .collect())
+        }
+
+        async fn save_secrets(
+            &self,
+            secrets: &[(SecretRef, String)],
+        ) -> Result<(), ApplicationError> {
+            for (secret, value) in secrets {
+                match secret {
+                    SecretRef::AppSetting(name) => {
+                        self.save_app_settings(&[(name.clone(), Some(value.clone()))])
+                            .await?
+                    }
+                    SecretRef::CredentialProfile(id) => {
+                        let mut profiles = self.profiles.lock().unwrap();
+                        let profile = profiles.iter_mut().find(|profile| profile.id == *id);
+                        profile.ok_or(ApplicationError::EmptyResult)?.api_key = value.clone();
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        async fn find_credential_profiles(
+            &self,
+        ) -> Result<Vec<CredentialProfileModel>, ApplicationError> {
+            Ok(self.profiles.lock().unwrap().clone())
+        }
+
+        async fn find_credential_profile_by_id(
+            &self,
+            id: i32,
+        ) -> Result<CredentialProfileModel, ApplicationError> {
+            let profiles = self.profiles.lock().unwrap();
+            let profile = profiles.iter().find(|profile| profile.id == id);
+            profile.cloned().ok_or(ApplicationError::EmptyResult)
+        }
+
+        async fn create_credential_profile(
+            &self,
+            param: CredentialProfileModel,
+        ) -> Result<i32, ApplicationError> {
+            let mut profiles = self.profiles.lock().unwrap();
+            let id = profiles.len() as i32 + 1;
+            profiles.push(CredentialProfileModel { id, ..param });
+            Ok(id)
+        }
+
+        async fn update_credential_profile(
+            &self,
+            param: CredentialProfileModel,
+        ) -> Result<(), ApplicationError> {
+            let mut profiles = self.profiles.lock().unwrap();
+            let profile = profiles.iter_mut().find(|profile| profile.id == param.id);
+            *profile.ok_or(ApplicationError::EmptyResult)? = param;
+            Ok(())
+        }
+
+        async fn logical_delete_credential_profile(&self, id: i32) -> Result<(), ApplicationError> {
+            let mut profiles = self.profiles.lock().unwrap();
+            let profile = profiles.iter_mut().find(|profile| profile.id == id);
+            let profile = profile.ok_or(ApplicationError::EmptyResult)?;
+            profile.deleted = true;
+            profile.api_key = String::new();
+            self.manager_profiles
+                .lock()
+                .unwrap()
+                .retain(|(_, _, profile_id)| *profile_id != id);
+            Ok(())
+        }
+
+        async fn find_manager_credential_profiles(
+            &self,
+            manager_id: i32,
+        ) -> Result<Vec<(ProviderType, i32)>, ApplicationError> {
+            Ok(self
+                .manager_profiles
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(id, _, _)| *id == manager_id)
+                .map(|(_, provider_type, profile_id)| (provider_type.clone(), *profile_id))
+                .collect())
+        }
+
+        async fn save_manager_credential_profile(
+            &self,
+            manager_id: i32,
+            provider_type: &ProviderType,
+            credential_profile_id: Option<i32>,
+        ) -> Result<(), ApplicationError> {
+            let mut manager_profiles = self.manager_profiles.lock().unwrap();
+            manager_profiles.retain(|(id, selected_provider_type, _)| {
+                *id != manager_id || selected_provider_type != provider_type
+            });
+            if let Some(credential_profile_id) = credential_profile_id {
+                manager_profiles.push((manager_id, provider_type.clone(), credential_profile_id));
+            }
+            Ok(())
+        }
+
+        async fn find_credential_profile_usages(
+            &self,
+        ) -> Result<Vec<CredentialProfileUsageModel>, ApplicationError> {
+            Ok(vec![
+                CredentialProfileUsageModel {
+                    credential_profile_id: None,
+                    provider_type: ProviderType::OpenAI,
+                    model: "gpt-4".to_string(),
+                    runs: 1,
+                    responses: 2,
+                    cached_responses: 0,
+                },
+                CredentialProfileUsageModel {
+                    credential_profile_id: Some(1),
+                    provider_type: ProviderType::OpenAI,
+                    model: "gpt-4".to_string(),
+                    runs: 2,
+                    responses: 3,
+                    cached_responses: 1,
+                },
+            ])
+        }
+    }
+
+    /// 鍵の世代を接頭辞につけるだけの暗号化（enc<世代>:<平文>）
+    #[derive(Default)]
+    struct MockSecretCipher {
+        // (現在の鍵の世代, ローテーション中か)
+        state: Mutex<(u32, bool)>,
+    }
+
+    impl MockSecretCipher {
+        fn prefix(&self) -> String {
+            let (generation, rotating) = *self.state.lock().unwrap();
+            format!("enc{}:", generation + rotating as u32)
+        }
+    }
+
+    impl SecretCipher for MockSecretCipher {
+        fn encrypt(&self, plaintext: &str) -> Result<String, ApplicationError> {
+            Ok(format!("{}{}", self.prefix(), plaintext))
+        }
+
+        fn decrypt(&self, value: &str) -> Result<String, ApplicationError> {
+            match value
+                .strip_prefix("enc")
+                .and_then(|value| value.split_once(':'))
+            {
+                Some((_, plaintext)) => Ok(plaintext.to_string()),
+                None => Ok(value.to_string()),
+            }
+        }
+
+        fn needs_reencryption(&self, value: &str) -> bool {
+            !value.starts_with(&self.prefix())
+        }
+
+        fn begin_key_rotation(&self) -> Result<(), ApplicationError> {
+            self.state.lock().unwrap().1 = true;
+            Ok(())
+        }
+
+        fn commit_key_rotation(&self) -> Result<(), ApplicationError> {
+            let mut state = self.state.lock().unwrap();
+            *state = (state.0 + 1, false);
+            Ok(())
+        }
+
+        fn abort_key_rotation(&self) -> Result<(), ApplicationError> {
+            self.state.lock().unwrap().1 = false;
+            Ok(())
+        }
+    }
+
+    impl ApiKeyUpdater for MockApiKeyUpdater {
+        fn update_api_key(&self, provider_type: &ProviderType, api_key: Option<&str>) {
+            self.updates.lock().unwrap().push((
+                provider_type.clone(),
+                api_key.map(|api_key| api_key.to_string()),
+            ));
+        }
+
+        fn update_azure_openai_settings(&self, settings: Option<&AzureOpenAISettings>) {
+            self.azure_openai_settings
+                .lock()
+                .unwrap()
+                .push(settings.cloned());
+        }
+
+        fn update_rate_limit(
+            &self,
+            provider_type: &ProviderType,
+            settings: Option<&RateLimitSettings>,
+        ) {
+            self.rate_limits
+                .lock()
+                .unwrap()
+                .push((provider_type.clone(), settings.cloned()));
+        }
+
+        /// "invalid"のプロキシはクライアントを作れない設定とする
+        fn update_http_client(
+            &self,
+            settings: Option<&HttpClientSettings>,
+        ) -> Result<(), ApplicationError> {
+            if settings.and_then(|settings| settings.proxy.as_deref()) == Some("invalid") {
+                return Err(ApplicationError::ValidationError(
+                    "invalid proxy".to_string(),
+                ));
+            }
+            self.http_client.lock().unwrap().push(settings.cloned());
+            Ok(())
+        }
+    }
+
+    fn app_setting_usecase(
+    ) -> AppSettingUsecase<MockAppSettingRepository, MockApiKeyUpdater, MockSecretCipher> {
+        AppSettingUsecase {
+            app_setting_repository: Arc::new(MockAppSettingRepository::default()),
+            api_key_updater: Arc::new(MockApiKeyUpdater::default()),
+            secret_cipher: Arc::new(MockSecretCipher::default()),
+            secret_lock: Default::default(),
+        }
+    }
+
+    fn stored_value(
+        usecase: &AppSettingUsecase<MockAppSettingRepository, MockApiKeyUpdater, MockSecretCipher>,
+        name: &AppSettingName,
+    ) -> Option<String> {
+        find_value(
+            &usecase.app_setting_repository.settings.lock().unwrap(),
+            name,
+        )
+        .map(|value| value.to_string())
+    }
+
+    /**
+     * Tests
+     */
+    #[tokio::test]
+    async fn test_get_app_settings_default() {
+        let usecase = app_setting_usecase();
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.api_keys.len(), 3);
+        assert_eq!(res.azure_openai, None);
+        assert!(res.api_keys.iter().all(|api_key| !api_key.configured));
+        assert_eq!(res.default_provider, None);
+        assert_eq!(res.default_model, None);
+        assert_eq!(res.theme, Theme::System);
+    }
+
+    #[tokio::test]
+    async fn test_save_app_settings() {
+        let usecase = app_setting_usecase();
+        usecase
+            .save_app_settings(SaveAppSettingsRequest {
+                api_keys: vec![ApiKeyInput {
+                    provider_type: ProviderType::OpenAI,
+                    api_key: Some(" sk-test-1234567890 ".into()),
+                }],
+                default_provider: Some(ProviderType::OpenAI),
+                default_model: Some("gpt-4".to_string()),
+                theme: Theme::Dark,
+                azure_openai: None,
+                trash_retention_days: Some(30),
+                rate_limits: Vec::new(),
+                http_client: None,
+            })
+            .await
+            .unwrap();
+
+        // APIキーは暗号化して保存する
+        assert_eq!(
+            stored_value(&usecase, &AppSettingName::ApiKey(ProviderType::OpenAI)).as_deref(),
+            Some("enc0:sk-test-1234567890")
+        );
+
+        // APIキーは末尾のみ返し、変更したAPIキーはクライアントに反映される
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(
+            res.api_keys[0],
+            ApiKeyItem {
+                provider_type: ProviderType::OpenAI,
+                configured: true,
+                masked_key: Some("****7890".to_string()),
+            }
+        );
+        assert!(!res.api_keys[1].configured);
+        assert!(!res.api_keys[2].configured);
+        assert_eq!(res.default_provider, Some(ProviderType::OpenAI));
+        assert_eq!(res.default_model.as_deref(), Some("gpt-4"));
+        assert_eq!(res.theme, Theme::Dark);
+        assert_eq!(res.trash_retention_days, Some(30));
+        assert_eq!(
+            *usecase.api_key_updater.updates.lock().unwrap(),
+            vec![(ProviderType::OpenAI, Some("sk-test-1234567890".to_string()))]
+        );
+
+        // 含まれないAPIキーは変更せず、空のAPIキーは削除して環境変数のAPIキーに戻す
+        usecase
+            .save_app_settings(SaveAppSettingsRequest {
+                api_keys: Vec::new(),
+                default_provider: None,
+                default_model: None,
+                theme: Theme::Light,
+                azure_openai: None,
+                trash_retention_days: None,
+                rate_limits: Vec::new(),
+                http_client: None,
+            })
+            .await
+            .unwrap();
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert!(res.api_keys[0].configured);
+        assert_eq!(res.default_provider, None);
+        assert_eq!(res.trash_retention_days, None);
+        usecase
+            .save_app_settings(SaveAppSettingsRequest {
+                api_keys: vec![ApiKeyInput {
+                    provider_type: ProviderType::OpenAI,
+                    api_key: Some("".into()),
+                }],
+                default_provider: None,
+                default_model: None,
+                theme: Theme::Light,
+                azure_openai: None,
+                trash_retention_days: None,
+                rate_limits: Vec::new(),
+                http_client: None,
+            })
+            .await
+            .unwrap();
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert!(!res.api_keys[0].configured);
+        assert_eq!(
+            usecase.api_key_updater.updates.lock().unwrap().last(),
+            Some(&(ProviderType::OpenAI, None))
+        );
+
+        // 自動で物理削除するまでの日数は1日以上
+        let result = usecase
+            .save_app_settings(SaveAppSettingsRequest {
+                api_keys: Vec::new(),
+                default_provider: None,
+                default_model: None,
+                theme: Theme::Light,
+                azure_openai: None,
+                trash_retention_days: Some(0),
+                rate_limits: Vec::new(),
+                http_client: None,
+            })
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_save_azure_openai_settings() {
+        let usecase = app_setting_usecase();
+        let request = |azure_openai: AzureOpenAISettings| SaveAppSettingsRequest {
+            api_keys: vec![ApiKeyInput {
+                provider_type: ProviderType::AzureOpenAI,
+                api_key: Some("azure-key".into()),
+            }],
+            default_provider: Some(ProviderType::AzureOpenAI),
+            default_model: Some("prod-gpt4o".to_string()),
+            theme: Theme::System,
+            azure_openai: Some(azure_openai),
+            trash_retention_days: None,
+            rate_limits: Vec::new(),
+            http_client: None,
+        };
+        let deployment = |deployment: &str, model: &str| AzureOpenAIDeployment {
+            deployment: deployment.to_string(),
+            model: model.to_string(),
+        };
+        usecase
+            .save_app_settings(request(AzureOpenAISettings {
+                endpoint: " https://example.openai.azure.com/ ".to_string(),
+                api_version: "2024-02-01".to_string(),
+                deployments: vec![deployment(" prod-gpt4o ", "gpt-4o")],
+            }))
+            .await
+            .unwrap();
+
+        // 前後の空白を除いて保存し、クライアントに反映する
+        let expected = AzureOpenAISettings {
+            endpoint: "https://example.openai.azure.com".to_string(),
+            api_version: "2024-02-01".to_string(),
+            deployments: vec![deployment("prod-gpt4o", "gpt-4o")],
+        };
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.azure_openai, Some(expected.clone()));
+        assert!(res.api_keys[1].configured);
+        // トークン数の計算にはデプロイメントのモデルを使う
+        let resolved = usecase
+            .resolve_token_model(&ProviderType::AzureOpenAI, "prod-gpt4o")
+            .await
+            .unwrap();
+        assert_eq!(resolved, "gpt-4o");
+        let resolved = usecase
+            .resolve_token_model(&ProviderType::AzureOpenAI, "unknown")
+            .await
+            .unwrap();
+        assert_eq!(resolved, "unknown");
+        let resolved = usecase
+            .resolve_token_model(&ProviderType::OpenAI, "prod-gpt4o")
+            .await
+            .unwrap();
+        assert_eq!(resolved, "prod-gpt4o");
+        assert_eq!(
+            *usecase
+                .api_key_updater
+                .azure_openai_settings
+                .lock()
+                .unwrap(),
+            vec![Some(expected.clone())]
+        );
+
+        // 起動時にも反映する
+        usecase.apply_api_keys().await.unwrap();
+        assert_eq!(
+            usecase
+                .api_key_updater
+                .azure_openai_settings
+                .lock()
+                .unwrap()
+                .last(),
+            Some(&Some(expected.clone()))
+        );
+
+        // 不正な設定は保存しない
+        let invalid_settings = vec![
+            AzureOpenAISettings {
+                endpoint: "http://example.openai.azure.com".to_string(),
+                ..expected.clone()
+            },
+            AzureOpenAISettings {
+                api_version: " ".to_string(),
+                ..expected.clone()
+            },
+            AzureOpenAISettings {
+                deployments: Vec::new(),
+                ..expected.clone()
+            },
+            AzureOpenAISettings {
+                deployments: vec![
+                    deployment("prod-gpt4o", "gpt-4o"),
+                    deployment("prod-gpt4o", "gpt-4o-mini"),
+                ],
+                ..expected.clone()
+            },
+        ];
+        for settings in invalid_settings {
+            let result = usecase.save_app_settings(request(settings)).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.azure_openai, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_save_http_client_settings() {
+        let usecase = app_setting_usecase();
+        let request = |http_client: Option<HttpClientSettings>| SaveAppSettingsRequest {
+            api_keys: Vec::new(),
+            default_provider: None,
+            default_model: None,
+            theme: Theme::System,
+            azure_openai: None,
+            trash_retention_days: None,
+            rate_limits: Vec::new(),
+            http_client,
+        };
+        usecase
+            .save_app_settings(request(Some(HttpClientSettings {
+                proxy: Some(" http://proxy.example.com:8080 ".to_string()),
+                ca_certs: vec![" ca.pem ".to_string(), "".to_string()],
+                request_timeout_secs: Some(60),
+                headers: BTreeMap::from([(" X-Tenant ".to_string(), "team-a".to_string())]),
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+
+        // 空白を除いて保存し、再起動せずにクライアントに反映する
+        let expected = HttpClientSettings {
+            proxy: Some("http://proxy.example.com:8080".to_string()),
+            ca_certs: vec!["ca.pem".to_string()],
+            request_timeout_secs: Some(60),
+            headers: BTreeMap::from([("X-Tenant".to_string(), "team-a".to_string())]),
+            ..Default::default()
+        };
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.http_client, Some(expected.clone()));
+        assert_eq!(
+            *usecase.api_key_updater.http_client.lock().unwrap(),
+            vec![Some(expected.clone())]
+        );
+
+        // 起動時にも反映する
+        usecase.api_key_updater.http_client.lock().unwrap().clear();
+        usecase.apply_api_keys().await.unwrap();
+        assert_eq!(
+            *usecase.api_key_updater.http_client.lock().unwrap(),
+            vec![Some(expected.clone())]
+        );
+
+        // クライアントを作れない設定と、0秒のタイムアウトは保存しない
+        let result = usecase
+            .save_app_settings(request(Some(HttpClientSettings {
+                proxy: Some("invalid".to_string()),
+                ..Default::default()
+            })))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let result = usecase
+            .save_app_settings(request(Some(HttpClientSettings {
+                connect_timeout_secs: Some(0),
+                ..Default::default()
+            })))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        assert_eq!(
+            stored_value(&usecase, &AppSettingName::HttpClient),
+            Some(serde_json::to_string(&expected).unwrap())
+        );
+
+        // 全ての項目が未指定の場合は削除して環境変数の設定に戻す
+        usecase.api_key_updater.http_client.lock().unwrap().clear();
+        usecase
+            .save_app_settings(request(Some(HttpClientSettings::default())))
+            .await
+            .unwrap();
+        assert_eq!(stored_value(&usecase, &AppSettingName::HttpClient), None);
+        assert_eq!(
+            *usecase.api_key_updater.http_client.lock().unwrap(),
+            vec![None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_rate_limits() {
+        let usecase = app_setting_usecase();
+        let request = |rate_limits: Vec<RateLimitItem>| SaveAppSettingsRequest {
+            api_keys: Vec::new(),
+            default_provider: None,
+            default_model: None,
+            theme: Theme::System,
+            azure_openai: None,
+            trash_retention_days: None,
+            rate_limits,
+            http_client: None,
+        };
+        let openai_limits = RateLimitSettings {
+            max_concurrent: Some(2),
+            requests_per_minute: Some(500),
+            tokens_per_minute: None,
+        };
+        let azure_limits = RateLimitSettings {
+            max_concurrent: None,
+            requests_per_minute: None,
+            tokens_per_minute: Some(30000),
+        };
+        usecase
+            .save_app_settings(request(vec![
+                RateLimitItem {
+                    provider_type: ProviderType::OpenAI,
+                    settings: openai_limits.clone(),
+                },
+                RateLimitItem {
+                    provider_type: ProviderType::AzureOpenAI,
+                    settings: azure_limits.clone(),
+                },
+            ]))
+            .await
+            .unwrap();
+
+        // プロバイダーごとに保存し、再起動せずにクライアントに反映する
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(
+            res.rate_limits,
+            vec![
+                RateLimitItem {
+                    provider_type: ProviderType::OpenAI,
+                    settings: openai_limits.clone(),
+                },
+                RateLimitItem {
+                    provider_type: ProviderType::AzureOpenAI,
+                    settings: azure_limits.clone(),
+                },
+            ]
+        );
+        assert_eq!(
+            *usecase.api_key_updater.rate_limits.lock().unwrap(),
+            vec![
+                (ProviderType::OpenAI, Some(openai_limits.clone())),
+                (ProviderType::AzureOpenAI, Some(azure_limits.clone())),
+                (ProviderType::Gemini, None),
+            ]
+        );
+
+        // 起動時にも反映する
+        usecase.api_key_updater.rate_limits.lock().unwrap().clear();
+        usecase.apply_api_keys().await.unwrap();
+        assert_eq!(
+            *usecase.api_key_updater.rate_limits.lock().unwrap(),
+            vec![
+                (ProviderType::OpenAI, Some(openai_limits.clone())),
+                (ProviderType::AzureOpenAI, Some(azure_limits)),
+            ]
+        );
+
+        // 含まれないプロバイダーと全ての項目が未指定のプロバイダーは削除して既定値に戻す
+        usecase.api_key_updater.rate_limits.lock().unwrap().clear();
+        usecase
+            .save_app_settings(request(vec![RateLimitItem {
+                provider_type: ProviderType::OpenAI,
+                settings: RateLimitSettings::default(),
+            }]))
+            .await
+            .unwrap();
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert!(res.rate_limits.is_empty());
+        assert_eq!(
+            *usecase.api_key_updater.rate_limits.lock().unwrap(),
+            vec![
+                (ProviderType::OpenAI, None),
+                (ProviderType::AzureOpenAI, None),
+                (ProviderType::Gemini, None),
+            ]
+        );
+
+        // 0と同じプロバイダーの重複は保存しない
+        let invalid_rate_limits = vec![
+            vec![RateLimitItem {
+                provider_type: ProviderType::OpenAI,
+                settings: RateLimitSettings {
+                    max_concurrent: Some(0),
+                    ..openai_limits.clone()
+                },
+            }],
+            vec![
+                RateLimitItem {
+                    provider_type: ProviderType::OpenAI,
+                    settings: openai_limits.clone(),
+                },
+                RateLimitItem {
+                    provider_type: ProviderType::OpenAI,
+                    settings: openai_limits.clone(),
+                },
+            ],
+        ];
+        for rate_limits in invalid_rate_limits {
+            let result = usecase.save_app_settings(request(rate_limits)).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_api_keys() {
+        let usecase = app_setting_usecase();
+        usecase
+            .app_setting_repository
+            .save_app_settings(&[(
+                AppSettingName::ApiKey(ProviderType::Gemini),
+                Some("gemini-key".to_string()),
+            )])
+            .await
+            .unwrap();
+        usecase.apply_api_keys().await.unwrap();
+        assert_eq!(
+            *usecase.api_key_updater.updates.lock().unwrap(),
+            vec![(ProviderType::Gemini, Some("gemini-key".to_string()))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reencrypt_secrets() {
+        let usecase = app_setting_usecase();
+        // 暗号化される前に保存された平文のAPIキー
+        usecase
+            .app_setting_repository
+            .save_app_settings(&[
+                (
+                    AppSettingName::ApiKey(ProviderType::OpenAI),
+                    Some("sk-plain".to_string()),
+                ),
+                (AppSettingName::DefaultModel, Some("gpt-4".to_string())),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(usecase.reencrypt_secrets().await.unwrap(), 1);
+        assert_eq!(
+            stored_value(&usecase, &AppSettingName::ApiKey(ProviderType::OpenAI)).as_deref(),
+            Some("enc0:sk-plain")
+        );
+        assert_eq!(
+            stored_value(&usecase, &AppSettingName::DefaultModel).as_deref(),
+            Some("gpt-4")
+        );
+        assert_eq!(usecase.reencrypt_secrets().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_secret_key() {
+        let usecase = app_setting_usecase();
+        usecase
+            .save_app_settings(SaveAppSettingsRequest {
+                api_keys: vec![
+                    ApiKeyInput {
+                        provider_type: ProviderType::OpenAI,
+                        api_key: Some("sk-test-1234567890".into()),
+                    },
+                    ApiKeyInput {
+                        provider_type: ProviderType::Gemini,
+                        api_key: Some("gemini-key-0987654321".into()),
+                    },
+                ],
+                default_provider: None,
+                default_model: None,
+                theme: Theme::System,
+                azure_openai: None,
+                trash_retention_days: None,
+                rate_limits: Vec::new(),
+                http_client: None,
+            })
+            .await
+            .unwrap();
+
+        usecase
+            .save_credential_profile(profile_request(None, "team", Some("sk-team")))
+            .await
+            .unwrap();
+
+        // プロファイルのAPIキーも新しい鍵で暗号化し直す
+        let res = usecase
+            .rotate_secret_key(RotateSecretKeyRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.reencrypted, 3);
+        assert_eq!(
+            usecase.app_setting_repository.profiles.lock().unwrap()[0].api_key,
+            "enc1:sk-team"
+        );
+        assert_eq!(
+            stored_value(&usecase, &AppSettingName::ApiKey(ProviderType::OpenAI)).as_deref(),
+            Some("enc1:sk-test-1234567890")
+        );
+        assert_eq!(
+            stored_value(&usecase, &AppSettingName::ApiKey(ProviderType::Gemini)).as_deref(),
+            Some("enc1:gemini-key-0987654321")
+        );
+        let res = usecase
+            .get_app_settings(GetAppSettingsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.api_keys[0].masked_key.as_deref(), Some("****7890"));
+    }
+
+    fn profile_request(
+        id: Option<i32>,
+        label: &str,
+        api_key: Option<&str>,
+    ) -> SaveCredentialProfileRequest {
+        SaveCredentialProfileRequest {
+            id,
+            provider_type: ProviderType::OpenAI,
+            label: label.to_string(),
+            api_key: api_key.map(SecretString::from),
+            organization_id: Some("org-team".to_string()),
+            project_id: Some(" ".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_credential_profile() {
+        let usecase = app_setting_usecase();
+        // 新規作成時はAPIキーが必須
+        let res = usecase
+            .save_credential_profile(profile_request(None, "team", None))
+            .await;
+        assert!(res.is_err());
+
+        let res = usecase
+            .save_credential_profile(profile_request(None, " team ", Some("sk-team-1234567890")))
+            .await
+            .unwrap();
+        assert_eq!(res.id, 1);
+        assert_eq!(
+            usecase.app_setting_repository.profiles.lock().unwrap()[0].api_key,
+            "enc0:sk-team-1234567890"
+        );
+
+        // APIキーを指定しない更新ではAPIキーを変更しない
+        usecase
+            .save_credential_profile(profile_request(Some(1), "team project", None))
+            .await
+            .unwrap();
+        let res = usecase
+            .get_credential_profiles(GetCredentialProfilesRequest {})
+            .await
+            .unwrap();
+        assert_eq!(
+            res.profiles,
+            vec![CredentialProfileItem {
+                id: 1,
+                provider_type: ProviderType::OpenAI,
+                label: "team project".to_string(),
+                masked_key: Some("****7890".to_string()),
+                organization_id: Some("org-team".to_string()),
+                project_id: None,
+            }]
+        );
+
+        // プロバイダーは変更できない
+        let res = usecase
+            .save_credential_profile(SaveCredentialProfileRequest {
+                provider_type: ProviderType::Gemini,
+                ..profile_request(Some(1), "team", None)
+            })
+            .await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_credential_profile() {
+        let usecase = app_setting_usecase();
+        usecase
+            .save_credential_profile(profile_request(None, "personal", Some("sk-personal")))
+            .await
+            .unwrap();
+        usecase
+            .save_credential_profile(profile_request(None, "client", Some("sk-client")))
+            .await
+            .unwrap();
+        usecase
+            .save_manager_credential_profile(SaveManagerCredentialProfileRequest {
+                manager_id: 1,
+                provider_type: ProviderType::OpenAI,
+                credential_profile_id: Some(2),
+            })
+            .await
+            .unwrap();
+
+        // 実行で指定されたプロファイル、プロンプトマネージャーで選択されたプロファイルの順に使う
+        let resolved = usecase
+            .resolve_credential_profile_id(1, &ProviderType::OpenAI, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(resolved, Some(1));
+        let resolved = usecase
+            .resolve_credential_profile_id(1, &ProviderType::OpenAI, None)
+            .await
+            .unwrap();
+        assert_eq!(resolved, Some(2));
+        let resolved = usecase
+            .resolve_credential_profile_id(1, &ProviderType::Gemini, None)
+            .await
+            .unwrap();
+        assert_eq!(resolved, None);
+        assert!(usecase
+            .resolve_credential_profile_id(1, &ProviderType::Gemini, Some(1))
+            .await
+            .is_err());
+
+        let credential = usecase.find_credential(2).await.unwrap();
+        assert_eq!(credential.api_key.expose(), "sk-client");
+        assert_eq!(credential.organization_id.as_deref(), Some("org-team"));
+
+        // 削除したプロファイルは選択が解除され、実行に使えない
+        usecase
+            .delete_credential_profile(DeleteCredentialProfileRequest { id: 2 })
+            .await
+            .unwrap();
+        let resolved = usecase
+            .resolve_credential_profile_id(1, &ProviderType::OpenAI, None)
+            .await
+            .unwrap();
+        assert_eq!(resolved, None);
+        assert!(usecase.find_credential(2).await.is_err());
+        let res = usecase
+            .get_credential_profiles(GetCredentialProfilesRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.profiles.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_credential_profile_usage() {
+        let usecase = app_setting_usecase();
+        usecase
+            .save_credential_profile(profile_request(None, "team", Some("sk-team")))
+            .await
+            .unwrap();
+        usecase
+            .delete_credential_profile(DeleteCredentialProfileRequest { id: 1 })
+            .await
+            .unwrap();
+
+        // 削除されたプロファイルもラベルを返す
+        let res = usecase
+            .get_credential_profile_usage(GetCredentialProfileUsageRequest {})
+            .await
+            .unwrap();
+        assert_eq!(res.items.len(), 2);
+        assert_eq!(res.items[0].label, None);
+        assert_eq!(res.items[1].label.as_deref(), Some("team"));
+        assert_eq!(res.items[1].responses, 3);
+    }
+
+    #[test]
+    fn test_api_key_input_debug() {
+        // IPCのログにAPIキーの平文を出力しない
+        let input: ApiKeyInput =
+            serde_json::from_str(r#"{"providerType":"OpenAI","apiKey":"sk-test-1234567890"}"#)
+                .unwrap();
+        assert_eq!(
+            input.api_key.as_ref().unwrap().expose(),
+            "sk-test-1234567890"
+        );
+        assert!(!format!("{:?}", input).contains("sk-test"));
+    }
+}
@@ -1,14 +1,54 @@
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use crate::common::cancellation::RunCancellations;
 use crate::common::errors::ApplicationError;
-use crate::domain::chat::{AIChat, ChatSettings};
+use crate::common::similarity::{normalize_text, token_similarity};
+use crate::domain::app_setting::ProviderCredential;
+use crate::domain::chat::{
+    AIChat, ChatMessage, ChatSettings, SamplingParameter, SamplingParameters, ToolCall, ToolChoice,
+    ToolDefinition,
+};
 use crate::domain::comparing_prompt::{
-    ComparingPromptRunRepository, ComparingPromptSettingRepository, ComparingPromptSettingRunModel,
-    ProviderType,
+    ComparingPromptRunHistoryModel, ComparingPromptRunRepository, ComparingPromptSettingRepository,
+    ComparingPromptSettingRunModel, ProviderType, RunHistoryTarget, SweepDefinition, SweepValues,
+    ToolScript,
 };
+use crate::domain::model_catalog::ModelCatalogRepository;
+use crate::domain::response_cache::{is_cacheable, response_cache_key, ResponseCacheRepository};
+use crate::domain::tokenizer::{TokenEncoding, Tokenizer};
+use crate::usecase::app_setting::CredentialResolver;
+use crate::usecase::regression::{DriftDetector, SampleDrift};
+
+/// 1バージョンあたりの最大サンプリング回数（OpenAIのnパラメータの上限に合わせる）
+const MAX_REPETITIONS: i32 = 128;
+
+/// 類似度分布のヒストグラムの区間数（0.0〜1.0を等分する）
+const SIMILARITY_HISTOGRAM_BINS: usize = 10;
+
+/// 1回のスイープで展開できる実行設定の最大数
+const MAX_SWEEP_CONFIGURATIONS: usize = 100;
+
+/// stopに指定できるシーケンスの最大数
+const MAX_STOP_SEQUENCES: usize = 4;
+
+/// 回答のキャッシュの有効期限（秒）のデフォルト値（7日）
+const DEFAULT_CACHE_TTL_SECONDS: u32 = 7 * 24 * 60 * 60;
+
+/// ツール名の最大長
+const MAX_TOOL_NAME_LENGTH: usize = 64;
+
+/// JSONモードを指定するresponse_formatの値
+const JSON_RESPONSE_FORMAT: &str = "json_object";
+
+/// エージェント実行のステップ数の既定値
+const DEFAULT_AGENT_MAX_STEPS: u8 = 10;
+
+/// エージェント実行で指定できるステップ数の上限
+const MAX_AGENT_STEPS: u8 = 50;
 
 #[derive(Clone, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -42,6 +82,18 @@ pub struct GetComparingPromptSettingsResponse {
     pub settings: Vec<ComparingPromptSettingItem>,
 }
 
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteComparingPromptSettingRequest {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreComparingPromptSettingRequest {
+    pub id: i32,
+}
+
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ComparingPromptSettingItem {
@@ -59,8 +111,13 @@ pub struct SaveComparingPromptRunRequest {
     pub provider_type: ProviderType,
     pub model: String,
     pub temperature: f64,
+    pub top_p: Option<f64>,
     pub max_tokens: Option<i32>,
     pub response_format: Option<String>,
+    pub repetitions: Option<i32>,           // 未指定の場合は1回
+    pub credential_profile_id: Option<i32>, // 未指定の場合はプロンプトマネージャーで選択されたプロファイル
+    #[serde(flatten)]
+    pub sampling: SamplingParameters,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -73,19 +130,251 @@ pub struct SaveComparingPromptRunResponse {
 #[serde(rename_all = "camelCase")] // jsonデコードする際にキャメルケースをスネークケースに変換する
 pub struct RunChatRequest {
     pub run_id: i32,
+    pub version_id: Option<i32>, // 指定された場合は実行結果を履歴に登録する
+    pub user_prompt: String,
+    pub system_prompt: String,
+    pub provider_type: ProviderType,
+    pub model: String,
+    pub temperature: f32,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u16>,
+    pub response_format: Option<String>,
+    pub repetitions: Option<u8>, // 未指定の場合は1回
+    #[serde(flatten)]
+    pub sampling: SamplingParameters,
+    /// 同じリクエストの回答をキャッシュから返す（temperatureが0、またはseedが指定されている場合のみ）
+    #[serde(default)]
+    pub use_cache: bool,
+    pub cache_ttl_seconds: Option<u32>, // 未指定の場合は7日
+    #[serde(skip)]
+    pub job_item_id: Option<i32>, // ジョブの項目から実行した場合のみ設定する
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveVersionToolsRequest {
+    pub version_id: i32,
+    pub tools: Vec<ToolDefinition>,
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(default)]
+    pub tool_scripts: Vec<ToolScript>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunAgentRequest {
+    pub run_id: i32,
+    pub version_id: i32, // ツール定義とスクリプトを読み込むバージョン
     pub user_prompt: String,
     pub system_prompt: String,
     pub provider_type: ProviderType,
     pub model: String,
     pub temperature: f32,
+    pub top_p: Option<f32>,
     pub max_tokens: Option<u16>,
     pub response_format: Option<String>,
+    pub max_steps: Option<u8>, // 未指定の場合は10ステップ
+    #[serde(flatten)]
+    pub sampling: SamplingParameters,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AgentStopReason {
+    Completed, // ツール呼び出しのない回答が返った
+    StepLimit, // ステップ数の上限に達した
+    Cancelled, // 回答が返る前にキャンセルされた
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentTranscript {
+    pub messages: Vec<ChatMessage>,
+    pub stop_reason: AgentStopReason,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunAgentResponse {
+    pub answer: String, // 最後の回答
+    pub history_id: i32,
+    pub steps: i32,
+    pub transcript: AgentTranscript,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveComparingPromptSweepRequest {
+    pub manager_id: i32,
+    pub user_prompt: String,
+    pub provider_type: ProviderType,
+    pub model: String,
+    pub response_format: Option<String>,
+    pub repetitions: Option<i32>, // 未指定の場合は1回
+    pub definition: SweepDefinition,
+    pub credential_profile_id: Option<i32>, // 未指定の場合はプロンプトマネージャーで選択されたプロファイル
+    #[serde(flatten)]
+    pub sampling: SamplingParameters, // 全ての実行設定で共通
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveComparingPromptSweepResponse {
+    pub id: i32,
+    pub runs: Vec<SweepRunItem>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepRunItem {
+    pub run_id: i32,
+    pub temperature: f64,
+    pub top_p: Option<f64>,
+    pub max_tokens: Option<i32>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSweepMatrixRequest {
+    pub sweep_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSweepMatrixResponse {
+    pub sweep_id: i32,
+    pub definition: SweepDefinition,
+    pub cells: Vec<SweepMatrixCell>,
+}
+
+/// 実行設定（パラメータの組み合わせ）ごとの結果
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepMatrixCell {
+    pub run_id: i32,
+    pub temperature: f64,
+    pub top_p: Option<f64>,
+    pub max_tokens: Option<i32>,
+    pub histories: Vec<SweepMatrixHistory>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepMatrixHistory {
+    pub history_id: i32,
+    pub version_id: i32,
+    pub setting_id: i32,
+    pub sample_index: i32,
+    pub response: String,
+    pub cancelled: bool,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RunChatResponse {
+    pub answer: String, // 最初のサンプルの回答
+    pub history_id: Option<i32>,
+    pub samples: Vec<RunChatSample>,
+    pub prompt_tokens: Option<i32>, // トークン数を数えられないモデルの場合はNone
+    pub completion_tokens: Option<i32>, // 全てのサンプルの回答のトークン数の合計
+    pub cache_hit: bool,            // キャッシュから回答を返した場合はtrue
+    pub cancelled: bool,            // 回答が返る前にキャンセルされた場合はtrue（回答は空）
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRunRequest {
+    pub run_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRunResponse {
+    pub cancelled: i32, // キャンセルした実行中の処理数（0の場合も、直後に登録される処理はキャンセルされる）
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearResponseCacheRequest {
+    pub provider_type: Option<ProviderType>, // 未指定の場合は全てのプロバイダー
+    pub model: Option<String>,               // 未指定の場合は全てのモデル
+    #[serde(default)]
+    pub expired_only: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearResponseCacheResponse {
+    pub deleted: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightRunRequest {
+    pub run_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightRunResponse {
+    pub model: String,
+    pub encoding: Option<TokenEncoding>, // トークン数を数えられないモデルの場合はNone
+    pub context_window: Option<i32>,
+    pub max_tokens: Option<i32>,
+    pub versions: Vec<VersionTokenCount>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionTokenCount {
+    pub setting_id: i32,
+    pub version_id: i32,
+    pub version: i32,
+    pub prompt_tokens: Option<i32>,
+    pub fits: bool, // プロンプトとmax_tokensの合計がコンテキストウィンドウに収まるか
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunChatSample {
+    pub sample_index: i32,
     pub answer: String,
+    pub history_id: Option<i32>,
+    pub drift: Option<SampleDrift>, // 設定と入力の組み合わせにベースラインがある場合のみ設定する
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConsistencyMetricsRequest {
+    pub run_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConsistencyMetricsResponse {
+    pub run_id: i32,
+    pub versions: Vec<VersionConsistencyItem>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionConsistencyItem {
+    pub version_id: i32,
+    pub setting_id: i32,
+    pub sample_count: usize,
+    pub unique_answer_ratio: f64, // 正規化した回答の種類数 / サンプル数
+    pub json_validity_rate: f64,  // JSONとしてパースできた回答の割合
+    pub pairwise_similarity: Option<SimilarityDistribution>, // サンプルが2件未満の場合はNone
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityDistribution {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub histogram: Vec<usize>, // 0.0〜1.0をSIMILARITY_HISTOGRAM_BINS等分した区間ごとのペア数
 }
 
 #[async_trait]
@@ -105,32 +394,127 @@ pub trait ComparingPrompt: Send + Sync {
         request: GetComparingPromptSettingsRequest,
     ) -> Result<GetComparingPromptSettingsResponse, ApplicationError>;
 
+    async fn delete_comparing_prompt_setting(
+        &self,
+        request: DeleteComparingPromptSettingRequest,
+    ) -> Result<(), ApplicationError>;
+
+    async fn restore_comparing_prompt_setting(
+        &self,
+        request: RestoreComparingPromptSettingRequest,
+    ) -> Result<(), ApplicationError>;
+
     async fn save_run(
         &self,
         request: SaveComparingPromptRunRequest,
     ) -> Result<SaveComparingPromptRunResponse, ApplicationError>;
 
     async fn run_chat(&self, request: RunChatRequest) -> Result<RunChatResponse, ApplicationError>;
+
+    async fn get_consistency_metrics(
+        &self,
+        request: GetConsistencyMetricsRequest,
+    ) -> Result<GetConsistencyMetricsResponse, ApplicationError>;
+
+    async fn save_sweep(
+        &self,
+        request: SaveComparingPromptSweepRequest,
+    ) -> Result<SaveComparingPromptSweepResponse, ApplicationError>;
+
+    async fn get_sweep_matrix(
+        &self,
+        request: GetSweepMatrixRequest,
+    ) -> Result<GetSweepMatrixResponse, ApplicationError>;
+
+    async fn save_version_tools(
+        &self,
+        request: SaveVersionToolsRequest,
+    ) -> Result<(), ApplicationError>;
+
+    async fn run_agent(
+        &self,
+        request: RunAgentRequest,
+    ) -> Result<RunAgentResponse, ApplicationError>;
+
+    async fn preflight_run(
+        &self,
+        request: PreflightRunRequest,
+    ) -> Result<PreflightRunResponse, ApplicationError>;
+
+    async fn clear_response_cache(
+        &self,
+        request: ClearResponseCacheRequest,
+    ) -> Result<ClearResponseCacheResponse, ApplicationError>;
+
+    async fn cancel_run(
+        &self,
+        request: CancelRunRequest,
+    ) -> Result<CancelRunResponse, ApplicationError>;
 }
 
-#[derive(Clone, Debug)]
-pub struct ChatUsecase<T, R, U>
+#[derive(Debug)]
+pub struct ChatUsecase<T, R, U, K, M, C, P, D>
 where
     T: AIChat,
     R: ComparingPromptSettingRepository,
     U: ComparingPromptRunRepository,
+    K: Tokenizer,
+    M: ModelCatalogRepository,
+    C: ResponseCacheRepository,
+    P: CredentialResolver,
+    D: DriftDetector,
 {
     ai_chat: Arc<T>,
     comparing_prompt_setting_repository: Arc<R>,
     comparing_prompt_run_repository: Arc<U>,
+    tokenizer: Arc<K>,
+    model_catalog_repository: Arc<M>,
+    response_cache_repository: Arc<C>,
+    credential_resolver: Arc<P>,
+    drift_detector: Arc<D>,
+    run_cancellations: Arc<RunCancellations>,
+}
+
+/// ジョブのワーカーとcontrollerで同じキャンセルトークンを共有するため、フィールドのArcを複製する
+impl<T, R, U, K, M, C, P, D> Clone for ChatUsecase<T, R, U, K, M, C, P, D>
+where
+    T: AIChat,
+    R: ComparingPromptSettingRepository,
+    U: ComparingPromptRunRepository,
+    K: Tokenizer,
+    M: ModelCatalogRepository,
+    C: ResponseCacheRepository,
+    P: CredentialResolver,
+    D: DriftDetector,
+{
+    fn clone(&self) -> Self {
+        ChatUsecase {
+            ai_chat: Arc::clone(&self.ai_chat),
+            comparing_prompt_setting_repository: Arc::clone(
+                &self.comparing_prompt_setting_repository,
+            ),
+            comparing_prompt_run_repository: Arc::clone(&self.comparing_prompt_run_repository),
+            tokenizer: Arc::clone(&self.tokenizer),
+            model_catalog_repository: Arc::clone(&self.model_catalog_repository),
+            response_cache_repository: Arc::clone(&self.response_cache_repository),
+            credential_resolver: Arc::clone(&self.credential_resolver),
+            drift_detector: Arc::clone(&self.drift_detector),
+            run_cancellations: Arc::clone(&self.run_cancellations),
+        }
+    }
 }
 
 #[async_trait]
-impl<T, R, U> ComparingPrompt for ChatUsecase<T, R, U>
+impl<T, R, U, K, M, C, P, D> ComparingPrompt for ChatUsecase<T, R, U, K, M, C, P, D>
 where
     T: AIChat,
     R: ComparingPromptSettingRepository,
     U: ComparingPromptRunRepository,
+    K: Tokenizer,
+    M: ModelCatalogRepository,
+    C: ResponseCacheRepository,
+    P: CredentialResolver,
+    D: DriftDetector,
 {
     async fn add_comparing_prompt_setting(
         &self,
@@ -179,10 +563,47 @@ where
         Ok(GetComparingPromptSettingsResponse { settings })
     }
 
+    async fn delete_comparing_prompt_setting(
+        &self,
+        request: DeleteComparingPromptSettingRequest,
+    ) -> Result<(), ApplicationError> {
+        self.comparing_prompt_setting_repository
+            .logical_delete_comparing_prompt_setting(request.id)
+            .await
+    }
+
+    async fn restore_comparing_prompt_setting(
+        &self,
+        request: RestoreComparingPromptSettingRequest,
+    ) -> Result<(), ApplicationError> {
+        self.comparing_prompt_setting_repository
+            .restore_comparing_prompt_setting(request.id)
+            .await
+    }
+
     async fn save_run(
         &self,
         request: SaveComparingPromptRunRequest,
     ) -> Result<SaveComparingPromptRunResponse, ApplicationError> {
+        let repetitions = request.repetitions.unwrap_or(1);
+        validate_repetitions(repetitions)?;
+        validate_max_tokens(request.max_tokens)?;
+        validate_sampling_parameters(&request.provider_type, request.top_p, &request.sampling)?;
+        self.validate_model(
+            &request.provider_type,
+            &request.model,
+            request.response_format.as_deref(),
+            request.max_tokens,
+        )
+        .await?;
+        let credential_profile_id = self
+            .credential_resolver
+            .resolve_credential_profile_id(
+                request.manager_id,
+                &request.provider_type,
+                request.credential_profile_id,
+            )
+            .await?;
         let run = ComparingPromptSettingRunModel {
             id: 0,
             manager_id: request.manager_id,
@@ -192,6 +613,11 @@ where
             temperature: request.temperature,
             max_tokens: request.max_tokens,
             response_format: request.response_format.clone(),
+            repetitions,
+            top_p: request.top_p,
+            sweep_id: None,
+            sampling: request.sampling,
+            credential_profile_id,
         };
         let run_id = self
             .comparing_prompt_run_repository
@@ -201,157 +627,1609 @@ where
     }
 
     async fn run_chat(&self, request: RunChatRequest) -> Result<RunChatResponse, ApplicationError> {
+        // バージョンが指定されている場合はバージョンに定義されたツールを使う
+        let (tools, tool_choice, setting_id) = match request.version_id {
+            Some(version_id) => {
+                let version = self
+                    .comparing_prompt_setting_repository
+                    .find_comparing_prompt_setting_version_by_id(version_id)
+                    .await?;
+                (version.tools, version.tool_choice, Some(version.setting_id))
+            }
+            None => (Vec::new(), None, None),
+        };
+        let history_target = request.version_id.map(|version_id| RunHistoryTarget {
+            run_id: request.run_id,
+            version_id,
+            job_item_id: request.job_item_id,
+        });
         let settings = ChatSettings {
             id: 0,
+            provider_type: request.provider_type.clone(),
             user_prompt: request.user_prompt.clone(),
             system_prompt: request.system_prompt.clone(),
             model: request.model.clone(),
             max_tokens: request.max_tokens,
             temperature: request.temperature,
+            top_p: request.top_p,
             response_format: request.response_format.clone(),
+            sampling: request.sampling.clone(),
+            tools,
+            tool_choice,
+            credential: self.find_run_credential(request.run_id).await?,
+        };
+        let repetitions = request.repetitions.unwrap_or(1);
+        validate_repetitions(repetitions as i32)?;
+        validate_sampling_parameters(
+            &request.provider_type,
+            request.top_p.map(|top_p| top_p as f64),
+            &request.sampling,
+        )?;
+        // 送信前にコンテキストウィンドウに収まるか確認する
+        let token_model = self
+            .credential_resolver
+            .resolve_token_model(&request.provider_type, &request.model)
+            .await?;
+        let prompt_tokens = self.check_context_window(
+            &token_model,
+            &request.system_prompt,
+            &request.user_prompt,
+            request.max_tokens,
+        )?;
+        // キャッシュは同じ入力から同じ回答が期待できる設定の場合のみ使う
+        let cache_key = if request.use_cache && is_cacheable(&settings) {
+            Some(response_cache_key(
+                &request.provider_type,
+                &settings,
+                repetitions,
+            )?)
+        } else {
+            None
+        };
+        let cached = match &cache_key {
+            Some(cache_key) => {
+                self.response_cache_repository
+                    .find_response_cache(cache_key)
+                    .await?
+            }
+            None => None,
         };
-        let res = self.ai_chat.do_chat(&settings).await;
-        // if let Err(err) = res {
-        //     log::error!("post_chat error: {}", err);
-        //     return Err(err);
-        // }
-        // TODO 結果を登録する
-        match res {
-            Ok(response) => Ok(RunChatResponse { answer: response }),
-            Err(err) => {
-                log::error!("post_chat error: {}", err);
-                Err(err)
+        let cache_hit = cached.is_some();
+        let answers = match cached {
+            Some(cached) => cached.responses,
+            None => {
+                // 実行がキャンセルされた場合は残りの回答を待たずに中断する
+                // 完了したサンプルの回答はcompletedに残るので、キャンセルしても履歴に登録する
+                let cancellation = self.run_cancellations.register(request.run_id);
+                let mut completed = Vec::with_capacity(repetitions as usize);
+                let chat = async {
+                    if repetitions == 1 {
+                        completed.push(self.ai_chat.do_chat(&settings).await?);
+                        Ok(())
+                    } else {
+                        self.ai_chat
+                            .do_chat_samples(&settings, repetitions, &mut completed)
+                            .await
+                    }
+                };
+                let res = tokio::select! {
+                    _ = cancellation.token().cancelled() => None,
+                    res = chat => Some(res),
+                };
+                let res = match res {
+                    Some(res) => res,
+                    None => {
+                        let completion_tokens =
+                            self.count_completion_tokens(&token_model, &completed);
+                        return self
+                            .cancel_chat(
+                                request.run_id,
+                                history_target.as_ref(),
+                                repetitions,
+                                completed,
+                                prompt_tokens,
+                                completion_tokens,
+                            )
+                            .await;
+                    }
+                };
+                let answers = match res {
+                    Ok(()) => completed,
+                    Err(err) => {
+                        log::error!("post_chat error: {}", err);
+                        return Err(err);
+                    }
+                };
+                if let Some(cache_key) = &cache_key {
+                    self.response_cache_repository
+                        .save_response_cache(
+                            cache_key,
+                            &request.provider_type,
+                            &request.model,
+                            &answers,
+                            request
+                                .cache_ttl_seconds
+                                .unwrap_or(DEFAULT_CACHE_TTL_SECONDS),
+                        )
+                        .await?;
+                }
+                answers
             }
+        };
+
+        let completion_tokens = self.count_completion_tokens(&token_model, &answers);
+
+        // 設定と入力の組み合わせにベースラインがある場合は、サンプルごとにドリフトを判定する
+        // 判定に失敗しても回答は得られているので、ドリフトなしで結果を返す
+        let drifts = match setting_id {
+            Some(setting_id) => match self
+                .drift_detector
+                .detect_drift(setting_id, &request.user_prompt, &answers)
+                .await
+            {
+                Ok(drifts) => drifts,
+                Err(err) => {
+                    log::error!("detect_drift error: {}", err);
+                    None
+                }
+            },
+            None => None,
+        };
+        let mut drifts = drifts.unwrap_or_default().into_iter();
+
+        // バージョンが指定されている場合は結果をサンプルごとに履歴に登録する
+        let mut samples = Vec::with_capacity(answers.len());
+        for (sample_index, answer) in answers.into_iter().enumerate() {
+            let sample_index = sample_index as i32;
+            let history_id = match &history_target {
+                Some(history_target) => Some(
+                    self.comparing_prompt_run_repository
+                        .create_comparing_prompt_run_history(
+                            history_target,
+                            sample_index,
+                            &answer,
+                            None,
+                            cache_hit,
+                        )
+                        .await?,
+                ),
+                None => None,
+            };
+            samples.push(RunChatSample {
+                sample_index,
+                answer,
+                history_id,
+                drift: drifts.next(),
+            });
         }
+
+        let first = samples.first().ok_or(ApplicationError::EmptyResult)?;
+        Ok(RunChatResponse {
+            answer: first.answer.clone(),
+            history_id: first.history_id,
+            samples,
+            prompt_tokens,
+            completion_tokens,
+            cache_hit,
+            cancelled: false,
+        })
     }
-}
 
-impl<T, R, U> ChatUsecase<T, R, U>
-where
-    T: AIChat,
-    R: ComparingPromptSettingRepository,
-    U: ComparingPromptRunRepository,
-{
-    pub fn new(
-        chat: Arc<T>,
-        comparing_prompt_setting_repository: Arc<R>,
-        comparing_prompt_run_repository: Arc<U>,
-    ) -> Self {
-        ChatUsecase {
-            ai_chat: chat,
-            comparing_prompt_setting_repository,
-            comparing_prompt_run_repository,
+    async fn get_consistency_metrics(
+        &self,
+        request: GetConsistencyMetricsRequest,
+    ) -> Result<GetConsistencyMetricsResponse, ApplicationError> {
+        let histories = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_histories_by_run_id(request.run_id)
+            .await?;
+
+        // バージョンごとにサンプルをまとめる（キャンセルされた履歴は回答がないので除く）
+        let mut grouped: BTreeMap<i32, Vec<ComparingPromptRunHistoryModel>> = BTreeMap::new();
+        for history in histories.into_iter().filter(|history| !history.cancelled) {
+            grouped.entry(history.version_id).or_default().push(history);
         }
+
+        let versions = grouped
+            .into_iter()
+            .map(|(version_id, histories)| {
+                let answers: Vec<&str> = histories.iter().map(|h| h.response.as_str()).collect();
+                VersionConsistencyItem {
+                    version_id,
+                    setting_id: histories[0].setting_id,
+                    sample_count: answers.len(),
+                    unique_answer_ratio: unique_answer_ratio(&answers),
+                    json_validity_rate: json_validity_rate(&answers),
+                    pairwise_similarity: pairwise_similarity_distribution(&answers),
+                }
+            })
+            .collect();
+        Ok(GetConsistencyMetricsResponse {
+            run_id: request.run_id,
+            versions,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use async_trait::async_trait;
-    use sea_orm::DbErr;
+    async fn save_sweep(
+        &self,
+        request: SaveComparingPromptSweepRequest,
+    ) -> Result<SaveComparingPromptSweepResponse, ApplicationError> {
+        let repetitions = request.repetitions.unwrap_or(1);
+        validate_repetitions(repetitions)?;
+        let configurations = expand_sweep(&request.definition)?;
+        let top_p = request.definition.top_p.as_ref().map(|_| 1.0);
+        validate_sampling_parameters(&request.provider_type, top_p, &request.sampling)?;
+        let max_tokens = configurations
+            .iter()
+            .filter_map(|(_, _, max_tokens)| *max_tokens)
+            .max();
+        self.validate_model(
+            &request.provider_type,
+            &request.model,
+            request.response_format.as_deref(),
+            max_tokens,
+        )
+        .await?;
+        let credential_profile_id = self
+            .credential_resolver
+            .resolve_credential_profile_id(
+                request.manager_id,
+                &request.provider_type,
+                request.credential_profile_id,
+            )
+            .await?;
 
-    use crate::common::errors::ApplicationError;
-    use crate::domain::chat::ChatSettings;
-    use crate::domain::comparing_prompt::ComparingPromptSettingModel;
+        let runs = configurations
+            .iter()
+            .map(
+                |(temperature, top_p, max_tokens)| ComparingPromptSettingRunModel {
+                    id: 0,
+                    manager_id: request.manager_id,
+                    user_prompt: request.user_prompt.clone(),
+                    provider_type: request.provider_type.clone(),
+                    model: request.model.clone(),
+                    temperature: *temperature,
+                    max_tokens: *max_tokens,
+                    response_format: request.response_format.clone(),
+                    repetitions,
+                    top_p: *top_p,
+                    sweep_id: None, // 登録時にリポジトリで設定される
+                    sampling: request.sampling.clone(),
+                    credential_profile_id,
+                },
+            )
+            .collect();
+        let (sweep_id, run_ids) = self
+            .comparing_prompt_run_repository
+            .create_comparing_prompt_sweep(request.manager_id, &request.definition, runs)
+            .await?;
 
-    use super::*;
+        let runs = run_ids
+            .into_iter()
+            .zip(configurations)
+            .map(|(run_id, (temperature, top_p, max_tokens))| SweepRunItem {
+                run_id,
+                temperature,
+                top_p,
+                max_tokens,
+            })
+            .collect();
+        Ok(SaveComparingPromptSweepResponse { id: sweep_id, runs })
+    }
 
-    /**
-     * Mocks
-     */
-    struct MockAIChat {}
-    struct MockComparingPromptSettingRepository {}
-    struct MockComparingPromptRunRepository {}
-    #[async_trait]
-    impl AIChat for MockAIChat {
-        async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
-            Ok("Test response".to_string())
+    async fn get_sweep_matrix(
+        &self,
+        request: GetSweepMatrixRequest,
+    ) -> Result<GetSweepMatrixResponse, ApplicationError> {
+        let sweep = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_sweep_by_id(request.sweep_id)
+            .await?;
+        let runs = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_runs_by_sweep_id(sweep.id)
+            .await?;
+
+        let mut cells = Vec::with_capacity(runs.len());
+        for run in runs {
+            let histories = self
+                .comparing_prompt_run_repository
+                .find_comparing_prompt_run_histories_by_run_id(run.id)
+                .await?
+                .into_iter()
+                .map(|history| SweepMatrixHistory {
+                    history_id: history.id,
+                    version_id: history.version_id,
+                    setting_id: history.setting_id,
+                    sample_index: history.sample_index,
+                    response: history.response,
+                    cancelled: history.cancelled,
+                })
+                .collect();
+            cells.push(SweepMatrixCell {
+                run_id: run.id,
+                temperature: run.temperature,
+                top_p: run.top_p,
+                max_tokens: run.max_tokens,
+                histories,
+            });
         }
+        Ok(GetSweepMatrixResponse {
+            sweep_id: sweep.id,
+            definition: sweep.definition,
+            cells,
+        })
     }
 
-    #[async_trait]
-    impl ComparingPromptRunRepository for MockComparingPromptRunRepository {
-        async fn find_comparing_prompt_run_by_id(
-            &self,
-            id: i32,
-        ) -> Result<ComparingPromptSettingRunModel, ApplicationError> {
-            Ok(ComparingPromptSettingRunModel {
-                id,
-                manager_id: 1,
-                user_prompt: "test_user_prompt".to_string(),
-                provider_type: ProviderType::OpenAI,
-                model: "test_model".to_string(),
-                temperature: 0.0,
-                max_tokens: None,
-                response_format: None,
+    async fn save_version_tools(
+        &self,
+        request: SaveVersionToolsRequest,
+    ) -> Result<(), ApplicationError> {
+        validate_tools(&request.tools, request.tool_choice.as_ref())?;
+        validate_tool_scripts(&request.tools, &request.tool_scripts)?;
+        self.comparing_prompt_setting_repository
+            .update_comparing_prompt_setting_version_tools(
+                request.version_id,
+                &request.tools,
+                request.tool_choice.as_ref(),
+                &request.tool_scripts,
+            )
+            .await
+    }
+
+    async fn run_agent(
+        &self,
+        request: RunAgentRequest,
+    ) -> Result<RunAgentResponse, ApplicationError> {
+        let max_steps = request.max_steps.unwrap_or(DEFAULT_AGENT_MAX_STEPS);
+        if max_steps == 0 || max_steps > MAX_AGENT_STEPS {
+            return Err(ApplicationError::ValidationError(format!(
+                "max_steps must be between 1 and {}",
+                MAX_AGENT_STEPS
+            )));
+        }
+        validate_sampling_parameters(
+            &request.provider_type,
+            request.top_p.map(|top_p| top_p as f64),
+            &request.sampling,
+        )?;
+        let token_model = self
+            .credential_resolver
+            .resolve_token_model(&request.provider_type, &request.model)
+            .await?;
+        self.check_context_window(
+            &token_model,
+            &request.system_prompt,
+            &request.user_prompt,
+            request.max_tokens,
+        )?;
+        let version = self
+            .comparing_prompt_setting_repository
+            .find_comparing_prompt_setting_version_by_id(request.version_id)
+            .await?;
+        let settings = ChatSettings {
+            id: 0,
+            provider_type: request.provider_type.clone(),
+            user_prompt: request.user_prompt.clone(),
+            system_prompt: request.system_prompt.clone(),
+            model: request.model.clone(),
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            response_format: request.response_format.clone(),
+            sampling: request.sampling.clone(),
+            tools: version.tools,
+            tool_choice: version.tool_choice,
+            credential: self.find_run_credential(request.run_id).await?,
+        };
+
+        // ツール呼び出しがなくなるか上限に達するまで、スクリプトの結果を返しながら会話を続ける
+        let mut messages = vec![ChatMessage::User {
+            content: request.user_prompt.clone(),
+        }];
+        let mut steps = 0;
+        let cancellation = self.run_cancellations.register(request.run_id);
+        let (answer, stop_reason) = loop {
+            let res = tokio::select! {
+                _ = cancellation.token().cancelled() => break (None, AgentStopReason::Cancelled),
+                res = self.ai_chat.do_chat_messages(&settings, &messages) => res,
+            };
+            let answer = match res {
+                Ok(answer) => answer,
+                Err(err) => {
+                    log::error!("run_agent error: {}", err);
+                    return Err(err);
+                }
+            };
+            steps += 1;
+            messages.push(ChatMessage::Assistant {
+                content: answer.content.clone(),
+                tool_calls: answer.tool_calls.clone(),
+            });
+            if answer.tool_calls.is_empty() {
+                break (Some(answer), AgentStopReason::Completed);
+            }
+            for tool_call in &answer.tool_calls {
+                messages.push(ChatMessage::Tool {
+                    tool_call_id: tool_call.id.clone(),
+                    name: tool_call.name.clone(),
+                    content: resolve_tool_script(&version.tool_scripts, tool_call),
+                });
+            }
+            if steps >= max_steps as i32 {
+                break (Some(answer), AgentStopReason::StepLimit);
+            }
+        };
+
+        let answer = answer.map(|answer| answer.into_response()).transpose()?;
+        let transcript = AgentTranscript {
+            messages,
+            stop_reason,
+        };
+        let transcript_json = serde_json::to_string(&transcript)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+        // キャンセルされた場合は途中までの会話を残す
+        let history_target = RunHistoryTarget {
+            run_id: request.run_id,
+            version_id: request.version_id,
+            job_item_id: None,
+        };
+        let history_id = match &answer {
+            Some(answer) => {
+                self.comparing_prompt_run_repository
+                    .create_comparing_prompt_run_history(
+                        &history_target,
+                        0,
+                        answer,
+                        Some(&transcript_json),
+                        false,
+                    )
+                    .await?
+            }
+            None => {
+                self.comparing_prompt_run_repository
+                    .create_cancelled_comparing_prompt_run_history(
+                        &history_target,
+                        0,
+                        Some(&transcript_json),
+                    )
+                    .await?
+            }
+        };
+        let answer = answer.unwrap_or_default();
+        Ok(RunAgentResponse {
+            answer,
+            history_id,
+            steps,
+            transcript,
+        })
+    }
+
+    async fn preflight_run(
+        &self,
+        request: PreflightRunRequest,
+    ) -> Result<PreflightRunResponse, ApplicationError> {
+        let run = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_by_id(request.run_id)
+            .await?;
+        let settings = self
+            .comparing_prompt_setting_repository
+            .find_all_comparing_prompt_settings_by_manager_id(run.manager_id)
+            .await?;
+        let max_tokens = run.max_tokens.map(|max_tokens| max_tokens.max(0) as usize);
+        let token_model = self
+            .credential_resolver
+            .resolve_token_model(&run.provider_type, &run.model)
+            .await?;
+        let limit = self.tokenizer.model_limit(&token_model);
+
+        // 設定ごとに現在のバージョンのプロンプトのトークン数を数える
+        let versions = settings
+            .iter()
+            .filter_map(|setting| {
+                setting
+                    .versions
+                    .iter()
+                    .find(|version| version.version == setting.current_version)
+            })
+            .map(|version| {
+                let usage = self.tokenizer.context_window_usage(
+                    &token_model,
+                    &version.system_prompt,
+                    &run.user_prompt,
+                    max_tokens,
+                );
+                VersionTokenCount {
+                    setting_id: version.setting_id,
+                    version_id: version.id,
+                    version: version.version,
+                    prompt_tokens: usage.as_ref().map(|usage| usage.prompt_tokens as i32),
+                    fits: usage.as_ref().is_none_or(|usage| usage.fits()),
+                }
             })
+            .collect();
+        Ok(PreflightRunResponse {
+            model: run.model,
+            encoding: limit.as_ref().map(|limit| limit.encoding),
+            context_window: limit.map(|limit| limit.context_window as i32),
+            max_tokens: run.max_tokens,
+            versions,
+        })
+    }
+
+    async fn clear_response_cache(
+        &self,
+        request: ClearResponseCacheRequest,
+    ) -> Result<ClearResponseCacheResponse, ApplicationError> {
+        let deleted = self
+            .response_cache_repository
+            .delete_response_caches(
+                request.provider_type.as_ref(),
+                request.model.as_deref(),
+                request.expired_only,
+            )
+            .await?;
+        Ok(ClearResponseCacheResponse {
+            deleted: deleted as i32,
+        })
+    }
+
+    async fn cancel_run(
+        &self,
+        request: CancelRunRequest,
+    ) -> Result<CancelRunResponse, ApplicationError> {
+        let cancelled = self.run_cancellations.cancel(request.run_id);
+        Ok(CancelRunResponse {
+            cancelled: cancelled as i32,
+        })
+    }
+}
+
+/// スクリプトが定義済みのツールを指しており、重複していないか検証する
+fn validate_tool_scripts(
+    tools: &[ToolDefinition],
+    tool_scripts: &[ToolScript],
+) -> Result<(), ApplicationError> {
+    let mut names = HashSet::new();
+    for script in tool_scripts {
+        if !tools.iter().any(|tool| tool.name == script.name) {
+            return Err(ApplicationError::ValidationError(format!(
+                "tool script refers to unknown tool: {}",
+                script.name
+            )));
         }
+        if !names.insert(script.name.as_str()) {
+            return Err(ApplicationError::ValidationError(format!(
+                "duplicate tool script: {}",
+                script.name
+            )));
+        }
+    }
+    Ok(())
+}
 
-        async fn create_comparing_prompt_run(
-            &self,
-            _param: ComparingPromptSettingRunModel,
-        ) -> Result<i32, ApplicationError> {
-            Ok(1)
+/// ツール呼び出しに対するスクリプトの結果を返す
+/// 引数に部分一致する最初のケース、なければdefaultを使い、どちらもなければエラーを返す
+fn resolve_tool_script(tool_scripts: &[ToolScript], tool_call: &ToolCall) -> String {
+    let arguments: serde_json::Value =
+        serde_json::from_str(&tool_call.arguments).unwrap_or(serde_json::Value::Null);
+    let result = tool_scripts
+        .iter()
+        .find(|script| script.name == tool_call.name)
+        .and_then(|script| {
+            script
+                .cases
+                .iter()
+                .find(|case| json_partial_match(&case.arguments, &arguments))
+                .map(|case| &case.result)
+                .or(script.default.as_ref())
+        });
+    match result {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+        None => serde_json::json!({
+            "error": format!("no scripted result for tool: {}", tool_call.name)
+        })
+        .to_string(),
+    }
+}
+
+/// patternのキーがすべてvalueに含まれ、値が一致するか判定する（オブジェクトは再帰的に比較する）
+fn json_partial_match(pattern: &serde_json::Value, value: &serde_json::Value) -> bool {
+    match (pattern, value) {
+        (serde_json::Value::Object(pattern), serde_json::Value::Object(value)) => {
+            pattern.iter().all(|(key, expected)| {
+                value
+                    .get(key)
+                    .is_some_and(|actual| json_partial_match(expected, actual))
+            })
         }
+        _ => pattern == value,
     }
+}
 
-    #[async_trait]
-    impl ComparingPromptSettingRepository for MockComparingPromptSettingRepository {
-        async fn find_comparing_prompt_setting_by_id(
-            &self,
-            _id: i32,
-        ) -> Result<ComparingPromptSettingModel, ApplicationError> {
-            Ok(ComparingPromptSettingModel {
-                id: 1,
-                manager_id: 1,
-                current_version: 1,
-                versions: vec![],
+/// ツール名の重複や、定義されていないツールの指定がないか検証する
+fn validate_tools(
+    tools: &[ToolDefinition],
+    tool_choice: Option<&ToolChoice>,
+) -> Result<(), ApplicationError> {
+    let mut names = HashSet::new();
+    for tool in tools {
+        // OpenAIのfunction名の制約に合わせる
+        let valid_name = !tool.name.is_empty()
+            && tool.name.len() <= MAX_TOOL_NAME_LENGTH
+            && tool
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            return Err(ApplicationError::ValidationError(format!(
+                "invalid tool name: {}",
+                tool.name
+            )));
+        }
+        if !names.insert(tool.name.as_str()) {
+            return Err(ApplicationError::ValidationError(format!(
+                "duplicate tool name: {}",
+                tool.name
+            )));
+        }
+        if !tool.parameters.is_object() {
+            return Err(ApplicationError::ValidationError(format!(
+                "parameters of tool {} must be a JSON Schema object",
+                tool.name
+            )));
+        }
+    }
+
+    match tool_choice {
+        Some(ToolChoice::Function { name }) if !names.contains(name.as_str()) => {
+            Err(ApplicationError::ValidationError(format!(
+                "tool_choice refers to unknown tool: {}",
+                name
+            )))
+        }
+        Some(_) if tools.is_empty() => Err(ApplicationError::ValidationError(
+            "tool_choice requires at least one tool".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// スイープ定義をtemperature, top_p, max_tokensの直積に展開する
+fn expand_sweep(
+    definition: &SweepDefinition,
+) -> Result<Vec<(f64, Option<f64>, Option<i32>)>, ApplicationError> {
+    let temperatures = expand_sweep_values("temperature", &definition.temperature)?;
+    validate_sweep_range("temperature", &temperatures, 0.0, 2.0)?;
+
+    // 未指定のパラメータはプロバイダーのデフォルト値を使うためNoneとする
+    let top_ps = match &definition.top_p {
+        Some(values) => {
+            let values = expand_sweep_values("top_p", values)?;
+            validate_sweep_range("top_p", &values, 0.0, 1.0)?;
+            values.into_iter().map(Some).collect()
+        }
+        None => vec![None],
+    };
+    let max_tokens = match &definition.max_tokens {
+        Some(values) => {
+            let values = expand_sweep_values("max_tokens", values)?;
+            validate_sweep_range("max_tokens", &values, 1.0, u16::MAX as f64)?;
+            if values.iter().any(|v| v.fract() != 0.0) {
+                return Err(ApplicationError::ValidationError(
+                    "max_tokens must be integers".to_string(),
+                ));
+            }
+            values.into_iter().map(|v| Some(v as i32)).collect()
+        }
+        None => vec![None],
+    };
+
+    let count = temperatures.len() * top_ps.len() * max_tokens.len();
+    if count > MAX_SWEEP_CONFIGURATIONS {
+        return Err(ApplicationError::ValidationError(format!(
+            "sweep expands to {} configurations, but the maximum is {}",
+            count, MAX_SWEEP_CONFIGURATIONS
+        )));
+    }
+
+    let mut configurations = Vec::with_capacity(count);
+    for temperature in &temperatures {
+        for top_p in &top_ps {
+            for max_token in &max_tokens {
+                configurations.push((*temperature, *top_p, *max_token));
+            }
+        }
+    }
+    Ok(configurations)
+}
+
+fn expand_sweep_values(name: &str, values: &SweepValues) -> Result<Vec<f64>, ApplicationError> {
+    let expanded = match values {
+        SweepValues::List { values } => values.clone(),
+        SweepValues::Range { start, end, step } => {
+            if !(*step > 0.0) || start > end {
+                return Err(ApplicationError::ValidationError(format!(
+                    "{} range must satisfy start <= end and step > 0",
+                    name
+                )));
+            }
+            // 浮動小数点の誤差でendが含まれなくなるのを防ぐ
+            let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+            if count > MAX_SWEEP_CONFIGURATIONS {
+                return Err(ApplicationError::ValidationError(format!(
+                    "{} range expands to more than {} values",
+                    name, MAX_SWEEP_CONFIGURATIONS
+                )));
+            }
+            (0..count)
+                .map(|i| ((start + step * i as f64) * 1e6).round() / 1e6)
+                .collect()
+        }
+    };
+    if expanded.is_empty() {
+        return Err(ApplicationError::ValidationError(format!(
+            "{} must have at least one value",
+            name
+        )));
+    }
+    Ok(expanded)
+}
+
+fn validate_sweep_range(
+    name: &str,
+    values: &[f64],
+    min: f64,
+    max: f64,
+) -> Result<(), ApplicationError> {
+    if values.iter().any(|v| !(min..=max).contains(v)) {
+        return Err(ApplicationError::ValidationError(format!(
+            "{} must be between {} and {}",
+            name, min, max
+        )));
+    }
+    Ok(())
+}
+
+/// プロバイダーが対応していないパラメータや範囲外の値が指定されていないか検証する
+fn validate_sampling_parameters(
+    provider_type: &ProviderType,
+    top_p: Option<f64>,
+    sampling: &SamplingParameters,
+) -> Result<(), ApplicationError> {
+    let mut specified = sampling.specified();
+    if top_p.is_some() {
+        specified.insert(0, SamplingParameter::TopP);
+    }
+    let unsupported: Vec<String> = specified
+        .iter()
+        .filter(|parameter| !provider_type.supports(parameter))
+        .map(|parameter| parameter.to_string())
+        .collect();
+    if !unsupported.is_empty() {
+        return Err(ApplicationError::ValidationError(format!(
+            "{} does not support parameters: {}",
+            provider_type,
+            unsupported.join(", ")
+        )));
+    }
+
+    if let Some(top_p) = top_p {
+        if !(0.0..=1.0).contains(&top_p) {
+            return Err(ApplicationError::ValidationError(
+                "top_p must be between 0 and 1".to_string(),
+            ));
+        }
+    }
+    for (name, penalty) in [
+        ("frequency_penalty", sampling.frequency_penalty),
+        ("presence_penalty", sampling.presence_penalty),
+    ] {
+        if let Some(penalty) = penalty {
+            if !(-2.0..=2.0).contains(&penalty) {
+                return Err(ApplicationError::ValidationError(format!(
+                    "{} must be between -2 and 2",
+                    name
+                )));
+            }
+        }
+    }
+    if let Some(stop) = &sampling.stop {
+        if stop.is_empty() || stop.len() > MAX_STOP_SEQUENCES {
+            return Err(ApplicationError::ValidationError(format!(
+                "stop must have between 1 and {} sequences",
+                MAX_STOP_SEQUENCES
+            )));
+        }
+        if stop.iter().any(|sequence| sequence.is_empty()) {
+            return Err(ApplicationError::ValidationError(
+                "stop sequences must not be empty".to_string(),
+            ));
+        }
+    }
+    if let Some(logit_bias) = &sampling.logit_bias {
+        for (token, bias) in logit_bias {
+            if token.parse::<u32>().is_err() {
+                return Err(ApplicationError::ValidationError(format!(
+                    "logit_bias key must be a token id: {}",
+                    token
+                )));
+            }
+            if !(-100..=100).contains(bias) {
+                return Err(ApplicationError::ValidationError(
+                    "logit_bias must be between -100 and 100".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_repetitions(repetitions: i32) -> Result<(), ApplicationError> {
+    if !(1..=MAX_REPETITIONS).contains(&repetitions) {
+        return Err(ApplicationError::ValidationError(format!(
+            "repetitions must be between 1 and {}",
+            MAX_REPETITIONS
+        )));
+    }
+    Ok(())
+}
+
+/// 実行時にu16へ変換するので、1からu16::MAXまでに制限する
+fn validate_max_tokens(max_tokens: Option<i32>) -> Result<(), ApplicationError> {
+    if let Some(max_tokens) = max_tokens {
+        if !(1..=u16::MAX as i32).contains(&max_tokens) {
+            return Err(ApplicationError::ValidationError(format!(
+                "max_tokens must be between 1 and {}",
+                u16::MAX
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 正規化した回答の種類数 / サンプル数
+fn unique_answer_ratio(answers: &[&str]) -> f64 {
+    if answers.is_empty() {
+        return 0.0;
+    }
+    let unique: HashSet<String> = answers.iter().map(|a| normalize_text(a)).collect();
+    unique.len() as f64 / answers.len() as f64
+}
+
+/// JSONとしてパースできた回答の割合
+fn json_validity_rate(answers: &[&str]) -> f64 {
+    if answers.is_empty() {
+        return 0.0;
+    }
+    let valid = answers
+        .iter()
+        .filter(|a| serde_json::from_str::<serde_json::Value>(a.trim()).is_ok())
+        .count();
+    valid as f64 / answers.len() as f64
+}
+
+/// 全てのサンプルの組み合わせについて類似度を計算し、その分布を返す
+fn pairwise_similarity_distribution(answers: &[&str]) -> Option<SimilarityDistribution> {
+    let mut similarities = Vec::new();
+    for i in 0..answers.len() {
+        for j in (i + 1)..answers.len() {
+            similarities.push(token_similarity(answers[i], answers[j]));
+        }
+    }
+    if similarities.is_empty() {
+        return None;
+    }
+    similarities.sort_by(|a, b| a.total_cmp(b));
+
+    let len = similarities.len();
+    let median = if len % 2 == 0 {
+        (similarities[len / 2 - 1] + similarities[len / 2]) / 2.0
+    } else {
+        similarities[len / 2]
+    };
+    let mut histogram = vec![0; SIMILARITY_HISTOGRAM_BINS];
+    for similarity in &similarities {
+        let bin = (similarity * SIMILARITY_HISTOGRAM_BINS as f64) as usize;
+        // 1.0は最後の区間に含める
+        histogram[bin.min(SIMILARITY_HISTOGRAM_BINS - 1)] += 1;
+    }
+    Some(SimilarityDistribution {
+        min: similarities[0],
+        max: similarities[len - 1],
+        mean: similarities.iter().sum::<f64>() / len as f64,
+        median,
+        histogram,
+    })
+}
+
+impl<T, R, U, K, M, C, P, D> ChatUsecase<T, R, U, K, M, C, P, D>
+where
+    T: AIChat,
+    R: ComparingPromptSettingRepository,
+    U: ComparingPromptRunRepository,
+    K: Tokenizer,
+    M: ModelCatalogRepository,
+    C: ResponseCacheRepository,
+    P: CredentialResolver,
+    D: DriftDetector,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        chat: Arc<T>,
+        comparing_prompt_setting_repository: Arc<R>,
+        comparing_prompt_run_repository: Arc<U>,
+        tokenizer: Arc<K>,
+        model_catalog_repository: Arc<M>,
+        response_cache_repository: Arc<C>,
+        credential_resolver: Arc<P>,
+        drift_detector: Arc<D>,
+    ) -> Self {
+        ChatUsecase {
+            ai_chat: chat,
+            comparing_prompt_setting_repository,
+            comparing_prompt_run_repository,
+            tokenizer,
+            model_catalog_repository,
+            response_cache_repository,
+            credential_resolver,
+            drift_detector,
+            run_cancellations: Arc::new(RunCancellations::default()),
+        }
+    }
+
+    /// 実行に記録されたプロファイルの認証情報を取得する
+    /// プロファイルが記録されていない場合は、設定画面または環境変数のAPIキーを使う
+    async fn find_run_credential(
+        &self,
+        run_id: i32,
+    ) -> Result<Option<ProviderCredential>, ApplicationError> {
+        let run = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_by_id(run_id)
+            .await?;
+        match run.credential_profile_id {
+            Some(credential_profile_id) => Ok(Some(
+                self.credential_resolver
+                    .find_credential(credential_profile_id)
+                    .await?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// キャンセルされたチャットのサンプルを履歴に登録する
+    /// キャンセルまでに完了したサンプルは回答を登録して返し、残りはキャンセルされたサンプルとして登録する
+    async fn cancel_chat(
+        &self,
+        run_id: i32,
+        history_target: Option<&RunHistoryTarget>,
+        repetitions: u8,
+        completed: Vec<String>,
+        prompt_tokens: Option<i32>,
+        completion_tokens: Option<i32>,
+    ) -> Result<RunChatResponse, ApplicationError> {
+        log::info!(
+            "run_chat cancelled: run_id={}, completed={}/{}",
+            run_id,
+            completed.len(),
+            repetitions
+        );
+        let mut completed = completed.into_iter();
+        let mut samples = Vec::with_capacity(repetitions as usize);
+        for sample_index in 0..repetitions as i32 {
+            let answer = completed.next();
+            let history_id = match (history_target, &answer) {
+                (Some(history_target), Some(answer)) => Some(
+                    self.comparing_prompt_run_repository
+                        .create_comparing_prompt_run_history(
+                            history_target,
+                            sample_index,
+                            answer,
+                            None,
+                            false,
+                        )
+                        .await?,
+                ),
+                (Some(history_target), None) => Some(
+                    self.comparing_prompt_run_repository
+                        .create_cancelled_comparing_prompt_run_history(
+                            history_target,
+                            sample_index,
+                            None,
+                        )
+                        .await?,
+                ),
+                (None, _) => None,
+            };
+            samples.push(RunChatSample {
+                sample_index,
+                answer: answer.unwrap_or_default(),
+                history_id,
+                drift: None,
+            });
+        }
+        Ok(RunChatResponse {
+            answer: samples
+                .first()
+                .map(|sample| sample.answer.clone())
+                .unwrap_or_default(),
+            history_id: samples.first().and_then(|sample| sample.history_id),
+            samples,
+            prompt_tokens,
+            completion_tokens,
+            cache_hit: false,
+            cancelled: true,
+        })
+    }
+
+    /// 実行設定のモデルがモデルカタログに存在し、指定した機能に対応しているか確認する
+    /// モデル一覧を未取得のプロバイダーや、対応機能が分からないモデルは確認できないので検証しない
+    async fn validate_model(
+        &self,
+        provider_type: &ProviderType,
+        model: &str,
+        response_format: Option<&str>,
+        max_tokens: Option<i32>,
+    ) -> Result<(), ApplicationError> {
+        let catalog = self
+            .model_catalog_repository
+            .find_model_catalog(Some(provider_type))
+            .await?;
+        if catalog.is_empty() {
+            return Ok(());
+        }
+        let entry = catalog
+            .iter()
+            .find(|entry| entry.model == model)
+            .ok_or_else(|| {
+                ApplicationError::ValidationError(format!(
+                    "unknown model for {}: {}",
+                    provider_type, model
+                ))
+            })?;
+        if !entry.known {
+            return Ok(());
+        }
+        if response_format == Some(JSON_RESPONSE_FORMAT) && !entry.capabilities.json_mode {
+            return Err(ApplicationError::ValidationError(format!(
+                "{} does not support JSON mode",
+                model
+            )));
+        }
+        if let (Some(max_tokens), Some(context_window)) =
+            (max_tokens, entry.capabilities.context_window)
+        {
+            if max_tokens > context_window {
+                return Err(ApplicationError::ValidationError(format!(
+                    "max_tokens exceeds the context window of {} ({} tokens)",
+                    model, context_window
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// プロンプトがモデルのコンテキストウィンドウに収まるか確認し、プロンプトのトークン数を返す
+    /// トークン数を数えられないモデルの場合はNoneを返す
+    fn check_context_window(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        user_prompt: &str,
+        max_tokens: Option<u16>,
+    ) -> Result<Option<i32>, ApplicationError> {
+        let usage = match self.tokenizer.context_window_usage(
+            model,
+            system_prompt,
+            user_prompt,
+            max_tokens.map(|max_tokens| max_tokens as usize),
+        ) {
+            Some(usage) => usage,
+            None => return Ok(None),
+        };
+        if !usage.fits() {
+            return Err(ApplicationError::ValidationError(format!(
+                "prompt ({} tokens) + max_tokens ({}) exceeds the context window of {} ({} tokens)",
+                usage.prompt_tokens,
+                usage.max_tokens.unwrap_or(0),
+                model,
+                usage.context_window
+            )));
+        }
+        Ok(Some(usage.prompt_tokens as i32))
+    }
+
+    /// 回答のトークン数を数える（トークン数を数えられないモデルの場合はNone）
+    fn count_completion_tokens(&self, model: &str, answers: &[String]) -> Option<i32> {
+        let limit = self.tokenizer.model_limit(model)?;
+        Some(
+            answers
+                .iter()
+                .map(|answer| self.tokenizer.count_tokens(limit.encoding, answer) as i32)
+                .sum(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use sea_orm::DbErr;
+
+    use crate::common::errors::ApplicationError;
+    use crate::common::secret::SecretString;
+    use crate::domain::chat::{ChatAnswer, ChatSettings};
+    use crate::domain::comparing_prompt::{
+        ComparingPromptRunHistoryModel, ComparingPromptSettingModel,
+        ComparingPromptSettingVersionModel, ComparingPromptSweepModel, ToolScriptCase,
+    };
+    use crate::domain::model_catalog::ModelCatalogModel;
+    use crate::domain::response_cache::ResponseCacheModel;
+    use crate::domain::tokenizer::ModelTokenLimit;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    struct MockAIChat {}
+    struct MockComparingPromptSettingRepository {}
+    struct MockComparingPromptRunRepository {}
+    struct MockTokenizer {}
+    struct MockCredentialResolver {}
+    struct MockDriftDetector {}
+    #[derive(Default)]
+    struct MockModelCatalogRepository {
+        models: Vec<ModelCatalogModel>,
+    }
+    #[derive(Default)]
+    struct MockResponseCacheRepository {
+        caches: Mutex<HashMap<String, Vec<String>>>,
+    }
+
+    /// test_modelのみ対応し、1文字を1トークンとして数える
+    impl Tokenizer for MockTokenizer {
+        fn model_limit(&self, model: &str) -> Option<ModelTokenLimit> {
+            (model == "test_model").then_some(ModelTokenLimit {
+                encoding: TokenEncoding::Cl100kBase,
+                context_window: 100,
             })
         }
 
-        async fn find_all_comparing_prompt_settings_by_manager_id(
+        fn count_tokens(&self, _encoding: TokenEncoding, text: &str) -> usize {
+            text.chars().count()
+        }
+    }
+    /// 指定されたプロファイルをそのまま使い、どのプロファイルも同じ認証情報を返す
+    /// Azure OpenAIのtest_deploymentはtest_modelのデプロイメントとする
+    #[async_trait]
+    impl CredentialResolver for MockCredentialResolver {
+        async fn resolve_credential_profile_id(
             &self,
             _manager_id: i32,
-        ) -> Result<Vec<ComparingPromptSettingModel>, ApplicationError> {
-            Ok(Vec::new())
+            _provider_type: &ProviderType,
+            credential_profile_id: Option<i32>,
+        ) -> Result<Option<i32>, ApplicationError> {
+            Ok(credential_profile_id)
         }
 
-        async fn create_comparing_prompt_setting(
+        async fn find_credential(
             &self,
-            _manager_id: i32,
-        ) -> Result<i32, ApplicationError> {
-            Ok(1)
+            _credential_profile_id: i32,
+        ) -> Result<ProviderCredential, ApplicationError> {
+            Ok(ProviderCredential {
+                api_key: SecretString::from("sk-team"),
+                organization_id: Some("org-team".to_string()),
+                project_id: None,
+            })
+        }
+
+        async fn resolve_token_model(
+            &self,
+            provider_type: &ProviderType,
+            model: &str,
+        ) -> Result<String, ApplicationError> {
+            Ok(match (provider_type, model) {
+                (ProviderType::AzureOpenAI, "test_deployment") => "test_model".to_string(),
+                _ => model.to_string(),
+            })
         }
     }
 
-    struct MockAIChatError {}
-    struct MockComparingPromptSettingRepositoryError {}
-    struct MockComparingPromptRunRepositoryError {}
+    /// 設定1のベースラインの回答を"Test response"とし、完全一致で判定する
     #[async_trait]
-    impl AIChat for MockAIChatError {
-        async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
-            Err(ApplicationError::OpenAPIError("open ai error".to_string()))
+    impl DriftDetector for MockDriftDetector {
+        async fn detect_drift(
+            &self,
+            setting_id: i32,
+            _user_prompt: &str,
+            responses: &[String],
+        ) -> Result<Option<Vec<SampleDrift>>, ApplicationError> {
+            if setting_id != 1 {
+                return Ok(None);
+            }
+            Ok(Some(
+                responses
+                    .iter()
+                    .map(|response| SampleDrift {
+                        baseline_id: 1,
+                        drifted: response != "Test response",
+                        similarity: None,
+                    })
+                    .collect(),
+            ))
         }
     }
 
+    struct MockDriftDetectorError {}
     #[async_trait]
-    impl ComparingPromptSettingRepository for MockComparingPromptSettingRepositoryError {
-        async fn find_comparing_prompt_setting_by_id(
+    impl DriftDetector for MockDriftDetectorError {
+        async fn detect_drift(
             &self,
-            _id: i32,
-        ) -> Result<ComparingPromptSettingModel, ApplicationError> {
+            _setting_id: i32,
+            _user_prompt: &str,
+            _responses: &[String],
+        ) -> Result<Option<Vec<SampleDrift>>, ApplicationError> {
             Err(ApplicationError::DBError(DbErr::Type(
                 "db error".to_string(),
             )))
         }
+    }
 
-        async fn find_all_comparing_prompt_settings_by_manager_id(
-            &self,
-            _manager_id: i32,
-        ) -> Result<Vec<ComparingPromptSettingModel>, ApplicationError> {
-            Err(ApplicationError::DBError(DbErr::Type(
-                "db error".to_string(),
-            )))
+    #[async_trait]
+    impl AIChat for MockAIChat {
+        async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
+            Ok("Test response".to_string())
+        }
+    }
+
+    #[async_trait]
+    impl ComparingPromptRunRepository for MockComparingPromptRunRepository {
+        async fn find_comparing_prompt_run_by_id(
+            &self,
+            id: i32,
+        ) -> Result<ComparingPromptSettingRunModel, ApplicationError> {
+            Ok(ComparingPromptSettingRunModel {
+                id,
+                manager_id: 1,
+                user_prompt: "test_user_prompt".to_string(),
+                provider_type: ProviderType::OpenAI,
+                model: "test_model".to_string(),
+                temperature: 0.0,
+                max_tokens: None,
+                response_format: None,
+                repetitions: 1,
+                top_p: None,
+                sweep_id: Some(1),
+                sampling: SamplingParameters::default(),
+                credential_profile_id: Some(1),
+            })
+        }
+
+        async fn create_comparing_prompt_run(
+            &self,
+            _param: ComparingPromptSettingRunModel,
+        ) -> Result<i32, ApplicationError> {
+            Ok(1)
+        }
+
+        async fn find_comparing_prompt_sweep_by_id(
+            &self,
+            id: i32,
+        ) -> Result<ComparingPromptSweepModel, ApplicationError> {
+            Ok(ComparingPromptSweepModel {
+                id,
+                manager_id: 1,
+                definition: SweepDefinition {
+                    temperature: SweepValues::List {
+                        values: vec![0.0, 1.0],
+                    },
+                    top_p: None,
+                    max_tokens: None,
+                },
+            })
+        }
+
+        async fn find_comparing_prompt_runs_by_sweep_id(
+            &self,
+            sweep_id: i32,
+        ) -> Result<Vec<ComparingPromptSettingRunModel>, ApplicationError> {
+            let run = |id: i32, temperature: f64| ComparingPromptSettingRunModel {
+                id,
+                manager_id: 1,
+                user_prompt: "test_user_prompt".to_string(),
+                provider_type: ProviderType::OpenAI,
+                model: "test_model".to_string(),
+                temperature,
+                max_tokens: None,
+                response_format: None,
+                repetitions: 1,
+                top_p: None,
+                sweep_id: Some(sweep_id),
+                sampling: SamplingParameters::default(),
+                credential_profile_id: None,
+            };
+            Ok(vec![run(1, 0.0), run(2, 1.0)])
+        }
+
+        async fn create_comparing_prompt_sweep(
+            &self,
+            _manager_id: i32,
+            _definition: &SweepDefinition,
+            runs: Vec<ComparingPromptSettingRunModel>,
+        ) -> Result<(i32, Vec<i32>), ApplicationError> {
+            Ok((1, (1..=runs.len() as i32).collect()))
+        }
+
+        async fn find_comparing_prompt_run_history_by_id(
+            &self,
+            id: i32,
+        ) -> Result<ComparingPromptRunHistoryModel, ApplicationError> {
+            Ok(ComparingPromptRunHistoryModel {
+                id,
+                run_id: 1,
+                version_id: 1,
+                setting_id: 1,
+                sample_index: 0,
+                response: "Test response".to_string(),
+                transcript: None,
+                cache_hit: false,
+                cancelled: false,
+            })
+        }
+
+        async fn find_comparing_prompt_run_histories_by_run_id(
+            &self,
+            run_id: i32,
+        ) -> Result<Vec<ComparingPromptRunHistoryModel>, ApplicationError> {
+            let history = |id: i32, version_id: i32, sample_index: i32, response: &str| {
+                ComparingPromptRunHistoryModel {
+                    id,
+                    run_id,
+                    version_id,
+                    setting_id: 1,
+                    sample_index,
+                    response: response.to_string(),
+                    transcript: None,
+                    cache_hit: false,
+                    cancelled: false,
+                }
+            };
+            Ok(vec![
+                history(1, 1, 0, r#"{"answer": "yes"}"#),
+                history(2, 1, 1, r#"{"answer": "yes"}"#),
+                history(3, 1, 2, "answer is no"),
+                history(4, 2, 0, "single sample"),
+            ])
+        }
+
+        async fn create_comparing_prompt_run_history(
+            &self,
+            _target: &RunHistoryTarget,
+            _sample_index: i32,
+            _response: &str,
+            _transcript: Option<&str>,
+            _cache_hit: bool,
+        ) -> Result<i32, ApplicationError> {
+            Ok(1)
+        }
+
+        async fn create_cancelled_comparing_prompt_run_history(
+            &self,
+            _target: &RunHistoryTarget,
+            _sample_index: i32,
+            _transcript: Option<&str>,
+        ) -> Result<i32, ApplicationError> {
+            Ok(2)
+        }
+
+        async fn delete_comparing_prompt_run_histories_by_job_item_id(
+            &self,
+            _job_item_id: i32,
+        ) -> Result<u64, ApplicationError> {
+            Ok(0)
+        }
+    }
+
+    #[async_trait]
+    impl ComparingPromptSettingRepository for MockComparingPromptSettingRepository {
+        async fn find_comparing_prompt_setting_by_id(
+            &self,
+            _id: i32,
+        ) -> Result<ComparingPromptSettingModel, ApplicationError> {
+            Ok(ComparingPromptSettingModel {
+                id: 1,
+                manager_id: 1,
+                current_version: 1,
+                versions: vec![],
+            })
+        }
+
+        async fn find_all_comparing_prompt_settings_by_manager_id(
+            &self,
+            manager_id: i32,
+        ) -> Result<Vec<ComparingPromptSettingModel>, ApplicationError> {
+            let version =
+                |id: i32, version: i32, system_prompt: &str| ComparingPromptSettingVersionModel {
+                    id,
+                    setting_id: 1,
+                    version,
+                    system_prompt: system_prompt.to_string(),
+                    tools: vec![],
+                    tool_choice: None,
+                    tool_scripts: vec![],
+                };
+            Ok(vec![ComparingPromptSettingModel {
+                id: 1,
+                manager_id,
+                current_version: 2,
+                versions: vec![
+                    version(1, 1, "old_system_prompt"),
+                    version(2, 2, "test_system_prompt"),
+                ],
+            }])
+        }
+
+        async fn create_comparing_prompt_setting(
+            &self,
+            _manager_id: i32,
+        ) -> Result<i32, ApplicationError> {
+            Ok(1)
+        }
+
+        async fn find_comparing_prompt_setting_version_by_id(
+            &self,
+            id: i32,
+        ) -> Result<ComparingPromptSettingVersionModel, ApplicationError> {
+            Ok(ComparingPromptSettingVersionModel {
+                id,
+                setting_id: 1,
+                version: 1,
+                system_prompt: "test_system_prompt".to_string(),
+                tools: vec![ToolDefinition {
+                    name: "get_weather".to_string(),
+                    description: None,
+                    parameters: serde_json::json!({ "type": "object" }),
+                }],
+                tool_choice: Some(ToolChoice::Auto),
+                tool_scripts: vec![ToolScript {
+                    name: "get_weather".to_string(),
+                    cases: vec![ToolScriptCase {
+                        arguments: serde_json::json!({ "city": "Tokyo" }),
+                        result: serde_json::json!({ "weather": "sunny" }),
+                    }],
+                    default: Some(serde_json::json!("unknown city")),
+                }],
+            })
+        }
+
+        async fn update_comparing_prompt_setting_version_tools(
+            &self,
+            _id: i32,
+            _tools: &[ToolDefinition],
+            _tool_choice: Option<&ToolChoice>,
+            _tool_scripts: &[ToolScript],
+        ) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        async fn logical_delete_comparing_prompt_setting(
+            &self,
+            _id: i32,
+        ) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        async fn restore_comparing_prompt_setting(&self, _id: i32) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl ModelCatalogRepository for MockModelCatalogRepository {
+        async fn find_model_catalog(
+            &self,
+            provider_type: Option<&ProviderType>,
+        ) -> Result<Vec<ModelCatalogModel>, ApplicationError> {
+            Ok(self
+                .models
+                .iter()
+                .filter(|model| provider_type.is_none_or(|p| &model.provider_type == p))
+                .cloned()
+                .collect())
+        }
+
+        async fn replace_model_catalog(
+            &self,
+            _provider_type: &ProviderType,
+            _models: Vec<ModelCatalogModel>,
+        ) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl ResponseCacheRepository for MockResponseCacheRepository {
+        async fn find_response_cache(
+            &self,
+            cache_key: &str,
+        ) -> Result<Option<ResponseCacheModel>, ApplicationError> {
+            Ok(self
+                .caches
+                .lock()
+                .unwrap()
+                .get(cache_key)
+                .map(|responses| ResponseCacheModel {
+                    id: 1,
+                    cache_key: cache_key.to_string(),
+                    provider_type: ProviderType::OpenAI,
+                    model: "test_model".to_string(),
+                    responses: responses.clone(),
+                }))
+        }
+
+        async fn save_response_cache(
+            &self,
+            cache_key: &str,
+            _provider_type: &ProviderType,
+            _model: &str,
+            responses: &[String],
+            _ttl_seconds: u32,
+        ) -> Result<(), ApplicationError> {
+            self.caches
+                .lock()
+                .unwrap()
+                .insert(cache_key.to_string(), responses.to_vec());
+            Ok(())
+        }
+
+        async fn delete_response_caches(
+            &self,
+            _provider_type: Option<&ProviderType>,
+            _model: Option<&str>,
+            _expired_only: bool,
+        ) -> Result<u64, ApplicationError> {
+            let mut caches = self.caches.lock().unwrap();
+            let deleted = caches.len() as u64;
+            caches.clear();
+            Ok(deleted)
+        }
+    }
+
+    struct MockAIChatError {}
+    struct MockComparingPromptSettingRepositoryError {}
+    struct MockComparingPromptRunRepositoryError {}
+    #[async_trait]
+    impl AIChat for MockAIChatError {
+        async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
+            Err(ApplicationError::OpenAPIError("open ai error".to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl ComparingPromptSettingRepository for MockComparingPromptSettingRepositoryError {
+        async fn find_comparing_prompt_setting_by_id(
+            &self,
+            _id: i32,
+        ) -> Result<ComparingPromptSettingModel, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn find_all_comparing_prompt_settings_by_manager_id(
+            &self,
+            _manager_id: i32,
+        ) -> Result<Vec<ComparingPromptSettingModel>, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
         }
 
         async fn create_comparing_prompt_setting(
@@ -362,6 +2240,42 @@ mod tests {
                 "db error".to_string(),
             )))
         }
+
+        async fn find_comparing_prompt_setting_version_by_id(
+            &self,
+            _id: i32,
+        ) -> Result<ComparingPromptSettingVersionModel, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn update_comparing_prompt_setting_version_tools(
+            &self,
+            _id: i32,
+            _tools: &[ToolDefinition],
+            _tool_choice: Option<&ToolChoice>,
+            _tool_scripts: &[ToolScript],
+        ) -> Result<(), ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn logical_delete_comparing_prompt_setting(
+            &self,
+            _id: i32,
+        ) -> Result<(), ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn restore_comparing_prompt_setting(&self, _id: i32) -> Result<(), ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
     }
 
     #[async_trait]
@@ -383,13 +2297,976 @@ mod tests {
                 "db error".to_string(),
             )))
         }
+
+        async fn find_comparing_prompt_sweep_by_id(
+            &self,
+            _id: i32,
+        ) -> Result<ComparingPromptSweepModel, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn find_comparing_prompt_runs_by_sweep_id(
+            &self,
+            _sweep_id: i32,
+        ) -> Result<Vec<ComparingPromptSettingRunModel>, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn create_comparing_prompt_sweep(
+            &self,
+            _manager_id: i32,
+            _definition: &SweepDefinition,
+            _runs: Vec<ComparingPromptSettingRunModel>,
+        ) -> Result<(i32, Vec<i32>), ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn find_comparing_prompt_run_history_by_id(
+            &self,
+            _id: i32,
+        ) -> Result<ComparingPromptRunHistoryModel, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn find_comparing_prompt_run_histories_by_run_id(
+            &self,
+            _run_id: i32,
+        ) -> Result<Vec<ComparingPromptRunHistoryModel>, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn create_comparing_prompt_run_history(
+            &self,
+            _target: &RunHistoryTarget,
+            _sample_index: i32,
+            _response: &str,
+            _transcript: Option<&str>,
+            _cache_hit: bool,
+        ) -> Result<i32, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn create_cancelled_comparing_prompt_run_history(
+            &self,
+            _target: &RunHistoryTarget,
+            _sample_index: i32,
+            _transcript: Option<&str>,
+        ) -> Result<i32, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn delete_comparing_prompt_run_histories_by_job_item_id(
+            &self,
+            _job_item_id: i32,
+        ) -> Result<u64, ApplicationError> {
+            Ok(0)
+        }
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_add_comparing_prompt_setting() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = AddComparingPromptSettingRequest { manager_id: 1 };
+        let result = chat_usecase.add_comparing_prompt_setting(request).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_add_comparing_prompt_setting_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = AddComparingPromptSettingRequest { manager_id: 1 };
+        let result = chat_usecase.add_comparing_prompt_setting(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_comparing_prompt_setting() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = GetComparingPromptSettingRequest { id: 1 };
+        let result = chat_usecase.get_comparing_prompt_setting(request).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_comparing_prompt_setting_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = GetComparingPromptSettingRequest { id: 1 };
+        let result = chat_usecase.get_comparing_prompt_setting(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_all_comparing_prompt_settings() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = GetComparingPromptSettingsRequest { manager_id: 1 };
+        let result = chat_usecase
+            .get_all_comparing_prompt_settings(request)
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_all_comparing_prompt_settings_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = GetComparingPromptSettingsRequest { manager_id: 1 };
+        let result = chat_usecase
+            .get_all_comparing_prompt_settings(request)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_run() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = SaveComparingPromptRunRequest {
+            manager_id: 1,
+            user_prompt: "".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            credential_profile_id: None,
+            sampling: SamplingParameters::default(),
+        };
+        let result = chat_usecase.save_run(request).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_save_run_model_catalog() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let mock_model_catalog_repository = MockModelCatalogRepository {
+            models: vec![
+                ModelCatalogModel::from_listed(ProviderType::OpenAI, "gpt-4-0613".to_string()),
+                ModelCatalogModel::from_listed(ProviderType::OpenAI, "custom-model".to_string()),
+            ],
+        };
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(mock_model_catalog_repository),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = |provider_type: ProviderType,
+                       model: &str,
+                       max_tokens: Option<i32>,
+                       response_format: Option<&str>| {
+            SaveComparingPromptRunRequest {
+                manager_id: 1,
+                user_prompt: "".to_string(),
+                provider_type,
+                model: model.to_string(),
+                temperature: 0.0,
+                top_p: None,
+                max_tokens,
+                response_format: response_format.map(|f| f.to_string()),
+                repetitions: None,
+                credential_profile_id: None,
+                sampling: SamplingParameters::default(),
+            }
+        };
+        let ok_cases = vec![
+            request(ProviderType::OpenAI, "gpt-4-0613", Some(1_000), None),
+            // 対応機能が分からないモデルは存在のみ確認する
+            request(
+                ProviderType::OpenAI,
+                "custom-model",
+                None,
+                Some("json_object"),
+            ),
+            // モデル一覧を未取得のプロバイダーは確認しない
+            request(ProviderType::Gemini, "gemini-typo", None, None),
+        ];
+        for request in ok_cases {
+            let result = chat_usecase.save_run(request).await;
+            assert!(result.is_ok());
+        }
+        let error_cases = vec![
+            // カタログに存在しないモデル
+            request(ProviderType::OpenAI, "gpt-4-typo", None, None),
+            // JSONモードに対応していない
+            request(
+                ProviderType::OpenAI,
+                "gpt-4-0613",
+                None,
+                Some("json_object"),
+            ),
+            // max_tokensがコンテキストウィンドウを超える
+            request(ProviderType::OpenAI, "gpt-4-0613", Some(10_000), None),
+        ];
+        for request in error_cases {
+            let result = chat_usecase.save_run(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_run_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = SaveComparingPromptRunRequest {
+            manager_id: 1,
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            credential_profile_id: None,
+            sampling: SamplingParameters::default(),
+        };
+        let result = chat_usecase.save_run(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_chat() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: None,
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        let result = chat_usecase.run_chat(request).await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.answer, "Test response");
+        assert_eq!(result.history_id, None);
+        // バージョンが指定されていない場合はベースラインと比較しない
+        assert_eq!(result.samples[0].drift, None);
+        // トークン数を数えられないモデルの場合は確認しない
+        assert_eq!(result.prompt_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_chat_response_cache() {
+        // AIの呼び出しを差し替えて、同じキャッシュを共有するユースケースを作成する
+        fn chat_usecase<T: AIChat>(
+            ai_chat: T,
+            response_cache_repository: &Arc<MockResponseCacheRepository>,
+        ) -> ChatUsecase<
+            T,
+            MockComparingPromptSettingRepository,
+            MockComparingPromptRunRepository,
+            MockTokenizer,
+            MockModelCatalogRepository,
+            MockResponseCacheRepository,
+            MockCredentialResolver,
+            MockDriftDetector,
+        > {
+            ChatUsecase {
+                ai_chat: Arc::new(ai_chat),
+                comparing_prompt_setting_repository: Arc::new(
+                    MockComparingPromptSettingRepository {},
+                ),
+                comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {}),
+                tokenizer: Arc::new(MockTokenizer {}),
+                model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+                response_cache_repository: Arc::clone(response_cache_repository),
+                credential_resolver: Arc::new(MockCredentialResolver {}),
+                drift_detector: Arc::new(MockDriftDetector {}),
+                run_cancellations: Arc::new(RunCancellations::default()),
+            }
+        }
+        let response_cache_repository = Arc::new(MockResponseCacheRepository::default());
+        let request = |temperature: f32| RunChatRequest {
+            run_id: 1,
+            version_id: None,
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: true,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+
+        // 初回はAIを呼び出してキャッシュに保存する
+        let result = chat_usecase(MockAIChat {}, &response_cache_repository)
+            .run_chat(request(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.answer, "Test response");
+        assert!(!result.cache_hit);
+
+        // 2回目はAIを呼び出さずにキャッシュから返す
+        let result = chat_usecase(MockAIChatError {}, &response_cache_repository)
+            .run_chat(request(0.0))
+            .await
+            .unwrap();
+        assert_eq!(result.answer, "Test response");
+        assert!(result.cache_hit);
+
+        // temperatureが0でなくseedも未指定の場合はキャッシュを使わない
+        let result = chat_usecase(MockAIChatError {}, &response_cache_repository)
+            .run_chat(request(0.7))
+            .await;
+        assert!(result.is_err());
+        assert_eq!(response_cache_repository.caches.lock().unwrap().len(), 1);
+
+        // キャッシュを削除すると再びAIを呼び出す
+        let result = chat_usecase(MockAIChat {}, &response_cache_repository)
+            .clear_response_cache(ClearResponseCacheRequest {
+                provider_type: None,
+                model: None,
+                expired_only: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.deleted, 1);
+        let result = chat_usecase(MockAIChatError {}, &response_cache_repository)
+            .run_chat(request(0.0))
+            .await;
+        assert!(result.is_err());
+    }
+
+    /// 回答を返さないAIChat（キャンセルの確認用）
+    struct MockPendingAIChat {}
+
+    #[async_trait]
+    impl AIChat for MockPendingAIChat {
+        async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
+            std::future::pending().await
+        }
+
+        /// 最初のサンプルだけ完了し、残りは終わらない
+        async fn do_chat_samples(
+            &self,
+            _settings: &ChatSettings,
+            _n: u8,
+            answers: &mut Vec<String>,
+        ) -> Result<(), ApplicationError> {
+            answers.push("Partial response".to_string());
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_run() {
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(MockPendingAIChat {}),
+            comparing_prompt_setting_repository: Arc::new(MockComparingPromptSettingRepository {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {}),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: Some(1),
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+
+        // 実行中のチャットをキャンセルする
+        let (result, cancelled) = tokio::join!(chat_usecase.run_chat(request), async {
+            chat_usecase
+                .cancel_run(CancelRunRequest { run_id: 1 })
+                .await
+                .unwrap()
+        });
+
+        // assert
+        assert_eq!(cancelled.cancelled, 1);
+        let result = result.unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.answer, "");
+        // キャンセルされた履歴として登録される
+        assert_eq!(result.history_id, Some(2));
+
+        // 実行中の処理がない場合は何もしない
+        let cancelled = chat_usecase
+            .cancel_run(CancelRunRequest { run_id: 1 })
+            .await
+            .unwrap();
+        assert_eq!(cancelled.cancelled, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_run_partial_samples() {
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(MockPendingAIChat {}),
+            comparing_prompt_setting_repository: Arc::new(MockComparingPromptSettingRepository {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {}),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: Some(1),
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: Some(3),
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+
+        // 最初のサンプルが完了した後にキャンセルする
+        let (result, cancelled) = tokio::join!(chat_usecase.run_chat(request), async {
+            chat_usecase
+                .cancel_run(CancelRunRequest { run_id: 1 })
+                .await
+                .unwrap()
+        });
+
+        // assert
+        assert_eq!(cancelled.cancelled, 1);
+        let result = result.unwrap();
+        assert!(result.cancelled);
+        // 完了したサンプルは回答とともに登録され、残りはキャンセルされた履歴として登録される
+        assert_eq!(result.answer, "Partial response");
+        assert_eq!(result.history_id, Some(1));
+        let samples: Vec<(i32, String, Option<i32>)> = result
+            .samples
+            .into_iter()
+            .map(|sample| (sample.sample_index, sample.answer, sample.history_id))
+            .collect();
+        assert_eq!(
+            samples,
+            vec![
+                (0, "Partial response".to_string(), Some(1)),
+                (1, "".to_string(), Some(2)),
+                (2, "".to_string(), Some(2)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_chat_context_window() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = |max_tokens: Option<u16>| RunChatRequest {
+            run_id: 1,
+            version_id: None,
+            user_prompt: "test_user_prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        // system(3 + 6 + 18) + user(3 + 4 + 16) + reply(3)
+        let result = chat_usecase.run_chat(request(Some(47))).await.unwrap();
+        assert_eq!(result.prompt_tokens, Some(53));
+        // "Test response"
+        assert_eq!(result.completion_tokens, Some(13));
+
+        // プロンプトとmax_tokensの合計がコンテキストウィンドウを超える
+        let result = chat_usecase.run_chat(request(Some(48))).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        // Azure OpenAIはデプロイメントのモデルでトークン数を数える
+        let azure_request = |max_tokens: Option<u16>| RunChatRequest {
+            provider_type: ProviderType::AzureOpenAI,
+            model: "test_deployment".to_string(),
+            ..request(max_tokens)
+        };
+        let result = chat_usecase
+            .run_chat(azure_request(Some(47)))
+            .await
+            .unwrap();
+        assert_eq!(result.prompt_tokens, Some(53));
+        assert_eq!(result.completion_tokens, Some(13));
+        let result = chat_usecase.run_chat(azure_request(Some(48))).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_preflight_run() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = PreflightRunRequest { run_id: 1 };
+        let result = chat_usecase.preflight_run(request).await.unwrap();
+        assert_eq!(result.model, "test_model");
+        assert_eq!(result.encoding, Some(TokenEncoding::Cl100kBase));
+        assert_eq!(result.context_window, Some(100));
+        // 現在のバージョンのみ数える
+        assert_eq!(result.versions.len(), 1);
+        assert_eq!(result.versions[0].version_id, 2);
+        assert_eq!(result.versions[0].prompt_tokens, Some(53));
+        assert!(result.versions[0].fits);
+    }
+
+    #[tokio::test]
+    async fn test_preflight_run_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = PreflightRunRequest { run_id: 1 };
+        let result = chat_usecase.preflight_run(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_chat_with_version() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: Some(1),
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        let result = chat_usecase.run_chat(request).await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.history_id, Some(1));
+        // バージョンの設定のベースラインと比較する
+        assert_eq!(
+            result.samples[0].drift,
+            Some(SampleDrift {
+                baseline_id: 1,
+                drifted: false,
+                similarity: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_chat_drift_error() {
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(MockAIChat {}),
+            comparing_prompt_setting_repository: Arc::new(MockComparingPromptSettingRepository {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {}),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetectorError {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: Some(1),
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        // ドリフトの判定に失敗しても回答は返す
+        let result = chat_usecase.run_chat(request).await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.history_id, Some(1));
+        assert_eq!(result.samples[0].drift, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_chat_with_version_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: Some(1),
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        let result = chat_usecase.run_chat(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_chat_error() {
+        struct MockAIChatError {}
+        #[async_trait]
+        impl AIChat for MockAIChatError {
+            async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
+                Err(ApplicationError::OpenAPIError("open ai error".to_string()))
+            }
+        }
+
+        let expected_prompt = "Test prompt".to_string();
+        let mock_chat = MockAIChatError {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: None,
+            user_prompt: expected_prompt,
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        let result = chat_usecase.run_chat(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_run_invalid_repetitions() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = SaveComparingPromptRunRequest {
+            manager_id: 1,
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: Some(0),
+            credential_profile_id: None,
+            sampling: SamplingParameters::default(),
+        };
+        let result = chat_usecase.save_run(request).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_save_run_invalid_max_tokens() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        // u16に収まらない値と0は保存できない
+        for max_tokens in [0, u16::MAX as i32 + 1] {
+            let request = SaveComparingPromptRunRequest {
+                manager_id: 1,
+                user_prompt: "test_user_prompt".to_string(),
+                provider_type: ProviderType::OpenAI,
+                model: "test_model".to_string(),
+                temperature: 0.0,
+                top_p: None,
+                max_tokens: Some(max_tokens),
+                response_format: None,
+                repetitions: None,
+                credential_profile_id: None,
+                sampling: SamplingParameters::default(),
+            };
+            let result = chat_usecase.save_run(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
     }
 
-    /**
-     * Test cases
-     */
     #[tokio::test]
-    async fn test_add_comparing_prompt_setting() {
+    async fn test_run_chat_with_repetitions() {
         let mock_chat = MockAIChat {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
@@ -397,29 +3274,105 @@ mod tests {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = AddComparingPromptSettingRequest { manager_id: 1 };
-        let result = chat_usecase.add_comparing_prompt_setting(request).await;
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: Some(1),
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 1.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: Some(3),
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        let result = chat_usecase.run_chat(request).await;
         assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.samples.len(), 3);
+        let indexes: Vec<i32> = result.samples.iter().map(|s| s.sample_index).collect();
+        assert_eq!(indexes, vec![0, 1, 2]);
+        assert!(result.samples.iter().all(|s| s.history_id == Some(1)));
+        assert_eq!(result.answer, "Test response");
     }
 
     #[tokio::test]
-    async fn test_add_comparing_prompt_setting_error() {
+    async fn test_get_consistency_metrics() {
         let mock_chat = MockAIChat {};
-        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = GetConsistencyMetricsRequest { run_id: 1 };
+        let result = chat_usecase.get_consistency_metrics(request).await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.versions.len(), 2);
+
+        // 3サンプルのバージョン
+        let item = &result.versions[0];
+        assert_eq!(item.version_id, 1);
+        assert_eq!(item.sample_count, 3);
+        assert!((item.unique_answer_ratio - 2.0 / 3.0).abs() < 1e-9);
+        assert!((item.json_validity_rate - 2.0 / 3.0).abs() < 1e-9);
+        let distribution = item.pairwise_similarity.as_ref().unwrap();
+        assert_eq!(distribution.max, 1.0);
+        assert_eq!(distribution.histogram.iter().sum::<usize>(), 3);
+        assert_eq!(distribution.histogram[SIMILARITY_HISTOGRAM_BINS - 1], 1);
+
+        // 1サンプルのバージョンは類似度を計算しない
+        let item = &result.versions[1];
+        assert_eq!(item.version_id, 2);
+        assert_eq!(item.sample_count, 1);
+        assert_eq!(item.unique_answer_ratio, 1.0);
+        assert_eq!(item.json_validity_rate, 0.0);
+        assert!(item.pairwise_similarity.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_consistency_metrics_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
         let chat_usecase = ChatUsecase {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = AddComparingPromptSettingRequest { manager_id: 1 };
-        let result = chat_usecase.add_comparing_prompt_setting(request).await;
+        let request = GetConsistencyMetricsRequest { run_id: 1 };
+        let result = chat_usecase.get_consistency_metrics(request).await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_get_comparing_prompt_setting() {
+    async fn test_save_sweep() {
         let mock_chat = MockAIChat {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
@@ -427,29 +3380,170 @@ mod tests {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = GetComparingPromptSettingRequest { id: 1 };
-        let result = chat_usecase.get_comparing_prompt_setting(request).await;
+        let request = SaveComparingPromptSweepRequest {
+            manager_id: 1,
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            response_format: None,
+            repetitions: None,
+            definition: SweepDefinition {
+                temperature: SweepValues::Range {
+                    start: 0.0,
+                    end: 1.0,
+                    step: 0.5,
+                },
+                top_p: Some(SweepValues::List {
+                    values: vec![0.1, 0.9],
+                }),
+                max_tokens: Some(SweepValues::List {
+                    values: vec![256.0],
+                }),
+            },
+            credential_profile_id: None,
+            sampling: SamplingParameters::default(),
+        };
+        let result = chat_usecase.save_sweep(request).await;
         assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.id, 1);
+        // 3(temperature) x 2(top_p) x 1(max_tokens)
+        assert_eq!(result.runs.len(), 6);
+        assert_eq!(
+            result.runs[0],
+            SweepRunItem {
+                run_id: 1,
+                temperature: 0.0,
+                top_p: Some(0.1),
+                max_tokens: Some(256),
+            }
+        );
+        assert_eq!(
+            result.runs[5],
+            SweepRunItem {
+                run_id: 6,
+                temperature: 1.0,
+                top_p: Some(0.9),
+                max_tokens: Some(256),
+            }
+        );
     }
 
     #[tokio::test]
-    async fn test_get_comparing_prompt_setting_error() {
+    async fn test_save_sweep_validation_error() {
         let mock_chat = MockAIChat {};
-        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let definitions = vec![
+            // 範囲外のtop_p
+            SweepDefinition {
+                temperature: SweepValues::List { values: vec![0.0] },
+                top_p: Some(SweepValues::List { values: vec![1.5] }),
+                max_tokens: None,
+            },
+            // stepが0
+            SweepDefinition {
+                temperature: SweepValues::Range {
+                    start: 0.0,
+                    end: 1.0,
+                    step: 0.0,
+                },
+                top_p: None,
+                max_tokens: None,
+            },
+            // 整数でないmax_tokens
+            SweepDefinition {
+                temperature: SweepValues::List { values: vec![0.0] },
+                top_p: None,
+                max_tokens: Some(SweepValues::List { values: vec![10.5] }),
+            },
+            // 展開後の組み合わせが多すぎる
+            SweepDefinition {
+                temperature: SweepValues::Range {
+                    start: 0.0,
+                    end: 2.0,
+                    step: 0.1,
+                },
+                top_p: Some(SweepValues::Range {
+                    start: 0.0,
+                    end: 1.0,
+                    step: 0.1,
+                }),
+                max_tokens: None,
+            },
+        ];
+        for definition in definitions {
+            let request = SaveComparingPromptSweepRequest {
+                manager_id: 1,
+                user_prompt: "test_user_prompt".to_string(),
+                provider_type: ProviderType::OpenAI,
+                model: "test_model".to_string(),
+                response_format: None,
+                repetitions: None,
+                definition,
+                credential_profile_id: None,
+                sampling: SamplingParameters::default(),
+            };
+            let result = chat_usecase.save_sweep(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_sweep_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
         let chat_usecase = ChatUsecase {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = GetComparingPromptSettingRequest { id: 1 };
-        let result = chat_usecase.get_comparing_prompt_setting(request).await;
+        let request = SaveComparingPromptSweepRequest {
+            manager_id: 1,
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "test_model".to_string(),
+            response_format: None,
+            repetitions: None,
+            definition: SweepDefinition {
+                temperature: SweepValues::List { values: vec![0.0] },
+                top_p: None,
+                max_tokens: None,
+            },
+            credential_profile_id: None,
+            sampling: SamplingParameters::default(),
+        };
+        let result = chat_usecase.save_sweep(request).await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_get_all_comparing_prompt_settings() {
+    async fn test_get_sweep_matrix() {
         let mock_chat = MockAIChat {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
@@ -457,33 +3551,48 @@ mod tests {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = GetComparingPromptSettingsRequest { manager_id: 1 };
-        let result = chat_usecase
-            .get_all_comparing_prompt_settings(request)
-            .await;
+        let request = GetSweepMatrixRequest { sweep_id: 1 };
+        let result = chat_usecase.get_sweep_matrix(request).await;
         assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.sweep_id, 1);
+        assert_eq!(result.cells.len(), 2);
+        assert_eq!(result.cells[0].temperature, 0.0);
+        assert_eq!(result.cells[1].temperature, 1.0);
+        assert_eq!(result.cells[0].histories.len(), 4);
+        assert_eq!(result.cells[0].histories[0].version_id, 1);
     }
 
     #[tokio::test]
-    async fn test_get_all_comparing_prompt_settings_error() {
+    async fn test_get_sweep_matrix_error() {
         let mock_chat = MockAIChat {};
-        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
         let chat_usecase = ChatUsecase {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = GetComparingPromptSettingsRequest { manager_id: 1 };
-        let result = chat_usecase
-            .get_all_comparing_prompt_settings(request)
-            .await;
+        let request = GetSweepMatrixRequest { sweep_id: 1 };
+        let result = chat_usecase.get_sweep_matrix(request).await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_save_run() {
+    async fn test_save_run_unsupported_parameter() {
         let mock_chat = MockAIChat {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
@@ -491,98 +3600,580 @@ mod tests {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
         let request = SaveComparingPromptRunRequest {
             manager_id: 1,
-            user_prompt: "".to_string(),
-            provider_type: ProviderType::OpenAI,
-            model: "".to_string(),
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::Gemini,
+            model: "test_model".to_string(),
             temperature: 0.0,
+            top_p: Some(0.9),
             max_tokens: None,
             response_format: None,
+            repetitions: None,
+            credential_profile_id: None,
+            sampling: SamplingParameters {
+                seed: Some(42),
+                logit_bias: Some([("50256".to_string(), -100)].into()),
+                ..Default::default()
+            },
         };
         let result = chat_usecase.save_run(request).await;
-        assert!(result.is_ok());
+        // 対応していないパラメータのみエラーメッセージに含まれる
+        assert_eq!(
+            result.unwrap_err(),
+            ApplicationError::ValidationError(
+                "Gemini does not support parameters: top_p, seed, logit_bias".to_string()
+            )
+        );
     }
 
     #[tokio::test]
-    async fn test_save_run_error() {
+    async fn test_run_chat_invalid_sampling_parameters() {
         let mock_chat = MockAIChat {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
-        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
         let chat_usecase = ChatUsecase {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = SaveComparingPromptRunRequest {
-            manager_id: 1,
-            user_prompt: "test_user_prompt".to_string(),
-            provider_type: ProviderType::OpenAI,
-            model: "test_model".to_string(),
-            temperature: 0.0,
-            max_tokens: None,
-            response_format: None,
-        };
-        let result = chat_usecase.save_run(request).await;
-        assert!(result.is_err());
+        let samplings = vec![
+            SamplingParameters {
+                frequency_penalty: Some(2.5),
+                ..Default::default()
+            },
+            SamplingParameters {
+                stop: Some(
+                    vec!["a", "b", "c", "d", "e"]
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                ),
+                ..Default::default()
+            },
+            SamplingParameters {
+                logit_bias: Some([("not_a_token".to_string(), 1)].into()),
+                ..Default::default()
+            },
+            SamplingParameters {
+                logit_bias: Some([("50256".to_string(), 101)].into()),
+                ..Default::default()
+            },
+        ];
+        for sampling in samplings {
+            let request = RunChatRequest {
+                run_id: 1,
+                version_id: None,
+                user_prompt: "Test prompt".to_string(),
+                system_prompt: "test_system_prompt".to_string(),
+                provider_type: ProviderType::OpenAI,
+                model: "".to_string(),
+                temperature: 0.0,
+                top_p: None,
+                max_tokens: None,
+                response_format: None,
+                repetitions: None,
+                sampling,
+                use_cache: false,
+                cache_ttl_seconds: None,
+                job_item_id: None,
+            };
+            let result = chat_usecase.run_chat(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_run_chat_request_with_sampling_parameters() {
+        // サンプリングパラメータはリクエストのトップレベルに指定する
+        let json = r#"{
+            "runId": 1,
+            "userPrompt": "Test prompt",
+            "systemPrompt": "test_system_prompt",
+            "providerType": "OpenAI",
+            "model": "test_model",
+            "temperature": 0.0,
+            "seed": 42,
+            "stop": ["END"],
+            "presencePenalty": 0.5
+        }"#;
+        let request: RunChatRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            request.sampling,
+            SamplingParameters {
+                presence_penalty: Some(0.5),
+                stop: Some(vec!["END".to_string()]),
+                seed: Some(42),
+                ..Default::default()
+            }
+        );
     }
 
     #[tokio::test]
-    async fn test_run_chat() {
-        let mock_chat = MockAIChat {};
+    async fn test_run_chat_with_version_tools() {
+        struct MockAIChatWithTools {}
+        #[async_trait]
+        impl AIChat for MockAIChatWithTools {
+            async fn do_chat(&self, settings: &ChatSettings) -> Result<String, ApplicationError> {
+                // バージョンに定義されたツールが渡されること
+                assert_eq!(settings.tools.len(), 1);
+                assert_eq!(settings.tools[0].name, "get_weather");
+                assert_eq!(settings.tool_choice, Some(ToolChoice::Auto));
+                Ok(r#"{"content":null,"toolCalls":[]}"#.to_string())
+            }
+        }
+
+        let mock_chat = MockAIChatWithTools {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
         let chat_usecase = ChatUsecase {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
         let request = RunChatRequest {
             run_id: 1,
+            version_id: Some(1),
             user_prompt: "Test prompt".to_string(),
             system_prompt: "test_system_prompt".to_string(),
             provider_type: ProviderType::OpenAI,
             model: "".to_string(),
             temperature: 0.0,
+            top_p: None,
             max_tokens: None,
             response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
         };
         let result = chat_usecase.run_chat(request).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().answer, "Test response");
     }
 
     #[tokio::test]
-    async fn test_run_chat_error() {
-        struct MockAIChatError {}
+    async fn test_run_chat_with_credential_profile() {
+        struct MockAIChatWithCredential {}
         #[async_trait]
-        impl AIChat for MockAIChatError {
-            async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
-                Err(ApplicationError::OpenAPIError("open ai error".to_string()))
+        impl AIChat for MockAIChatWithCredential {
+            async fn do_chat(&self, settings: &ChatSettings) -> Result<String, ApplicationError> {
+                // 実行に記録されたプロファイルの認証情報が渡されること
+                let credential = settings.credential.as_ref().unwrap();
+                assert_eq!(credential.api_key.expose(), "sk-team");
+                assert_eq!(credential.organization_id.as_deref(), Some("org-team"));
+                Ok("Test response".to_string())
             }
         }
 
-        let expected_prompt = "Test prompt".to_string();
-        let mock_chat = MockAIChatError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(MockAIChatWithCredential {}),
+            comparing_prompt_setting_repository: Arc::new(MockComparingPromptSettingRepository {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {}),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = RunChatRequest {
+            run_id: 1,
+            version_id: None,
+            user_prompt: "Test prompt".to_string(),
+            system_prompt: "test_system_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "".to_string(),
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            response_format: None,
+            repetitions: None,
+            sampling: SamplingParameters::default(),
+            use_cache: false,
+            cache_ttl_seconds: None,
+            job_item_id: None,
+        };
+        let result = chat_usecase.run_chat(request).await;
+        assert_eq!(result.unwrap().answer, "Test response");
+    }
+
+    #[tokio::test]
+    async fn test_save_version_tools() {
+        let mock_chat = MockAIChat {};
         let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
         let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
         let chat_usecase = ChatUsecase {
             ai_chat: Arc::new(mock_chat),
             comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
             comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
         };
-        let request = RunChatRequest {
+        let request = SaveVersionToolsRequest {
+            version_id: 1,
+            tools: vec![ToolDefinition {
+                name: "get_weather".to_string(),
+                description: Some("現在の天気を取得する".to_string()),
+                parameters: serde_json::json!({ "type": "object" }),
+            }],
+            tool_choice: Some(ToolChoice::Function {
+                name: "get_weather".to_string(),
+            }),
+            tool_scripts: vec![ToolScript {
+                name: "get_weather".to_string(),
+                cases: vec![],
+                default: Some(serde_json::json!({ "weather": "sunny" })),
+            }],
+        };
+        let result = chat_usecase.save_version_tools(request).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_save_version_tools_validation_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let tool = |name: &str| ToolDefinition {
+            name: name.to_string(),
+            description: None,
+            parameters: serde_json::json!({ "type": "object" }),
+        };
+        let cases = vec![
+            // 不正なツール名
+            (vec![tool("get weather")], None),
+            // ツール名の重複
+            (vec![tool("get_weather"), tool("get_weather")], None),
+            // 定義されていないツールの指定
+            (
+                vec![tool("get_weather")],
+                Some(ToolChoice::Function {
+                    name: "search".to_string(),
+                }),
+            ),
+            // ツールが無いのに選択方針を指定
+            (vec![], Some(ToolChoice::Auto)),
+            // parametersがオブジェクトでない
+            (
+                vec![ToolDefinition {
+                    name: "get_weather".to_string(),
+                    description: None,
+                    parameters: serde_json::json!("string"),
+                }],
+                None,
+            ),
+        ];
+        for (tools, tool_choice) in cases {
+            let request = SaveVersionToolsRequest {
+                version_id: 1,
+                tools,
+                tool_choice,
+                tool_scripts: vec![],
+            };
+            let result = chat_usecase.save_version_tools(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_version_tools_error() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepositoryError {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let request = SaveVersionToolsRequest {
+            version_id: 1,
+            tools: vec![],
+            tool_choice: None,
+            tool_scripts: vec![],
+        };
+        let result = chat_usecase.save_version_tools(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_version_tools_invalid_scripts() {
+        let mock_chat = MockAIChat {};
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let script = |name: &str| ToolScript {
+            name: name.to_string(),
+            cases: vec![],
+            default: None,
+        };
+        let cases = vec![
+            // 定義されていないツールのスクリプト
+            vec![script("search")],
+            // スクリプトの重複
+            vec![script("get_weather"), script("get_weather")],
+        ];
+        for tool_scripts in cases {
+            let request = SaveVersionToolsRequest {
+                version_id: 1,
+                tools: vec![ToolDefinition {
+                    name: "get_weather".to_string(),
+                    description: None,
+                    parameters: serde_json::json!({ "type": "object" }),
+                }],
+                tool_choice: None,
+                tool_scripts,
+            };
+            let result = chat_usecase.save_version_tools(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    /// ユーザーの発言にはツール呼び出しを返し、ツールの結果を受け取ったら回答する
+    struct MockAgentChat {
+        always_call_tools: bool,
+    }
+    #[async_trait]
+    impl AIChat for MockAgentChat {
+        async fn do_chat(&self, _settings: &ChatSettings) -> Result<String, ApplicationError> {
+            unreachable!()
+        }
+
+        async fn do_chat_messages(
+            &self,
+            _settings: &ChatSettings,
+            messages: &[ChatMessage],
+        ) -> Result<ChatAnswer, ApplicationError> {
+            match messages.last() {
+                Some(ChatMessage::Tool { content, .. }) if !self.always_call_tools => {
+                    Ok(ChatAnswer {
+                        content: Some(format!("result: {}", content)),
+                        tool_calls: vec![],
+                    })
+                }
+                _ => Ok(ChatAnswer {
+                    content: None,
+                    tool_calls: vec![
+                        ToolCall {
+                            id: "call_1".to_string(),
+                            name: "get_weather".to_string(),
+                            arguments: r#"{"city":"Tokyo","unit":"celsius"}"#.to_string(),
+                        },
+                        ToolCall {
+                            id: "call_2".to_string(),
+                            name: "get_weather".to_string(),
+                            arguments: r#"{"city":"Osaka"}"#.to_string(),
+                        },
+                    ],
+                }),
+            }
+        }
+    }
+
+    fn agent_request(max_steps: Option<u8>) -> RunAgentRequest {
+        RunAgentRequest {
             run_id: 1,
-            user_prompt: expected_prompt,
+            version_id: 1,
+            user_prompt: "Test prompt".to_string(),
             system_prompt: "test_system_prompt".to_string(),
             provider_type: ProviderType::OpenAI,
-            model: "test_model".to_string(),
+            model: "".to_string(),
             temperature: 0.0,
+            top_p: None,
             max_tokens: None,
             response_format: None,
+            max_steps,
+            sampling: SamplingParameters::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_agent() {
+        let mock_chat = MockAgentChat {
+            always_call_tools: false,
         };
-        let result = chat_usecase.run_chat(request).await;
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let result = chat_usecase.run_agent(agent_request(None)).await.unwrap();
+        assert_eq!(result.steps, 2);
+        assert_eq!(result.history_id, 1);
+        assert_eq!(result.transcript.stop_reason, AgentStopReason::Completed);
+        // 部分一致したケースの結果と、一致しない場合のdefaultが返されること
+        assert_eq!(result.answer, "result: unknown city");
+        let tool_results: Vec<&str> = result
+            .transcript
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                ChatMessage::Tool { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tool_results, vec![r#"{"weather":"sunny"}"#, "unknown city"]);
+        assert_eq!(result.transcript.messages.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_run_agent_step_limit() {
+        let mock_chat = MockAgentChat {
+            always_call_tools: true,
+        };
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepository {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let result = chat_usecase
+            .run_agent(agent_request(Some(3)))
+            .await
+            .unwrap();
+        assert_eq!(result.steps, 3);
+        assert_eq!(result.transcript.stop_reason, AgentStopReason::StepLimit);
+
+        // ステップ数が範囲外
+        for max_steps in [0, MAX_AGENT_STEPS + 1] {
+            let result = chat_usecase.run_agent(agent_request(Some(max_steps))).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_agent_error() {
+        let mock_chat = MockAgentChat {
+            always_call_tools: false,
+        };
+        let mock_comparing_prompt_setting_repository = MockComparingPromptSettingRepository {};
+        let mock_comparing_prompt_run_repository = MockComparingPromptRunRepositoryError {};
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(mock_chat),
+            comparing_prompt_setting_repository: Arc::new(mock_comparing_prompt_setting_repository),
+            comparing_prompt_run_repository: Arc::new(mock_comparing_prompt_run_repository),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let result = chat_usecase.run_agent(agent_request(None)).await;
+        assert!(result.is_err());
+
+        // 複数ターンの会話に対応していないプロバイダー
+        let chat_usecase = ChatUsecase {
+            ai_chat: Arc::new(MockAIChat {}),
+            comparing_prompt_setting_repository: Arc::new(MockComparingPromptSettingRepository {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {}),
+            tokenizer: Arc::new(MockTokenizer {}),
+            model_catalog_repository: Arc::new(MockModelCatalogRepository::default()),
+            response_cache_repository: Arc::new(MockResponseCacheRepository::default()),
+            credential_resolver: Arc::new(MockCredentialResolver {}),
+            drift_detector: Arc::new(MockDriftDetector {}),
+            run_cancellations: Arc::new(RunCancellations::default()),
+        };
+        let result = chat_usecase.run_agent(agent_request(None)).await;
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_resolve_tool_script() {
+        let scripts = vec![ToolScript {
+            name: "search".to_string(),
+            cases: vec![ToolScriptCase {
+                arguments: serde_json::json!({ "filter": { "lang": "ja" } }),
+                result: serde_json::json!(["a", "b"]),
+            }],
+            default: None,
+        }];
+        let call = |name: &str, arguments: &str| ToolCall {
+            id: "call_1".to_string(),
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        };
+        // ネストしたオブジェクトも部分一致で比較する
+        assert_eq!(
+            resolve_tool_script(
+                &scripts,
+                &call("search", r#"{"q":"x","filter":{"lang":"ja","limit":1}}"#)
+            ),
+            r#"["a","b"]"#
+        );
+        // 一致するケースもdefaultもない場合はエラーを返す
+        assert_eq!(
+            resolve_tool_script(&scripts, &call("search", "not json")),
+            r#"{"error":"no scripted result for tool: search"}"#
+        );
+        assert_eq!(
+            resolve_tool_script(&scripts, &call("unknown", "{}")),
+            r#"{"error":"no scripted result for tool: unknown"}"#
+        );
+    }
 }
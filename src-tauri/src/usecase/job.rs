@@ -0,0 +1,1129 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::{
+    ComparingPromptRunRepository, ComparingPromptSettingRepository, ComparingPromptSettingRunModel,
+    ComparingPromptSettingVersionModel,
+};
+use crate::domain::event::EventEmitter;
+use crate::domain::job::{
+    JobItemModel, JobItemStatus, JobModel, JobRepository, JobStatus, JobType,
+};
+use crate::usecase::comparing_prompt::{ComparingPrompt, RunChatRequest};
+use crate::usecase::progress::{
+    emit_progress, JobFinishedEvent, JobItemFailedEvent, JobItemFinishedEvent, JobItemStartedEvent,
+    JobProgress, JobStartedEvent,
+};
+
+/// 待機中のワーカーがキューを確認する間隔
+/// 登録時に通知するので、通知を取りこぼした場合の保険
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// ワーカーの処理でエラーが発生した場合に再開するまでの待ち時間
+const JOB_ERROR_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueRunJobRequest {
+    pub run_id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueSweepJobRequest {
+    pub sweep_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueJobResponse {
+    pub id: i32,
+    pub total_items: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetJobRequest {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetJobResponse {
+    pub job: JobSummary,
+    pub items: Vec<JobItemSummary>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetJobsRequest {
+    pub status: Option<JobStatus>, // 未指定の場合は全てのジョブ
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetJobsResponse {
+    pub jobs: Vec<JobSummary>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSummary {
+    pub id: i32,
+    pub job_type: JobType,
+    pub status: JobStatus,
+    pub total_items: i32,
+    pub completed_items: i32,
+    pub failed_items: i32,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobItemSummary {
+    pub id: i32,
+    pub item_index: i32,
+    pub payload: String, // JSON
+    pub status: JobItemStatus,
+    pub result: Option<String>, // JSON
+    pub error: Option<String>,
+}
+
+/// ComparingPromptRunジョブの内容
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunJobPayload {
+    pub run_id: i32,
+}
+
+/// ComparingPromptSweepジョブの内容
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepJobPayload {
+    pub sweep_id: i32,
+}
+
+/// 実行と設定のバージョンの組み合わせごとの項目
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunJobItemPayload {
+    pub run_id: i32,
+    pub version_id: i32,
+}
+
+/// 項目の実行結果
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobItemOutput {
+    pub result: String, // JSON
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunJobItemResult {
+    pub history_ids: Vec<i32>,
+    pub cancelled: bool,
+}
+
+#[async_trait]
+pub trait JobQueue: Send + Sync {
+    async fn enqueue_run_job(
+        &self,
+        request: EnqueueRunJobRequest,
+    ) -> Result<EnqueueJobResponse, ApplicationError>;
+
+    async fn enqueue_sweep_job(
+        &self,
+        request: EnqueueSweepJobRequest,
+    ) -> Result<EnqueueJobResponse, ApplicationError>;
+
+    async fn get_job(&self, request: GetJobRequest) -> Result<GetJobResponse, ApplicationError>;
+
+    async fn get_jobs(&self, request: GetJobsRequest) -> Result<GetJobsResponse, ApplicationError>;
+}
+
+/// ジョブの種類ごとに項目の展開と実行を行う
+#[async_trait]
+pub trait JobExecutor: Send + Sync {
+    /// ジョブの内容を項目（JSON）に展開する
+    async fn plan(
+        &self,
+        job_type: &JobType,
+        payload: &str,
+    ) -> Result<Vec<String>, ApplicationError>;
+
+    /// 項目を実行し、結果を返す
+    /// 実行中に保存するデータには、再実行時に破棄できるよう項目のIDを記録する
+    async fn execute_item(
+        &self,
+        job_type: &JobType,
+        item_id: i32,
+        payload: &str,
+    ) -> Result<JobItemOutput, ApplicationError>;
+
+    /// 中断された前回の実行で項目が途中まで保存したデータを破棄する
+    async fn discard_item(&self, job_type: &JobType, item_id: i32) -> Result<(), ApplicationError>;
+}
+
+#[derive(Debug)]
+pub struct JobUsecase<J, E, V>
+where
+    J: JobRepository,
+    E: JobExecutor,
+    V: EventEmitter,
+{
+    job_repository: Arc<J>,
+    job_executor: Arc<E>,
+    event_emitter: Arc<V>,
+    notify: Arc<Notify>,
+}
+
+impl<J, E, V> Clone for JobUsecase<J, E, V>
+where
+    J: JobRepository,
+    E: JobExecutor,
+    V: EventEmitter,
+{
+    fn clone(&self) -> Self {
+        JobUsecase {
+            job_repository: Arc::clone(&self.job_repository),
+            job_executor: Arc::clone(&self.job_executor),
+            event_emitter: Arc::clone(&self.event_emitter),
+            notify: Arc::clone(&self.notify),
+        }
+    }
+}
+
+#[async_trait]
+impl<J, E, V> JobQueue for JobUsecase<J, E, V>
+where
+    J: JobRepository,
+    E: JobExecutor,
+    V: EventEmitter,
+{
+    async fn enqueue_run_job(
+        &self,
+        request: EnqueueRunJobRequest,
+    ) -> Result<EnqueueJobResponse, ApplicationError> {
+        let payload = RunJobPayload {
+            run_id: request.run_id,
+        };
+        self.enqueue(JobType::ComparingPromptRun, &payload).await
+    }
+
+    async fn enqueue_sweep_job(
+        &self,
+        request: EnqueueSweepJobRequest,
+    ) -> Result<EnqueueJobResponse, ApplicationError> {
+        let payload = SweepJobPayload {
+            sweep_id: request.sweep_id,
+        };
+        self.enqueue(JobType::ComparingPromptSweep, &payload).await
+    }
+
+    async fn get_job(&self, request: GetJobRequest) -> Result<GetJobResponse, ApplicationError> {
+        let job = self.job_repository.find_job_by_id(request.id).await?;
+        let items = self
+            .job_repository
+            .find_job_items_by_job_id(request.id)
+            .await?;
+        Ok(GetJobResponse {
+            job: to_job_summary(job),
+            items: items.into_iter().map(to_job_item_summary).collect(),
+        })
+    }
+
+    async fn get_jobs(&self, request: GetJobsRequest) -> Result<GetJobsResponse, ApplicationError> {
+        let jobs = self
+            .job_repository
+            .find_jobs(request.status.as_ref())
+            .await?;
+        Ok(GetJobsResponse {
+            jobs: jobs.into_iter().map(to_job_summary).collect(),
+        })
+    }
+}
+
+impl<J, E, V> JobUsecase<J, E, V>
+where
+    J: JobRepository,
+    E: JobExecutor,
+    V: EventEmitter,
+{
+    pub fn new(job_repository: Arc<J>, job_executor: Arc<E>, event_emitter: Arc<V>) -> Self {
+        JobUsecase {
+            job_repository,
+            job_executor,
+            event_emitter,
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 前回の起動時に中断されたジョブをキューに戻す
+    /// 完了した項目はそのまま残るので、ワーカーは未完了の項目から再開する
+    pub async fn resume_interrupted_jobs(&self) -> Result<u64, ApplicationError> {
+        let resumed = self.job_repository.resume_interrupted_jobs().await?;
+        if resumed > 0 {
+            log::info!("resume {} interrupted jobs", resumed);
+        }
+        Ok(resumed)
+    }
+
+    /// キューの先頭のジョブを処理する（処理するジョブがない場合はfalse）
+    pub async fn run_next_job(&self) -> Result<bool, ApplicationError> {
+        let job = match self.job_repository.claim_next_job().await? {
+            Some(job) => job,
+            None => return Ok(false),
+        };
+        log::info!("start job: {} ({})", job.id, job.job_type);
+        let mut progress = JobProgress {
+            total_items: job.total_items,
+            completed_items: job.completed_items,
+            failed_items: job.failed_items,
+        };
+        emit_progress(
+            self.event_emitter.as_ref(),
+            JobStartedEvent {
+                job_id: job.id,
+                job_type: job.job_type,
+                progress,
+            },
+        );
+        let (status, error) = match self.run_job_items(&job, &mut progress).await {
+            Ok(()) if progress.failed_items > 0 => (
+                JobStatus::Failed,
+                Some(format!(
+                    "{} of {} items failed",
+                    progress.failed_items, progress.total_items
+                )),
+            ),
+            Ok(()) => (JobStatus::Completed, None),
+            Err(err) => (JobStatus::Failed, Some(err.to_string())),
+        };
+        self.job_repository
+            .finish_job(job.id, &status, error.as_deref())
+            .await?;
+        log::info!("finish job: {} ({})", job.id, status);
+        emit_progress(
+            self.event_emitter.as_ref(),
+            JobFinishedEvent {
+                job_id: job.id,
+                status,
+                error,
+                progress,
+            },
+        );
+        Ok(true)
+    }
+
+    /// 未完了の項目を順に実行し、進捗を更新する
+    /// 項目の失敗は記録して次の項目に進む
+    async fn run_job_items(
+        &self,
+        job: &JobModel,
+        progress: &mut JobProgress,
+    ) -> Result<(), ApplicationError> {
+        let items = self.job_repository.find_job_items_by_job_id(job.id).await?;
+        for item in items {
+            if item.status != JobItemStatus::Pending {
+                continue;
+            }
+            // 実行中にアプリが終了した項目は、保存済みの途中結果を破棄してから再実行する
+            self.job_executor
+                .discard_item(&job.job_type, item.id)
+                .await?;
+            self.job_repository.start_job_item(item.id).await?;
+            emit_progress(
+                self.event_emitter.as_ref(),
+                JobItemStartedEvent {
+                    job_id: job.id,
+                    item_id: item.id,
+                    item_index: item.item_index,
+                    payload: item.payload.clone(),
+                },
+            );
+            let started = Instant::now();
+            let result = self
+                .job_executor
+                .execute_item(&job.job_type, item.id, &item.payload)
+                .await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+            match result {
+                Ok(output) => {
+                    self.job_repository
+                        .finish_job_item(item.id, Ok(output.result.clone()))
+                        .await?;
+                    progress.completed_items += 1;
+                    emit_progress(
+                        self.event_emitter.as_ref(),
+                        JobItemFinishedEvent {
+                            job_id: job.id,
+                            item_id: item.id,
+                            item_index: item.item_index,
+                            result: output.result,
+                            latency_ms,
+                            prompt_tokens: output.prompt_tokens,
+                            completion_tokens: output.completion_tokens,
+                            progress: *progress,
+                        },
+                    );
+                }
+                Err(err) => {
+                    log::error!("job {} item {} error: {}", job.id, item.item_index, err);
+                    self.job_repository
+                        .finish_job_item(item.id, Err(err.to_string()))
+                        .await?;
+                    progress.failed_items += 1;
+                    emit_progress(
+                        self.event_emitter.as_ref(),
+                        JobItemFailedEvent {
+                            job_id: job.id,
+                            item_id: item.id,
+                            item_index: item.item_index,
+                            error: err.to_string(),
+                            latency_ms,
+                            progress: *progress,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn enqueue<P: Serialize + Sync>(
+        &self,
+        job_type: JobType,
+        payload: &P,
+    ) -> Result<EnqueueJobResponse, ApplicationError> {
+        let payload = serde_json::to_string(payload)
+            .map_err(|e| ApplicationError::ParseError(e.to_string()))?;
+        let items = self.job_executor.plan(&job_type, &payload).await?;
+        if items.is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "job has no items to run".to_string(),
+            ));
+        }
+        let id = self
+            .job_repository
+            .create_job(&job_type, &payload, &items)
+            .await?;
+        self.notify.notify_one();
+        Ok(EnqueueJobResponse {
+            id,
+            total_items: items.len() as i32,
+        })
+    }
+}
+
+impl<J, E, V> JobUsecase<J, E, V>
+where
+    J: JobRepository + 'static,
+    E: JobExecutor + 'static,
+    V: EventEmitter + 'static,
+{
+    /// tokioのランタイム上でワーカーを起動する
+    /// 各ワーカーはキューが空になるまでジョブを順に処理し、空の場合は登録の通知を待つ
+    pub fn start_workers(&self, workers: usize) {
+        for worker in 0..workers {
+            let usecase = self.clone();
+            tokio::spawn(async move {
+                loop {
+                    match usecase.run_next_job().await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            let _ =
+                                tokio::time::timeout(JOB_POLL_INTERVAL, usecase.notify.notified())
+                                    .await;
+                        }
+                        Err(err) => {
+                            log::error!("job worker {} error: {}", worker, err);
+                            tokio::time::sleep(JOB_ERROR_BACKOFF).await;
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// 実行をマネージャーの全ての設定の現在のバージョンで実行するジョブの実装
+#[derive(Debug)]
+pub struct ComparingPromptJobExecutor<R, U, P>
+where
+    R: ComparingPromptSettingRepository,
+    U: ComparingPromptRunRepository,
+    P: ComparingPrompt,
+{
+    comparing_prompt_setting_repository: Arc<R>,
+    comparing_prompt_run_repository: Arc<U>,
+    comparing_prompt: Arc<P>,
+}
+
+#[async_trait]
+impl<R, U, P> JobExecutor for ComparingPromptJobExecutor<R, U, P>
+where
+    R: ComparingPromptSettingRepository,
+    U: ComparingPromptRunRepository,
+    P: ComparingPrompt,
+{
+    async fn plan(
+        &self,
+        job_type: &JobType,
+        payload: &str,
+    ) -> Result<Vec<String>, ApplicationError> {
+        let runs = match job_type {
+            JobType::ComparingPromptRun => {
+                let payload: RunJobPayload = parse_json(payload)?;
+                vec![
+                    self.comparing_prompt_run_repository
+                        .find_comparing_prompt_run_by_id(payload.run_id)
+                        .await?,
+                ]
+            }
+            JobType::ComparingPromptSweep => {
+                let payload: SweepJobPayload = parse_json(payload)?;
+                // スイープが存在しない場合はエラーにする
+                self.comparing_prompt_run_repository
+                    .find_comparing_prompt_sweep_by_id(payload.sweep_id)
+                    .await?;
+                self.comparing_prompt_run_repository
+                    .find_comparing_prompt_runs_by_sweep_id(payload.sweep_id)
+                    .await?
+            }
+        };
+        let mut items = Vec::new();
+        for run in runs {
+            for item in self.plan_run(&run).await? {
+                items.push(
+                    serde_json::to_string(&item)
+                        .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+                );
+            }
+        }
+        Ok(items)
+    }
+
+    async fn execute_item(
+        &self,
+        _job_type: &JobType,
+        item_id: i32,
+        payload: &str,
+    ) -> Result<JobItemOutput, ApplicationError> {
+        let item: RunJobItemPayload = parse_json(payload)?;
+        let run = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_by_id(item.run_id)
+            .await?;
+        let version = self
+            .comparing_prompt_setting_repository
+            .find_comparing_prompt_setting_version_by_id(item.version_id)
+            .await?;
+        let request = to_run_chat_request(run, version, item_id)?;
+        let res = self.comparing_prompt.run_chat(request).await?;
+        let result = RunJobItemResult {
+            history_ids: res
+                .samples
+                .iter()
+                .filter_map(|sample| sample.history_id)
+                .collect(),
+            cancelled: res.cancelled,
+        };
+        Ok(JobItemOutput {
+            result: serde_json::to_string(&result)
+                .map_err(|e| ApplicationError::ParseError(e.to_string()))?,
+            prompt_tokens: res.prompt_tokens,
+            completion_tokens: res.completion_tokens,
+        })
+    }
+
+    async fn discard_item(
+        &self,
+        _job_type: &JobType,
+        item_id: i32,
+    ) -> Result<(), ApplicationError> {
+        let deleted = self
+            .comparing_prompt_run_repository
+            .delete_comparing_prompt_run_histories_by_job_item_id(item_id)
+            .await?;
+        if deleted > 0 {
+            log::info!("discard {} histories of job item {}", deleted, item_id);
+        }
+        Ok(())
+    }
+}
+
+impl<R, U, P> ComparingPromptJobExecutor<R, U, P>
+where
+    R: ComparingPromptSettingRepository,
+    U: ComparingPromptRunRepository,
+    P: ComparingPrompt,
+{
+    pub fn new(
+        comparing_prompt_setting_repository: Arc<R>,
+        comparing_prompt_run_repository: Arc<U>,
+        comparing_prompt: Arc<P>,
+    ) -> Self {
+        ComparingPromptJobExecutor {
+            comparing_prompt_setting_repository,
+            comparing_prompt_run_repository,
+            comparing_prompt,
+        }
+    }
+
+    /// 実行をマネージャーの各設定の現在のバージョンごとの項目に展開する
+    async fn plan_run(
+        &self,
+        run: &ComparingPromptSettingRunModel,
+    ) -> Result<Vec<RunJobItemPayload>, ApplicationError> {
+        let settings = self
+            .comparing_prompt_setting_repository
+            .find_all_comparing_prompt_settings_by_manager_id(run.manager_id)
+            .await?;
+        Ok(settings
+            .iter()
+            .filter_map(|setting| {
+                setting
+                    .versions
+                    .iter()
+                    .find(|version| version.version == setting.current_version)
+            })
+            .map(|version| RunJobItemPayload {
+                run_id: run.id,
+                version_id: version.id,
+            })
+            .collect())
+    }
+}
+
+/// 保存された実行とバージョンから実行のリクエストを作る
+/// 保存時に検証しているが、範囲外の値が保存されている場合は切り詰めずに失敗させる
+fn to_run_chat_request(
+    run: ComparingPromptSettingRunModel,
+    version: ComparingPromptSettingVersionModel,
+    job_item_id: i32,
+) -> Result<RunChatRequest, ApplicationError> {
+    let max_tokens = run
+        .max_tokens
+        .map(|max_tokens| {
+            u16::try_from(max_tokens).map_err(|_| {
+                ApplicationError::ValidationError(format!(
+                    "max_tokens is out of range: {}",
+                    max_tokens
+                ))
+            })
+        })
+        .transpose()?;
+    let repetitions = u8::try_from(run.repetitions).map_err(|_| {
+        ApplicationError::ValidationError(format!(
+            "repetitions is out of range: {}",
+            run.repetitions
+        ))
+    })?;
+    Ok(RunChatRequest {
+        run_id: run.id,
+        version_id: Some(version.id),
+        user_prompt: run.user_prompt,
+        system_prompt: version.system_prompt,
+        provider_type: run.provider_type,
+        model: run.model,
+        temperature: run.temperature as f32,
+        top_p: run.top_p.map(|top_p| top_p as f32),
+        max_tokens,
+        response_format: run.response_format,
+        repetitions: Some(repetitions),
+        sampling: run.sampling,
+        use_cache: false,
+        cache_ttl_seconds: None,
+        job_item_id: Some(job_item_id),
+    })
+}
+
+fn parse_json<T: for<'de> Deserialize<'de>>(payload: &str) -> Result<T, ApplicationError> {
+    serde_json::from_str(payload).map_err(|e| ApplicationError::ParseError(e.to_string()))
+}
+
+fn to_job_summary(job: JobModel) -> JobSummary {
+    JobSummary {
+        id: job.id,
+        job_type: job.job_type,
+        status: job.status,
+        total_items: job.total_items,
+        completed_items: job.completed_items,
+        failed_items: job.failed_items,
+        error: job.error,
+        created_at: job.created_at,
+        updated_at: job.updated_at,
+    }
+}
+
+fn to_job_item_summary(item: JobItemModel) -> JobItemSummary {
+    JobItemSummary {
+        id: item.id,
+        item_index: item.item_index,
+        payload: item.payload,
+        status: item.status,
+        result: item.result,
+        error: item.error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::domain::chat::SamplingParameters;
+    use crate::domain::comparing_prompt::ProviderType;
+    use crate::usecase::progress::ProgressEvent;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    #[derive(Default)]
+    struct MockJobRepository {
+        jobs: Mutex<Vec<JobModel>>,
+        items: Mutex<Vec<JobItemModel>>,
+    }
+
+    /// 項目の内容が"fail"の場合は失敗し、それ以外は内容をそのまま結果として返す
+    /// 成功した項目は項目のIDと内容の組をsavedに保存する
+    #[derive(Default)]
+    struct MockJobExecutor {
+        executed: Mutex<Vec<String>>,
+        saved: Mutex<Vec<(i32, String)>>,
+    }
+
+    /// 通知されたイベントを名前とpayloadの組で記録する
+    #[derive(Default)]
+    struct MockEventEmitter {
+        events: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl EventEmitter for MockEventEmitter {
+        fn emit<T: Serialize + Clone>(&self, event: &str, payload: T) {
+            self.events
+                .lock()
+                .unwrap()
+                .push((event.to_string(), serde_json::to_value(payload).unwrap()));
+        }
+    }
+
+    #[async_trait]
+    impl JobRepository for MockJobRepository {
+        async fn create_job(
+            &self,
+            job_type: &JobType,
+            payload: &str,
+            items: &[String],
+        ) -> Result<i32, ApplicationError> {
+            let mut jobs = self.jobs.lock().unwrap();
+            let id = jobs.len() as i32 + 1;
+            jobs.push(JobModel {
+                id,
+                job_type: *job_type,
+                payload: payload.to_string(),
+                status: JobStatus::Queued,
+                total_items: items.len() as i32,
+                completed_items: 0,
+                failed_items: 0,
+                error: None,
+                created_at: "2024-01-01 00:00:00".to_string(),
+                updated_at: "2024-01-01 00:00:00".to_string(),
+            });
+            let mut job_items = self.items.lock().unwrap();
+            for (item_index, item) in items.iter().enumerate() {
+                let item_id = job_items.len() as i32 + 1;
+                job_items.push(JobItemModel {
+                    id: item_id,
+                    job_id: id,
+                    item_index: item_index as i32,
+                    payload: item.clone(),
+                    status: JobItemStatus::Pending,
+                    result: None,
+                    error: None,
+                });
+            }
+            Ok(id)
+        }
+
+        async fn find_job_by_id(&self, id: i32) -> Result<JobModel, ApplicationError> {
+            let jobs = self.jobs.lock().unwrap();
+            jobs.iter()
+                .find(|job| job.id == id)
+                .cloned()
+                .ok_or(ApplicationError::EmptyResult)
+        }
+
+        async fn find_jobs(
+            &self,
+            status: Option<&JobStatus>,
+        ) -> Result<Vec<JobModel>, ApplicationError> {
+            let jobs = self.jobs.lock().unwrap();
+            Ok(jobs
+                .iter()
+                .rev()
+                .filter(|job| status.is_none() || status == Some(&job.status))
+                .cloned()
+                .collect())
+        }
+
+        async fn find_job_items_by_job_id(
+            &self,
+            job_id: i32,
+        ) -> Result<Vec<JobItemModel>, ApplicationError> {
+            let items = self.items.lock().unwrap();
+            Ok(items
+                .iter()
+                .filter(|item| item.job_id == job_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn claim_next_job(&self) -> Result<Option<JobModel>, ApplicationError> {
+            let mut jobs = self.jobs.lock().unwrap();
+            Ok(jobs
+                .iter_mut()
+                .find(|job| job.status == JobStatus::Queued)
+                .map(|job| {
+                    job.status = JobStatus::Running;
+                    job.clone()
+                }))
+        }
+
+        async fn start_job_item(&self, item_id: i32) -> Result<(), ApplicationError> {
+            let mut items = self.items.lock().unwrap();
+            let item = items.iter_mut().find(|item| item.id == item_id).unwrap();
+            item.status = JobItemStatus::Running;
+            Ok(())
+        }
+
+        async fn finish_job_item(
+            &self,
+            item_id: i32,
+            result: Result<String, String>,
+        ) -> Result<(), ApplicationError> {
+            let mut items = self.items.lock().unwrap();
+            let item = items.iter_mut().find(|item| item.id == item_id).unwrap();
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs.iter_mut().find(|job| job.id == item.job_id).unwrap();
+            match result {
+                Ok(result) => {
+                    item.status = JobItemStatus::Completed;
+                    item.result = Some(result);
+                    job.completed_items += 1;
+                }
+                Err(error) => {
+                    item.status = JobItemStatus::Failed;
+                    item.error = Some(error);
+                    job.failed_items += 1;
+                }
+            }
+            Ok(())
+        }
+
+        async fn finish_job(
+            &self,
+            id: i32,
+            status: &JobStatus,
+            error: Option<&str>,
+        ) -> Result<(), ApplicationError> {
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs.iter_mut().find(|job| job.id == id).unwrap();
+            job.status = *status;
+            job.error = error.map(|error| error.to_string());
+            Ok(())
+        }
+
+        async fn resume_interrupted_jobs(&self) -> Result<u64, ApplicationError> {
+            let mut items = self.items.lock().unwrap();
+            for item in items.iter_mut() {
+                if item.status == JobItemStatus::Running {
+                    item.status = JobItemStatus::Pending;
+                }
+            }
+            let mut jobs = self.jobs.lock().unwrap();
+            let mut resumed = 0;
+            for job in jobs.iter_mut() {
+                if job.status == JobStatus::Running {
+                    job.status = JobStatus::Queued;
+                    resumed += 1;
+                }
+            }
+            Ok(resumed)
+        }
+    }
+
+    #[async_trait]
+    impl JobExecutor for MockJobExecutor {
+        async fn plan(
+            &self,
+            _job_type: &JobType,
+            payload: &str,
+        ) -> Result<Vec<String>, ApplicationError> {
+            let payload: RunJobPayload = parse_json(payload)?;
+            Ok(match payload.run_id {
+                0 => Vec::new(),
+                _ => vec!["a".to_string(), "fail".to_string(), "b".to_string()],
+            })
+        }
+
+        async fn execute_item(
+            &self,
+            _job_type: &JobType,
+            item_id: i32,
+            payload: &str,
+        ) -> Result<JobItemOutput, ApplicationError> {
+            self.executed.lock().unwrap().push(payload.to_string());
+            match payload {
+                "fail" => Err(ApplicationError::UnknownError("item failed".to_string())),
+                _ => {
+                    self.saved
+                        .lock()
+                        .unwrap()
+                        .push((item_id, payload.to_string()));
+                    Ok(JobItemOutput {
+                        result: payload.to_string(),
+                        prompt_tokens: Some(10),
+                        completion_tokens: Some(5),
+                    })
+                }
+            }
+        }
+
+        async fn discard_item(
+            &self,
+            _job_type: &JobType,
+            item_id: i32,
+        ) -> Result<(), ApplicationError> {
+            self.saved.lock().unwrap().retain(|(id, _)| *id != item_id);
+            Ok(())
+        }
+    }
+
+    fn job_usecase() -> JobUsecase<MockJobRepository, MockJobExecutor, MockEventEmitter> {
+        JobUsecase {
+            job_repository: Arc::new(MockJobRepository::default()),
+            job_executor: Arc::new(MockJobExecutor::default()),
+            event_emitter: Arc::new(MockEventEmitter::default()),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /**
+     * Tests
+     */
+    #[tokio::test]
+    async fn test_run_next_job() {
+        let job_usecase = job_usecase();
+        let enqueued = job_usecase
+            .enqueue_run_job(EnqueueRunJobRequest { run_id: 1 })
+            .await
+            .unwrap();
+        assert_eq!(enqueued.total_items, 3);
+
+        // 失敗した項目があっても残りの項目は実行される
+        assert!(job_usecase.run_next_job().await.unwrap());
+        assert!(!job_usecase.run_next_job().await.unwrap());
+
+        let res = job_usecase
+            .get_job(GetJobRequest { id: enqueued.id })
+            .await
+            .unwrap();
+        assert_eq!(res.job.status, JobStatus::Failed);
+        assert_eq!(res.job.completed_items, 2);
+        assert_eq!(res.job.failed_items, 1);
+        assert_eq!(res.job.error.as_deref(), Some("1 of 3 items failed"));
+        assert_eq!(res.items[1].status, JobItemStatus::Failed);
+        assert!(res.items[1].error.as_ref().unwrap().contains("item failed"));
+        assert_eq!(res.items[2].result.as_deref(), Some("b"));
+    }
+
+    #[tokio::test]
+    async fn test_run_next_job_progress_events() {
+        let job_usecase = job_usecase();
+        let enqueued = job_usecase
+            .enqueue_run_job(EnqueueRunJobRequest { run_id: 1 })
+            .await
+            .unwrap();
+        job_usecase.run_next_job().await.unwrap();
+
+        let events = job_usecase.event_emitter.events.lock().unwrap().clone();
+        let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                JobStartedEvent::NAME,
+                JobItemStartedEvent::NAME,
+                JobItemFinishedEvent::NAME,
+                JobItemStartedEvent::NAME,
+                JobItemFailedEvent::NAME,
+                JobItemStartedEvent::NAME,
+                JobItemFinishedEvent::NAME,
+                JobFinishedEvent::NAME,
+            ]
+        );
+
+        // 項目の完了ごとに結果、トークン数、進捗を通知する
+        let finished: JobItemFinishedEvent = serde_json::from_value(events[2].1.clone()).unwrap();
+        assert_eq!(finished.job_id, enqueued.id);
+        assert_eq!(finished.item_index, 0);
+        assert_eq!(finished.result, "a");
+        assert_eq!(finished.prompt_tokens, Some(10));
+        assert_eq!(finished.completion_tokens, Some(5));
+        assert_eq!(finished.progress.completed_items, 1);
+        let failed: JobItemFailedEvent = serde_json::from_value(events[4].1.clone()).unwrap();
+        assert!(failed.error.contains("item failed"));
+        assert_eq!(failed.progress.failed_items, 1);
+
+        let job_finished: JobFinishedEvent = serde_json::from_value(events[7].1.clone()).unwrap();
+        assert_eq!(job_finished.status, JobStatus::Failed);
+        assert_eq!(
+            job_finished.progress,
+            JobProgress {
+                total_items: 3,
+                completed_items: 2,
+                failed_items: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_next_job_resume() {
+        let job_usecase = job_usecase();
+        let enqueued = job_usecase
+            .enqueue_run_job(EnqueueRunJobRequest { run_id: 1 })
+            .await
+            .unwrap();
+
+        // 1件目の完了後、2件目の実行中にアプリが終了した状態にする
+        let repository = &job_usecase.job_repository;
+        let job = repository.claim_next_job().await.unwrap().unwrap();
+        let items = repository.find_job_items_by_job_id(job.id).await.unwrap();
+        repository
+            .finish_job_item(items[0].id, Ok("a".to_string()))
+            .await
+            .unwrap();
+        repository.start_job_item(items[1].id).await.unwrap();
+
+        // 再起動時にキューに戻し、完了していない項目だけを実行する
+        assert_eq!(job_usecase.resume_interrupted_jobs().await.unwrap(), 1);
+        assert!(job_usecase.run_next_job().await.unwrap());
+
+        let executed = job_usecase.job_executor.executed.lock().unwrap().clone();
+        assert_eq!(executed, vec!["fail", "b"]);
+        let res = job_usecase
+            .get_jobs(GetJobsRequest {
+                status: Some(JobStatus::Failed),
+            })
+            .await
+            .unwrap();
+        assert_eq!(res.jobs.len(), 1);
+        assert_eq!(res.jobs[0].id, enqueued.id);
+        assert_eq!(res.jobs[0].completed_items, 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_next_job_resume_discards_partial_results() {
+        let job_usecase = job_usecase();
+        job_usecase
+            .enqueue_run_job(EnqueueRunJobRequest { run_id: 1 })
+            .await
+            .unwrap();
+
+        // 事前データ
+        // 1件目の完了後、3件目の結果を途中まで保存したところでアプリが終了した状態にする
+        let repository = &job_usecase.job_repository;
+        let job = repository.claim_next_job().await.unwrap().unwrap();
+        let items = repository.find_job_items_by_job_id(job.id).await.unwrap();
+        repository
+            .finish_job_item(items[0].id, Ok("a".to_string()))
+            .await
+            .unwrap();
+        repository.start_job_item(items[2].id).await.unwrap();
+        *job_usecase.job_executor.saved.lock().unwrap() = vec![
+            (items[0].id, "a".to_string()),
+            (items[2].id, "b".to_string()),
+            (items[2].id, "b".to_string()),
+        ];
+
+        // テスト対象のメソッドを呼び出し
+        job_usecase.resume_interrupted_jobs().await.unwrap();
+        assert!(job_usecase.run_next_job().await.unwrap());
+
+        // assert
+        // 途中までの結果は破棄され、再実行した結果だけが残る
+        let saved = job_usecase.job_executor.saved.lock().unwrap().clone();
+        assert_eq!(
+            saved,
+            vec![
+                (items[0].id, "a".to_string()),
+                (items[2].id, "b".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_run_job_empty() {
+        let job_usecase = job_usecase();
+        let result = job_usecase
+            .enqueue_run_job(EnqueueRunJobRequest { run_id: 0 })
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_to_run_chat_request() {
+        // 事前データ
+        let run = |max_tokens: Option<i32>, repetitions: i32| ComparingPromptSettingRunModel {
+            id: 1,
+            manager_id: 1,
+            user_prompt: "test_user_prompt".to_string(),
+            provider_type: ProviderType::OpenAI,
+            model: "gpt-4o".to_string(),
+            temperature: 0.5,
+            max_tokens,
+            response_format: None,
+            repetitions,
+            top_p: None,
+            sweep_id: None,
+            sampling: SamplingParameters::default(),
+            credential_profile_id: None,
+        };
+        let version = ComparingPromptSettingVersionModel {
+            id: 2,
+            setting_id: 1,
+            version: 1,
+            system_prompt: "test_system_prompt".to_string(),
+            tools: Vec::new(),
+            tool_choice: None,
+            tool_scripts: Vec::new(),
+        };
+
+        // テスト対象のメソッドを呼び出し
+        let result =
+            to_run_chat_request(run(Some(u16::MAX as i32), 3), version.clone(), 3).unwrap();
+
+        // assert
+        assert_eq!(result.version_id, Some(2));
+        assert_eq!(result.system_prompt, "test_system_prompt");
+        assert_eq!(result.max_tokens, Some(u16::MAX));
+        assert_eq!(result.repetitions, Some(3));
+        assert_eq!(result.job_item_id, Some(3));
+
+        // 範囲外の値は切り詰めずに失敗する
+        let result = to_run_chat_request(run(Some(u16::MAX as i32 + 1), 1), version.clone(), 3);
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        let result = to_run_chat_request(run(None, 256), version, 3);
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+}
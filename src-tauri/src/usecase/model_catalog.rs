@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::comparing_prompt::ProviderType;
+use crate::domain::model_catalog::{AIModelList, ModelCatalogModel, ModelCatalogRepository};
+use crate::domain::prompt_manager::APIType;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncModelsRequest {
+    pub provider_type: ProviderType,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncModelsResponse {
+    pub count: i32,
+    pub known_count: i32, // マニフェストで対応機能が分かるモデルの数
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetModelsRequest {
+    pub provider_type: Option<ProviderType>,
+    pub api_type: Option<APIType>, // 指定した場合は対応機能が分かるモデルのみ返す
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetModelsResponse {
+    pub models: Vec<ModelItem>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelItem {
+    pub provider_type: ProviderType,
+    pub model: String,
+    pub known: bool,
+    pub vision: bool,
+    pub json_mode: bool,
+    pub tools: bool,
+    pub context_window: Option<i32>,
+    pub input_price: Option<f64>, // 100万トークンあたりのUSD
+    pub output_price: Option<f64>,
+}
+
+#[async_trait]
+pub trait ModelCatalog: Send + Sync {
+    async fn sync_models(
+        &self,
+        request: SyncModelsRequest,
+    ) -> Result<SyncModelsResponse, ApplicationError>;
+
+    async fn get_models(
+        &self,
+        request: GetModelsRequest,
+    ) -> Result<GetModelsResponse, ApplicationError>;
+}
+
+#[derive(Clone, Debug)]
+pub struct ModelCatalogUsecase<L, M>
+where
+    L: AIModelList,
+    M: ModelCatalogRepository,
+{
+    ai_model_list: Arc<L>,
+    model_catalog_repository: Arc<M>,
+}
+
+#[async_trait]
+impl<L, M> ModelCatalog for ModelCatalogUsecase<L, M>
+where
+    L: AIModelList,
+    M: ModelCatalogRepository,
+{
+    async fn sync_models(
+        &self,
+        request: SyncModelsRequest,
+    ) -> Result<SyncModelsResponse, ApplicationError> {
+        let models: Vec<ModelCatalogModel> = self
+            .ai_model_list
+            .list_models(&request.provider_type)
+            .await?
+            .into_iter()
+            .map(|listed| match listed.base_model {
+                Some(base_model) => ModelCatalogModel::from_deployment(
+                    request.provider_type.clone(),
+                    listed.model,
+                    &base_model,
+                ),
+                None => ModelCatalogModel::from_listed(request.provider_type.clone(), listed.model),
+            })
+            .collect();
+        let count = models.len() as i32;
+        let known_count = models.iter().filter(|model| model.known).count() as i32;
+        self.model_catalog_repository
+            .replace_model_catalog(&request.provider_type, models)
+            .await?;
+        Ok(SyncModelsResponse { count, known_count })
+    }
+
+    async fn get_models(
+        &self,
+        request: GetModelsRequest,
+    ) -> Result<GetModelsResponse, ApplicationError> {
+        let models = self
+            .model_catalog_repository
+            .find_model_catalog(request.provider_type.as_ref())
+            .await?;
+        // マニフェストには対話用のモデルのみ含まれるので、Chatは対応機能が分かるモデルに絞る
+        let models = models
+            .into_iter()
+            .filter(|model| match request.api_type {
+                Some(APIType::Chat) => model.known,
+                Some(APIType::Vision) => model.known && model.capabilities.vision,
+                None => true,
+            })
+            .map(|model| ModelItem {
+                provider_type: model.provider_type,
+                model: model.model,
+                known: model.known,
+                vision: model.capabilities.vision,
+                json_mode: model.capabilities.json_mode,
+                tools: model.capabilities.tools,
+                context_window: model.capabilities.context_window,
+                input_price: model.capabilities.input_price,
+                output_price: model.capabilities.output_price,
+            })
+            .collect();
+        Ok(GetModelsResponse { models })
+    }
+}
+
+impl<L, M> ModelCatalogUsecase<L, M>
+where
+    L: AIModelList,
+    M: ModelCatalogRepository,
+{
+    pub fn new(ai_model_list: Arc<L>, model_catalog_repository: Arc<M>) -> Self {
+        ModelCatalogUsecase {
+            ai_model_list,
+            model_catalog_repository,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use sea_orm::DbErr;
+
+    use crate::domain::model_catalog::ListedModel;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    struct MockAIModelList {}
+    #[async_trait]
+    impl AIModelList for MockAIModelList {
+        async fn list_models(
+            &self,
+            _provider_type: &ProviderType,
+        ) -> Result<Vec<ListedModel>, ApplicationError> {
+            Ok(vec![
+                ListedModel::new("gpt-4o"),
+                ListedModel::new("gpt-4-0613"),
+                ListedModel::new("text-embedding-3-small"),
+                ListedModel {
+                    model: "prod-gpt4o".to_string(),
+                    base_model: Some("gpt-4o-mini".to_string()),
+                },
+            ])
+        }
+    }
+
+    #[derive(Default)]
+    struct MockModelCatalogRepository {
+        models: Mutex<Vec<ModelCatalogModel>>,
+    }
+    #[async_trait]
+    impl ModelCatalogRepository for MockModelCatalogRepository {
+        async fn find_model_catalog(
+            &self,
+            provider_type: Option<&ProviderType>,
+        ) -> Result<Vec<ModelCatalogModel>, ApplicationError> {
+            Ok(self
+                .models
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|model| provider_type.is_none_or(|p| &model.provider_type == p))
+                .cloned()
+                .collect())
+        }
+
+        async fn replace_model_catalog(
+            &self,
+            _provider_type: &ProviderType,
+            models: Vec<ModelCatalogModel>,
+        ) -> Result<(), ApplicationError> {
+            *self.models.lock().unwrap() = models;
+            Ok(())
+        }
+    }
+
+    struct MockModelCatalogRepositoryError {}
+    #[async_trait]
+    impl ModelCatalogRepository for MockModelCatalogRepositoryError {
+        async fn find_model_catalog(
+            &self,
+            _provider_type: Option<&ProviderType>,
+        ) -> Result<Vec<ModelCatalogModel>, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn replace_model_catalog(
+            &self,
+            _provider_type: &ProviderType,
+            _models: Vec<ModelCatalogModel>,
+        ) -> Result<(), ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_sync_and_get_models() {
+        let usecase = ModelCatalogUsecase::new(
+            Arc::new(MockAIModelList {}),
+            Arc::new(MockModelCatalogRepository::default()),
+        );
+        let result = usecase
+            .sync_models(SyncModelsRequest {
+                provider_type: ProviderType::OpenAI,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.count, 4);
+        // デプロイメントは元のモデルの対応機能を使う
+        assert_eq!(result.known_count, 3);
+
+        let get_models = |api_type: Option<APIType>| {
+            let usecase = &usecase;
+            async move {
+                usecase
+                    .get_models(GetModelsRequest {
+                        provider_type: Some(ProviderType::OpenAI),
+                        api_type,
+                    })
+                    .await
+                    .unwrap()
+                    .models
+                    .into_iter()
+                    .map(|model| model.model)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(get_models(None).await.len(), 4);
+        assert_eq!(
+            get_models(Some(APIType::Chat)).await,
+            vec![
+                "gpt-4o".to_string(),
+                "gpt-4-0613".to_string(),
+                "prod-gpt4o".to_string()
+            ]
+        );
+        assert_eq!(
+            get_models(Some(APIType::Vision)).await,
+            vec!["gpt-4o".to_string(), "prod-gpt4o".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_models_error() {
+        let usecase = ModelCatalogUsecase::new(
+            Arc::new(MockAIModelList {}),
+            Arc::new(MockModelCatalogRepositoryError {}),
+        );
+        let result = usecase
+            .sync_models(SyncModelsRequest {
+                provider_type: ProviderType::OpenAI,
+            })
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::event::EventEmitter;
+use crate::domain::job::{JobStatus, JobType};
+
+/// フロントエンドに通知する進捗イベント
+/// イベント名ごとにpayloadの型が決まる
+pub trait ProgressEvent: Serialize + Clone {
+    const NAME: &'static str;
+}
+
+/// 進捗イベントを通知する
+pub fn emit_progress<E: EventEmitter, P: ProgressEvent>(emitter: &E, payload: P) {
+    emitter.emit(P::NAME, payload);
+}
+
+/// ジョブ全体の進捗（各イベントに含め、プログレスバーの表示に使う）
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub total_items: i32,
+    pub completed_items: i32,
+    pub failed_items: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStartedEvent {
+    pub job_id: i32,
+    pub job_type: JobType,
+    pub progress: JobProgress, // 再開されたジョブの場合は完了済みの項目を含む
+}
+
+impl ProgressEvent for JobStartedEvent {
+    const NAME: &'static str = "job-started";
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobItemStartedEvent {
+    pub job_id: i32,
+    pub item_id: i32,
+    pub item_index: i32,
+    pub payload: String, // JSON
+}
+
+impl ProgressEvent for JobItemStartedEvent {
+    const NAME: &'static str = "job-item-started";
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobItemFinishedEvent {
+    pub job_id: i32,
+    pub item_id: i32,
+    pub item_index: i32,
+    pub result: String, // JSON（途中結果の表の表示に使う）
+    pub latency_ms: u64,
+    pub prompt_tokens: Option<i32>, // トークン数を数えられないモデルの場合はNone
+    pub completion_tokens: Option<i32>,
+    pub progress: JobProgress,
+}
+
+impl ProgressEvent for JobItemFinishedEvent {
+    const NAME: &'static str = "job-item-finished";
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobItemFailedEvent {
+    pub job_id: i32,
+    pub item_id: i32,
+    pub item_index: i32,
+    pub error: String,
+    pub latency_ms: u64,
+    pub progress: JobProgress,
+}
+
+impl ProgressEvent for JobItemFailedEvent {
+    const NAME: &'static str = "job-item-failed";
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFinishedEvent {
+    pub job_id: i32,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub progress: JobProgress,
+}
+
+impl ProgressEvent for JobFinishedEvent {
+    const NAME: &'static str = "job-finished";
+}
@@ -1,10 +1,19 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 
 use crate::common::errors::ApplicationError;
-use crate::domain::prompt_manager::{APIType, ActionType, PromptManagerRepository};
+use crate::domain::prompt_manager::{
+    APIType, ActionType, PromptManagerCursor, PromptManagerQuery, PromptManagerRepository,
+    PromptManagerSort, TagMatch,
+};
+
+/// 1ページの件数の既定値と上限
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
 
 #[derive(Clone, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")] // jsonデコードする際にキャメルケースをスネークケースに変換する
@@ -40,14 +49,25 @@ pub struct GetPromptManagerRequest {
 
 type GetPromptManagerResponse = PromptManagerItem;
 
-#[derive(Clone, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct GetAllPromptManagersRequest {}
+/// 未指定の条件では絞り込まない（全て未指定の場合は作成日時の新しい順に1ページ目を返す）
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GetAllPromptManagersRequest {
+    pub title: Option<String>, // タイトルの部分一致
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    pub action_type: Option<ActionType>,
+    pub api_type: Option<APIType>,
+    pub sort: PromptManagerSort,
+    pub cursor: Option<String>, // 前のページのnextCursor
+    pub limit: Option<u64>,
+}
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GetAllPromptManagersResponse {
     pub managers: Vec<PromptManagerItem>,
+    pub next_cursor: Option<String>, // 次のページが無い場合はNone
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -136,16 +156,18 @@ where
 
     async fn get_all_prompt_managers(
         &self,
-        _request: GetAllPromptManagersRequest,
+        request: GetAllPromptManagersRequest,
     ) -> Result<GetAllPromptManagersResponse, ApplicationError> {
+        let query = to_prompt_manager_query(request)?;
         let prompt_managers = self
             .prompt_manager_repository
-            .find_all_prompt_managers()
+            .find_prompt_managers(&query)
             .await;
 
         match prompt_managers {
-            Ok(mana) => {
-                let managers = mana
+            Ok(page) => {
+                let managers = page
+                    .managers
                     .into_iter()
                     .map(|m| PromptManagerItem {
                         id: m.id,
@@ -155,7 +177,10 @@ where
                         tags: m.tags,
                     })
                     .collect();
-                Ok(GetAllPromptManagersResponse { managers })
+                Ok(GetAllPromptManagersResponse {
+                    managers,
+                    next_cursor: page.next_cursor.map(|cursor| encode_cursor(&cursor)),
+                })
             }
             Err(err) => {
                 log::error!("get_all_prompt_managers error: {}", err);
@@ -235,6 +260,51 @@ where
     }
 }
 
+fn to_prompt_manager_query(
+    request: GetAllPromptManagersRequest,
+) -> Result<PromptManagerQuery, ApplicationError> {
+    let limit = request.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(ApplicationError::ValidationError(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for tag in request.tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(PromptManagerQuery {
+        title: request
+            .title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty()),
+        tags,
+        tag_match: request.tag_match,
+        action_type: request.action_type,
+        api_type: request.api_type,
+        sort: request.sort,
+        cursor: request.cursor.as_deref().map(decode_cursor).transpose()?,
+        limit,
+    })
+}
+
+/// カーソルは画面では中身を扱わないので、不透明な文字列にする
+fn encode_cursor(cursor: &PromptManagerCursor) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor).unwrap_or_default())
+}
+
+fn decode_cursor(cursor: &str) -> Result<PromptManagerCursor, ApplicationError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| ApplicationError::ValidationError(format!("invalid cursor: {}", cursor)))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -243,7 +313,10 @@ mod tests {
     use sea_orm::DbErr;
 
     use crate::common::errors::ApplicationError;
-    use crate::domain::prompt_manager::{ActionType, PromptManagerModel, PromptManagerRepository};
+    use crate::domain::prompt_manager::{
+        ActionType, PromptManagerCursor, PromptManagerModel, PromptManagerPage, PromptManagerQuery,
+        PromptManagerRepository, PromptManagerSort,
+    };
     use crate::usecase::prompt_manager::{
         APIType, CreatePromptManagerRequest, DeletePromptManagerRequest,
         GetAllPromptManagersRequest, GetPromptManagerRequest, PromptManager, PromptManagerUsecase,
@@ -266,10 +339,22 @@ mod tests {
             })
         }
 
-        async fn find_all_prompt_managers(
+        async fn find_prompt_managers(
             &self,
-        ) -> Result<Vec<PromptManagerModel>, ApplicationError> {
-            Ok(Vec::new())
+            query: &PromptManagerQuery,
+        ) -> Result<PromptManagerPage, ApplicationError> {
+            // 2ページ目は無い
+            let next_cursor = match query.cursor {
+                Some(_) => None,
+                None => Some(PromptManagerCursor::Title {
+                    title: "Test title".to_string(),
+                    id: 1,
+                }),
+            };
+            Ok(PromptManagerPage {
+                managers: Vec::new(),
+                next_cursor,
+            })
         }
 
         async fn create_prompt_manager(&self, _title: &str) -> Result<i32, ApplicationError> {
@@ -304,9 +389,10 @@ mod tests {
             )))
         }
 
-        async fn find_all_prompt_managers(
+        async fn find_prompt_managers(
             &self,
-        ) -> Result<Vec<PromptManagerModel>, ApplicationError> {
+            _query: &PromptManagerQuery,
+        ) -> Result<PromptManagerPage, ApplicationError> {
             Err(ApplicationError::DBError(DbErr::Type(
                 "db error".to_string(),
             )))
@@ -367,12 +453,53 @@ mod tests {
         let prompt_manager_usecase = PromptManagerUsecase {
             prompt_manager_repository: Arc::new(mock_repository),
         };
-        let request = GetAllPromptManagersRequest {};
+        let request = GetAllPromptManagersRequest::default();
+        let result = prompt_manager_usecase
+            .get_all_prompt_managers(request)
+            .await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.managers.len(), 0);
+
+        // 返されたカーソルで次のページを取得できる
+        let request = GetAllPromptManagersRequest {
+            sort: PromptManagerSort::Title,
+            cursor: result.next_cursor,
+            ..Default::default()
+        };
         let result = prompt_manager_usecase
             .get_all_prompt_managers(request)
             .await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().managers.len(), 0);
+        assert_eq!(result.unwrap().next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_prompt_managers_invalid_request() {
+        let mock_repository = MockPromptManagersRepository {};
+        let prompt_manager_usecase = PromptManagerUsecase {
+            prompt_manager_repository: Arc::new(mock_repository),
+        };
+        let requests = vec![
+            GetAllPromptManagersRequest {
+                cursor: Some("not a cursor".to_string()),
+                ..Default::default()
+            },
+            GetAllPromptManagersRequest {
+                limit: Some(0),
+                ..Default::default()
+            },
+            GetAllPromptManagersRequest {
+                limit: Some(1000),
+                ..Default::default()
+            },
+        ];
+        for request in requests {
+            let result = prompt_manager_usecase
+                .get_all_prompt_managers(request)
+                .await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
     }
 
     #[tokio::test]
@@ -381,7 +508,7 @@ mod tests {
         let prompt_manager_usecase = PromptManagerUsecase {
             prompt_manager_repository: Arc::new(mock_repository),
         };
-        let request = GetAllPromptManagersRequest {};
+        let request = GetAllPromptManagersRequest::default();
         let result = prompt_manager_usecase
             .get_all_prompt_managers(request)
             .await;
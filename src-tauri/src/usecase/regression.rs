@@ -0,0 +1,907 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::common::similarity::{cosine_similarity, normalize_text};
+use crate::domain::comparing_prompt::{
+    ComparingPromptBaselineModel, ComparingPromptBaselineRepository, ComparingPromptRunRepository,
+    DriftMeasure,
+};
+use crate::domain::embedding::AIEmbedding;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveBaselineRequest {
+    pub history_id: i32,
+    pub drift_measure: DriftMeasure,
+    pub threshold: Option<f64>, // Semanticの場合は必須（0.0〜1.0）
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveBaselineResponse {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBaselinesRequest {
+    pub setting_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBaselinesResponse {
+    pub baselines: Vec<BaselineItem>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineItem {
+    pub id: i32,
+    pub setting_id: i32,
+    pub history_id: i32,
+    pub user_prompt: String,
+    pub response: String,
+    pub drift_measure: DriftMeasure,
+    pub threshold: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteBaselineRequest {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRegressionReportRequest {
+    pub run_id: i32,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRegressionReportResponse {
+    pub run_id: i32,
+    pub changed: Vec<RegressionItem>, // ベースラインからドリフトした出力
+    pub new: Vec<RegressionItem>,     // ベースラインが存在しない出力
+    pub missing: Vec<RegressionItem>, // ベースラインはあるが今回の実行に含まれない出力
+    pub unchanged: Vec<RegressionItem>, // ベースラインと一致した出力
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegressionItem {
+    pub setting_id: i32,
+    pub version_id: Option<i32>,
+    pub history_id: Option<i32>,
+    pub baseline_id: Option<i32>,
+    pub response: Option<String>,
+    pub baseline_response: Option<String>,
+    pub similarity: Option<f64>, // Semanticで判定した場合のみ設定する
+}
+
+/// 1つの回答をベースラインと比較した結果
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleDrift {
+    pub baseline_id: i32,
+    pub drifted: bool,
+    pub similarity: Option<f64>, // Semanticで判定した場合のみ設定する
+}
+
+#[async_trait]
+pub trait Regression: Send + Sync {
+    async fn approve_baseline(
+        &self,
+        request: ApproveBaselineRequest,
+    ) -> Result<ApproveBaselineResponse, ApplicationError>;
+
+    async fn get_baselines(
+        &self,
+        request: GetBaselinesRequest,
+    ) -> Result<GetBaselinesResponse, ApplicationError>;
+
+    async fn delete_baseline(&self, request: DeleteBaselineRequest)
+        -> Result<(), ApplicationError>;
+
+    async fn get_regression_report(
+        &self,
+        request: GetRegressionReportRequest,
+    ) -> Result<GetRegressionReportResponse, ApplicationError>;
+}
+
+/// 実行の回答をベースラインと比較するtrait（比較の実行から使う）
+#[async_trait]
+pub trait DriftDetector: Send + Sync {
+    /// 設定と入力の組み合わせのベースラインと回答ごとに比較する（ベースラインがない場合はNone）
+    async fn detect_drift(
+        &self,
+        setting_id: i32,
+        user_prompt: &str,
+        responses: &[String],
+    ) -> Result<Option<Vec<SampleDrift>>, ApplicationError>;
+}
+
+#[derive(Debug)]
+pub struct RegressionUsecase<E, U, B>
+where
+    E: AIEmbedding,
+    U: ComparingPromptRunRepository,
+    B: ComparingPromptBaselineRepository,
+{
+    ai_embedding: Arc<E>,
+    comparing_prompt_run_repository: Arc<U>,
+    comparing_prompt_baseline_repository: Arc<B>,
+}
+
+/// controllerとChatUsecaseで同じユースケースを使うため、フィールドのArcを複製する
+impl<E, U, B> Clone for RegressionUsecase<E, U, B>
+where
+    E: AIEmbedding,
+    U: ComparingPromptRunRepository,
+    B: ComparingPromptBaselineRepository,
+{
+    fn clone(&self) -> Self {
+        RegressionUsecase {
+            ai_embedding: Arc::clone(&self.ai_embedding),
+            comparing_prompt_run_repository: Arc::clone(&self.comparing_prompt_run_repository),
+            comparing_prompt_baseline_repository: Arc::clone(
+                &self.comparing_prompt_baseline_repository,
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl<E, U, B> Regression for RegressionUsecase<E, U, B>
+where
+    E: AIEmbedding,
+    U: ComparingPromptRunRepository,
+    B: ComparingPromptBaselineRepository,
+{
+    async fn approve_baseline(
+        &self,
+        request: ApproveBaselineRequest,
+    ) -> Result<ApproveBaselineResponse, ApplicationError> {
+        let threshold = match request.drift_measure {
+            DriftMeasure::Semantic => match request.threshold {
+                Some(threshold) if (0.0..=1.0).contains(&threshold) => Some(threshold),
+                _ => {
+                    return Err(ApplicationError::ValidationError(
+                        "threshold must be between 0.0 and 1.0 for Semantic".to_string(),
+                    ))
+                }
+            },
+            _ => None,
+        };
+
+        let history = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_history_by_id(request.history_id)
+            .await?;
+        if history.cancelled {
+            return Err(ApplicationError::ValidationError(
+                "cancelled history cannot be a baseline".to_string(),
+            ));
+        }
+        let run = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_by_id(history.run_id)
+            .await?;
+
+        let id = self
+            .comparing_prompt_baseline_repository
+            .upsert_comparing_prompt_baseline(ComparingPromptBaselineModel {
+                id: 0,
+                setting_id: history.setting_id,
+                history_id: history.id,
+                user_prompt: run.user_prompt,
+                response: history.response,
+                drift_measure: request.drift_measure,
+                threshold,
+            })
+            .await?;
+        Ok(ApproveBaselineResponse { id })
+    }
+
+    async fn get_baselines(
+        &self,
+        request: GetBaselinesRequest,
+    ) -> Result<GetBaselinesResponse, ApplicationError> {
+        let baselines = self
+            .comparing_prompt_baseline_repository
+            .find_comparing_prompt_baselines_by_setting_id(request.setting_id)
+            .await?;
+        let baselines = baselines
+            .into_iter()
+            .map(|baseline| BaselineItem {
+                id: baseline.id,
+                setting_id: baseline.setting_id,
+                history_id: baseline.history_id,
+                user_prompt: baseline.user_prompt,
+                response: baseline.response,
+                drift_measure: baseline.drift_measure,
+                threshold: baseline.threshold,
+            })
+            .collect();
+        Ok(GetBaselinesResponse { baselines })
+    }
+
+    async fn delete_baseline(
+        &self,
+        request: DeleteBaselineRequest,
+    ) -> Result<(), ApplicationError> {
+        self.comparing_prompt_baseline_repository
+            .delete_comparing_prompt_baseline(request.id)
+            .await
+    }
+
+    async fn get_regression_report(
+        &self,
+        request: GetRegressionReportRequest,
+    ) -> Result<GetRegressionReportResponse, ApplicationError> {
+        let run = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_by_id(request.run_id)
+            .await?;
+        // キャンセルされた履歴は回答がないので判定しない
+        let histories: Vec<_> = self
+            .comparing_prompt_run_repository
+            .find_comparing_prompt_run_histories_by_run_id(run.id)
+            .await?
+            .into_iter()
+            .filter(|history| !history.cancelled)
+            .collect();
+        let baselines = self
+            .comparing_prompt_baseline_repository
+            .find_comparing_prompt_baselines_by_manager_id_and_user_prompt(
+                run.manager_id,
+                &run.user_prompt,
+            )
+            .await?;
+        let baselines: HashMap<i32, ComparingPromptBaselineModel> = baselines
+            .into_iter()
+            .map(|baseline| (baseline.setting_id, baseline))
+            .collect();
+
+        // Semanticで判定するものはまとめてembeddingする
+        let semantic_pairs: Vec<(String, String)> = histories
+            .iter()
+            .filter_map(|history| {
+                baselines
+                    .get(&history.setting_id)
+                    .filter(|baseline| baseline.drift_measure == DriftMeasure::Semantic)
+                    .map(|baseline| (baseline.response.clone(), history.response.clone()))
+            })
+            .collect();
+        let mut similarities = self
+            .semantic_similarities(semantic_pairs)
+            .await?
+            .into_iter();
+
+        let mut report = GetRegressionReportResponse {
+            run_id: run.id,
+            changed: Vec::new(),
+            new: Vec::new(),
+            missing: Vec::new(),
+            unchanged: Vec::new(),
+        };
+        let mut executed_setting_ids: HashSet<i32> = HashSet::new();
+        for history in histories {
+            executed_setting_ids.insert(history.setting_id);
+            let baseline = match baselines.get(&history.setting_id) {
+                Some(baseline) => baseline,
+                None => {
+                    report.new.push(RegressionItem {
+                        setting_id: history.setting_id,
+                        version_id: Some(history.version_id),
+                        history_id: Some(history.id),
+                        baseline_id: None,
+                        response: Some(history.response),
+                        baseline_response: None,
+                        similarity: None,
+                    });
+                    continue;
+                }
+            };
+
+            let similarity = match baseline.drift_measure {
+                DriftMeasure::Semantic => {
+                    Some(similarities.next().ok_or(ApplicationError::EmptyResult)?)
+                }
+                _ => None,
+            };
+            let drifted = is_drifted(baseline, &history.response, similarity);
+            let item = RegressionItem {
+                setting_id: history.setting_id,
+                version_id: Some(history.version_id),
+                history_id: Some(history.id),
+                baseline_id: Some(baseline.id),
+                response: Some(history.response),
+                baseline_response: Some(baseline.response.clone()),
+                similarity,
+            };
+            if drifted {
+                report.changed.push(item);
+            } else {
+                report.unchanged.push(item);
+            }
+        }
+
+        // ベースラインがあるのに今回実行されていない設定
+        let mut missing: Vec<&ComparingPromptBaselineModel> = baselines
+            .values()
+            .filter(|baseline| !executed_setting_ids.contains(&baseline.setting_id))
+            .collect();
+        missing.sort_by_key(|baseline| baseline.id);
+        report.missing = missing
+            .into_iter()
+            .map(|baseline| RegressionItem {
+                setting_id: baseline.setting_id,
+                version_id: None,
+                history_id: None,
+                baseline_id: Some(baseline.id),
+                response: None,
+                baseline_response: Some(baseline.response.clone()),
+                similarity: None,
+            })
+            .collect();
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl<E, U, B> DriftDetector for RegressionUsecase<E, U, B>
+where
+    E: AIEmbedding,
+    U: ComparingPromptRunRepository,
+    B: ComparingPromptBaselineRepository,
+{
+    async fn detect_drift(
+        &self,
+        setting_id: i32,
+        user_prompt: &str,
+        responses: &[String],
+    ) -> Result<Option<Vec<SampleDrift>>, ApplicationError> {
+        let baseline = self
+            .comparing_prompt_baseline_repository
+            .find_comparing_prompt_baselines_by_setting_id(setting_id)
+            .await?
+            .into_iter()
+            .find(|baseline| baseline.user_prompt == user_prompt);
+        let baseline = match baseline {
+            Some(baseline) => baseline,
+            None => return Ok(None),
+        };
+
+        let similarities: Vec<Option<f64>> = match baseline.drift_measure {
+            DriftMeasure::Semantic => self
+                .semantic_similarities(
+                    responses
+                        .iter()
+                        .map(|response| (baseline.response.clone(), response.clone()))
+                        .collect(),
+                )
+                .await?
+                .into_iter()
+                .map(Some)
+                .collect(),
+            _ => vec![None; responses.len()],
+        };
+        Ok(Some(
+            responses
+                .iter()
+                .zip(similarities)
+                .map(|(response, similarity)| SampleDrift {
+                    baseline_id: baseline.id,
+                    drifted: is_drifted(&baseline, response, similarity),
+                    similarity,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl<E, U, B> RegressionUsecase<E, U, B>
+where
+    E: AIEmbedding,
+    U: ComparingPromptRunRepository,
+    B: ComparingPromptBaselineRepository,
+{
+    pub fn new(
+        ai_embedding: Arc<E>,
+        comparing_prompt_run_repository: Arc<U>,
+        comparing_prompt_baseline_repository: Arc<B>,
+    ) -> Self {
+        RegressionUsecase {
+            ai_embedding,
+            comparing_prompt_run_repository,
+            comparing_prompt_baseline_repository,
+        }
+    }
+
+    /// (ベースライン, 今回の出力)の組ごとにembeddingのコサイン類似度を計算する
+    async fn semantic_similarities(
+        &self,
+        pairs: Vec<(String, String)>,
+    ) -> Result<Vec<f64>, ApplicationError> {
+        if pairs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let inputs: Vec<String> = pairs
+            .into_iter()
+            .flat_map(|(baseline, response)| vec![baseline, response])
+            .collect();
+        let vectors = self.ai_embedding.embed(inputs).await?;
+        Ok(vectors
+            .chunks(2)
+            .map(|pair| cosine_similarity(&pair[0], &pair[1]))
+            .collect())
+    }
+}
+
+/// 回答がベースラインからドリフトしたか（Semanticの場合は計算した類似度を渡す）
+fn is_drifted(
+    baseline: &ComparingPromptBaselineModel,
+    response: &str,
+    similarity: Option<f64>,
+) -> bool {
+    match baseline.drift_measure {
+        DriftMeasure::Exact => baseline.response != response,
+        DriftMeasure::Normalized => normalize_text(&baseline.response) != normalize_text(response),
+        DriftMeasure::Semantic => match similarity {
+            Some(similarity) => similarity < baseline.threshold.unwrap_or(1.0),
+            None => true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use sea_orm::DbErr;
+
+    use crate::common::errors::ApplicationError;
+    use crate::domain::chat::SamplingParameters;
+    use crate::domain::comparing_prompt::{
+        ComparingPromptRunHistoryModel, ComparingPromptSettingRunModel, ComparingPromptSweepModel,
+        ProviderType, RunHistoryTarget, SweepDefinition,
+    };
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    struct MockAIEmbedding {}
+    #[async_trait]
+    impl AIEmbedding for MockAIEmbedding {
+        async fn embed(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>, ApplicationError> {
+            // 先頭が"a"なら[1, 0]、それ以外は[0, 1]とする
+            Ok(inputs
+                .iter()
+                .map(|input| {
+                    if input.starts_with('a') {
+                        vec![1.0, 0.0]
+                    } else {
+                        vec![0.0, 1.0]
+                    }
+                })
+                .collect())
+        }
+    }
+
+    /// 各設定(setting_id)の実行履歴を返すモック
+    struct MockComparingPromptRunRepository {
+        histories: Vec<ComparingPromptRunHistoryModel>,
+    }
+    #[async_trait]
+    impl ComparingPromptRunRepository for MockComparingPromptRunRepository {
+        async fn find_comparing_prompt_run_by_id(
+            &self,
+            id: i32,
+        ) -> Result<ComparingPromptSettingRunModel, ApplicationError> {
+            Ok(ComparingPromptSettingRunModel {
+                id,
+                manager_id: 1,
+                user_prompt: "test_user_prompt".to_string(),
+                provider_type: ProviderType::OpenAI,
+                model: "test_model".to_string(),
+                temperature: 0.0,
+                max_tokens: None,
+                response_format: None,
+                repetitions: 1,
+                top_p: None,
+                sweep_id: None,
+                sampling: SamplingParameters::default(),
+                credential_profile_id: None,
+            })
+        }
+
+        async fn create_comparing_prompt_run(
+            &self,
+            _param: ComparingPromptSettingRunModel,
+        ) -> Result<i32, ApplicationError> {
+            Ok(1)
+        }
+
+        async fn find_comparing_prompt_sweep_by_id(
+            &self,
+            _id: i32,
+        ) -> Result<ComparingPromptSweepModel, ApplicationError> {
+            Err(ApplicationError::EmptyResult)
+        }
+
+        async fn find_comparing_prompt_runs_by_sweep_id(
+            &self,
+            _sweep_id: i32,
+        ) -> Result<Vec<ComparingPromptSettingRunModel>, ApplicationError> {
+            Ok(Vec::new())
+        }
+
+        async fn create_comparing_prompt_sweep(
+            &self,
+            _manager_id: i32,
+            _definition: &SweepDefinition,
+            _runs: Vec<ComparingPromptSettingRunModel>,
+        ) -> Result<(i32, Vec<i32>), ApplicationError> {
+            Ok((1, Vec::new()))
+        }
+
+        async fn find_comparing_prompt_run_history_by_id(
+            &self,
+            id: i32,
+        ) -> Result<ComparingPromptRunHistoryModel, ApplicationError> {
+            self.histories
+                .iter()
+                .find(|history| history.id == id)
+                .cloned()
+                .ok_or(ApplicationError::EmptyResult)
+        }
+
+        async fn find_comparing_prompt_run_histories_by_run_id(
+            &self,
+            _run_id: i32,
+        ) -> Result<Vec<ComparingPromptRunHistoryModel>, ApplicationError> {
+            Ok(self.histories.clone())
+        }
+
+        async fn create_comparing_prompt_run_history(
+            &self,
+            _target: &RunHistoryTarget,
+            _sample_index: i32,
+            _response: &str,
+            _transcript: Option<&str>,
+            _cache_hit: bool,
+        ) -> Result<i32, ApplicationError> {
+            Ok(1)
+        }
+
+        async fn create_cancelled_comparing_prompt_run_history(
+            &self,
+            _target: &RunHistoryTarget,
+            _sample_index: i32,
+            _transcript: Option<&str>,
+        ) -> Result<i32, ApplicationError> {
+            Ok(1)
+        }
+
+        async fn delete_comparing_prompt_run_histories_by_job_item_id(
+            &self,
+            _job_item_id: i32,
+        ) -> Result<u64, ApplicationError> {
+            Ok(0)
+        }
+    }
+
+    struct MockComparingPromptBaselineRepository {
+        baselines: Vec<ComparingPromptBaselineModel>,
+        upserted: Mutex<Vec<ComparingPromptBaselineModel>>,
+    }
+    #[async_trait]
+    impl ComparingPromptBaselineRepository for MockComparingPromptBaselineRepository {
+        async fn find_comparing_prompt_baselines_by_setting_id(
+            &self,
+            setting_id: i32,
+        ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError> {
+            Ok(self
+                .baselines
+                .iter()
+                .filter(|baseline| baseline.setting_id == setting_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn find_comparing_prompt_baselines_by_manager_id_and_user_prompt(
+            &self,
+            _manager_id: i32,
+            _user_prompt: &str,
+        ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError> {
+            Ok(self.baselines.clone())
+        }
+
+        async fn upsert_comparing_prompt_baseline(
+            &self,
+            param: ComparingPromptBaselineModel,
+        ) -> Result<i32, ApplicationError> {
+            self.upserted.lock().unwrap().push(param);
+            Ok(1)
+        }
+
+        async fn delete_comparing_prompt_baseline(&self, _id: i32) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+    }
+
+    struct MockComparingPromptBaselineRepositoryError {}
+    #[async_trait]
+    impl ComparingPromptBaselineRepository for MockComparingPromptBaselineRepositoryError {
+        async fn find_comparing_prompt_baselines_by_setting_id(
+            &self,
+            _setting_id: i32,
+        ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn find_comparing_prompt_baselines_by_manager_id_and_user_prompt(
+            &self,
+            _manager_id: i32,
+            _user_prompt: &str,
+        ) -> Result<Vec<ComparingPromptBaselineModel>, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn upsert_comparing_prompt_baseline(
+            &self,
+            _param: ComparingPromptBaselineModel,
+        ) -> Result<i32, ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+
+        async fn delete_comparing_prompt_baseline(&self, _id: i32) -> Result<(), ApplicationError> {
+            Err(ApplicationError::DBError(DbErr::Type(
+                "db error".to_string(),
+            )))
+        }
+    }
+
+    fn history(id: i32, setting_id: i32, response: &str) -> ComparingPromptRunHistoryModel {
+        ComparingPromptRunHistoryModel {
+            id,
+            run_id: 1,
+            version_id: setting_id * 10,
+            setting_id,
+            sample_index: 0,
+            response: response.to_string(),
+            transcript: None,
+            cache_hit: false,
+            cancelled: false,
+        }
+    }
+
+    fn baseline(
+        id: i32,
+        setting_id: i32,
+        response: &str,
+        drift_measure: DriftMeasure,
+        threshold: Option<f64>,
+    ) -> ComparingPromptBaselineModel {
+        ComparingPromptBaselineModel {
+            id,
+            setting_id,
+            history_id: id,
+            user_prompt: "test_user_prompt".to_string(),
+            response: response.to_string(),
+            drift_measure,
+            threshold,
+        }
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_approve_baseline() {
+        let baseline_repository = Arc::new(MockComparingPromptBaselineRepository {
+            baselines: vec![],
+            upserted: Mutex::new(vec![]),
+        });
+        let usecase = RegressionUsecase {
+            ai_embedding: Arc::new(MockAIEmbedding {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {
+                histories: vec![history(5, 2, "approved")],
+            }),
+            comparing_prompt_baseline_repository: Arc::clone(&baseline_repository),
+        };
+        let request = ApproveBaselineRequest {
+            history_id: 5,
+            drift_measure: DriftMeasure::Normalized,
+            threshold: Some(0.5),
+        };
+        let result = usecase.approve_baseline(request).await;
+        assert!(result.is_ok());
+
+        let upserted = baseline_repository.upserted.lock().unwrap();
+        assert_eq!(upserted.len(), 1);
+        assert_eq!(upserted[0].setting_id, 2);
+        assert_eq!(upserted[0].history_id, 5);
+        assert_eq!(upserted[0].user_prompt, "test_user_prompt");
+        assert_eq!(upserted[0].response, "approved");
+        // Semantic以外は閾値を保存しない
+        assert_eq!(upserted[0].threshold, None);
+    }
+
+    #[tokio::test]
+    async fn test_approve_baseline_invalid_threshold_error() {
+        let usecase = RegressionUsecase {
+            ai_embedding: Arc::new(MockAIEmbedding {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {
+                histories: vec![history(5, 2, "approved")],
+            }),
+            comparing_prompt_baseline_repository: Arc::new(MockComparingPromptBaselineRepository {
+                baselines: vec![],
+                upserted: Mutex::new(vec![]),
+            }),
+        };
+        let request = ApproveBaselineRequest {
+            history_id: 5,
+            drift_measure: DriftMeasure::Semantic,
+            threshold: None,
+        };
+        let result = usecase.approve_baseline(request).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_approve_baseline_error() {
+        let usecase = RegressionUsecase {
+            ai_embedding: Arc::new(MockAIEmbedding {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {
+                histories: vec![history(5, 2, "approved")],
+            }),
+            comparing_prompt_baseline_repository: Arc::new(
+                MockComparingPromptBaselineRepositoryError {},
+            ),
+        };
+        let request = ApproveBaselineRequest {
+            history_id: 5,
+            drift_measure: DriftMeasure::Exact,
+            threshold: None,
+        };
+        let result = usecase.approve_baseline(request).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_regression_report() {
+        let usecase = RegressionUsecase {
+            ai_embedding: Arc::new(MockAIEmbedding {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {
+                histories: vec![
+                    history(1, 1, "same"),
+                    history(2, 2, "Hello   World"),
+                    history(3, 3, "b-different meaning"),
+                    history(4, 4, "a-similar meaning"),
+                    history(5, 5, "brand new"),
+                    history(6, 6, "changed"),
+                ],
+            }),
+            comparing_prompt_baseline_repository: Arc::new(MockComparingPromptBaselineRepository {
+                baselines: vec![
+                    baseline(1, 1, "same", DriftMeasure::Exact, None),
+                    baseline(2, 2, "hello world", DriftMeasure::Normalized, None),
+                    baseline(3, 3, "a-original", DriftMeasure::Semantic, Some(0.9)),
+                    baseline(4, 4, "a-original", DriftMeasure::Semantic, Some(0.9)),
+                    baseline(6, 6, "original", DriftMeasure::Exact, None),
+                    baseline(7, 7, "not executed", DriftMeasure::Exact, None),
+                ],
+                upserted: Mutex::new(vec![]),
+            }),
+        };
+        let result = usecase
+            .get_regression_report(GetRegressionReportRequest { run_id: 1 })
+            .await;
+        assert!(result.is_ok());
+        let report = result.unwrap();
+
+        let setting_ids =
+            |items: &Vec<RegressionItem>| items.iter().map(|i| i.setting_id).collect::<Vec<_>>();
+        assert_eq!(setting_ids(&report.unchanged), vec![1, 2, 4]);
+        assert_eq!(setting_ids(&report.changed), vec![3, 6]);
+        assert_eq!(setting_ids(&report.new), vec![5]);
+        assert_eq!(setting_ids(&report.missing), vec![7]);
+        assert_eq!(report.changed[0].similarity, Some(0.0));
+        assert_eq!(report.unchanged[2].similarity, Some(1.0));
+        assert_eq!(report.missing[0].response, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_regression_report_error() {
+        let usecase = RegressionUsecase {
+            ai_embedding: Arc::new(MockAIEmbedding {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {
+                histories: vec![],
+            }),
+            comparing_prompt_baseline_repository: Arc::new(
+                MockComparingPromptBaselineRepositoryError {},
+            ),
+        };
+        let result = usecase
+            .get_regression_report(GetRegressionReportRequest { run_id: 1 })
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_detect_drift() {
+        let usecase = RegressionUsecase {
+            ai_embedding: Arc::new(MockAIEmbedding {}),
+            comparing_prompt_run_repository: Arc::new(MockComparingPromptRunRepository {
+                histories: vec![],
+            }),
+            comparing_prompt_baseline_repository: Arc::new(MockComparingPromptBaselineRepository {
+                baselines: vec![
+                    baseline(1, 1, "same", DriftMeasure::Exact, None),
+                    baseline(2, 2, "a-original", DriftMeasure::Semantic, Some(0.9)),
+                ],
+                upserted: Mutex::new(vec![]),
+            }),
+        };
+        let responses = vec!["same".to_string(), "a-similar meaning".to_string()];
+
+        // Exactで判定する
+        let result = usecase
+            .detect_drift(1, "test_user_prompt", &responses)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            result,
+            vec![
+                SampleDrift {
+                    baseline_id: 1,
+                    drifted: false,
+                    similarity: None,
+                },
+                SampleDrift {
+                    baseline_id: 1,
+                    drifted: true,
+                    similarity: None,
+                },
+            ]
+        );
+
+        // Semanticで判定する
+        let result = usecase
+            .detect_drift(2, "test_user_prompt", &responses)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result[0].similarity, Some(0.0));
+        assert!(result[0].drifted);
+        assert_eq!(result[1].similarity, Some(1.0));
+        assert!(!result[1].drifted);
+
+        // 入力が異なる場合と、ベースラインがない設定の場合は比較しない
+        let result = usecase
+            .detect_drift(1, "other_user_prompt", &responses)
+            .await;
+        assert_eq!(result.unwrap(), None);
+        let result = usecase
+            .detect_drift(3, "test_user_prompt", &responses)
+            .await;
+        assert_eq!(result.unwrap(), None);
+    }
+}
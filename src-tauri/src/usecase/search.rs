@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::search::{SearchHitKind, SearchRepository, SnippetPart};
+
+/// 検索結果の件数の上限（未指定の場合）
+const DEFAULT_SEARCH_LIMIT: u64 = 50;
+const MAX_SEARCH_LIMIT: u64 = 200;
+/// 索引はtrigramなので、3文字未満の検索語は一致しない
+const MIN_TERM_CHARS: usize = 3;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRequest {
+    pub query: String, // 空白で区切った全ての語を含む項目を検索する
+    #[serde(default)]
+    pub kinds: Vec<SearchHitKind>, // 空の場合は全ての種類
+    pub limit: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse {
+    pub hits: Vec<SearchHitItem>,
+}
+
+/// 一致した項目と、画面で開くマネージャー、バージョン、実行
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHitItem {
+    pub kind: SearchHitKind,
+    pub manager_id: i32,
+    pub manager_title: String,
+    pub setting_id: Option<i32>,
+    pub version_id: Option<i32>,
+    pub version: Option<i32>,
+    pub run_id: Option<i32>,
+    pub snippet: Vec<SnippetPart>,
+    pub score: f64,
+}
+
+#[async_trait]
+pub trait Search: Send + Sync {
+    async fn search(&self, request: SearchRequest) -> Result<SearchResponse, ApplicationError>;
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchUsecase<T>
+where
+    T: SearchRepository,
+{
+    search_repository: Arc<T>,
+}
+
+#[async_trait]
+impl<T> Search for SearchUsecase<T>
+where
+    T: SearchRepository,
+{
+    async fn search(&self, request: SearchRequest) -> Result<SearchResponse, ApplicationError> {
+        let query = to_match_query(&request.query)?;
+        let limit = request.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        if limit == 0 || limit > MAX_SEARCH_LIMIT {
+            return Err(ApplicationError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_SEARCH_LIMIT
+            )));
+        }
+        let kinds = if request.kinds.is_empty() {
+            vec![
+                SearchHitKind::Manager,
+                SearchHitKind::Version,
+                SearchHitKind::Run,
+                SearchHitKind::Response,
+            ]
+        } else {
+            request
+                .kinds
+                .into_iter()
+                .fold(Vec::new(), |mut kinds, kind| {
+                    if !kinds.contains(&kind) {
+                        kinds.push(kind);
+                    }
+                    kinds
+                })
+        };
+        let hits = self
+            .search_repository
+            .search(&query, &kinds, limit)
+            .await
+            .map_err(|err| {
+                log::error!("search error: {}", err);
+                err
+            })?;
+        Ok(SearchResponse {
+            hits: hits
+                .into_iter()
+                .map(|hit| SearchHitItem {
+                    kind: hit.kind,
+                    manager_id: hit.manager_id,
+                    manager_title: hit.manager_title,
+                    setting_id: hit.setting_id,
+                    version_id: hit.version_id,
+                    version: hit.version,
+                    run_id: hit.run_id,
+                    snippet: hit.snippet,
+                    score: hit.score,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl<T> SearchUsecase<T>
+where
+    T: SearchRepository,
+{
+    pub fn new(search_repository: Arc<T>) -> Self {
+        SearchUsecase { search_repository }
+    }
+}
+
+/// 検索語をFTS5のMATCHの構文にする
+/// 演算子として解釈されないように、語はそれぞれ二重引用符で囲んで文字列として扱う
+fn to_match_query(query: &str) -> Result<String, ApplicationError> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Err(ApplicationError::ValidationError(
+            "query must not be empty".to_string(),
+        ));
+    }
+    if let Some(term) = terms
+        .iter()
+        .find(|term| term.chars().count() < MIN_TERM_CHARS)
+    {
+        return Err(ApplicationError::ValidationError(format!(
+            "search term must be at least {} characters: {}",
+            MIN_TERM_CHARS, term
+        )));
+    }
+    Ok(terms
+        .iter()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::domain::search::SearchHitModel;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    /// 受け取った検索条件を記録する
+    #[derive(Default)]
+    struct MockSearchRepository {
+        calls: Mutex<Vec<(String, Vec<SearchHitKind>, u64)>>,
+    }
+    #[async_trait]
+    impl SearchRepository for MockSearchRepository {
+        async fn search(
+            &self,
+            query: &str,
+            kinds: &[SearchHitKind],
+            limit: u64,
+        ) -> Result<Vec<SearchHitModel>, ApplicationError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((query.to_string(), kinds.to_vec(), limit));
+            Ok(vec![SearchHitModel {
+                kind: SearchHitKind::Run,
+                manager_id: 1,
+                manager_title: "test_title".to_string(),
+                setting_id: None,
+                version_id: None,
+                version: None,
+                run_id: Some(2),
+                snippet: vec![SnippetPart {
+                    text: "weather".to_string(),
+                    highlighted: true,
+                }],
+                score: -1.5,
+            }])
+        }
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_search() {
+        let repository = Arc::new(MockSearchRepository::default());
+        let usecase = SearchUsecase::new(Arc::clone(&repository));
+
+        let request = SearchRequest {
+            query: "  weather say\"hi\"  ".to_string(),
+            kinds: vec![],
+            limit: None,
+        };
+        let result = usecase.search(request).await.unwrap();
+        assert_eq!(result.hits.len(), 1);
+        assert_eq!(result.hits[0].run_id, Some(2));
+
+        // 語は引用符で囲み、全ての種類を検索する
+        let calls = repository.calls.lock().unwrap();
+        assert_eq!(calls[0].0, "\"weather\" \"say\"\"hi\"\"\"");
+        assert_eq!(calls[0].1.len(), 4);
+        assert_eq!(calls[0].2, DEFAULT_SEARCH_LIMIT);
+    }
+
+    #[tokio::test]
+    async fn test_search_invalid_request() {
+        let usecase = SearchUsecase::new(Arc::new(MockSearchRepository::default()));
+
+        for (query, limit) in [
+            ("   ", None),
+            ("weather ab", None),
+            ("weather", Some(0)),
+            ("weather", Some(MAX_SEARCH_LIMIT + 1)),
+        ] {
+            let request = SearchRequest {
+                query: query.to_string(),
+                kinds: vec![SearchHitKind::Manager],
+                limit,
+            };
+            let result = usecase.search(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+}
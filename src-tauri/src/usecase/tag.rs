@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::tag::TagRepository;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTagsRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTagsResponse {
+    pub tags: Vec<TagItem>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagItem {
+    pub id: i32,
+    pub value: String,
+    pub usage_count: i32, // タグが付いている削除されていないマネージャーの数
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameTagRequest {
+    pub id: i32,
+    pub value: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeTagsRequest {
+    pub source_ids: Vec<i32>, // 統合後に削除するタグ
+    pub target_id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteTagRequest {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUnusedTagsRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUnusedTagsResponse {
+    pub deleted: u64,
+}
+
+#[async_trait]
+pub trait TagManager: Send + Sync {
+    async fn get_tags(&self, request: GetTagsRequest) -> Result<GetTagsResponse, ApplicationError>;
+
+    async fn rename_tag(&self, request: RenameTagRequest) -> Result<(), ApplicationError>;
+
+    async fn merge_tags(&self, request: MergeTagsRequest) -> Result<(), ApplicationError>;
+
+    async fn delete_tag(&self, request: DeleteTagRequest) -> Result<(), ApplicationError>;
+
+    async fn delete_unused_tags(
+        &self,
+        request: DeleteUnusedTagsRequest,
+    ) -> Result<DeleteUnusedTagsResponse, ApplicationError>;
+}
+
+#[derive(Clone, Debug)]
+pub struct TagUsecase<T>
+where
+    T: TagRepository,
+{
+    tag_repository: Arc<T>,
+}
+
+#[async_trait]
+impl<T> TagManager for TagUsecase<T>
+where
+    T: TagRepository,
+{
+    async fn get_tags(
+        &self,
+        _request: GetTagsRequest,
+    ) -> Result<GetTagsResponse, ApplicationError> {
+        let tags = self
+            .tag_repository
+            .find_tags()
+            .await?
+            .into_iter()
+            .map(|tag| TagItem {
+                id: tag.id,
+                value: tag.value,
+                usage_count: tag.usage_count,
+            })
+            .collect();
+        Ok(GetTagsResponse { tags })
+    }
+
+    async fn rename_tag(&self, request: RenameTagRequest) -> Result<(), ApplicationError> {
+        let value = request.value.trim();
+        if value.is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "tag must not be empty".to_string(),
+            ));
+        }
+        self.tag_repository.rename_tag(request.id, value).await
+    }
+
+    async fn merge_tags(&self, request: MergeTagsRequest) -> Result<(), ApplicationError> {
+        let mut source_ids = request.source_ids;
+        source_ids.sort();
+        source_ids.dedup();
+        if source_ids.is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "source tags must not be empty".to_string(),
+            ));
+        }
+        if source_ids.contains(&request.target_id) {
+            return Err(ApplicationError::ValidationError(
+                "source tags must not contain the target tag".to_string(),
+            ));
+        }
+        self.tag_repository
+            .merge_tags(&source_ids, request.target_id)
+            .await
+    }
+
+    async fn delete_tag(&self, request: DeleteTagRequest) -> Result<(), ApplicationError> {
+        self.tag_repository.delete_tag(request.id).await
+    }
+
+    async fn delete_unused_tags(
+        &self,
+        _request: DeleteUnusedTagsRequest,
+    ) -> Result<DeleteUnusedTagsResponse, ApplicationError> {
+        let deleted = self.tag_repository.delete_unused_tags().await?;
+        Ok(DeleteUnusedTagsResponse { deleted })
+    }
+}
+
+impl<T> TagUsecase<T>
+where
+    T: TagRepository,
+{
+    pub fn new(tag_repository: Arc<T>) -> Self {
+        TagUsecase { tag_repository }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::domain::tag::TagModel;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    /// 受け取った引数を記録する
+    #[derive(Default)]
+    struct MockTagRepository {
+        renamed: Mutex<Vec<(i32, String)>>,
+        merged: Mutex<Vec<(Vec<i32>, i32)>>,
+    }
+    #[async_trait]
+    impl TagRepository for MockTagRepository {
+        async fn find_tags(&self) -> Result<Vec<TagModel>, ApplicationError> {
+            Ok(vec![])
+        }
+
+        async fn rename_tag(&self, id: i32, value: &str) -> Result<(), ApplicationError> {
+            self.renamed.lock().unwrap().push((id, value.to_string()));
+            Ok(())
+        }
+
+        async fn merge_tags(
+            &self,
+            source_ids: &[i32],
+            target_id: i32,
+        ) -> Result<(), ApplicationError> {
+            self.merged
+                .lock()
+                .unwrap()
+                .push((source_ids.to_vec(), target_id));
+            Ok(())
+        }
+
+        async fn delete_tag(&self, _id: i32) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        async fn delete_unused_tags(&self) -> Result<u64, ApplicationError> {
+            Ok(0)
+        }
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_rename_tag() {
+        let repository = Arc::new(MockTagRepository::default());
+        let usecase = TagUsecase::new(Arc::clone(&repository));
+
+        let request = RenameTagRequest {
+            id: 1,
+            value: " python ".to_string(),
+        };
+        usecase.rename_tag(request).await.unwrap();
+        assert_eq!(
+            *repository.renamed.lock().unwrap(),
+            vec![(1, "python".to_string())]
+        );
+
+        let request = RenameTagRequest {
+            id: 1,
+            value: "  ".to_string(),
+        };
+        let result = usecase.rename_tag(request).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_merge_tags() {
+        let repository = Arc::new(MockTagRepository::default());
+        let usecase = TagUsecase::new(Arc::clone(&repository));
+
+        let request = MergeTagsRequest {
+            source_ids: vec![3, 2, 3],
+            target_id: 1,
+        };
+        usecase.merge_tags(request).await.unwrap();
+        assert_eq!(*repository.merged.lock().unwrap(), vec![(vec![2, 3], 1)]);
+
+        // 統合元が空の場合と、統合先を含む場合
+        for source_ids in [vec![], vec![1, 2]] {
+            let request = MergeTagsRequest {
+                source_ids,
+                target_id: 1,
+            };
+            let result = usecase.merge_tags(request).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::tokenizer::{TokenEncoding, Tokenizer};
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensRequest {
+    pub model: String,
+    pub system_prompt: String,
+    pub user_prompt: String,
+    pub max_tokens: Option<u16>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensResponse {
+    pub encoding: TokenEncoding,
+    pub prompt_tokens: i32,
+    pub max_tokens: Option<i32>,
+    pub context_window: i32,
+    pub fits: bool, // プロンプトとmax_tokensの合計がコンテキストウィンドウに収まるか
+}
+
+#[async_trait]
+pub trait TokenCount: Send + Sync {
+    async fn count_tokens(
+        &self,
+        request: CountTokensRequest,
+    ) -> Result<CountTokensResponse, ApplicationError>;
+}
+
+#[derive(Clone, Debug)]
+pub struct TokenCountUsecase<T>
+where
+    T: Tokenizer,
+{
+    tokenizer: Arc<T>,
+}
+
+#[async_trait]
+impl<T> TokenCount for TokenCountUsecase<T>
+where
+    T: Tokenizer,
+{
+    async fn count_tokens(
+        &self,
+        request: CountTokensRequest,
+    ) -> Result<CountTokensResponse, ApplicationError> {
+        let usage = self
+            .tokenizer
+            .context_window_usage(
+                &request.model,
+                &request.system_prompt,
+                &request.user_prompt,
+                request.max_tokens.map(|max_tokens| max_tokens as usize),
+            )
+            .ok_or_else(|| {
+                ApplicationError::ValidationError(format!(
+                    "token counting is not supported for model: {}",
+                    request.model
+                ))
+            })?;
+        Ok(CountTokensResponse {
+            encoding: usage.encoding,
+            prompt_tokens: usage.prompt_tokens as i32,
+            max_tokens: usage.max_tokens.map(|max_tokens| max_tokens as i32),
+            context_window: usage.context_window as i32,
+            fits: usage.fits(),
+        })
+    }
+}
+
+impl<T> TokenCountUsecase<T>
+where
+    T: Tokenizer,
+{
+    pub fn new(tokenizer: Arc<T>) -> Self {
+        TokenCountUsecase { tokenizer }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::tokenizer::ModelTokenLimit;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    /// 1文字を1トークンとして数える
+    struct MockTokenizer {}
+    impl Tokenizer for MockTokenizer {
+        fn model_limit(&self, model: &str) -> Option<ModelTokenLimit> {
+            (model == "test-model").then_some(ModelTokenLimit {
+                encoding: TokenEncoding::Cl100kBase,
+                context_window: 100,
+            })
+        }
+
+        fn count_tokens(&self, _encoding: TokenEncoding, text: &str) -> usize {
+            text.chars().count()
+        }
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_count_tokens() {
+        let usecase = TokenCountUsecase::new(Arc::new(MockTokenizer {}));
+        let request = CountTokensRequest {
+            model: "test-model".to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: "user".to_string(),
+            max_tokens: Some(70),
+        };
+        let result = usecase.count_tokens(request).await.unwrap();
+        // system(3 + 6 + 6) + user(3 + 4 + 4) + reply(3)
+        assert_eq!(result.prompt_tokens, 29);
+        assert_eq!(result.context_window, 100);
+        assert!(result.fits);
+
+        let request = CountTokensRequest {
+            model: "test-model".to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: "user".to_string(),
+            max_tokens: Some(72),
+        };
+        let result = usecase.count_tokens(request).await.unwrap();
+        assert!(!result.fits);
+    }
+
+    #[tokio::test]
+    async fn test_count_tokens_unknown_model() {
+        let usecase = TokenCountUsecase::new(Arc::new(MockTokenizer {}));
+        let request = CountTokensRequest {
+            model: "unknown-model".to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: "user".to_string(),
+            max_tokens: None,
+        };
+        let result = usecase.count_tokens(request).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::errors::ApplicationError;
+use crate::domain::trash::TrashRepository;
+use crate::usecase::app_setting::TrashRetention;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDeletedPromptManagersRequest {}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDeletedPromptManagersResponse {
+    pub managers: Vec<DeletedPromptManagerItem>,
+    pub retention_days: Option<u32>, // 自動で物理削除するまでの日数
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedPromptManagerItem {
+    pub id: i32,
+    pub title: String,
+    pub deleted_at: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePromptManagerRequest {
+    pub id: i32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgePromptManagerRequest {
+    pub id: i32,
+}
+
+#[async_trait]
+pub trait Trash: Send + Sync {
+    async fn get_deleted_prompt_managers(
+        &self,
+        request: GetDeletedPromptManagersRequest,
+    ) -> Result<GetDeletedPromptManagersResponse, ApplicationError>;
+
+    async fn restore_prompt_manager(
+        &self,
+        request: RestorePromptManagerRequest,
+    ) -> Result<(), ApplicationError>;
+
+    async fn purge_prompt_manager(
+        &self,
+        request: PurgePromptManagerRequest,
+    ) -> Result<(), ApplicationError>;
+}
+
+#[derive(Clone, Debug)]
+pub struct TrashUsecase<T, R>
+where
+    T: TrashRepository,
+    R: TrashRetention,
+{
+    trash_repository: Arc<T>,
+    trash_retention: Arc<R>,
+}
+
+#[async_trait]
+impl<T, R> Trash for TrashUsecase<T, R>
+where
+    T: TrashRepository,
+    R: TrashRetention,
+{
+    async fn get_deleted_prompt_managers(
+        &self,
+        _request: GetDeletedPromptManagersRequest,
+    ) -> Result<GetDeletedPromptManagersResponse, ApplicationError> {
+        let managers = self
+            .trash_repository
+            .find_deleted_prompt_managers()
+            .await?
+            .into_iter()
+            .map(|manager| DeletedPromptManagerItem {
+                id: manager.id,
+                title: manager.title,
+                deleted_at: manager.deleted_at,
+            })
+            .collect();
+        Ok(GetDeletedPromptManagersResponse {
+            managers,
+            retention_days: self.trash_retention.trash_retention_days().await?,
+        })
+    }
+
+    async fn restore_prompt_manager(
+        &self,
+        request: RestorePromptManagerRequest,
+    ) -> Result<(), ApplicationError> {
+        self.trash_repository
+            .restore_prompt_manager(request.id)
+            .await
+    }
+
+    async fn purge_prompt_manager(
+        &self,
+        request: PurgePromptManagerRequest,
+    ) -> Result<(), ApplicationError> {
+        self.trash_repository.purge_prompt_manager(request.id).await
+    }
+}
+
+impl<T, R> TrashUsecase<T, R>
+where
+    T: TrashRepository,
+    R: TrashRetention,
+{
+    pub fn new(trash_repository: Arc<T>, trash_retention: Arc<R>) -> Self {
+        TrashUsecase {
+            trash_repository,
+            trash_retention,
+        }
+    }
+
+    /// 保持する日数を過ぎたマネージャーを物理削除する（日数が設定されていない場合は何もしない）
+    /// マネージャーごとに削除するので、途中で失敗しても削除済みのマネージャーは元に戻らない
+    pub async fn purge_expired_prompt_managers(&self) -> Result<u64, ApplicationError> {
+        let Some(retention_days) = self.trash_retention.trash_retention_days().await? else {
+            return Ok(0);
+        };
+        let expires_at = (Utc::now() - Duration::days(retention_days as i64)).naive_utc();
+        let mut purged = 0;
+        for manager in self.trash_repository.find_deleted_prompt_managers().await? {
+            match parse_deleted_at(&manager.deleted_at) {
+                Some(deleted_at) if deleted_at < expires_at => {
+                    self.trash_repository
+                        .purge_prompt_manager(manager.id)
+                        .await?;
+                    purged += 1;
+                }
+                Some(_) => {}
+                None => log::warn!(
+                    "cannot parse deleted_at of prompt manager {}: {}",
+                    manager.id,
+                    manager.deleted_at
+                ),
+            }
+        }
+        if purged > 0 {
+            log::info!("purge {} expired prompt managers", purged);
+        }
+        Ok(purged)
+    }
+}
+
+/// 論理削除した日時（chrono::Utc::now().to_string()の形式）を読み込む
+fn parse_deleted_at(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S%.f").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::domain::trash::DeletedPromptManagerModel;
+
+    use super::*;
+
+    /**
+     * Mocks
+     */
+    /// 物理削除したマネージャーを記録する
+    struct MockTrashRepository {
+        managers: Vec<DeletedPromptManagerModel>,
+        purged: Mutex<Vec<i32>>,
+    }
+    #[async_trait]
+    impl TrashRepository for MockTrashRepository {
+        async fn find_deleted_prompt_managers(
+            &self,
+        ) -> Result<Vec<DeletedPromptManagerModel>, ApplicationError> {
+            Ok(self.managers.clone())
+        }
+
+        async fn restore_prompt_manager(&self, _id: i32) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        async fn purge_prompt_manager(&self, id: i32) -> Result<(), ApplicationError> {
+            self.purged.lock().unwrap().push(id);
+            Ok(())
+        }
+    }
+
+    struct MockTrashRetention {
+        retention_days: Option<u32>,
+    }
+    #[async_trait]
+    impl TrashRetention for MockTrashRetention {
+        async fn trash_retention_days(&self) -> Result<Option<u32>, ApplicationError> {
+            Ok(self.retention_days)
+        }
+    }
+
+    fn trash_usecase(
+        retention_days: Option<u32>,
+    ) -> TrashUsecase<MockTrashRepository, MockTrashRetention> {
+        let deleted_manager = |id: i32, days_ago: i64| DeletedPromptManagerModel {
+            id,
+            title: format!("title{}", id),
+            deleted_at: (Utc::now() - Duration::days(days_ago)).to_string(),
+        };
+        TrashUsecase::new(
+            Arc::new(MockTrashRepository {
+                managers: vec![
+                    deleted_manager(1, 1),
+                    deleted_manager(2, 31),
+                    DeletedPromptManagerModel {
+                        id: 3,
+                        title: "title3".to_string(),
+                        deleted_at: "unknown".to_string(),
+                    },
+                ],
+                purged: Mutex::new(Vec::new()),
+            }),
+            Arc::new(MockTrashRetention { retention_days }),
+        )
+    }
+
+    /**
+     * Test cases
+     */
+    #[tokio::test]
+    async fn test_purge_expired_prompt_managers() {
+        // 保持する日数を過ぎたマネージャーのみ削除する
+        let usecase = trash_usecase(Some(30));
+        assert_eq!(usecase.purge_expired_prompt_managers().await.unwrap(), 1);
+        assert_eq!(*usecase.trash_repository.purged.lock().unwrap(), vec![2]);
+
+        // 日数が設定されていない場合は削除しない
+        let usecase = trash_usecase(None);
+        assert_eq!(usecase.purge_expired_prompt_managers().await.unwrap(), 0);
+        assert!(usecase.trash_repository.purged.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_deleted_at() {
+        let now = Utc::now();
+        assert_eq!(parse_deleted_at(&now.to_string()), Some(now.naive_utc()));
+        assert_eq!(
+            parse_deleted_at("2024-01-02 03:04:05 UTC"),
+            NaiveDateTime::parse_from_str("2024-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").ok()
+        );
+        assert_eq!(parse_deleted_at("unknown"), None);
+    }
+}
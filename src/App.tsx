@@ -6,6 +6,7 @@ import DefaultLayout from './layouts/default'
 import { RecoilRoot } from 'recoil'
 import { ThemeProvider } from './layouts/theme-provider'
 import Home from './pages/Home'
+import Search from './pages/Search'
 
 const App = () => {
   return (
@@ -16,6 +17,7 @@ const App = () => {
             <Route path="/" element={<Home />} />
             <Route path="/prompt_manager/:id" element={<PromptManager />} />
             <Route path="/config" element={<Config />} />
+            <Route path="/search" element={<Search />} />
           </Routes>
         </DefaultLayout>
       </RecoilRoot>
@@ -65,6 +65,28 @@ export const getComparingPromptSettingAction = async (
   return JSON.parse(response) as GetComparingPromptSettingResponse
 }
 
+interface DeleteComparingPromptSettingRequest {
+  id: number
+}
+
+// 削除した設定の履歴とベースラインも取得されなくなる
+export const deleteComparingPromptSettingAction = async (
+  request: DeleteComparingPromptSettingRequest,
+): Promise<void> => {
+  await invoke('delete_comparing_prompt_setting', { request })
+}
+
+interface RestoreComparingPromptSettingRequest {
+  id: number
+}
+
+// マネージャーが削除されている場合は元に戻せない
+export const restoreComparingPromptSettingAction = async (
+  request: RestoreComparingPromptSettingRequest,
+): Promise<void> => {
+  await invoke('restore_comparing_prompt_setting', { request })
+}
+
 export interface SaveComparingPromptRunRequest {
   managerId: number
   userPrompt: string
@@ -73,6 +95,7 @@ export interface SaveComparingPromptRunRequest {
   temperature: number
   maxToken?: number
   responseFormat?: string
+  credentialProfileId?: number // 未指定の場合はプロンプトマネージャーで選択されたプロファイル
 }
 
 interface SaveComparingPromptRunResponse {
@@ -0,0 +1,159 @@
+import { invoke } from '@tauri-apps/api/tauri'
+import {
+  AppSettings,
+  AzureOpenAISettings,
+  CredentialProfileItem,
+  CredentialProfileUsageItem,
+  ManagerCredentialProfileItem,
+  HttpClientSettings,
+  ProviderType,
+  RateLimitItem,
+  Theme,
+} from '@/features/config/types'
+
+interface GetAppSettingsRequest {}
+
+type GetAppSettingsResponse = AppSettings
+
+export const getAppSettingsAction =
+  async (): Promise<GetAppSettingsResponse> => {
+    const request: GetAppSettingsRequest = {}
+    const response = (await invoke('get_app_settings', {
+      request,
+    })) as string
+    return JSON.parse(response) as GetAppSettingsResponse
+  }
+
+interface ApiKeyInput {
+  providerType: ProviderType
+  apiKey: string | null // nullまたは空文字の場合は削除する
+}
+
+interface SaveAppSettingsRequest {
+  apiKeys: ApiKeyInput[] // 変更するAPIキーのみ指定する
+  defaultProvider: ProviderType | null
+  defaultModel: string | null
+  theme: Theme
+  azureOpenai: AzureOpenAISettings | null // nullの場合は削除する
+  trashRetentionDays: number | null // nullの場合は自動で物理削除しない
+  rateLimits: RateLimitItem[] // 含まれないプロバイダーは環境変数の既定値に戻す
+  httpClient: HttpClientSettings | null // nullの場合は環境変数の設定に戻す
+}
+
+export const saveAppSettingsAction = async (
+  request: SaveAppSettingsRequest,
+): Promise<void> => {
+  await invoke('save_app_settings', {
+    request,
+  })
+}
+
+interface RotateSecretKeyRequest {}
+
+interface RotateSecretKeyResponse {
+  reencrypted: number // 新しい鍵で暗号化し直した設定の数
+}
+
+export const rotateSecretKeyAction =
+  async (): Promise<RotateSecretKeyResponse> => {
+    const request: RotateSecretKeyRequest = {}
+    const response = (await invoke('rotate_secret_key', {
+      request,
+    })) as string
+    return JSON.parse(response) as RotateSecretKeyResponse
+  }
+
+interface GetCredentialProfilesRequest {}
+
+interface GetCredentialProfilesResponse {
+  profiles: CredentialProfileItem[]
+}
+
+export const getCredentialProfilesAction =
+  async (): Promise<GetCredentialProfilesResponse> => {
+    const request: GetCredentialProfilesRequest = {}
+    const response = (await invoke('get_credential_profiles', {
+      request,
+    })) as string
+    return JSON.parse(response) as GetCredentialProfilesResponse
+  }
+
+interface SaveCredentialProfileRequest {
+  id: number | null // nullの場合は新規登録
+  providerType: ProviderType
+  label: string
+  apiKey: string | null // 更新時にnullの場合は保存済みのAPIキーを使う
+  organizationId: string | null
+  projectId: string | null
+}
+
+interface SaveCredentialProfileResponse {
+  id: number
+}
+
+export const saveCredentialProfileAction = async (
+  request: SaveCredentialProfileRequest,
+): Promise<SaveCredentialProfileResponse> => {
+  const response = (await invoke('save_credential_profile', {
+    request,
+  })) as string
+  return JSON.parse(response) as SaveCredentialProfileResponse
+}
+
+interface DeleteCredentialProfileRequest {
+  id: number
+}
+
+export const deleteCredentialProfileAction = async (
+  request: DeleteCredentialProfileRequest,
+): Promise<void> => {
+  await invoke('delete_credential_profile', {
+    request,
+  })
+}
+
+interface GetManagerCredentialProfilesRequest {
+  managerId: number
+}
+
+interface GetManagerCredentialProfilesResponse {
+  profiles: ManagerCredentialProfileItem[]
+}
+
+export const getManagerCredentialProfilesAction = async (
+  request: GetManagerCredentialProfilesRequest,
+): Promise<GetManagerCredentialProfilesResponse> => {
+  const response = (await invoke('get_manager_credential_profiles', {
+    request,
+  })) as string
+  return JSON.parse(response) as GetManagerCredentialProfilesResponse
+}
+
+interface SaveManagerCredentialProfileRequest {
+  managerId: number
+  providerType: ProviderType
+  credentialProfileId: number | null // nullの場合は選択を解除する
+}
+
+export const saveManagerCredentialProfileAction = async (
+  request: SaveManagerCredentialProfileRequest,
+): Promise<void> => {
+  await invoke('save_manager_credential_profile', {
+    request,
+  })
+}
+
+interface GetCredentialProfileUsageRequest {}
+
+interface GetCredentialProfileUsageResponse {
+  items: CredentialProfileUsageItem[]
+}
+
+export const getCredentialProfileUsageAction =
+  async (): Promise<GetCredentialProfileUsageResponse> => {
+    const request: GetCredentialProfileUsageRequest = {}
+    const response = (await invoke('get_credential_profile_usage', {
+      request,
+    })) as string
+    return JSON.parse(response) as GetCredentialProfileUsageResponse
+  }
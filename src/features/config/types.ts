@@ -0,0 +1,72 @@
+export type ProviderType = 'OpenAI' | 'AzureOpenAI' | 'Gemini'
+export type Theme = 'light' | 'dark' | 'system'
+
+export interface ApiKeyItem {
+  providerType: ProviderType
+  configured: boolean
+  maskedKey: string | null
+}
+
+export interface AppSettings {
+  apiKeys: ApiKeyItem[]
+  defaultProvider: ProviderType | null
+  defaultModel: string | null
+  theme: Theme
+  azureOpenai: AzureOpenAISettings | null
+  trashRetentionDays: number | null // nullの場合は自動で物理削除しない
+  rateLimits: RateLimitItem[] // 保存されているプロバイダーのみ
+  httpClient: HttpClientSettings | null
+}
+
+// nullの項目は環境変数の既定値を使う
+export interface RateLimitItem {
+  providerType: ProviderType
+  maxConcurrent: number | null
+  requestsPerMinute: number | null
+  tokensPerMinute: number | null
+}
+
+// nullまたは空の項目は環境変数の既定値を使う
+export interface HttpClientSettings {
+  proxy: string | null
+  noProxy: string | null // プロキシを経由しないホスト（カンマ区切り）
+  caCerts: string[] // 追加で信頼するルート証明書（PEM形式）のパス
+  connectTimeoutSecs: number | null
+  requestTimeoutSecs: number | null
+  headers: Record<string, string>
+}
+
+export interface AzureOpenAIDeployment {
+  deployment: string // 実行にはデプロイメント名をモデルとして記録する
+  model: string
+}
+
+export interface AzureOpenAISettings {
+  endpoint: string // https://<リソース名>.openai.azure.com
+  apiVersion: string
+  deployments: AzureOpenAIDeployment[]
+}
+
+export interface CredentialProfileItem {
+  id: number
+  providerType: ProviderType
+  label: string
+  maskedKey: string | null // 復号できない場合はnull
+  organizationId: string | null
+  projectId: string | null
+}
+
+export interface ManagerCredentialProfileItem {
+  providerType: ProviderType
+  credentialProfileId: number
+}
+
+export interface CredentialProfileUsageItem {
+  credentialProfileId: number | null // nullは設定画面または環境変数のAPIキー
+  label: string | null
+  providerType: ProviderType
+  model: string
+  runs: number
+  responses: number
+  cachedResponses: number
+}
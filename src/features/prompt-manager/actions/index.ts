@@ -3,6 +3,8 @@ import {
   ActionType,
   ApiType,
   PromptManager,
+  PromptManagerSort,
+  TagMatch,
 } from '@/features/prompt-manager/types'
 
 interface GetPromptManagerRequest {
@@ -21,16 +23,26 @@ export const getPromptManagerAction = async (
   return JSON.parse(response) as GetPromptManagerResponse
 }
 
-interface GetPromptManagersRequest {}
+// 未指定の条件では絞り込まない
+export interface GetPromptManagersRequest {
+  title?: string // タイトルの部分一致
+  tags?: string[]
+  tagMatch?: TagMatch
+  actionType?: ActionType
+  apiType?: ApiType
+  sort?: PromptManagerSort
+  cursor?: string // 前のページのnextCursor
+  limit?: number
+}
 
 interface GetPromptManagersResponse {
   managers: PromptManager[]
+  nextCursor: string | null // 次のページが無い場合はnull
 }
 
-export const getAllPromptManagersAction = async (): Promise<
-  GetPromptManagersResponse
-> => {
-  const request: GetPromptManagersRequest = {}
+export const getAllPromptManagersAction = async (
+  request: GetPromptManagersRequest = {},
+): Promise<GetPromptManagersResponse> => {
   const response = (await invoke('get_all_prompt_managers', {
     request,
   })) as string
@@ -1,6 +1,10 @@
-import { useEffect } from 'react'
+import { useEffect, useState } from 'react'
 import Card from '../../../components/ui/Card'
-import { PromptManager } from '../types'
+import { TextInput } from '@/components/ui/TextInput'
+import ButtonWithIcon from '@/components/ui/ButtonWithIcon'
+import { Label } from '@/components/ui/label'
+import { RadioGroup, RadioGroupItem } from '@/components/ui/radio-group'
+import { PromptManager, PromptManagerSort } from '../types'
 import NewPromptManagerInputForm from './ui/NewPromptManagerInputForm'
 import PromptManagerCard from './ui/PromptManagerCard'
 import {
@@ -13,6 +17,8 @@ import { toast } from 'react-toastify'
 import { useRecoilValue, useSetRecoilState } from 'recoil'
 import { promptManagersAtom } from '@/store/atoms'
 
+const SORTS: PromptManagerSort[] = ['Created', 'Title', 'LastRun']
+
 export interface PromptManagerListProps {
   isVisibleNewManagerForm: boolean
   setIsVisibleNewManagerForm: (isVisible: boolean) => void
@@ -29,18 +35,35 @@ const PromptManagerList = ({
     promptManagersAtom,
   )
 
-  useEffect(() => {
-    const fetchPromptManagers = async () => {
-      try {
-        const res = await getAllPromptManagersAction()
-        console.log(res)
-        setPromptManagers(res.managers)
-      } catch (error) {
-        toast.error(`Failed to fetch prompt manager: ${error}`)
-      }
+  const [title, setTitle] = useState('')
+  const [tags, setTags] = useState('')
+  const [sort, setSort] = useState<PromptManagerSort>('Created')
+  const [nextCursor, setNextCursor] = useState<string | null>(null)
+
+  // カーソルを指定した場合は次のページを追加する
+  const fetchPromptManagers = async (cursor?: string) => {
+    try {
+      const res = await getAllPromptManagersAction({
+        title: title || undefined,
+        tags: tags
+          .split(',')
+          .map((tag) => tag.trim())
+          .filter((tag) => tag),
+        sort,
+        cursor,
+      })
+      setPromptManagers(
+        cursor ? [...promptManagers, ...res.managers] : res.managers,
+      )
+      setNextCursor(res.nextCursor)
+    } catch (error) {
+      toast.error(`Failed to fetch prompt manager: ${error}`)
     }
+  }
+
+  useEffect(() => {
     fetchPromptManagers()
-  }, [])
+  }, [title, tags, sort])
 
   const selectPromptManager = (id: number) => {
     const URL_PATH = '/prompt_manager/:id'
@@ -55,8 +78,8 @@ const PromptManagerList = ({
       // 状態の更新
       setIsVisibleNewManagerForm(false)
       setPromptManagers([
-        ...promptManagers,
         { id, title, actionType: null, apiType: null, tags: [] },
+        ...promptManagers,
       ])
       selectPromptManager(id)
       toast.info('Save Prompt Manager Success!')
@@ -80,6 +103,31 @@ const PromptManagerList = ({
 
   return (
     <>
+      <div className="flex items-center gap-2">
+        <TextInput
+          placeholder="Search title..."
+          value={title}
+          onChange={(e) => setTitle(e.target.value)}
+        />
+        <TextInput
+          placeholder="Tags (comma separated)"
+          value={tags}
+          onChange={(e) => setTags(e.target.value)}
+        />
+        <RadioGroup
+          onValueChange={(value) => setSort(value as PromptManagerSort)}
+          value={sort}
+        >
+          <div className="flex row gap-4">
+            {SORTS.map((item) => (
+              <div key={item} className="flex row items-center space-x-2">
+                <RadioGroupItem value={item} id={`sort-${item}`} />
+                <Label htmlFor={`sort-${item}`}>{item}</Label>
+              </div>
+            ))}
+          </div>
+        </RadioGroup>
+      </div>
       {promptManagers.length === 0 ? (
         <Card>
           <span className="font-bold">No prompts</span>
@@ -96,6 +144,15 @@ const PromptManagerList = ({
           ))}
         </>
       )}
+      {nextCursor && (
+        <ButtonWithIcon
+          text="Load more"
+          type="button"
+          icon="i-solar-alt-arrow-down-linear"
+          color="info"
+          onClick={() => fetchPromptManagers(nextCursor)}
+        />
+      )}
       {isTemporaryPromptManagerVisible && (
         <NewPromptManagerInputForm
           handleSubmit={savePromptManager}
@@ -1,5 +1,7 @@
 export type ActionType = 'ComparingPrompt' | 'ComparingModel'
 export type ApiType = 'Chat' | 'Vision'
+export type PromptManagerSort = 'Title' | 'Created' | 'LastRun'
+export type TagMatch = 'Any' | 'All'
 
 export interface PromptManager {
   id: number
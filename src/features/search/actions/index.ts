@@ -0,0 +1,19 @@
+import { invoke } from '@tauri-apps/api/tauri'
+import { SearchHitItem, SearchHitKind } from '@/features/search/types'
+
+interface SearchRequest {
+  query: string // 空白で区切った全ての語（3文字以上）を含む項目を検索する
+  kinds?: SearchHitKind[] // 未指定の場合は全ての種類
+  limit?: number
+}
+
+interface SearchResponse {
+  hits: SearchHitItem[]
+}
+
+export const searchAction = async (
+  request: SearchRequest,
+): Promise<SearchResponse> => {
+  const response = (await invoke('search', { request })) as string
+  return JSON.parse(response) as SearchResponse
+}
@@ -0,0 +1,18 @@
+export type SearchHitKind = 'manager' | 'version' | 'run' | 'response'
+
+export interface SnippetPart {
+  text: string
+  highlighted: boolean // 検索語に一致した部分
+}
+
+export interface SearchHitItem {
+  kind: SearchHitKind
+  managerId: number
+  managerTitle: string
+  settingId: number | null
+  versionId: number | null
+  version: number | null
+  runId: number | null
+  snippet: SnippetPart[]
+  score: number // 小さいほど関連度が高い
+}
@@ -0,0 +1,61 @@
+import { invoke } from '@tauri-apps/api/tauri'
+import { TagItem } from '@/features/tag/types'
+
+interface GetTagsRequest {}
+
+interface GetTagsResponse {
+  tags: TagItem[]
+}
+
+export const getTagsAction = async (): Promise<GetTagsResponse> => {
+  const request: GetTagsRequest = {}
+  const response = (await invoke('get_tags', { request })) as string
+  return JSON.parse(response) as GetTagsResponse
+}
+
+interface RenameTagRequest {
+  id: number
+  value: string // 既にある値には変更できない（統合する）
+}
+
+export const renameTagAction = async (
+  request: RenameTagRequest,
+): Promise<void> => {
+  await invoke('rename_tag', { request })
+}
+
+interface MergeTagsRequest {
+  sourceIds: number[] // 統合後に削除するタグ
+  targetId: number
+}
+
+export const mergeTagsAction = async (
+  request: MergeTagsRequest,
+): Promise<void> => {
+  await invoke('merge_tags', { request })
+}
+
+interface DeleteTagRequest {
+  id: number
+}
+
+export const deleteTagAction = async (
+  request: DeleteTagRequest,
+): Promise<void> => {
+  await invoke('delete_tag', { request })
+}
+
+interface DeleteUnusedTagsRequest {}
+
+interface DeleteUnusedTagsResponse {
+  deleted: number
+}
+
+export const deleteUnusedTagsAction =
+  async (): Promise<DeleteUnusedTagsResponse> => {
+    const request: DeleteUnusedTagsRequest = {}
+    const response = (await invoke('delete_unused_tags', {
+      request,
+    })) as string
+    return JSON.parse(response) as DeleteUnusedTagsResponse
+  }
@@ -0,0 +1,109 @@
+import { useEffect, useState } from 'react'
+import { toast } from 'react-toastify'
+import { TextInput } from '@/components/ui/TextInput'
+import { Label } from '@/components/ui/label'
+import ButtonWithIcon from '@/components/ui/ButtonWithIcon'
+import {
+  deleteTagAction,
+  deleteUnusedTagsAction,
+  getTagsAction,
+  mergeTagsAction,
+  renameTagAction,
+} from '@/features/tag/actions'
+import { TagItem } from '@/features/tag/types'
+
+const TagSettings = () => {
+  const [tags, setTags] = useState<TagItem[]>([])
+  const [tagInputs, setTagInputs] = useState<Record<number, string>>({})
+
+  const fetchTags = async () => {
+    try {
+      const res = await getTagsAction()
+      setTags(res.tags)
+      setTagInputs({})
+    } catch (error) {
+      toast.error(`Failed to fetch tags: ${error}`)
+    }
+  }
+
+  useEffect(() => {
+    fetchTags()
+  }, [])
+
+  // 既にあるタグの値に変更した場合は、そのタグに統合する
+  const renameTag = async (tag: TagItem) => {
+    const value = (tagInputs[tag.id] ?? tag.value).trim()
+    const target = tags.find((item) => item.value === value)
+    try {
+      if (target && target.id !== tag.id) {
+        await mergeTagsAction({ sourceIds: [tag.id], targetId: target.id })
+      } else {
+        await renameTagAction({ id: tag.id, value })
+      }
+      await fetchTags()
+    } catch (error) {
+      toast.error(`Failed to rename tag: ${error}`)
+    }
+  }
+
+  const deleteTag = async (id: number) => {
+    try {
+      await deleteTagAction({ id })
+      await fetchTags()
+    } catch (error) {
+      toast.error(`Failed to delete tag: ${error}`)
+    }
+  }
+
+  const deleteUnusedTags = async () => {
+    try {
+      const res = await deleteUnusedTagsAction()
+      await fetchTags()
+      toast.info(`Deleted ${res.deleted} unused tags`)
+    } catch (error) {
+      toast.error(`Failed to delete unused tags: ${error}`)
+    }
+  }
+
+  return (
+    <div>
+      <Label>Tags</Label>
+      {tags.map((tag) => (
+        <div key={tag.id} className="flex items-center gap-2">
+          <div className="w-60">
+            <TextInput
+              value={tagInputs[tag.id] ?? tag.value}
+              onChange={(e) =>
+                setTagInputs({ ...tagInputs, [tag.id]: e.target.value })
+              }
+            />
+          </div>
+          <span className="w-28">{tag.usageCount} managers</span>
+          <ButtonWithIcon
+            text="Rename"
+            type="button"
+            icon="i-solar-pen-linear"
+            color="info"
+            onClick={() => renameTag(tag)}
+          />
+          <ButtonWithIcon
+            text="Delete"
+            type="button"
+            icon="i-solar-trash-bin-trash-bold"
+            color="warn"
+            onClick={() => deleteTag(tag.id)}
+          />
+        </div>
+      ))}
+      <ButtonWithIcon
+        text="Delete Unused Tags"
+        type="button"
+        icon="i-solar-trash-bin-trash-bold"
+        color="warn"
+        onClick={deleteUnusedTags}
+      />
+    </div>
+  )
+}
+
+export default TagSettings
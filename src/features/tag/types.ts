@@ -0,0 +1,5 @@
+export interface TagItem {
+  id: number
+  value: string
+  usageCount: number // タグが付いている削除されていないマネージャーの数
+}
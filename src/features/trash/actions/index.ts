@@ -0,0 +1,38 @@
+import { invoke } from '@tauri-apps/api/tauri'
+import { DeletedPromptManagerItem } from '@/features/trash/types'
+
+interface GetDeletedPromptManagersRequest {}
+
+interface GetDeletedPromptManagersResponse {
+  managers: DeletedPromptManagerItem[]
+  retentionDays: number | null // 自動で物理削除するまでの日数
+}
+
+export const getDeletedPromptManagersAction =
+  async (): Promise<GetDeletedPromptManagersResponse> => {
+    const request: GetDeletedPromptManagersRequest = {}
+    const response = (await invoke('get_deleted_prompt_managers', {
+      request,
+    })) as string
+    return JSON.parse(response) as GetDeletedPromptManagersResponse
+  }
+
+interface RestorePromptManagerRequest {
+  id: number
+}
+
+export const restorePromptManagerAction = async (
+  request: RestorePromptManagerRequest,
+): Promise<void> => {
+  await invoke('restore_prompt_manager', { request })
+}
+
+interface PurgePromptManagerRequest {
+  id: number
+}
+
+export const purgePromptManagerAction = async (
+  request: PurgePromptManagerRequest,
+): Promise<void> => {
+  await invoke('purge_prompt_manager', { request })
+}
@@ -0,0 +1,84 @@
+import { useEffect, useState } from 'react'
+import { toast } from 'react-toastify'
+import { Label } from '@/components/ui/label'
+import ButtonWithIcon from '@/components/ui/ButtonWithIcon'
+import {
+  getDeletedPromptManagersAction,
+  purgePromptManagerAction,
+  restorePromptManagerAction,
+} from '@/features/trash/actions'
+import { DeletedPromptManagerItem } from '@/features/trash/types'
+
+const TrashSettings = () => {
+  const [managers, setManagers] = useState<DeletedPromptManagerItem[]>([])
+  const [retentionDays, setRetentionDays] = useState<number | null>(null)
+
+  const fetchDeletedPromptManagers = async () => {
+    try {
+      const res = await getDeletedPromptManagersAction()
+      setManagers(res.managers)
+      setRetentionDays(res.retentionDays)
+    } catch (error) {
+      toast.error(`Failed to fetch trash: ${error}`)
+    }
+  }
+
+  useEffect(() => {
+    fetchDeletedPromptManagers()
+  }, [])
+
+  const restorePromptManager = async (id: number) => {
+    try {
+      await restorePromptManagerAction({ id })
+      await fetchDeletedPromptManagers()
+    } catch (error) {
+      toast.error(`Failed to restore prompt manager: ${error}`)
+    }
+  }
+
+  // 物理削除は元に戻せないので確認する
+  const purgePromptManager = async (manager: DeletedPromptManagerItem) => {
+    if (!window.confirm(`Permanently delete "${manager.title}"?`)) {
+      return
+    }
+    try {
+      await purgePromptManagerAction({ id: manager.id })
+      await fetchDeletedPromptManagers()
+    } catch (error) {
+      toast.error(`Failed to purge prompt manager: ${error}`)
+    }
+  }
+
+  return (
+    <div>
+      <Label>Trash</Label>
+      {retentionDays && (
+        <p className="text-sm">
+          Deleted managers are purged after {retentionDays} days.
+        </p>
+      )}
+      {managers.map((manager) => (
+        <div key={manager.id} className="flex items-center gap-2">
+          <span className="w-60">{manager.title}</span>
+          <span className="w-60">{manager.deletedAt}</span>
+          <ButtonWithIcon
+            text="Restore"
+            type="button"
+            icon="i-solar-restart-bold"
+            color="info"
+            onClick={() => restorePromptManager(manager.id)}
+          />
+          <ButtonWithIcon
+            text="Purge"
+            type="button"
+            icon="i-solar-trash-bin-trash-bold"
+            color="warn"
+            onClick={() => purgePromptManager(manager)}
+          />
+        </div>
+      ))}
+    </div>
+  )
+}
+
+export default TrashSettings
@@ -0,0 +1,5 @@
+export interface DeletedPromptManagerItem {
+  id: number
+  title: string
+  deletedAt: string
+}
@@ -12,6 +12,10 @@ const SideMenu = () => {
     navigate('/config')
   }
 
+  const goToSearchPage = () => {
+    navigate('/search')
+  }
+
   const addTemporaryPromptManager = () => {
     setIsVisibleNewManagerForm(true)
   }
@@ -21,6 +25,7 @@ const SideMenu = () => {
       <SideMenuHeader
         onClickAdd={addTemporaryPromptManager}
         onClickConfig={goToConfigPage}
+        onClickSearch={goToSearchPage}
       />
       <div className="overflow-y-scroll">
         <PromptManagerList
@@ -3,15 +3,21 @@ import IconButton from '../../components/ui/IconButton'
 export interface SideMenuProps {
   onClickConfig: () => void
   onClickAdd: () => void
+  onClickSearch: () => void
 }
 
-const SideMenuHeader = ({ onClickConfig, onClickAdd }: SideMenuProps) => {
+const SideMenuHeader = ({
+  onClickConfig,
+  onClickAdd,
+  onClickSearch,
+}: SideMenuProps) => {
   // TODO filterの追加、削除したものの表示をするようにする
   return (
     <div className="flex flex-row justify-between items-center">
       <span className="font-bold text-lg">Promptory</span>
       <div className="flex flex-row gap-px items-center">
         <IconButton icon="i-solar-add-circle-linear" onClick={onClickAdd} />
+        <IconButton icon="i-solar-magnifer-linear" onClick={onClickSearch} />
         <IconButton icon="i-solar-settings-bold" onClick={onClickConfig} />
       </div>
     </div>
@@ -1,7 +1,589 @@
+import { useEffect, useState } from 'react'
+import { toast } from 'react-toastify'
+import { TextInput } from '@/components/ui/TextInput'
+import { Textarea } from '@/components/ui/textarea'
+import { Label } from '@/components/ui/label'
+import { RadioGroup, RadioGroupItem } from '@/components/ui/radio-group'
+import ButtonWithIcon from '@/components/ui/ButtonWithIcon'
+import { useTheme } from '@/layouts/theme-provider'
+import TagSettings from '@/features/tag/components/TagSettings'
+import TrashSettings from '@/features/trash/components/TrashSettings'
+import {
+  deleteCredentialProfileAction,
+  getAppSettingsAction,
+  getCredentialProfileUsageAction,
+  getCredentialProfilesAction,
+  rotateSecretKeyAction,
+  saveAppSettingsAction,
+  saveCredentialProfileAction,
+} from '@/features/config/actions'
+import {
+  ApiKeyItem,
+  AzureOpenAIDeployment,
+  CredentialProfileItem,
+  CredentialProfileUsageItem,
+  HttpClientSettings,
+  ProviderType,
+  RateLimitItem,
+  Theme,
+} from '@/features/config/types'
+
+const PROVIDER_TYPES: ProviderType[] = ['OpenAI', 'AzureOpenAI', 'Gemini']
+const THEMES: Theme[] = ['light', 'dark', 'system']
+// Geminiのクライアントは未実装なのでレート制限は設定しない
+const RATE_LIMIT_PROVIDER_TYPES: ProviderType[] = ['OpenAI', 'AzureOpenAI']
+
+type RateLimitField = 'maxConcurrent' | 'requestsPerMinute' | 'tokensPerMinute'
+const RATE_LIMIT_FIELDS: { field: RateLimitField; placeholder: string }[] = [
+  { field: 'maxConcurrent', placeholder: 'Max concurrent' },
+  { field: 'requestsPerMinute', placeholder: 'Requests / min' },
+  { field: 'tokensPerMinute', placeholder: 'Tokens / min' },
+]
+type RateLimitInput = Partial<Record<RateLimitField, string>>
+
+const toRateLimitInput = (item: RateLimitItem): RateLimitInput => ({
+  maxConcurrent: item.maxConcurrent?.toString() ?? '',
+  requestsPerMinute: item.requestsPerMinute?.toString() ?? '',
+  tokensPerMinute: item.tokensPerMinute?.toString() ?? '',
+})
+
+// 空の項目は環境変数の既定値を使う
+const toRateLimitItem = (
+  providerType: ProviderType,
+  input: RateLimitInput,
+): RateLimitItem => {
+  const value = (field: RateLimitField) =>
+    input[field] ? Number(input[field]) : null
+  return {
+    providerType,
+    maxConcurrent: value('maxConcurrent'),
+    requestsPerMinute: value('requestsPerMinute'),
+    tokensPerMinute: value('tokensPerMinute'),
+  }
+}
+
+// デプロイメントは1行に1つ「デプロイメント名=モデル名」の形式で入力する
+const formatDeployments = (deployments: AzureOpenAIDeployment[]) =>
+  deployments.map((item) => `${item.deployment}=${item.model}`).join('\n')
+
+const parseDeployments = (text: string): AzureOpenAIDeployment[] =>
+  text
+    .split('\n')
+    .filter((line) => line.trim())
+    .map((line) => {
+      const [deployment, ...model] = line.split('=')
+      return { deployment, model: model.join('=') }
+    })
+
+// ヘッダーは1行に1つ「ヘッダー名: 値」の形式で入力する
+const formatHeaders = (headers: Record<string, string>) =>
+  Object.entries(headers)
+    .map(([name, value]) => `${name}: ${value}`)
+    .join('\n')
+
+const parseHeaders = (text: string): Record<string, string> =>
+  Object.fromEntries(
+    text
+      .split('\n')
+      .filter((line) => line.trim())
+      .map((line) => {
+        const [name, ...value] = line.split(':')
+        return [name, value.join(':')]
+      }),
+  )
+
 const Config = () => {
-  // TODO API_KEYの入力欄とテーマ切り替えのトグルボタンを追加する
-  // トグルはhttps://ui.shadcn.com/docs/dark-mode/viteを参考に作成する
-  return <div className="container">config page</div>
+  const { setTheme } = useTheme()
+
+  const [apiKeys, setApiKeys] = useState<ApiKeyItem[]>([])
+  // 入力されたAPIキーのみ保存する（保存済みのAPIキーは画面に返さない）
+  const [apiKeyInputs, setApiKeyInputs] = useState<
+    Partial<Record<ProviderType, string>>
+  >({})
+  const [clearedProviders, setClearedProviders] = useState<ProviderType[]>([])
+  const [defaultProvider, setDefaultProvider] = useState<ProviderType | null>(
+    null,
+  )
+  const [defaultModel, setDefaultModel] = useState('')
+  const [theme, setThemeValue] = useState<Theme>('system')
+  const [azureEndpoint, setAzureEndpoint] = useState('')
+  const [azureApiVersion, setAzureApiVersion] = useState('')
+  const [azureDeployments, setAzureDeployments] = useState('')
+  const [trashRetentionDays, setTrashRetentionDays] = useState('')
+  const [httpProxy, setHttpProxy] = useState('')
+  const [httpNoProxy, setHttpNoProxy] = useState('')
+  const [httpCaCerts, setHttpCaCerts] = useState('')
+  const [httpConnectTimeout, setHttpConnectTimeout] = useState('')
+  const [httpRequestTimeout, setHttpRequestTimeout] = useState('')
+  const [httpHeaders, setHttpHeaders] = useState('')
+  const [rateLimitInputs, setRateLimitInputs] = useState<
+    Partial<Record<ProviderType, RateLimitInput>>
+  >({})
+
+  const [profiles, setProfiles] = useState<CredentialProfileItem[]>([])
+  const [profileUsages, setProfileUsages] = useState<
+    CredentialProfileUsageItem[]
+  >([])
+  const [profileProvider, setProfileProvider] = useState<ProviderType>('OpenAI')
+  const [profileLabel, setProfileLabel] = useState('')
+  const [profileApiKey, setProfileApiKey] = useState('')
+  const [profileOrganizationId, setProfileOrganizationId] = useState('')
+  const [profileProjectId, setProfileProjectId] = useState('')
+
+  const fetchAppSettings = async () => {
+    try {
+      const res = await getAppSettingsAction()
+      setApiKeys(res.apiKeys)
+      setApiKeyInputs({})
+      setClearedProviders([])
+      setDefaultProvider(res.defaultProvider)
+      setDefaultModel(res.defaultModel ?? '')
+      setThemeValue(res.theme)
+      setTheme(res.theme)
+      setAzureEndpoint(res.azureOpenai?.endpoint ?? '')
+      setAzureApiVersion(res.azureOpenai?.apiVersion ?? '')
+      setAzureDeployments(
+        formatDeployments(res.azureOpenai?.deployments ?? []),
+      )
+      setTrashRetentionDays(res.trashRetentionDays?.toString() ?? '')
+      setHttpProxy(res.httpClient?.proxy ?? '')
+      setHttpNoProxy(res.httpClient?.noProxy ?? '')
+      setHttpCaCerts((res.httpClient?.caCerts ?? []).join('\n'))
+      setHttpConnectTimeout(
+        res.httpClient?.connectTimeoutSecs?.toString() ?? '',
+      )
+      setHttpRequestTimeout(
+        res.httpClient?.requestTimeoutSecs?.toString() ?? '',
+      )
+      setHttpHeaders(formatHeaders(res.httpClient?.headers ?? {}))
+      setRateLimitInputs(
+        Object.fromEntries(
+          res.rateLimits.map((item) => [
+            item.providerType,
+            toRateLimitInput(item),
+          ]),
+        ),
+      )
+    } catch (error) {
+      toast.error(`Failed to fetch settings: ${error}`)
+    }
+  }
+
+  const fetchCredentialProfiles = async () => {
+    try {
+      const res = await getCredentialProfilesAction()
+      setProfiles(res.profiles)
+      const usage = await getCredentialProfileUsageAction()
+      setProfileUsages(usage.items)
+    } catch (error) {
+      toast.error(`Failed to fetch credential profiles: ${error}`)
+    }
+  }
+
+  useEffect(() => {
+    fetchAppSettings()
+    fetchCredentialProfiles()
+  }, [])
+
+  const toHttpClientSettings = (): HttpClientSettings => ({
+    proxy: httpProxy || null,
+    noProxy: httpNoProxy || null,
+    caCerts: httpCaCerts.split('\n').filter((path) => path.trim()),
+    connectTimeoutSecs: httpConnectTimeout ? Number(httpConnectTimeout) : null,
+    requestTimeoutSecs: httpRequestTimeout ? Number(httpRequestTimeout) : null,
+    headers: parseHeaders(httpHeaders),
+  })
+
+  const saveAppSettings = async () => {
+    try {
+      await saveAppSettingsAction({
+        apiKeys: PROVIDER_TYPES.flatMap((providerType) => {
+          const apiKey = apiKeyInputs[providerType]
+          if (apiKey) {
+            return [{ providerType, apiKey }]
+          }
+          return clearedProviders.includes(providerType)
+            ? [{ providerType, apiKey: null }]
+            : []
+        }),
+        defaultProvider,
+        defaultModel: defaultModel || null,
+        theme,
+        // エンドポイントが空の場合はAzure OpenAIの設定を削除する
+        azureOpenai: azureEndpoint
+          ? {
+              endpoint: azureEndpoint,
+              apiVersion: azureApiVersion,
+              deployments: parseDeployments(azureDeployments),
+            }
+          : null,
+        trashRetentionDays: trashRetentionDays
+          ? Number(trashRetentionDays)
+          : null,
+        rateLimits: RATE_LIMIT_PROVIDER_TYPES.map((providerType) =>
+          toRateLimitItem(providerType, rateLimitInputs[providerType] ?? {}),
+        ),
+        // 全ての項目が空の場合は保存せず、環境変数の設定に戻す
+        httpClient: toHttpClientSettings(),
+      })
+      setTheme(theme)
+      await fetchAppSettings()
+      toast.info('Save Settings Success!')
+    } catch (error) {
+      toast.error(`Failed to save settings: ${error}`)
+    }
+  }
+
+  const rotateSecretKey = async () => {
+    try {
+      const res = await rotateSecretKeyAction()
+      toast.info(`Rotated encryption key (${res.reencrypted} keys)`)
+    } catch (error) {
+      toast.error(`Failed to rotate encryption key: ${error}`)
+    }
+  }
+
+  const addCredentialProfile = async () => {
+    try {
+      await saveCredentialProfileAction({
+        id: null,
+        providerType: profileProvider,
+        label: profileLabel,
+        apiKey: profileApiKey,
+        organizationId: profileOrganizationId || null,
+        projectId: profileProjectId || null,
+      })
+      setProfileLabel('')
+      setProfileApiKey('')
+      setProfileOrganizationId('')
+      setProfileProjectId('')
+      await fetchCredentialProfiles()
+      toast.info('Add Credential Profile Success!')
+    } catch (error) {
+      toast.error(`Failed to add credential profile: ${error}`)
+    }
+  }
+
+  const deleteCredentialProfile = async (id: number) => {
+    try {
+      await deleteCredentialProfileAction({ id })
+      await fetchCredentialProfiles()
+    } catch (error) {
+      toast.error(`Failed to delete credential profile: ${error}`)
+    }
+  }
+
+  const maskedKey = (providerType: ProviderType) =>
+    apiKeys.find((item) => item.providerType === providerType)?.maskedKey
+
+  return (
+    <div className="container flex flex-col gap-4">
+      <div>
+        <Label>API Keys</Label>
+        {PROVIDER_TYPES.map((providerType) => (
+          <div key={providerType} className="flex items-center gap-2">
+            <Label className="w-20" htmlFor={`api-key-${providerType}`}>
+              {providerType}
+            </Label>
+            <TextInput
+              id={`api-key-${providerType}`}
+              type="password"
+              autoComplete="off"
+              placeholder={
+                clearedProviders.includes(providerType)
+                  ? 'Cleared on save'
+                  : maskedKey(providerType) ?? 'Enter API key...'
+              }
+              value={apiKeyInputs[providerType] ?? ''}
+              onChange={(e) =>
+                setApiKeyInputs({
+                  ...apiKeyInputs,
+                  [providerType]: e.target.value,
+                })
+              }
+            />
+            {maskedKey(providerType) && (
+              <ButtonWithIcon
+                text="Clear"
+                type="button"
+                icon="i-solar-trash-bin-trash-bold"
+                color="warn"
+                onClick={() => {
+                  setApiKeyInputs({ ...apiKeyInputs, [providerType]: '' })
+                  setClearedProviders([...clearedProviders, providerType])
+                }}
+              />
+            )}
+          </div>
+        ))}
+        <ButtonWithIcon
+          text="Rotate Encryption Key"
+          type="button"
+          icon="i-solar-refresh-bold"
+          color="info"
+          onClick={rotateSecretKey}
+        />
+      </div>
+
+      <div>
+        <Label>Azure OpenAI</Label>
+        <div className="flex items-center gap-2">
+          <TextInput
+            placeholder="https://<resource>.openai.azure.com"
+            value={azureEndpoint}
+            onChange={(e) => setAzureEndpoint(e.target.value)}
+          />
+          <TextInput
+            placeholder="API version (e.g. 2024-02-01)"
+            value={azureApiVersion}
+            onChange={(e) => setAzureApiVersion(e.target.value)}
+          />
+        </div>
+        <Textarea
+          placeholder="deployment=model (one per line)"
+          value={azureDeployments}
+          onChange={(e) => setAzureDeployments(e.target.value)}
+        />
+      </div>
+
+      <div>
+        <Label>Rate Limits</Label>
+        {RATE_LIMIT_PROVIDER_TYPES.map((providerType) => (
+          <div key={providerType} className="flex items-center gap-2">
+            <span className="w-20">{providerType}</span>
+            {RATE_LIMIT_FIELDS.map(({ field, placeholder }) => (
+              <TextInput
+                key={field}
+                type="number"
+                min={1}
+                placeholder={`${placeholder} (default)`}
+                value={rateLimitInputs[providerType]?.[field] ?? ''}
+                onChange={(e) =>
+                  setRateLimitInputs({
+                    ...rateLimitInputs,
+                    [providerType]: {
+                      ...rateLimitInputs[providerType],
+                      [field]: e.target.value,
+                    },
+                  })
+                }
+              />
+            ))}
+          </div>
+        ))}
+      </div>
+
+      <div>
+        <Label>HTTP Client</Label>
+        <div className="flex items-center gap-2">
+          <TextInput
+            placeholder="Proxy URL (default)"
+            value={httpProxy}
+            onChange={(e) => setHttpProxy(e.target.value)}
+          />
+          <TextInput
+            placeholder="No proxy hosts (comma separated)"
+            value={httpNoProxy}
+            onChange={(e) => setHttpNoProxy(e.target.value)}
+          />
+        </div>
+        <div className="flex items-center gap-2">
+          <TextInput
+            type="number"
+            min={1}
+            placeholder="Connect timeout secs (default)"
+            value={httpConnectTimeout}
+            onChange={(e) => setHttpConnectTimeout(e.target.value)}
+          />
+          <TextInput
+            type="number"
+            min={1}
+            placeholder="Request timeout secs (default)"
+            value={httpRequestTimeout}
+            onChange={(e) => setHttpRequestTimeout(e.target.value)}
+          />
+        </div>
+        <Textarea
+          placeholder="CA certificate paths (PEM, one per line)"
+          value={httpCaCerts}
+          onChange={(e) => setHttpCaCerts(e.target.value)}
+        />
+        <Textarea
+          placeholder="Name: value (one header per line)"
+          value={httpHeaders}
+          onChange={(e) => setHttpHeaders(e.target.value)}
+        />
+      </div>
+
+      <div>
+        <Label>Credential Profiles</Label>
+        {profiles.map((profile) => (
+          <div key={profile.id} className="flex items-center gap-2">
+            <span className="w-20">{profile.providerType}</span>
+            <span className="w-40">{profile.label}</span>
+            <span className="w-40">{profile.maskedKey ?? '(unreadable)'}</span>
+            <span className="w-40">{profile.organizationId ?? ''}</span>
+            <span className="w-40">{profile.projectId ?? ''}</span>
+            <ButtonWithIcon
+              text="Delete"
+              type="button"
+              icon="i-solar-trash-bin-trash-bold"
+              color="warn"
+              onClick={() => deleteCredentialProfile(profile.id)}
+            />
+          </div>
+        ))}
+        <RadioGroup
+          onValueChange={(value) => setProfileProvider(value as ProviderType)}
+          value={profileProvider}
+        >
+          <div className="flex row gap-4">
+            {PROVIDER_TYPES.map((providerType) => (
+              <div
+                key={providerType}
+                className="flex row items-center space-x-2"
+              >
+                <RadioGroupItem
+                  value={providerType}
+                  id={`profile-provider-${providerType}`}
+                />
+                <Label htmlFor={`profile-provider-${providerType}`}>
+                  {providerType}
+                </Label>
+              </div>
+            ))}
+          </div>
+        </RadioGroup>
+        <div className="flex items-center gap-2">
+          <TextInput
+            placeholder="Label"
+            value={profileLabel}
+            onChange={(e) => setProfileLabel(e.target.value)}
+          />
+          <TextInput
+            type="password"
+            autoComplete="off"
+            placeholder="API key"
+            value={profileApiKey}
+            onChange={(e) => setProfileApiKey(e.target.value)}
+          />
+          <TextInput
+            placeholder="Organization ID (optional)"
+            value={profileOrganizationId}
+            onChange={(e) => setProfileOrganizationId(e.target.value)}
+          />
+          <TextInput
+            placeholder="Project ID (optional)"
+            value={profileProjectId}
+            onChange={(e) => setProfileProjectId(e.target.value)}
+          />
+          <ButtonWithIcon
+            text="Add"
+            type="button"
+            icon="i-solar-add-circle-linear"
+            color="success"
+            onClick={addCredentialProfile}
+          />
+        </div>
+      </div>
+
+      <div>
+        <Label>Usage by Profile</Label>
+        {profileUsages.map((item) => (
+          <div
+            key={`${item.credentialProfileId}-${item.providerType}-${item.model}`}
+            className="flex items-center gap-2"
+          >
+            <span className="w-40">{item.label ?? 'Default API key'}</span>
+            <span className="w-20">{item.providerType}</span>
+            <span className="w-40">{item.model}</span>
+            <span>
+              {item.runs} runs / {item.responses} responses (
+              {item.cachedResponses} cached)
+            </span>
+          </div>
+        ))}
+      </div>
+
+      <TagSettings />
+
+      <TrashSettings />
+
+      <div>
+        <Label htmlFor="trash-retention-days">Purge Trash After (days)</Label>
+        <TextInput
+          id="trash-retention-days"
+          type="number"
+          min={1}
+          placeholder="Keep forever"
+          value={trashRetentionDays}
+          onChange={(e) => setTrashRetentionDays(e.target.value)}
+        />
+      </div>
+
+      <div>
+        <Label>Default Provider</Label>
+        <RadioGroup
+          onValueChange={(value) => setDefaultProvider(value as ProviderType)}
+          value={defaultProvider ?? undefined}
+        >
+          <div className="flex row gap-4">
+            {PROVIDER_TYPES.map((providerType) => (
+              <div
+                key={providerType}
+                className="flex row items-center space-x-2"
+              >
+                <RadioGroupItem
+                  value={providerType}
+                  id={`provider-${providerType}`}
+                />
+                <Label htmlFor={`provider-${providerType}`}>
+                  {providerType}
+                </Label>
+              </div>
+            ))}
+          </div>
+        </RadioGroup>
+      </div>
+
+      <div>
+        <Label htmlFor="default-model">Default Model</Label>
+        <TextInput
+          id="default-model"
+          placeholder="Enter model..."
+          value={defaultModel}
+          onChange={(e) => setDefaultModel(e.target.value)}
+        />
+      </div>
+
+      <div>
+        <Label>Theme</Label>
+        <RadioGroup
+          onValueChange={(value) => setThemeValue(value as Theme)}
+          value={theme}
+        >
+          <div className="flex row gap-4">
+            {THEMES.map((item) => (
+              <div key={item} className="flex row items-center space-x-2">
+                <RadioGroupItem value={item} id={`theme-${item}`} />
+                <Label htmlFor={`theme-${item}`}>{item}</Label>
+              </div>
+            ))}
+          </div>
+        </RadioGroup>
+      </div>
+
+      <div>
+        <ButtonWithIcon
+          text="Save"
+          type="button"
+          icon="i-solar-diskette-bold"
+          color="success"
+          onClick={saveAppSettings}
+        />
+      </div>
+    </div>
+  )
 }
 
 export default Config
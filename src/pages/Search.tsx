@@ -0,0 +1,90 @@
+import { useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { toast } from 'react-toastify'
+import { TextInput } from '@/components/ui/TextInput'
+import ButtonWithIcon from '@/components/ui/ButtonWithIcon'
+import { searchAction } from '@/features/search/actions'
+import { SearchHitItem, SearchHitKind } from '@/features/search/types'
+
+const KIND_LABELS: Record<SearchHitKind, string> = {
+  manager: 'Title',
+  version: 'System Prompt',
+  run: 'User Prompt',
+  response: 'Response',
+}
+
+// 一致した項目を持つバージョンと実行
+const hitLocation = (hit: SearchHitItem) =>
+  [
+    hit.version !== null ? `v${hit.version}` : null,
+    hit.runId !== null ? `run #${hit.runId}` : null,
+  ]
+    .filter((item) => item)
+    .join(' / ')
+
+const Search = () => {
+  const navigate = useNavigate()
+
+  const [query, setQuery] = useState('')
+  const [hits, setHits] = useState<SearchHitItem[]>([])
+
+  const search = async () => {
+    try {
+      const res = await searchAction({ query })
+      setHits(res.hits)
+    } catch (error) {
+      toast.error(`Failed to search: ${error}`)
+    }
+  }
+
+  return (
+    <div className="flex flex-col gap-2 p-2 h-full overflow-y-scroll">
+      <div className="flex flex-row gap-2 items-center">
+        <div className="grow">
+          <TextInput
+            value={query}
+            placeholder="Search titles, prompts and responses"
+            onChange={(e) => setQuery(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === 'Enter') {
+                search()
+              }
+            }}
+          />
+        </div>
+        <ButtonWithIcon
+          text="Search"
+          type="button"
+          icon="i-solar-magnifer-linear"
+          color="info"
+          onClick={search}
+        />
+      </div>
+      {hits.map((hit, index) => (
+        <div
+          key={index}
+          className="flex flex-col gap-1 p-2 cursor-pointer dark:bg-zinc-700 hover:dark:bg-zinc-600"
+          onClick={() => navigate(`/prompt_manager/${hit.managerId}`)}
+        >
+          <div className="flex flex-row gap-2 text-sm">
+            <span className="font-bold">{hit.managerTitle}</span>
+            <span className="text-muted-foreground">
+              {KIND_LABELS[hit.kind]} {hitLocation(hit)}
+            </span>
+          </div>
+          <div className="whitespace-pre-wrap">
+            {hit.snippet.map((part, i) =>
+              part.highlighted ? (
+                <mark key={i}>{part.text}</mark>
+              ) : (
+                <span key={i}>{part.text}</span>
+              ),
+            )}
+          </div>
+        </div>
+      ))}
+    </div>
+  )
+}
+
+export default Search